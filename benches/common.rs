@@ -16,6 +16,8 @@ pub enum BenchmarkKeys {
     KeyC,
     #[hclog(name = "level_disable", level = Level::Warn)]
     KeyD,
+    #[hclog(name = "print_large_vec")]
+    KeyE,
 }
 pub use BenchmarkKeys::*;
 
@@ -28,6 +30,9 @@ pub const RAND_VEC: &[i16; 64] = &[
     0x45, 0x57, 0x58, 0x87
 ];
 
+// renders to a single message of ~400KB
+pub static LARGE_VEC: [i16; 65536] = [0x5a5a; 65536];
+
 pub fn init() -> bool {
     println!("init std::stdio structures");
     eprintln!("init std::stdio structures");
@@ -42,4 +47,7 @@ pub mod benches {
     pub fn log_random_vec() { lI!(KeyB, "{:?}", RAND_VEC); }
     pub fn log_simple_fmt() { lI!(KeyC, "This {} a simple test {} + {} = {}", "is", 1, 2, 1 + 2); }
     pub fn log_level_disable() { lI!(KeyD, "This won't get printed: {:?}", RAND_VEC); }
+    pub fn log_large_vec() { lI!(KeyE, "{:?}", LARGE_VEC); }
+    pub fn log_debug10_disable() { lD10!(KeyD, "This won't get printed: {:?}", LARGE_VEC); }
+    pub fn test_level_disable() -> bool { tI!(KeyD) }
 }
@@ -1,3 +1,5 @@
+// black_box also wraps the calls returning (), to keep them from being optimized away
+#![allow(clippy::unit_arg)]
 extern crate hclog;
 use criterion::{criterion_group, criterion_main, Criterion};
 use std::hint::black_box;
@@ -15,10 +17,43 @@ fn hclog(bencher: &mut Criterion) {
     bencher.bench_function(&KeyB.to_string(), move |b| b.iter(|| black_box(log_random_vec())));
     bencher.bench_function(&KeyC.to_string(), move |b| b.iter(|| black_box(log_simple_fmt())));
     bencher.bench_function(&KeyC.to_string(), move |b| b.iter(|| black_box(log_level_disable())));
+    bencher.bench_function(&KeyE.to_string(), move |b| b.iter(|| black_box(log_large_vec())));
 }
+// compare the cost of a disabled level between the default and the single-thread build:
+//      cargo bench --bench criterion_bench -- level_disable
+//      cargo bench --bench criterion_bench --features single-thread -- level_disable
+fn disabled_level(bencher: &mut Criterion) {
+    common::init();
+    let mode = if cfg!(feature = "single-thread") { "single-thread" } else { "rwlock" };
+    let mut group = bencher.benchmark_group("level_disable");
+    group.bench_function(mode, move |b| b.iter(|| black_box(log_level_disable())));
+    group.bench_function(format!("{}/debug10", mode),
+        move |b| b.iter(|| black_box(log_debug10_disable())));
+    group.bench_function(format!("{}/test_level", mode),
+        move |b| b.iter(|| black_box(test_level_disable())));
+    group.finish();
+}
+
+// cost of a flush per message compared to coalesced flushes on the file facade:
+//      cargo bench --bench criterion_bench -- flush_policy
+fn flush_policy(bencher: &mut Criterion) {
+    common::init();
+    let mut group = bencher.benchmark_group("flush_policy");
+    for (name, policy) in [
+        ("every_message", FlushPolicy::EveryMessage),
+        ("every_128", FlushPolicy::EveryN(128)),
+    ] {
+        hclog::set_flush_policy(KeyC, policy).unwrap();
+        group.bench_function(name, move |b| b.iter(|| black_box(log_simple_fmt())));
+    }
+    hclog::flush().unwrap();
+    hclog::set_flush_policy(KeyC, FlushPolicy::EveryMessage).unwrap();
+    group.finish();
+}
+
 criterion_group!{
     name = benches;
     config = Criterion::default().significance_level(0.1).sample_size(500);
-    targets = hclog
+    targets = hclog, disabled_level, flush_policy
 }
 criterion_main!(benches);
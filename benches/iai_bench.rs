@@ -1,6 +1,11 @@
+// black_box also wraps the calls returning (), to keep them from being optimized away
+#![allow(clippy::unit_arg)]
 extern crate hclog;
 #[allow(unused,dead_code)]
-use iai_callgrind::{main, library_benchmark_group, library_benchmark};
+use iai_callgrind::{
+    main, library_benchmark_group, library_benchmark, EventKind, LibraryBenchmarkConfig,
+    RegressionConfig,
+};
 use std::hint::black_box;
 
 #[path = "common.rs"]
@@ -23,15 +28,43 @@ fn hclog_simple_fmt(_: bool) {
     black_box(benches::log_simple_fmt());
 }
 
+#[library_benchmark]
+#[bench::setup(common::init())]
+fn hclog_large_vec(_: bool) {
+    black_box(benches::log_large_vec());
+}
+
 #[library_benchmark]
 #[bench::setup(common::init())]
 fn hclog_level_disabled(_: bool) {
     black_box(benches::log_level_disable());
 }
 
+#[library_benchmark]
+#[bench::setup(common::init())]
+fn hclog_debug10_disabled(_: bool) {
+    black_box(benches::log_debug10_disable());
+}
+
+#[library_benchmark]
+#[bench::setup(common::init())]
+fn hclog_test_level_disabled(_: bool) -> bool {
+    black_box(benches::test_level_disable())
+}
+
 library_benchmark_group!(
     name = hclog_bench_group;
     benchmarks = hclog_hello_world, hclog_random_vec, hclog_simple_fmt,
-                 hclog_level_disabled,
+                 hclog_large_vec,
+);
+/*
+ * the disabled level path must stay cheap whatever the arguments are. These fail the run if
+ * the instruction count grows by more than 5% compared to the last run (the baseline)
+ */
+library_benchmark_group!(
+    name = hclog_disabled_group;
+    config = LibraryBenchmarkConfig::default()
+        .regression(RegressionConfig::default().limits([(EventKind::Ir, 5.0)]));
+    benchmarks = hclog_level_disabled, hclog_debug10_disabled, hclog_test_level_disabled,
 );
-main!(library_benchmark_groups = hclog_bench_group);
+main!(library_benchmark_groups = hclog_bench_group, hclog_disabled_group);
@@ -1,4 +1,4 @@
-#![allow(unused_variables,dead_code)]
+#![allow(unused_variables,dead_code,clippy::upper_case_acronyms)]
 #[macro_use]
 extern crate hclog;
 
@@ -17,7 +17,7 @@ enum LogKeys {
 }
 use LogKeys::*;
 
-static LOG_KEYS: &'static [LogKeys] = &[ASYNC, GLOBAL];
+static LOG_KEYS: &[LogKeys] = &[ASYNC, GLOBAL];
 impl LogKey for LogKeys {
     fn log_key(&self) -> ContextKey { *self as usize }
 }
@@ -48,7 +48,9 @@ mod foo {
     pub fn task_local_log(key: LogKeys) -> impl std::future::Future {
         hclog::scope("Task1", key, async move {
             lI!(ASYNC, "in task_local_log within Scope::Task");
-            hclog::dump(&mut std::io::stdout()).unwrap();
+            println!("running in task scope {:?}", hclog::current_scope_ident());
+            // dumps the global and the task-local context
+            hclog::dump_always(&mut std::io::stdout()).unwrap();
             foo::bar::async_global_log("task_local_log").await;
             hclog::set_logdest(ASYNC, FacadeVariant::StdErr).unwrap();
             lD10!(ASYNC, "log with debug10 -- not printed");
@@ -70,6 +72,7 @@ fn main() {
     let j1 = async { foo::task_local_log(ASYNC).await };
     let j2 = async { foo::bar::async_global_log("main").await };
 
+    println!("task scope outside of the futures: {:?}", hclog::current_scope_ident());
     // run futures
     futures::executor::block_on(j1);
     futures::executor::block_on(j2);
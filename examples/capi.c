@@ -0,0 +1,46 @@
+/*
+ * Logging from C into the scopes of a Rust application via the hclog C interface
+ *
+ * The Rust side initializes the scope and provides the keys, e.g.:
+ *
+ *     #[derive(HCLog, Copy, Clone)]
+ *     #[hclog(scope = hclog::ScopeKey::Application, default_level = hclog::Level::Info,
+ *             default_facade = hclog::FacadeVariant::StdOut)]
+ *     enum Keys { Net, Storage }
+ *
+ *     #[no_mangle]
+ *     pub extern "C" fn app_init_logging() {
+ *         Keys::init_with_defaults("mixed").unwrap();
+ *     }
+ *
+ * Build that crate as staticlib with the "capi" feature of hclog enabled and link it:
+ *
+ *     cc -I hclog/include examples/capi.c -L target/release -lmixed -o capi
+ */
+#include <stdio.h>
+#include "hclog.h"
+
+/* indices of the Rust LogKeys (the enum discriminants) */
+enum { KEY_NET = 0, KEY_STORAGE = 1 };
+
+#define LOG(key, level, msg) \
+    hclog_log(HCLOG_SCOPE_APPLICATION, (key), (level), __FILE__, __LINE__, (msg))
+
+extern void app_init_logging(void);
+
+int main(void)
+{
+    int ret;
+
+    app_init_logging();
+
+    ret = LOG(KEY_NET, HCLOG_LEVEL_INFO, "connected");
+    if (ret != HCLOG_OK)
+        fprintf(stderr, "hclog_log failed: %d\n", ret);
+
+    hclog_set_level(HCLOG_SCOPE_APPLICATION, KEY_STORAGE, HCLOG_LEVEL_DEBUG3);
+    if (hclog_test_log(HCLOG_SCOPE_APPLICATION, KEY_STORAGE, HCLOG_LEVEL_DEBUG3))
+        LOG(KEY_STORAGE, HCLOG_LEVEL_DEBUG3, "storage debugging enabled");
+
+    return 0;
+}
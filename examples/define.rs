@@ -0,0 +1,26 @@
+#![allow(clippy::upper_case_acronyms)]
+#[macro_use]
+extern crate hclog;
+use hclog::{Level, FacadeVariant};
+
+// the LogKeys, the Scope and init_logging in a single item
+hclog::define_logging! {
+    name: "define",
+    level: Level::Info,
+    facade: FacadeVariant::StdOut,
+    keys: {
+        NET: { level: Level::Debug3 },
+        DB: {},
+        AUDIT: { name: "audit", facade: FacadeVariant::StdErr },
+    }
+}
+
+fn main() {
+    init_logging().unwrap();
+    lD3!(NET, "connecting to {}", "db.example.org");
+    lI!(DB, "connected");
+    // won't be printed because DB logs at info
+    lD1!(DB, "pool size {}", 4);
+    lN!(AUDIT, "user {} logged in", "admin");
+    hclog::list_modules_detailed(&mut std::io::stdout()).unwrap();
+}
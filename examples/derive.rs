@@ -1,7 +1,7 @@
 #[macro_use]
 extern crate hclog;
 
-use hclog::{Level, FacadeVariant, ScopeKey};
+use hclog::{Level, FacadeVariant, Scope, ScopeKey};
 use hclog_macros::HCLog;
 use log::{trace, warn};
 
@@ -41,9 +41,14 @@ fn main() {
         lI!(KeyC, "Key3 => {} => {:?}", KeyC, mem::discriminant(&KeyC));
     }
     assert!(hclog::has_module(KeyE).unwrap()); // ensure KeyE is enabled
+    // a slice of keys like before owned keys were accepted
+    #[allow(clippy::needless_borrows_for_generic_args)]
     hclog::add_submodules(&[KeyC]).unwrap();
     assert!(hclog::has_module(KeyC).unwrap());
     assert!(!hclog::has_module(KeyD).unwrap());
+    // owned keys, e.g. collected into a Vec, can be added as well
+    hclog::add_submodules(vec![KeyD]).unwrap();
+    assert!(hclog::has_module(KeyD).unwrap());
     warn!("will also work with logcompat enabled");
     trace!("won't print trace");
     hclog::dump(&mut std::io::stdout()).unwrap();
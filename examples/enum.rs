@@ -1,3 +1,4 @@
+#![allow(clippy::upper_case_acronyms)]
 #[macro_use]
 extern crate hclog;
 
@@ -53,7 +54,7 @@ fn main() {
     assert_eq!(hclog::has_module(BAR), Ok(true));
     assert_eq!(hclog::has_module(BAZ), Ok(false));
 
-    assert_eq!(tE!(FOO), true);
+    assert!(tE!(FOO));
 
     hclog::list_modules(&mut std::io::stdout()).unwrap();
 
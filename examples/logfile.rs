@@ -1,7 +1,7 @@
 #[macro_use]
 extern crate hclog;
 
-use hclog::{FacadeVariant, Level};
+use hclog::{FacadeVariant, Level, Scope};
 use hclog_macros::HCLog;
 
 #[derive(HCLog, Copy, Clone, Debug)]
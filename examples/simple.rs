@@ -1,3 +1,4 @@
+#![allow(clippy::upper_case_acronyms)]
 #[macro_use]
 extern crate hclog;
 use hclog::{
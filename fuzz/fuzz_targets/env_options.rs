@@ -0,0 +1,21 @@
+#![no_main]
+/*
+ * the HCLOG_OPT_* variables never panic, a value which isn't a number is rejected
+ */
+use hclog::options::parse_options_from_env;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: (&str, &str)| {
+    let (name, value) = data;
+    // set_var panics on these, they can't be part of a real environment either
+    if [name, value].iter().any(|s| s.contains('\0')) || name.is_empty() || name.contains('=') {
+        return;
+    }
+    let key = format!("HCLOG_OPT_{}", name);
+    std::env::set_var(&key, value);
+    let parsed = parse_options_from_env();
+    std::env::remove_var(&key);
+    if value.parse::<u16>().is_ok() {
+        assert!(parsed.is_ok(), "{}={} rejected", key, value);
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+/*
+ * FacadeVariant::from_str never panics and the spec of a parsed facade parses to the same spec
+ */
+use hclog::FacadeVariant;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    if let Ok(facade) = s.parse::<FacadeVariant>() {
+        let spec = facade.spec().expect("parsed facade without spec");
+        let reparsed = spec.parse::<FacadeVariant>().expect("spec doesn't parse");
+        assert_eq!(reparsed.spec(), Some(spec));
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+/*
+ * Level::from_str never panics and a parsed level survives the round trip via to_string
+ */
+use hclog::Level;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    if let Ok(level) = s.parse::<Level>() {
+        assert_eq!(level.to_string().parse::<Level>(), Ok(level));
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+/*
+ * Options::from_str never panics and parsed options survive the round trip via to_string
+ */
+use hclog::options::Options;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    if let Ok(opts) = s.parse::<Options>() {
+        assert_eq!(opts.to_string().parse::<Options>(), Ok(opts));
+    }
+});
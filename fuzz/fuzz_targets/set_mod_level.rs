@@ -0,0 +1,42 @@
+#![no_main]
+/*
+ * set_mod_level never panics and either applies all arguments or none of them
+ */
+use hclog::{options::NONE, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+use libfuzzer_sys::fuzz_target;
+use std::sync::Once;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum FuzzKeys {
+    Net,
+    Db,
+    NetRx,
+}
+
+fn detailed() -> String {
+    let mut out = Vec::new();
+    hclog::list_modules_detailed(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fuzz_target!(|arg: &str| {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| FuzzKeys::init("fuzz", Level::Info, FacadeVariant::None, NONE).unwrap());
+    if !arg.is_ascii() {
+        return;
+    }
+    hclog::set_mod_level(["_all:info,_all:unmute"]).unwrap();
+    let before = detailed();
+    match hclog::set_mod_level([arg]) {
+        Err(_) => assert_eq!(detailed(), before),
+        Ok(()) => {
+            let batch = detailed();
+            hclog::set_mod_level(["_all:info,_all:unmute"]).unwrap();
+            for chunk in arg.split(',') {
+                hclog::set_mod_level([chunk]).unwrap();
+            }
+            assert_eq!(detailed(), batch);
+        }
+    }
+});
@@ -0,0 +1,72 @@
+/*
+ * C interface of the hclog crate (cargo feature "capi")
+ *
+ * Scopes and keys are initialized on the Rust side and addressed by their indices here: the
+ * index of the ScopeKey and the index returned by LogKey::log_key (the discriminant for
+ * derived LogKeys). All functions are safe to call before initialization, they return an
+ * error code instead.
+ */
+#ifndef HCLOG_H
+#define HCLOG_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* ScopeKey */
+#define HCLOG_SCOPE_APPLICATION 0
+#define HCLOG_SCOPE_CLOG        1
+#define HCLOG_SCOPE_LIB         2
+
+/* Level */
+#define HCLOG_LEVEL_OFF     0
+#define HCLOG_LEVEL_EMERG   1
+#define HCLOG_LEVEL_ALERT   2
+#define HCLOG_LEVEL_CRIT    3
+#define HCLOG_LEVEL_ERROR   4
+#define HCLOG_LEVEL_WARN    5
+#define HCLOG_LEVEL_NOTICE  6
+#define HCLOG_LEVEL_INFO    7
+#define HCLOG_LEVEL_DEBUG1  8
+#define HCLOG_LEVEL_DEBUG2  9
+#define HCLOG_LEVEL_DEBUG3  10
+#define HCLOG_LEVEL_DEBUG4  11
+#define HCLOG_LEVEL_DEBUG5  12
+#define HCLOG_LEVEL_DEBUG6  13
+#define HCLOG_LEVEL_DEBUG7  14
+#define HCLOG_LEVEL_DEBUG8  15
+#define HCLOG_LEVEL_DEBUG9  16
+#define HCLOG_LEVEL_DEBUG10 17
+
+/* return codes */
+#define HCLOG_OK          0
+#define HCLOG_ERR_SCOPE  -1 /* scope index invalid or scope not initialized */
+#define HCLOG_ERR_KEY    -2 /* key index invalid or key not initialized */
+#define HCLOG_ERR_LEVEL  -3 /* invalid level (or HCLOG_LEVEL_OFF for a message) */
+#define HCLOG_ERR_ARG    -4 /* string argument NULL or not valid UTF-8 */
+#define HCLOG_ERR_LOCK   -5 /* context can't be accessed */
+#define HCLOG_ERR_WRITE  -6 /* writing to the facade failed */
+#define HCLOG_ERR_OTHER  -7 /* any other error */
+
+/*
+ * Log msg with level via the key. file may be NULL. msg is logged as is, it is not a
+ * format string.
+ */
+int hclog_log(size_t scope_key, size_t context_key, uint32_t level,
+              const char *file, uint32_t line, const char *msg);
+
+/* Set the level of the key */
+int hclog_set_level(size_t scope_key, size_t context_key, uint32_t level);
+
+/* Test if a message with level would be logged via the key, false on any error */
+bool hclog_test_log(size_t scope_key, size_t context_key, uint32_t level);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* HCLOG_H */
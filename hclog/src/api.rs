@@ -1,17 +1,30 @@
 use crate::{
-    context::CTX,
+    context::{CTX, Context, DefaultKey, LogDepth, ScopeSnapshot, Staging, MAX_LOG_DEPTH},
     options::*,
-    facades::FacadeVariant,
+    facades::{FacadeScope, FacadeVariant, FlushPolicy, Newline, FACADE_FAILURES, FACADE_SKIPPED},
+    logmod::{MergePolicy, Provenance, ScopeKey, Source},
+    submodule::{name_eq, Mirror, Submodule},
+    severe::{SevereHandler, SevereHandlerId},
+    subscription::Subscription,
+    validate::{check_keys, check_scope, ConfigWarning, KeyMismatch},
+    config::{ConfigReport, OptionsArg},
+    metadata::MetadataFormat,
     Level,
-    ErrorKind::*,
+    ErrorKind::{self, *},
     InternalLogKeys::{self, *},
-    Scope, LogKey, Result,
+    Scope, LogKey, IntoLogKey, ContextKey, Result,
 };
 use std::{
+    collections::HashMap,
     future::Future,
     fmt::{Display, Arguments},
     io::Write,
     env,
+    time::Duration,
+    panic::Location,
+    sync::{atomic::{AtomicBool, AtomicU64, Ordering}, OnceLock},
+    cmp,
+    cell::Cell,
 };
 
 #[cfg(doctest)]
@@ -21,17 +34,31 @@ use hclog_macros::HCLog;
 ///
 /// This is the main initialization function used in the [`Scope`] implementation.
 /// It initializes the internal logging system with a given name `S`, a [`Level`],
-/// a [`FacadeVariant`]. If the [`LOGCOMPAT`] option is set in the [`Options`], the
-/// compatibility layer to the `log` crate will be initialized as well.
+/// a [`FacadeVariant`] (or anything that converts into one). If the [`LOGCOMPAT`] option
+/// is set in the [`Options`], the compatibility layer to the `log` crate will be initialized
+/// as well.
+///
+/// To select the facade by name, e.g. from a config file, use [`init_try`].
+///
+/// With the `single-thread` feature enabled the context is bound to the calling thread. It is
+/// not safe to assume that a [`Scope`] initialized here is visible to other threads, see
+/// [Single-thread mode](crate#single-thread-mode).
 ///
 /// # Errors
 ///
+/// Returns an error if:
+/// * [`InvalidScope`]: `I::logscope()` doesn't return a usable [`ScopeKey`](crate::ScopeKey)
+/// * [`ParseArg`]: the name is empty, only consists of whitespace or contains a nul byte.
+///   Surrounding whitespace is removed from the name.
+/// * [`ContextLock`]: the internal context can't be accessed
+/// * any error while initializing the compatibility layer if [`LOGCOMPAT`] is set
 pub fn init<I, S>(
-    name: S, level: Level, facade: FacadeVariant, options: Options
+    name: S, level: Level, facade: impl Into<FacadeVariant>, options: Options
 ) -> Result<()>
     where I: Scope,
           S: Display
 {
+    let facade = facade.into();
     InternalLogKeys::init_with_defaults(&name)?;
     CTX::get_mut()?.init_mod::<I, S>(name, level, facade.clone(), options)?;
     if options.has(LOGCOMPAT) {
@@ -40,6 +67,93 @@ pub fn init<I, S>(
     Ok(())
 }
 
+/// Initialize the logging system with a fallible facade conversion
+///
+/// Same as [`init`] but accepts anything that tries to convert into a [`FacadeVariant`], like
+/// the facade names `"none"`, `"stdout"`, `"stderr"`, `"syslog"`, `"file"` or `"binary"`. This
+/// allows config file driven initialization without parsing the facade upfront.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{Level, Options};
+/// # use hclog_macros::HCLog;
+/// # #[derive(Copy, Clone, HCLog)]
+/// # enum Keys { A }
+///
+/// hclog::init_try::<Keys, _>("config", Level::Info, "stdout", Options::default()).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if:
+/// * [`ParseArg`]: the facade conversion failed
+/// * any error returned by [`init`]
+pub fn init_try<I, S>(
+    name: S, level: Level, facade: impl TryInto<FacadeVariant, Error = String>, options: Options
+) -> Result<()>
+    where I: Scope,
+          S: Display
+{
+    let facade = facade.try_into().map_err(|_| ParseArg)?;
+    self::init::<I, S>(name, level, facade, options)
+}
+
+/// Initialize a [`Scope`] again from scratch
+///
+/// **This is destructive**: the scope `K` is removed from the context including all of its
+/// [`LogKey`]s and every change made at runtime (levels, facades, options, parents, ...).
+/// Afterwards `K::init` is called with the given arguments as if the scope was never
+/// initialized before. Pending [`DEDUP`](crate::options::DEDUP) summaries of the removed
+/// LogKeys are flushed to their old facades.
+///
+/// In contrast, calling [`Scope::init`] on an initialized scope keeps the existing scope. This
+/// is intended for tests and for plugins which are loaded again at runtime. Task local copies
+/// of the scope (see [`scope`]) are not affected. The new scope is built aside and replaces
+/// the old one at once, so other threads keep logging via the old scope until then. If
+/// `K::init` fails the old scope is kept.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{Level, FacadeVariant, options::Options};
+/// # use hclog_macros::HCLog;
+/// # #[derive(Copy, Clone, HCLog)]
+/// # enum Keys { A }
+///
+/// hclog::init::<Keys, _>("plugin", Level::Info, FacadeVariant::None, Options::default())
+///     .unwrap();
+/// hclog::reinitialize::<Keys, _>("plugin", Level::Debug1, FacadeVariant::StdErr,
+///     Options::default()).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if:
+/// * [`InvalidScope`]: `K::logscope()` doesn't return a usable [`ScopeKey`](crate::ScopeKey)
+/// * [`ContextLock`]: the internal context can't be accessed
+/// * any error returned by `K::init`
+/// * [`IoError`]: flushing the removed LogKeys failed. The scope is initialized anyway.
+pub fn reinitialize<K, S>(
+    name: S, level: Level, facade: FacadeVariant, options: Options
+) -> Result<()>
+    where K: Scope,
+          S: Display
+{
+    let scope = K::logscope();
+    // K::init builds the new scope in a copy of the context, the old one stays in use meanwhile
+    let mut copy = CTX::get()?.clone();
+    copy.clear_mod(scope)?;
+    let staging = Staging::enter(copy)?;
+    K::init(name, level, facade, options)?;
+    let staged = staging.finish()?;
+    let old = CTX::get_mut()?.replace_mod(scope, staged)?;
+    if old.initialized() {
+        old.flush()?;
+    }
+    Ok(())
+}
+
 /// Initialize the compatibility layer to the [`crate-log`] crate
 ///
 /// Some other crates might use the `log` crate for logging. The intention of this compatibility
@@ -52,6 +166,19 @@ pub fn init<I, S>(
 /// [`InteralLogKeys::LogCompat`](enum@InternalLogKeys#variant@LogCompat)
 /// module key.
 ///
+/// The layer is also initialized when the [`LOGCOMPAT`] option is newly set on any [`LogKey`]
+/// via [`set_module_options()`], which includes passing [`Options::default()`]. Since the
+/// logger installed into the `log` crate can't be uninstalled, unsetting [`LOGCOMPAT`] on the
+/// `LogCompat` key via [`unset_module_options()`] only mutes the layer. Setting the option
+/// again unmutes it.
+///
+/// ## Task scopes
+///
+/// Within a future passed to [`async_scope_for`] or `scope` the messages of the `log` crate
+/// carry the ident of the task like messages of the own `LogKey`s, since the `LogCompat` key is
+/// copied into the task local context as well. Like for the other copied keys, changes of the
+/// `LogCompat` key within the task don't affect the global one.
+///
 /// ## Mapping from `log` to internal log levels
 ///
 /// The `boxed_logger` is always initialized with the highest LevelFilter `Trace`. The filtering
@@ -103,7 +230,14 @@ pub fn init_log_compat<S: Display>(
 /// [`std::io::Stdout`].
 ///
 /// The dump will only be performed if the environment variable `HCLOG_DUMP_MODULES` is set to `1`.
-/// This is a safety measure to prevent accidental dumps in production code.
+/// This is a safety measure to prevent accidental dumps in production code. The dump starts with
+/// the line returned by [`health_summary`]. Use [`dump_always`] to dump regardless of the
+/// environment.
+///
+/// Within a task scope (see [`scope`] and [`async_scope_for`]) the global context is labeled
+/// `=== global context ===` and followed by the task-local copy, labeled
+/// `=== task-local context (ident=<ident>) ===`. Outside of a task scope only the global
+/// context is dumped.
 ///
 /// # Examples
 ///
@@ -122,15 +256,103 @@ pub fn init_log_compat<S: Display>(
 /// This function might panic for any reason the used writer might panic. Please refer
 /// to the according documentation of the writer in use.
 pub fn dump<W: Write>(w: &mut W) -> Result<()> {
-    if let Ok(s) = env::var("HCLOG_DUMP_MODULES") {
-        if s == "1" {
-            let ctx = CTX::get()?;
-            w.write_fmt(format_args!("{:#?}", ctx))?;
-        }
+    match env::var("HCLOG_DUMP_MODULES") {
+        Ok(s) if s == "1" => dump_always(w),
+        _ => Ok(()),
+    }
+}
+
+/// Dump the internal state of the logging system to the supplied writer unconditionally
+///
+/// Same as [`dump`] but without checking `HCLOG_DUMP_MODULES`. Meant for crash dumps, admin
+/// endpoints or calls from a debugger, where setting an environment variable beforehand isn't
+/// possible.
+///
+/// # Examples
+///
+/// ```rust
+///     hclog::dump_always(&mut std::io::stdout()).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if:
+/// * [`ContextLock`]: the internal context can't be accessed
+/// * [`IoError`]: any underlying I/O error while writing to the supplied writer
+///
+/// # Panics
+///
+/// This function might panic for any reason the used writer might panic. Please refer
+/// to the according documentation of the writer in use.
+pub fn dump_always<W: Write>(w: &mut W) -> Result<()> {
+    w.write_fmt(format_args!("{}\n", health_summary()))?;
+    let ident = CTX::task_ident();
+    if CTX::in_task_scope() {
+        w.write_all(b"=== global context ===\n")?;
     }
+    w.write_fmt(format_args!("{:#?}", *CTX::get()?))?;
+    CTX::with_task_context(|ctx| {
+        w.write_fmt(format_args!("\n=== task-local context (ident={}) ===\n{:#?}",
+            ident.as_deref().unwrap_or_default(), ctx))
+    }).transpose()?;
     Ok(())
 }
 
+/// Dump the internal state of the logging system to `stderr` unconditionally
+///
+/// Shortcut for [`dump_always`] with [`std::io::stderr`], e.g. to be called from a debugger.
+///
+/// # Errors
+///
+/// See [`dump_always`].
+pub fn dump_to_stderr() -> Result<()> {
+    dump_always(&mut std::io::stderr().lock())
+}
+
+/// Get a compact one-line summary of the logging system
+///
+/// The summary consists of `key=value` pairs separated by a single space in a fixed order:
+///
+/// * `scopes`: number of initialized [`Scope`]s (including the internal one)
+/// * `keys`: number of initialized [`LogKey`]s
+/// * `active`: number of [`LogKey`]s with a [`Level`] other than `Off` and a facade other than
+///   [`FacadeVariant::None`]
+/// * `errors_last_min`: reserved for a runtime counter. It's not collected yet and always `0`.
+/// * `facade_failures`: number of messages dropped by a facade because they couldn't be sent,
+///   e.g. by the remote syslog facades of the `net-syslog` feature or the secondary of a
+///   [`FacadeVariant::WithBestEffort`] facade
+/// * `dropped`: number of messages suppressed by [`set_sampling`], skipped by [`try_log`] or
+///   skipped by the secondary of a [`FacadeVariant::WithBestEffort`] facade
+///
+/// Only the read lock of the context is taken, so it's cheap enough to be called periodically.
+/// If the context can't be accessed all values are `0`.
+///
+/// # Examples
+///
+/// ```rust
+/// let summary = hclog::health_summary();
+/// assert!(summary.starts_with("scopes="));
+/// ```
+pub fn health_summary() -> String {
+    let (mut scopes, mut keys, mut active) = (0, 0, 0);
+    let mut dropped = CONTENDED.load(Ordering::Relaxed) + FACADE_SKIPPED.load(Ordering::Relaxed);
+    if let Ok(ctx) = CTX::get() {
+        for lm in ctx.logmods().filter(|lm| lm.initialized()) {
+            scopes += 1;
+            for submod in lm.submodules().filter(|s| s.initialized()) {
+                keys += 1;
+                if submod.is_active(lm.effective_level(submod)) {
+                    active += 1;
+                }
+                dropped += submod.sampled_out();
+            }
+        }
+    }
+    let failures = FACADE_FAILURES.load(Ordering::Relaxed);
+    format!("scopes={} keys={} active={} errors_last_min={} facade_failures={} dropped={}",
+        scopes, keys, active, 0, failures, dropped)
+}
+
 /// Print a list of all available modules to the supplied writer `w`
 ///
 /// Print a comma separated list of all available [`LogKey`]s in all [`Scope`]s to the
@@ -150,7 +372,7 @@ pub fn dump<W: Write>(w: &mut W) -> Result<()> {
 ///
 pub fn list_modules<W: Write>(w: &mut W) -> Result<()> {
     let ctx = CTX::get()?;
-    w.write(b"List of available modules:\n")?;
+    w.write_all(b"List of available modules:\n")?;
     let joined = ctx.logmods().flat_map(
         |l| l.submodules().map(|s| s.to_string())
     ).collect::<Vec<_>>();
@@ -159,225 +381,1204 @@ pub fn list_modules<W: Write>(w: &mut W) -> Result<()> {
     Ok(())
 }
 
-/* scope access (async stuff) */
-#[doc(hidden)]
-pub fn scope<I, K, F>(ident: I, key: K, future: F) -> Result<impl Future>
-where
-    I: Display + Send,
-    K: LogKey,
-    F: Future + Send
-{
-    CTX::new_scoped(ident, key, future)
-}
-
-/* submod mgmt functions */
-/// Initialize a [`Scope`] with a list of provided [`LogKey`]s.
+/// Print the configuration of all initialized modules to the supplied writer `w`
 ///
-/// The passed [`Level`], [`FacadeVariant`] and [`Options`] will be used for the [`Scope`]
-/// and passed down to the each LogKey unless they provide custom values via the [`LogKey`]
-/// trait implementation.
+/// Print one line per initialized [`LogKey`] of all [`Scope`]s to the supplied [`Write`]r `w`
+/// instance. Each line contains the name of the LogKey followed by the level it currently logs
+/// with, the name of its facade and its [`Options`](crate::options::Options), each followed by
+/// its [`Source`] (see [`provenance`]):
 ///
-/// The given modname `S` is the name of the scope used for logging like the binary name in
-/// syslog. The modules `I` input is everything which can be converted to a iterator.
+/// ```text
+///   net [level=debug3 (env)] [facade=stdout (code)] [opts=TIMESTAMP+SEVERITY+MODULE (code)]
+/// ```
 ///
-/// This is a shortcut for calling [`init()`] and [`add_submodules()`] in sequence.
+/// LogKeys without a facade are listed with `facade=none`, muted LogKeys (see [`mute`]) end
+/// with `[muted]`. The LogKeys of a Scope are followed by the environment variables which
+/// changed its defaults (see [`env_overrides`]):
+///
+/// ```text
+///   env HCLOG_OPT_PID=0: option PID of scope app disabled
+/// ```
 ///
 /// # Examples
 ///
 /// ```rust
-/// use hclog::{Scope, Level, FacadeVariant, options::Options, Result};
-/// # use hclog::{LogKey, ContextKey};
-///
-/// #[derive(Copy, Clone)]
-/// enum LogKeys { IA, IB, IC }
-///
-/// # impl std::fmt::Display for LogKeys {
-/// #    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-/// #        match *self {
-/// #            Self::IA => fmt.write_str("IA"),
-/// #            Self::IB => fmt.write_str("IB"),
-/// #            Self::IC => fmt.write_str("IC"),
-/// #        }
-/// #    }
-/// # }
-///
-/// impl Scope for LogKeys {
-///     fn init<S: std::fmt::Display>(
-///         name: S, level: Level, facade: FacadeVariant, options: Options
-///     ) -> Result<()> {
-///         // instead of calling:
-///         // hclog::init<Self, S>(name, level, facade, options)?;
-///         // hclog::add_submodules(&[Self::IA, Self::IB, Self::IC])
-///         // you could call:
-///         hclog::init_modules(name, &[Self::IA, Self::IB, Self::IC], level, facade, options)
-///     }
-/// }
-///
-/// # impl LogKey for LogKeys {
-/// #    fn log_key(&self) -> ContextKey { *self as ContextKey }
-/// # }
+///     hclog::list_modules_detailed(&mut std::io::stdout()).unwrap();
 /// ```
 ///
 /// # Errors
 ///
 /// Returns an error if:
-/// * [ContextLock]: the internal context can't be accessed
-/// * [LogCompatInitialized]: the compatibility layer is already initialized
+/// * [`ContextLock`]: the internal context can't be accessed
+/// * [`IoError`]: any underlying I/O error while writing to the supplied writer
 ///
-pub fn init_modules<'a, I, K, S>(
-    modname: S, modules: I, l: Level, f: FacadeVariant, o: Options
-) -> Result<()>
-where
-    I: IntoIterator<Item = &'a K>,
-    K: LogKey + 'a,
-    S: Display,
-{
-    self::init::<K, S>(modname, l, f, o)?;
-    add_submodules(modules)
+pub fn list_modules_detailed<W: Write>(w: &mut W) -> Result<()> {
+    let ctx = CTX::get()?;
+    for lm in ctx.logmods().filter(|l| l.initialized()) {
+        for submod in lm.submodules().filter(|s| s.initialized()) {
+            let source = lm.provenance(submod);
+            w.write_fmt(format_args!("  {} [level={} ({})] [facade={} ({})] [opts={} ({})]",
+                submod, lm.effective_level(submod), source.level, submod.facade_variant_name(),
+                source.facade, submod.options(), source.options))?;
+            if submod.muted() {
+                w.write_all(b" [muted]")?;
+            }
+            w.write_all(b"\n")?;
+        }
+        for (var, value, effect) in lm.env_overrides() {
+            w.write_fmt(format_args!("  env {}={}: {}\n", var, value, effect))?;
+        }
+    }
+    w.flush()?;
+    Ok(())
 }
 
-/// Add a list of [`LogKey`]s to the a [`Scope`]
-///
-/// Add a set of [`LogKey`] implementors to their reserved [`Scope`]. The [`Scope`] must be
-/// initialized before calling this function. The passed Iterator `I` must yield a reference
-/// to a [`LogKey`] implementor.
-///
-/// This function is usually called inside the [`init()`] function of a [`Scope`] implementation
-/// but is not limited to. It can be called at any time to add more modules to a scope.
+/// Generate a document listing all initialized LogKeys, e.g. for the man page
 ///
-/// The `LogKey`s receive the [`Level`], [`FacadeVariant`] and [`Options`] from the [`Scope`]
-/// or can provide custom values via the [`LogKey`] trait implementation.
+/// Lists every initialized [`LogKey`] of all [`Scope`]s with the [`Level`] and the name of the
+/// facade it got on initialization and its description (see [`LogKey::description`]). Changes
+/// at runtime, e.g. via [`set_level`], are not reflected. The Scopes are sorted by their name
+/// and the LogKeys of each Scope by their name, so the document only changes with the
+/// definition of the LogKeys. See [`MetadataFormat`] for the available formats.
 ///
 /// # Examples
 ///
 /// ```rust
-/// use hclog::{Scope, Level, FacadeVariant, options::Options, Result};
-/// # use hclog::{LogKey, ContextKey};
-///
-/// #[derive(Copy, Clone)]
-/// enum LogKeys { AA, AB, AC }
-///
-/// # impl std::fmt::Display for LogKeys {
-/// #    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-/// #        match *self {
-/// #            Self::AA => fmt.write_str("AA"),
-/// #            Self::AB => fmt.write_str("AB"),
-/// #            Self::AC => fmt.write_str("AC"),
-/// #        }
-/// #    }
-/// # }
-///
-/// # impl LogKey for LogKeys {
-/// #    fn log_key(&self) -> ContextKey { *self as ContextKey }
-/// # }
+/// use hclog::{Level, FacadeVariant, MetadataFormat, Scope};
+/// # use hclog_macros::HCLog;
 ///
-/// impl Scope for LogKeys {
-///     fn init<S: std::fmt::Display>(
-///         name: S, level: Level, facade: FacadeVariant, options: Options
-///     ) -> Result<()> {
-///         hclog::init::<Self, S>(name, level, facade, options)?;
-///         hclog::add_submodules(&[Self::AA, Self::AB, Self::AC])
-///     }
+/// #[derive(Copy, Clone, HCLog)]
+/// enum Keys {
+///     #[hclog(description = "connections to the database")]
+///     Db,
 /// }
+///
+/// Keys::init("app", Level::Info, FacadeVariant::StdErr, Default::default()).unwrap();
+/// let doc = hclog::export_metadata(MetadataFormat::Markdown).unwrap();
+/// assert!(doc.contains("| Db | info | stderr | connections to the database |"));
 /// ```
 ///
 /// # Errors
 ///
-/// Returns an error if:
-/// * [ContextLock]: the internal context can't be accessed
-/// * [ScopeNotInitialized]: the scope is not initialized
-///
-pub fn add_submodules<'a, K, I>(it: I) -> Result<()>
-where
-    K: LogKey + 'a,
-    I: IntoIterator<Item = &'a K>
-{
-    let mut ctx = CTX::get_mut()?;
-    for m in it.into_iter() {
-        ctx.get_mod_mut(K::logscope())?.add_submodule(*m)?;
-    }
-    Ok(())
+/// Returns [`ContextLock`] if the internal context can't be accessed.
+pub fn export_metadata(format: MetadataFormat) -> Result<String> {
+    CTX::call(|ctx| Ok(crate::metadata::render(ctx, format)))
 }
 
-/// Set the log level for a list of modules.
+/// Get the environment variables which changed the configuration on initialization
 ///
-/// The input `I` must be an iterator of string slices which are formatted as
-/// `key:level,key:level,...`. The `key` is the name of the module and the `level`
-/// is a valid [`Level`] as string.
+/// Returns a `(variable, value, effect)` tuple for every variable which actually changed
+/// something when a [`Scope`] was initialized: an `HCLOG_OPT_*` variable flipping an option,
+/// `HCLOG_LEVEL` or `HCLOG_FACADE` replacing the default level or facade, `HCLOG_INSTANCE_ID`
+/// and `CLOG_DEBUG` for the internal Scope. Variables which are set but match the code, can't
+/// be parsed or are read later on (like `HCLOG_DUMP_MODULES`) are not listed. The entries are
+/// ordered by Scope and recorded per Scope, so a variable affecting multiple Scopes is listed
+/// once for each of them.
 ///
-/// The `key` can be the name of a known [`LogKey`] or `_all` to set the log level
-/// for all available `LogKey`s in the current `Scope`. The `LogKey` and `Level` names
-/// are case insensitive.
+/// The list is empty if the context can't be accessed or the environment is ignored (see
+/// [`ignore_env`]).
 ///
-/// This function is primarily used for setting the log level at runtime via the commandline
-/// or environment variables.
+/// # Examples
+///
+/// ```rust
+/// for (var, value, effect) in hclog::env_overrides() {
+///     eprintln!("{}={} ({})", var, value, effect);
+/// }
+/// ```
+pub fn env_overrides() -> Vec<(String, String, String)> {
+    CTX::get().map(|ctx| {
+        ctx.logmods().filter(|lm| lm.initialized())
+            .flat_map(|lm| lm.env_overrides().iter().cloned()).collect()
+    }).unwrap_or_default()
+}
+
+/// Ignore the environment variables of `hclog` on initialization
+///
+/// With `ignore` set the `HCLOG_OPT_*` variables, `HCLOG_LEVEL`, `HCLOG_FACADE`,
+/// `HCLOG_INSTANCE_ID` and `CLOG_DEBUG` are not read when a [`Scope`] is initialized, so the
+/// configuration of the code is used as is. This is meant for hermetic deployments where a
+/// variable lingering in a shell must not alter the logging. Call it before the first
+/// initialization; already initialized Scopes keep their configuration. The helpers of the
+/// [`env`](crate::env) module still read the environment.
 ///
 /// # Examples
 ///
 /// ```rust
+/// use hclog::{Level, FacadeVariant, Scope, options::Options};
 /// # use hclog_macros::HCLog;
-/// // assume a call something like this:
-/// // $ myapp _all:warn,ma:info,mb:debug10
 ///
 /// # #[derive(Copy, Clone, HCLog)]
-/// enum LogKeys { MA, MB }
+/// enum Keys { IE }
 ///
-/// fn main() {
-///     // library initialization here - skipped for brevity
-///     let args = std::env::args().skip(1).collect::<Vec<_>>();
-///     match hclog::set_mod_level(&args) {
-///         Ok(_) => (),
-///         Err(e) => panic!("Error: {}", e),
-///     }
-/// }
+/// hclog::ignore_env(true);
+/// Keys::init("hermetic", Level::Info, FacadeVariant::StdOut, Options::default()).unwrap();
+/// assert!(hclog::env_overrides().is_empty());
 /// ```
-///
-/// # Errors
-///
-/// Returns an error if:
-/// * [ContextLock]: the internal context can't be accessed
-/// * [ParseArg]: parsing the input fails
-/// * [KeyNotInitialized]: the module is not initialized
-/// * [UnknownLogLevel]: the log level is unknown
-///
-pub fn set_mod_level<'a, I, S>(it: I) -> Result<()>
-where
-    S: AsRef<str> + ?Sized + 'a,
-    I: IntoIterator<Item = &'a S>
-{
-    let mut ctx = CTX::get_mut()?;
-    for arg in it.into_iter().flat_map(|a| {
-        a.as_ref().split(',').collect::<Vec<_>>()
-    }) {
-        let Some((module, level)) = arg.split_once(":") else {
-            return Err(ParseArg);
-        };
-        if module.is_empty() || level.is_empty() {
-            return Err(ParseArg);
-        }
-        let level = level.parse::<Level>()?;
-        if module.eq_ignore_ascii_case("_all") {
-            for logmod in ctx.logmods_mut() {
-                for submod in logmod.submodules_mut() {
-                    submod.set_logsev(level);
-                }
-            }
-        } else {
-            match ctx.get_submod_by_name(module) {
-                None => return Err(KeyNotInitialized),
-                Some(m) => m.set_logsev(level),
-            };
-        }
-    }
-    Ok(())
+pub fn ignore_env(ignore: bool) {
+    crate::env::set_ignored(ignore);
 }
 
-/// Check if a module is initialized in a given [`Scope`]
+/// Get the origin of the level, facade and options of a LogKey `K`
+///
+/// Tells whether the current level, facade and options of the LogKey were set by the code on
+/// initialization, taken from the environment, passed via [`set_mod_level_cli`] or changed at
+/// runtime. A LogKey inheriting its level from a parent (see [`set_parent`]) reports the
+/// [`Source`] of the inherited level.
 ///
 /// # Examples
 ///
 /// ```rust
+/// use hclog::{Level, Source};
 /// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Keys { PR }
+///
+/// # Keys::init_with_defaults("test").unwrap();
+/// hclog::set_level(Keys::PR, Level::Debug1).unwrap();
+/// let provenance = hclog::provenance(Keys::PR).unwrap();
+/// assert_eq!(provenance.level, Source::Runtime);
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn provenance<K: LogKey>(k: K) -> Result<Provenance> {
+    CTX::call(|ctx| {
+        let lm = ctx.get_mod(K::logscope())?;
+        let submod = lm.get_submodule(k).filter(|s| s.initialized()).ok_or(KeyNotInitialized)?;
+        Ok(lm.provenance(submod))
+    })
+}
+
+/// Check the configuration of all initialized [`Scope`]s for common mistakes
+///
+/// The returned list contains a [`ConfigWarning`] for each misconfiguration found, e.g. a
+/// [`LogKey`] which is enabled but has no facade or options which have no effect. See
+/// [`ConfigWarningKind`](crate::ConfigWarningKind) for all checks. An application may log the
+/// warnings or fail its tests if the list is not empty. The list is empty as well if the
+/// internal context can't be accessed.
+///
+/// # Examples
+///
+/// ```rust
+/// for warning in hclog::validate_configuration() {
+///     eprintln!("hclog: {}", warning);
+/// }
+/// ```
+pub fn validate_configuration() -> Vec<ConfigWarning> {
+    match CTX::get() {
+        Ok(ctx) => ctx.logmods().filter(|lm| lm.initialized()).flat_map(check_scope).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Verify that LogKeys match their entries in the context
+///
+/// Checks for each of the given LogKeys that the entry of its [`Scope`] at the index returned
+/// by [`LogKey::log_key`] exists, is initialized and has the name of the LogKey. Otherwise the
+/// messages of the LogKey end up at the wrong entry or fail, which happens e.g. with hand
+/// written [`LogKey`] implementations or after merging two enums into one Scope. The returned
+/// list contains a [`KeyMismatch`] for each inconsistency found and is empty if all LogKeys are
+/// consistent.
+///
+/// The derive macro generates a test calling this for all variants with the `selftest`
+/// attribute.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Keys { VA, VB }
+///
+/// # Keys::init_with_defaults("test").unwrap();
+/// assert_eq!(hclog::verify_keys(&[Keys::VA, Keys::VB]), Ok(vec![]));
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn verify_keys<'a, K: LogKey + 'a>(keys: impl IntoIterator<Item = &'a K>)
+    -> Result<Vec<KeyMismatch>>
+{
+    let keys = keys.into_iter().collect::<Vec<_>>();
+    CTX::call(|ctx| Ok(check_keys(ctx.get_mod(K::logscope())?, keys.iter().copied())))
+}
+
+/* scope access (async stuff) */
+#[doc(hidden)]
+pub fn scope<I, K, F>(ident: I, key: K, future: F) -> Result<impl Future<Output = F::Output>>
+where
+    I: Display + Send,
+    K: LogKey,
+    F: Future + Send
+{
+    CTX::new_scoped(ident, key, future)
+}
+
+/// Get the ident of the task scope the caller runs in
+///
+/// Returns the `ident` passed to [`scope`] or [`async_scope_for`] if called from within the
+/// future, `None` in the global context. This allows to tag other output like metrics with
+/// the same identifier as the log messages. Only the task local context is read, no lock is
+/// taken.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::ScopeKey;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// # enum Keys { Net }
+/// # Keys::init_with_defaults("test").unwrap();
+/// assert_eq!(hclog::current_scope_ident(), None);
+/// let task = hclog::async_scope_for("worker-1", ScopeKey::Application, async {
+///     assert_eq!(hclog::current_scope_ident().as_deref(), Some("worker-1"));
+/// }).unwrap();
+/// // poll `task` with the executor of your choice
+/// ```
+pub fn current_scope_ident() -> Option<String> {
+    CTX::task_ident()
+}
+
+/// Take a copy of the task scope the caller runs in
+///
+/// Returns `None` in the global context. The snapshot can be moved to another thread and
+/// installed there via [`ScopeSnapshot::install_for`], which makes the messages logged from
+/// that thread carry the ident of the task. [`spawn_scoped`] does both for a new thread.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{ScopeKey, lI};
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// # enum Keys { Net }
+/// # use Keys::*;
+/// # Keys::init_with_defaults("test").unwrap();
+/// assert!(hclog::current_scope_snapshot().is_none());
+/// let task = hclog::async_scope_for("worker-1", ScopeKey::Application, async {
+///     let snapshot = hclog::current_scope_snapshot().unwrap();
+///     std::thread::spawn(move || snapshot.install_for(|| {
+///         lI!(Net, "logged with the task identifier");
+///     })).join().unwrap();
+/// }).unwrap();
+/// // poll `task` with the executor of your choice
+/// ```
+pub fn current_scope_snapshot() -> Option<ScopeSnapshot> {
+    CTX::snapshot()
+}
+
+/// Spawn a named thread which runs in the task scope of the caller
+///
+/// A thin wrapper around [`std::thread::Builder`]: the task local context of the caller is
+/// copied via [`current_scope_snapshot`] before the thread is spawned and installed for the
+/// duration of `f`. Called outside of a task scope this behaves exactly like spawning the
+/// thread with the builder directly.
+///
+/// With the `single-thread` feature enabled the context isn't shared with other threads (see
+/// [Single-thread mode](crate#single-thread-mode)), so there is nothing to copy: the thread is
+/// spawned without a task scope and logging from it returns
+/// [`ScopeNotInitialized`](ErrorKind::ScopeNotInitialized) like from any other thread.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{ScopeKey, lI};
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// # enum Keys { Net }
+/// # use Keys::*;
+/// # Keys::init_with_defaults("test").unwrap();
+/// let task = hclog::async_scope_for("worker-1", ScopeKey::Application, async {
+///     let child = hclog::spawn_scoped("worker-1-io", || {
+///         lI!(Net, "logged with the task identifier");
+///     }).unwrap();
+///     child.join().unwrap();
+/// }).unwrap();
+/// // poll `task` with the executor of your choice
+/// ```
+///
+/// # Errors
+///
+/// Returns the error of [`std::thread::Builder::spawn`] if the thread can't be created.
+pub fn spawn_scoped<N, F, T>(name: N, f: F) -> std::io::Result<std::thread::JoinHandle<T>>
+where
+    N: Into<String>,
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    // the facades of the snapshot can't be sent to another thread in single-thread mode
+    #[cfg(not(feature = "single-thread"))]
+    let f = {
+        let snapshot = CTX::snapshot();
+        move || match snapshot {
+            Some(snapshot) => snapshot.install_for(f),
+            None => f(),
+        }
+    };
+    std::thread::Builder::new().name(name.into()).spawn(f)
+}
+
+/// Check if the caller runs within a task scope
+///
+/// Returns `true` if called from within a future passed to [`scope`] or [`async_scope_for`].
+/// Like [`current_scope_ident`] no lock is taken.
+pub fn in_task_scope() -> bool {
+    CTX::in_task_scope()
+}
+
+/// Run a future with a task local copy of a whole [`Scope`]
+///
+/// The returned future runs `future` with a task local context containing all initialized
+/// LogKeys of the scope `scope_key`. Messages logged within the future carry `ident` as the
+/// task identifier. Changes to the configuration of those LogKeys within the future (e.g. via
+/// [`set_level`]) only affect the task local copy. LogKeys of other scopes fall back to the
+/// global context.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{Level, ScopeKey, lI};
+/// # use hclog_macros::HCLog;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Keys { Net, Db }
+/// use Keys::*;
+/// # use hclog::Scope;
+///
+/// # Keys::init_with_defaults("test").unwrap();
+/// let task = hclog::async_scope_for("worker-1", ScopeKey::Application, async {
+///     hclog::set_level(Db, Level::Debug5).unwrap();
+///     lI!(Net, "logged with the task identifier");
+/// }).unwrap();
+/// // poll `task` with the executor of your choice
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the scope is not initialized ([`ScopeNotInitialized`])
+/// * the `scope_key` doesn't belong to a usable scope ([`InvalidScope`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn async_scope_for<I, F>(ident: I, scope_key: ScopeKey, future: F)
+    -> Result<impl Future<Output = F::Output>>
+where
+    I: Display + Send,
+    F: Future + Send
+{
+    CTX::new_scoped_for(ident, scope_key, future)
+}
+
+/* submod mgmt functions */
+/// Initialize a [`Scope`] with a list of provided [`LogKey`]s.
+///
+/// The passed [`Level`], [`FacadeVariant`] and [`Options`] will be used for the [`Scope`]
+/// and passed down to the each LogKey unless they provide custom values via the [`LogKey`]
+/// trait implementation.
+///
+/// The given modname `S` is the name of the scope used for logging like the binary name in
+/// syslog. The modules `I` input is everything which can be converted to a iterator of
+/// [`LogKey`]s or references to them, see [`add_submodules()`].
+///
+/// This is a shortcut for calling [`init()`] and [`add_submodules()`] in sequence.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{Scope, Level, FacadeVariant, options::Options, Result};
+/// # use hclog::{LogKey, ContextKey};
+///
+/// #[derive(Copy, Clone)]
+/// enum LogKeys { IA, IB, IC }
+///
+/// # impl std::fmt::Display for LogKeys {
+/// #    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// #        match *self {
+/// #            Self::IA => fmt.write_str("IA"),
+/// #            Self::IB => fmt.write_str("IB"),
+/// #            Self::IC => fmt.write_str("IC"),
+/// #        }
+/// #    }
+/// # }
+///
+/// impl Scope for LogKeys {
+///     fn init<S: std::fmt::Display>(
+///         name: S, level: Level, facade: FacadeVariant, options: Options
+///     ) -> Result<()> {
+///         // instead of calling:
+///         // hclog::init<Self, S>(name, level, facade, options)?;
+///         // hclog::add_submodules(&[Self::IA, Self::IB, Self::IC])
+///         // you could call:
+///         hclog::init_modules(name, &[Self::IA, Self::IB, Self::IC], level, facade, options)
+///     }
+/// }
+///
+/// # impl LogKey for LogKeys {
+/// #    fn log_key(&self) -> ContextKey { *self as ContextKey }
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if:
+/// * [ContextLock]: the internal context can't be accessed
+/// * [LogCompatInitialized]: the compatibility layer is already initialized
+///
+pub fn init_modules<I, S>(
+    modname: S, modules: I, l: Level, f: FacadeVariant, o: Options
+) -> Result<()>
+where
+    I: IntoIterator,
+    I::Item: IntoLogKey,
+    S: Display,
+{
+    self::init::<<I::Item as IntoLogKey>::Key, S>(modname, l, f, o)?;
+    add_submodules(modules)
+}
+
+/// Add a list of [`LogKey`]s to the a [`Scope`]
+///
+/// Add a set of [`LogKey`] implementors to their reserved [`Scope`]. The [`Scope`] must be
+/// initialized before calling this function. The passed Iterator `I` may yield references to
+/// a [`LogKey`] implementor, e.g. from a slice, or the keys itself if they implement
+/// [`IntoLogKey`] as done by the derive macro. So `&[A, B]`, `vec![A, B]` or
+/// `MyKeys::iter().filter(..)` can be passed alike.
+///
+/// The keys are added in order. The first key failing to be added aborts the call, the keys
+/// added before stay in the [`Scope`].
+///
+/// This function is usually called inside the [`init()`] function of a [`Scope`] implementation
+/// but is not limited to. It can be called at any time to add more modules to a scope.
+///
+/// The `LogKey`s receive the [`Level`], [`FacadeVariant`] and [`Options`] from the [`Scope`]
+/// or can provide custom values via the [`LogKey`] trait implementation.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{Scope, Level, FacadeVariant, options::Options, Result};
+/// # use hclog::{LogKey, ContextKey};
+///
+/// #[derive(Copy, Clone)]
+/// enum LogKeys { AA, AB, AC }
+///
+/// # impl std::fmt::Display for LogKeys {
+/// #    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// #        match *self {
+/// #            Self::AA => fmt.write_str("AA"),
+/// #            Self::AB => fmt.write_str("AB"),
+/// #            Self::AC => fmt.write_str("AC"),
+/// #        }
+/// #    }
+/// # }
+///
+/// # impl LogKey for LogKeys {
+/// #    fn log_key(&self) -> ContextKey { *self as ContextKey }
+/// # }
+///
+/// impl Scope for LogKeys {
+///     fn init<S: std::fmt::Display>(
+///         name: S, level: Level, facade: FacadeVariant, options: Options
+///     ) -> Result<()> {
+///         hclog::init::<Self, S>(name, level, facade, options)?;
+///         hclog::add_submodules(&[Self::AA, Self::AB, Self::AC])
+///     }
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if:
+/// * [ContextLock]: the internal context can't be accessed
+/// * [ScopeNotInitialized]: the scope is not initialized
+/// * [KeyCollision]: the index of a `LogKey` is already used by a `LogKey` with a different
+///   name or another `LogKey` of the `Scope` has a name only differing by case, see
+///   [`add_submodules_with`]
+/// * [KeyNotInitialized]: the index of a `LogKey` is not below
+///   [`MAX_SUBMODULES`](crate::MAX_SUBMODULES)
+/// * [ParseArg]: the name of a `LogKey` is empty or only consists of whitespace. Surrounding
+///   whitespace is removed from the name.
+///
+pub fn add_submodules<I>(it: I) -> Result<()>
+where
+    I: IntoIterator,
+    I::Item: IntoLogKey,
+{
+    add_submodules_with(it, MergePolicy::Error)
+}
+
+/// Add a list of [`LogKey`]s to a [`Scope`] with a given [`MergePolicy`]
+///
+/// Works like [`add_submodules()`] but allows to choose what happens if the index of a
+/// [`LogKey`] is already used by a [`LogKey`] with a different name. This happens if multiple
+/// types are initialized in the same [`Scope`], e.g. a library exposing its own keys which
+/// are initialized in the [`ScopeKey::Application`](crate::ScopeKey) scope of the application.
+///
+/// Adding a [`LogKey`] with the same name again is always ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::MergePolicy;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Key { AW }
+///
+/// # Key::init_with_defaults("test").unwrap();
+/// hclog::add_submodules_with(&[Key::AW], MergePolicy::Skip).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if:
+/// * [ContextLock]: the internal context can't be accessed
+/// * [ScopeNotInitialized]: the scope is not initialized
+/// * [KeyCollision]: the index of a `LogKey` is already in use or another `LogKey` of the
+///   `Scope` has a name only differing by case, and the policy is not [`MergePolicy::Skip`]
+/// * [KeyNotInitialized]: the index of a `LogKey` is not below
+///   [`MAX_SUBMODULES`](crate::MAX_SUBMODULES)
+pub fn add_submodules_with<I>(it: I, policy: MergePolicy) -> Result<()>
+where
+    I: IntoIterator,
+    I::Item: IntoLogKey,
+{
+    let mut ctx = CTX::get_mut()?;
+    for m in it.into_iter() {
+        let m = m.into_log_key();
+        ctx.get_mod_mut(<I::Item as IntoLogKey>::Key::logscope())?.merge_submodule(m, policy)?;
+    }
+    Ok(())
+}
+
+// Number of used indices in the Scope of `S`
+#[doc(hidden)]
+pub fn submodule_count<S: Scope>() -> Result<usize> {
+    Ok(CTX::get()?.get_mod(S::logscope())?.submodule_count())
+}
+
+/*
+ * add the LogKeys of a derived enum with MergePolicy::Append. The offset applied by their
+ * log_key() is the number of indices used in the Scope on the first call, taken under the
+ * same lock the keys are added with.
+ */
+#[doc(hidden)]
+pub fn append_submodules<I>(it: I, offset: &OnceLock<ContextKey>) -> Result<()>
+where
+    I: IntoIterator,
+    I::Item: IntoLogKey,
+{
+    let mut ctx = CTX::get_mut()?;
+    let logmod = ctx.get_mod_mut(<I::Item as IntoLogKey>::Key::logscope())?;
+    offset.get_or_init(|| logmod.submodule_count());
+    for m in it.into_iter() {
+        logmod.merge_submodule(m.into_log_key(), MergePolicy::Append)?;
+    }
+    Ok(())
+}
+
+static AUDIT: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable the audit trail of runtime changes
+///
+/// With the audit trail enabled every successful call to [`set_level`], [`set_mod_level`],
+/// [`set_logdest`], [`set_module_options`], [`unset_module_options`] and
+/// [`reset_module_options`] emits a message describing the change, like
+/// `level of 'db' changed info -> debug5`. The messages are logged with [`Level::Info`] via the
+/// [`InternalLogKeys::Internal`](enum@InternalLogKeys#variant@Internal) key, so its level and
+/// facade have to be set accordingly. The audit trail is disabled by default.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{InternalLogKeys, Level, FacadeVariant};
+///
+/// hclog::set_logdest(InternalLogKeys::Internal, FacadeVariant::StdErr).unwrap();
+/// hclog::set_level(InternalLogKeys::Internal, Level::Info).unwrap();
+/// hclog::set_audit(true);
+/// ```
+pub fn set_audit(enabled: bool) {
+    AUDIT.store(enabled, Ordering::Relaxed);
+}
+
+/*
+ * The Internal key needs the context itself - only call this after the context lock
+ * is released again.
+ */
+fn audit(change: Arguments) {
+    if AUDIT.load(Ordering::Relaxed) {
+        // the change itself succeeded, don't report a failing audit facade to the caller
+        let _ = log(Internal, Level::Info, file!(), None, line!(), &change);
+    }
+}
+
+// level of a `key:level` argument of set_mod_level
+#[derive(Copy, Clone)]
+enum LevelArg {
+    Absolute(Level),
+    // steps relative to the current level, e.g. `+2`
+    Relative(isize),
+    // the pseudo levels `mute` and `unmute`, the level is kept
+    Mute(bool),
+}
+impl LevelArg {
+    fn parse(s: &str) -> Result<Self> {
+        if name_eq(s, "mute") || name_eq(s, "unmute") {
+            return Ok(Self::Mute(name_eq(s, "mute")));
+        }
+        if !s.starts_with(['+', '-']) {
+            return Ok(Self::Absolute(s.parse()?));
+        }
+        match s[1..].parse::<u8>() {
+            Ok(n) if s.starts_with('-') => Ok(Self::Relative(-(n as isize))),
+            Ok(n) => Ok(Self::Relative(n as isize)),
+            Err(_) => Err(ParseArg),
+        }
+    }
+    fn apply(self, current: Level) -> Level {
+        match self {
+            Self::Absolute(level) => level,
+            Self::Relative(n) => {
+                let level = (current as isize + n)
+                    .clamp(Level::min() as isize, Level::max() as isize);
+                Level::from_u8(level as u8).unwrap_or(current)
+            },
+            Self::Mute(_) => current,
+        }
+    }
+}
+impl Display for LevelArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Absolute(level) => write!(f, "changed to {}", level),
+            Self::Relative(n) => write!(f, "changed by {:+}", n),
+            Self::Mute(true) => write!(f, "muted"),
+            Self::Mute(false) => write!(f, "unmuted"),
+        }
+    }
+}
+
+/// Set the log level for a list of modules.
+///
+/// The input `I` must be an iterator of string slices which are formatted as
+/// `key:level,key:level,...`. The `key` is the name of the module and the `level`
+/// is a valid [`Level`] as string, including the syslog forms like `debug` or the numeric
+/// severity `6` (see [`Level::from_str`](Level#impl-FromStr-for-Level)).
+///
+/// The `key` can be the name of a known [`LogKey`] or `_all` to set the log level
+/// for all available `LogKey`s in the current `Scope`, except those exempt via
+/// [`set_all_exempt`]. The `Scope`, `LogKey` and `Level` names
+/// are case insensitive (ASCII). A `key` can be qualified with the name of its `Scope` as
+/// `scope/key` to address a name used in several `Scope`s.
+///
+/// A `key` ending with `.*` (e.g. `net.*:debug3`) sets the level of the `LogKey` and resets
+/// all its descendants (see [`set_parent`]) to inherit it, dropping their explicit levels.
+///
+/// A `level` starting with `+` or `-` followed by a number (e.g. `net:+2`) adjusts the current
+/// level of the `LogKey` by the given number of steps instead. The result is clamped to the
+/// range from [`Off`](Level::Off) to [`Debug10`](Level::Debug10), so `+1` on
+/// [`Info`](Level::Info) yields [`Debug1`](Level::Debug1).
+///
+/// The pseudo levels `mute` and `unmute` (e.g. `net:mute`) switch the `LogKey` off and on
+/// again without changing its level, see [`mute`].
+///
+/// The arguments are applied in order, so a later argument overrides an earlier one for the
+/// same `LogKey` (last wins) and relative levels build on the levels set before. All arguments
+/// are parsed and resolved before the first level changes and are applied with a single lock
+/// of the context. If any of them fails, no level is changed at all.
+///
+/// This function is primarily used for setting the log level at runtime via the commandline
+/// or environment variables.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hclog_macros::HCLog;
+/// // assume a call something like this:
+/// // $ myapp _all:warn,ma:info,mb:debug10
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum LogKeys { MA, MB }
+///
+/// fn main() {
+///     // library initialization here - skipped for brevity
+///     let args = std::env::args().skip(1).collect::<Vec<_>>();
+///     match hclog::set_mod_level(&args) {
+///         Ok(_) => (),
+///         Err(e) => panic!("Error: {}", e),
+///     }
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if:
+/// * [ContextLock]: the internal context can't be accessed
+/// * [InvalidLevelArg]: an argument failed, it contains the argument and one of the following
+///   errors:
+///   * [ParseArg]: parsing the argument fails
+///   * [KeyNotInitialized]: the module is not initialized
+///   * [AmbiguousKeyName]: the module name is used by `LogKey`s in different `Scope`s. Qualify
+///     the name with the `Scope` or use the `prefix` attribute of the derive macro to make the
+///     names unique.
+///   * [UnknownLogLevel]: the log level is unknown. The message of the error suggests a level
+///     with a similar name, if there is one.
+///
+pub fn set_mod_level<'a, I, S>(it: I) -> Result<()>
+where
+    S: AsRef<str> + ?Sized + 'a,
+    I: IntoIterator<Item = &'a S>
+{
+    set_mod_level_from(it, Source::Runtime)
+}
+
+/// Set the log level for a list of modules from the commandline
+///
+/// Works like [`set_mod_level`] but records [`Source::Cli`] as the origin of the changed
+/// levels instead of [`Source::Runtime`], see [`provenance`]. Use this for the levels passed
+/// by an operator, e.g. via a `-l` flag, to tell them apart from levels set by the application.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{Level, Source};
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum LogKeys { MA, MB }
+///
+/// # LogKeys::init_with_defaults("test").unwrap();
+/// hclog::set_mod_level_cli(["ma:debug10"]).unwrap();
+/// assert_eq!(hclog::provenance(LogKeys::MA).unwrap().level, Source::Cli);
+/// ```
+///
+/// # Errors
+///
+/// Returns the same errors as [`set_mod_level`].
+pub fn set_mod_level_cli<'a, I, S>(it: I) -> Result<()>
+where
+    S: AsRef<str> + ?Sized + 'a,
+    I: IntoIterator<Item = &'a S>
+{
+    set_mod_level_from(it, Source::Cli)
+}
+
+fn set_mod_level_from<'a, I, S>(it: I, source: Source) -> Result<()>
+where
+    S: AsRef<str> + ?Sized + 'a,
+    I: IntoIterator<Item = &'a S>
+{
+    let chunks = it.into_iter().flat_map(|a| a.as_ref().split(',')).collect::<Vec<_>>();
+    let mut ctx = CTX::get_mut()?;
+    // resolve every argument first, nothing is changed if any of them fails
+    let plan = chunks.into_iter()
+        .map(|chunk| LevelStep::resolve(&mut ctx, chunk)
+            .map_err(|e| InvalidLevelArg(chunk.to_owned(), Box::new(e))))
+        .collect::<Result<Vec<_>>>()?;
+    let mut changes = Vec::with_capacity(plan.len());
+    for step in plan {
+        changes.push(step.apply(&mut ctx, source));
+    }
+    drop(ctx);
+    for change in changes {
+        audit(format_args!("{}", change));
+    }
+    Ok(())
+}
+
+// a `key:level` argument of set_mod_level resolved against the current context
+struct LevelStep<'a> {
+    module: &'a str,
+    target: LevelTarget,
+    arg: LevelArg,
+}
+enum LevelTarget {
+    All,
+    // the key and all its descendants
+    Subtree(ScopeKey, ContextKey),
+    Key(ScopeKey, ContextKey),
+}
+impl<'a> LevelStep<'a> {
+    fn resolve(ctx: &mut Context, chunk: &'a str) -> Result<Self> {
+        let Some((module, level)) = chunk.split_once(':') else {
+            return Err(ParseArg);
+        };
+        if module.is_empty() || level.is_empty() {
+            return Err(ParseArg);
+        }
+        let arg = LevelArg::parse(level)?;
+        if name_eq(module, "_all") {
+            return Ok(Self { module, target: LevelTarget::All, arg });
+        }
+        let (module, subtree) = match module.strip_suffix(".*") {
+            Some(root) => (root, true),
+            None => (module, false),
+        };
+        let (logmod, key) = ctx.get_submod_by_name(module)?;
+        logmod.get_submodule_at(key).ok_or(KeyNotInitialized)?;
+        let target = match subtree {
+            true => LevelTarget::Subtree(logmod.key(), key),
+            false => LevelTarget::Key(logmod.key(), key),
+        };
+        Ok(Self { module, target, arg })
+    }
+    // the keys were resolved under the same lock, so they are still initialized
+    fn apply(self, ctx: &mut Context, source: Source) -> String {
+        let arg = self.arg;
+        if let LevelArg::Mute(muted) = arg {
+            return self.apply_mute(ctx, muted);
+        }
+        match self.target {
+            LevelTarget::All => {
+                let mut skipped = 0;
+                for logmod in ctx.logmods_mut() {
+                    // inherited levels are read before any key of the scope changes
+                    let levels = logmod.submodules()
+                        .map(|submod| arg.apply(logmod.effective_level(submod)))
+                        .map(|level| logmod.normalize_level(level))
+                        .collect::<Vec<_>>();
+                    for (submod, level) in logmod.submodules_mut().zip(levels) {
+                        if submod.all_exempt() {
+                            skipped += 1;
+                            continue;
+                        }
+                        submod.set_logsev(level).set_level_source(source);
+                    }
+                }
+                ctx.mirror_all(Mirror::Level);
+                match skipped {
+                    0 => format!("level of all keys {}", arg),
+                    n => format!("level of all keys {}, {} exempt keys skipped", arg, n),
+                }
+            }
+            LevelTarget::Subtree(scope, key) => {
+                let logmod = &mut ctx[scope];
+                if let Some(submod) = logmod.get_submodule_at(key) {
+                    let level = logmod.normalize_level(arg.apply(logmod.effective_level(submod)));
+                    let changed = logmod.set_subtree_level(key, level);
+                    if let Some(root) = logmod.get_submodule_at_mut(key) {
+                        root.set_level_source(source);
+                    }
+                    for key in changed {
+                        ctx.mirror((scope, key), Mirror::Level);
+                    }
+                }
+                format!("level of '{}' and its descendants {}", self.module, arg)
+            }
+            LevelTarget::Key(scope, key) => {
+                let logmod = &mut ctx[scope];
+                let level = logmod.get_submodule_at(key)
+                    .map(|submod| arg.apply(logmod.effective_level(submod)))
+                    .map(|level| logmod.normalize_level(level));
+                let change = match (level, logmod.get_submodule_at_mut(key)) {
+                    (Some(level), Some(submod)) => {
+                        let old = submod.logsev();
+                        submod.set_logsev(level).set_level_source(source);
+                        format!("level of '{}' changed {} -> {}", submod.name(), old, level)
+                    }
+                    _ => format!("level of '{}' {}", self.module, arg),
+                };
+                ctx.mirror((scope, key), Mirror::Level);
+                change
+            }
+        }
+    }
+    // like apply, but only the mute switch changes
+    fn apply_mute(self, ctx: &mut Context, muted: bool) -> String {
+        match self.target {
+            LevelTarget::All => {
+                let mut skipped = 0;
+                for logmod in ctx.logmods_mut() {
+                    for submod in logmod.submodules_mut() {
+                        if submod.all_exempt() {
+                            skipped += 1;
+                            continue;
+                        }
+                        submod.set_muted(muted);
+                    }
+                }
+                ctx.mirror_all(Mirror::Level);
+                match skipped {
+                    0 => format!("all keys {}", self.arg),
+                    n => format!("all keys {}, {} exempt keys skipped", self.arg, n),
+                }
+            }
+            LevelTarget::Subtree(scope, key) => {
+                for key in ctx[scope].set_subtree_muted(key, muted) {
+                    ctx.mirror((scope, key), Mirror::Level);
+                }
+                format!("'{}' and its descendants {}", self.module, self.arg)
+            }
+            LevelTarget::Key(scope, key) => {
+                if let Some(submod) = ctx[scope].get_submodule_at_mut(key) {
+                    submod.set_muted(muted);
+                }
+                ctx.mirror((scope, key), Mirror::Level);
+                format!("'{}' {}", self.module, self.arg)
+            }
+        }
+    }
+}
+
+/// Exempt a LogKey `K` from level changes for all LogKeys
+///
+/// An exempt LogKey keeps its level if the level of all LogKeys is set via `_all` in
+/// [`set_mod_level`] (or the `level._all` entry of [`apply_config_map`]), e.g. an audit
+/// LogKey which must not be silenced by `_all:off` or a very verbose LogKey which must not be
+/// turned up by `_all:debug10`. The same holds for the `HCLOG_LEVEL` environment variable
+/// while the LogKey is initialized, this can be set via the `pin_level` attribute of the
+/// derive macro. Setting the level of the LogKey by its name or via [`set_level`] still
+/// changes it.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::Level;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Keys { Audit, Net }
+///
+/// # Keys::init("test", Level::Info, hclog::FacadeVariant::None, hclog::options::NONE).unwrap();
+/// hclog::set_all_exempt(Keys::Audit, true).unwrap();
+/// hclog::set_mod_level(["_all:off"]).unwrap();
+/// assert_eq!(hclog::test_log(Keys::Audit, Level::Info), Ok(true));
+/// assert_eq!(hclog::test_log(Keys::Net, Level::Info), Ok(false));
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn set_all_exempt<K: LogKey>(k: K, exempt: bool) -> Result<()> {
+    CTX::call_mut(|ctx| {
+        ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k).ok_or(KeyNotInitialized)?
+            .set_all_exempt(exempt);
+        Ok(())
+    })
+}
+
+/// Mute a LogKey `K` without changing its level
+///
+/// A muted LogKey doesn't log any message regardless of its [`Level`], including the messages
+/// of the log crate if `K` is the
+/// [`InternalLogKeys::LogCompat`](enum@InternalLogKeys#variant@LogCompat) key. Other than
+/// setting the level to [`Off`](Level::Off) the configured level is kept, so [`unmute`]
+/// restores the previous verbosity exactly. Changes of the level while muted take effect after
+/// unmuting. The descendants of `K` (see [`set_parent`]) aren't muted, use `key.*:mute` in
+/// [`set_mod_level`] for that.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::Level;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Keys { Net }
+///
+/// # Keys::init("test", Level::Info, hclog::FacadeVariant::None, hclog::options::NONE).unwrap();
+/// hclog::mute(Keys::Net).unwrap();
+/// assert_eq!(hclog::test_log(Keys::Net, Level::Emerg), Ok(false));
+/// hclog::unmute(Keys::Net).unwrap();
+/// assert_eq!(hclog::test_log(Keys::Net, Level::Info), Ok(true));
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn mute<K: LogKey>(k: K) -> Result<()> {
+    set_muted(k, true)
+}
+
+/// Unmute a LogKey `K` muted by [`mute`]
+///
+/// The LogKey logs with its current level again. Unmuting a LogKey which isn't muted has no
+/// effect.
+///
+/// # Errors
+///
+/// Returns the same errors as [`mute`].
+pub fn unmute<K: LogKey>(k: K) -> Result<()> {
+    set_muted(k, false)
+}
+
+fn set_muted<K: LogKey>(k: K, muted: bool) -> Result<()> {
+    let name = CTX::call_mut(|ctx| {
+        let submod = ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k)
+            .ok_or(KeyNotInitialized)?;
+        let name = submod.set_muted(muted).name().to_owned();
+        ctx.mirror((K::logscope(), k.log_key()), Mirror::Level);
+        Ok(name)
+    })?;
+    audit(format_args!("'{}' {}", name, LevelArg::Mute(muted)));
+    Ok(())
+}
+
+/// Check whether a LogKey `K` is muted
+///
+/// See [`mute`].
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn is_muted<K: LogKey>(k: K) -> Result<bool> {
+    CTX::call(|ctx| {
+        Ok(ctx.get_mod(K::logscope())?.get_submodule(k).ok_or(KeyNotInitialized)?.muted())
+    })
+}
+
+/// Apply levels, facades and options from a flat map of settings
+///
+/// Each key of the map has the form `<setting>.<key>`, where `<setting>` is `level`, `facade`
+/// or `options` and `<key>` is the name of a [`LogKey`], a name qualified by its Scope
+/// (`scope/key`) or `_all` for all initialized LogKeys. The values are parsed as:
+///
+/// * `level`: a [`Level`] or a relative change as accepted by [`set_mod_level`]
+/// * `facade`: a [`FacadeVariant`] with an optional argument, e.g. `file:/var/log/app.log`
+/// * `options`: [`Options`] replacing the current ones, e.g. `TIMESTAMP,PID`, or flags added to
+///   and removed from the current ones if the value starts with `+` or `-`, e.g. `+PID-FILE`
+///
+/// Entries for `_all` are applied first, so the entries of single LogKeys take precedence
+/// regardless of the order of the map. A failing entry doesn't stop the remaining ones, the
+/// returned [`ConfigReport`] lists which entries were applied, skipped because the setting or
+/// LogKey is unknown or failed. A facade writing to a file is opened before the context is
+/// locked and shared by all addressed LogKeys, a file which can't be opened fails the entry with
+/// [`IoError`]. Setting [`LOGCOMPAT`] this way doesn't install the compatibility layer, use
+/// [`set_module_options`] for that.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::HashMap;
+///
+/// let map = HashMap::from([
+///     ("level._all".to_owned(), "warn".to_owned()),
+///     ("options._all".to_owned(), "+PID".to_owned()),
+///     ("facade.unknown".to_owned(), "stderr".to_owned()),
+/// ]);
+/// let report = hclog::apply_config_map(&map).unwrap();
+/// for (entry, err) in report.failed() {
+///     eprintln!("{}: {}", entry, err);
+/// }
+/// ```
+///
+/// # Errors
+///
+/// * [`ContextLock`]: failed to lock the internal context, entries applied before stay applied
+pub fn apply_config_map(map: &HashMap<String, String>) -> Result<ConfigReport> {
+    let is_all = |entry: &str| entry.split_once('.').is_some_and(|(_, key)| name_eq(key, "_all"));
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(entry, _)| (!is_all(entry), entry.as_str()));
+    let mut report = ConfigReport::default();
+    for (entry, value) in entries {
+        let res = match entry.split_once('.') {
+            // a value with a separator of set_mod_level would change other keys as well
+            Some((setting, _)) if name_eq(setting, "level") && value.contains([',', ':']) =>
+                Err(ParseArg),
+            Some((setting, key)) if name_eq(setting, "level") =>
+                set_mod_level(&[format!("{}:{}", key, value.trim())]).map_err(|e| match e {
+                    // the entry itself already tells which argument failed
+                    InvalidLevelArg(_, e) => *e,
+                    e => e,
+                }),
+            Some((setting, key)) if name_eq(setting, "facade") => value.trim()
+                .parse::<FacadeVariant>().map_err(|_| ParseArg)
+                .and_then(|facade| {
+                    // a file doesn't depend on the ident, open it once without holding the lock
+                    let opened = match facade.opens_file() {
+                        true => Some(FacadeScope::try_new(&facade, None)?),
+                        false => None,
+                    };
+                    with_keys_by_name(key, Mirror::Facade, |ident, submod| match opened {
+                        Some(ref logdest) => {
+                            submod.share_logdest(logdest);
+                        }
+                        None => {
+                            submod.set_logdest(&facade, ident);
+                        }
+                    })?;
+                    audit(format_args!("facade of '{}' changed to {}", key, facade));
+                    Ok(())
+                }),
+            Some((setting, key)) if name_eq(setting, "options") => OptionsArg::parse(value)
+                .and_then(|arg| {
+                    with_keys_by_name(key, Mirror::Options, |_, submod| {
+                        let new = arg.apply(submod.options());
+                        submod.unset_options(new.complement()).set_options(new);
+                    })?;
+                    audit(format_args!("options of '{}' changed by '{}'", key, value));
+                    Ok(())
+                }),
+            _ => {
+                report.skip(entry);
+                continue;
+            }
+        };
+        if matches!(res, Err(ContextLock)) {
+            return Err(ContextLock);
+        }
+        report.add(entry, res);
+    }
+    Ok(report)
+}
+
+// call `f` with the Scope name for the initialized LogKeys addressed by a name or `_all` and
+// mirror the changed part onto their aliases
+fn with_keys_by_name(
+    name: &str, what: Mirror, mut f: impl FnMut(&str, &mut Submodule),
+) -> Result<()> {
+    let mut ctx = CTX::get_mut()?;
+    if name_eq(name, "_all") {
+        for lm in ctx.logmods_mut().filter(|lm| lm.initialized()) {
+            let ident = lm.name().to_owned();
+            lm.submodules_mut().filter(|s| s.initialized()).for_each(|s| f(&ident, s));
+        }
+        ctx.mirror_all(what);
+        return Ok(());
+    }
+    let (lm, key) = ctx.get_submod_by_name(name)?;
+    let (scope, ident) = (lm.key(), lm.name().to_owned());
+    f(&ident, lm.get_submodule_at_mut(key).ok_or(KeyNotInitialized)?);
+    ctx.mirror((scope, key), what);
+    Ok(())
+}
+
+/// Check if a module is initialized in a given [`Scope`]
+///
+/// # Examples
+///
+/// ```rust
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
 ///
 /// # #[derive(Copy, Clone, HCLog)]
 /// enum LogKeys {
@@ -387,40 +1588,783 @@ where
 ///     C,
 /// }
 ///
-/// # LogKeys::init_with_defaults("test").unwrap();
-/// assert_eq!(hclog::has_module(LogKeys::A).unwrap(), true);
-/// assert_eq!(hclog::has_module(LogKeys::B).unwrap(), true);
-/// // assume C doesn't get initialized
-/// assert_eq!(hclog::has_module(LogKeys::C).unwrap(), false);
+/// # LogKeys::init_with_defaults("test").unwrap();
+/// assert_eq!(hclog::has_module(LogKeys::A).unwrap(), true);
+/// assert_eq!(hclog::has_module(LogKeys::B).unwrap(), true);
+/// // assume C doesn't get initialized
+/// assert_eq!(hclog::has_module(LogKeys::C).unwrap(), false);
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * [ContextLock]: the internal context can't be accessed
+/// * [ScopeNotInitialized]: the scope is not initialized
+///
+pub fn has_module<K: LogKey>(k: K) -> Result<bool> {
+    CTX::call(|ctx| { Ok(ctx.get_mod(K::logscope())?.has_submodule(k)) })
+}
+
+/// Set the log destination `FacadeVariant` for a given LogKey `K`
+///
+/// Alter the currently set [`FacadeVariant`] for a given LogKey at runtime. The LogKey `K` is an
+/// initialized logkey as passed to the `init_modules` or `add_submodules` function.
+/// The `FacadeVariant` can be any valid [`FacadeVariant`].
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::FacadeVariant;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum SomeKey { SL }
+///
+/// # SomeKey::init_with_defaults("test").unwrap();
+/// hclog::set_logdest(SomeKey::SL, FacadeVariant::StdErr).unwrap();
+/// ```
+///
+/// # Ordering
+///
+/// Once `set_logdest` returned, no log call started afterwards writes to the previous facade,
+/// regardless of the thread it's made from. The facade is replaced under the write lock of
+/// the internal context, while a log call holds the read lock from looking up the LogKey until
+/// the facade returned. So the change waits for the log calls in progress and the release of
+/// the write lock happens before every later acquisition of the read lock, which makes the
+/// new facade visible to those calls. Two things aren't covered:
+///
+/// * task local copies of the Scope (see [`scope`]) keep the facade they were created with
+/// * messages held back by a [`FlushPolicy`] and a pending repeat counter of the [`DEDUP`]
+///   option are written when they're flushed, the counter to the new facade
+///
+/// Use [`set_logdest_sync`] if those have to reach the previous facade before it's replaced.
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn set_logdest<K: LogKey>(k: K, facade: FacadeVariant) -> Result<()> {
+    CTX::call_mut(|ctx| replace_logdest(ctx, k, &facade))?;
+    audit(format_args!("facade of '{}' changed to {}", k, facade));
+    Ok(())
+}
+
+/// Set the log destination `FacadeVariant` for a LogKey `K` after flushing the previous one
+///
+/// Works like [`set_logdest`], but the pending repeat counter of the [`DEDUP`] option is
+/// written and the previous facade is flushed (see [`flush`]) before it's replaced. Both
+/// happen under the same write lock of the internal context as the replacement, so every
+/// message which went to the previous facade is completely written when the call returns,
+/// e.g. to verify a log file right after it was rotated. If another
+/// LogKey shares the previous facade, its buffered messages are flushed as well.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::FacadeVariant;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum SomeKey { SL }
+///
+/// # SomeKey::init_with_defaults("test").unwrap();
+/// hclog::set_logdest_sync(SomeKey::SL, FacadeVariant::StdErr).unwrap();
+/// // everything logged via SL so far has been written to the previous facade
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+/// * flushing the previous facade failed, the facade is replaced nevertheless
+pub fn set_logdest_sync<K: LogKey>(k: K, facade: FacadeVariant) -> Result<()> {
+    let flushed = CTX::call_mut(|ctx| {
+        let lm = ctx.get_mod(K::logscope())?;
+        let m = lm.get_submodule(k).ok_or(KeyNotInitialized)?;
+        let (ident, instance) = (lm.env_ident(), lm.instance_id());
+        let flushed = m.flush(lm.name(), lm.env(), ident, instance, lm.func_max_len());
+        replace_logdest(ctx, k, &facade)?;
+        Ok(flushed)
+    })?;
+    audit(format_args!("facade of '{}' changed to {}", k, facade));
+    flushed
+}
+
+// set_logdest within the lock held by the caller
+fn replace_logdest<K: LogKey>(ctx: &mut Context, k: K, facade: &FacadeVariant) -> Result<()> {
+    let lm = ctx.get_mod_mut(K::logscope())?;
+    let ident = lm.name().to_owned();
+    lm.get_submodule_mut(k).ok_or(KeyNotInitialized)?.set_logdest(facade, &ident);
+    ctx.mirror((K::logscope(), k.log_key()), Mirror::Facade);
+    Ok(())
+}
+
+/// Set the log destination `FacadeVariant` for all LogKeys matching a name pattern
+///
+/// Changes the facade like [`set_logdest`] for every initialized [`LogKey`] of all [`Scope`]s
+/// whose name matches `pattern`, e.g. to redirect a set of noisy LogKeys to a separate file
+/// during an incident. The `pattern` is one of:
+///
+/// * the name of a LogKey, matching the LogKeys with this name in all Scopes
+/// * a prefix followed by `*` (e.g. `net*`), matching all LogKeys whose name starts with it
+/// * `_all` (or `*`), matching all LogKeys
+///
+/// Like in [`set_mod_level`] the names are case insensitive (ASCII) and the pattern can be
+/// qualified with the name of a Scope as `scope/pattern` to only match the LogKeys of that
+/// Scope. All LogKeys are changed under a single lock of the context and share the facade,
+/// a file is opened only once. Syslog facades of LogKeys with the
+/// [`SYSLOG_IDENT`](crate::options::SYSLOG_IDENT) option are created per Scope to keep the
+/// ident of their Scope.
+///
+/// Returns the number of changed LogKeys.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::FacadeVariant;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Keys { NetRx, NetTx, Db }
+///
+/// # Keys::init_with_defaults("test").unwrap();
+/// assert_eq!(hclog::set_logdest_by_name("net*", FacadeVariant::StdErr), Ok(2));
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * no initialized LogKey matches the pattern ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+///
+/// # Panics
+///
+/// Changing the facade panics like [`set_logdest`], e.g. if the file can't be opened.
+pub fn set_logdest_by_name(pattern: &str, facade: FacadeVariant) -> Result<usize> {
+    let (scope, name) = match pattern.split_once('/') {
+        Some((scope, name)) => (Some(scope), name),
+        None => (None, pattern),
+    };
+    let matches = |key: &str| match name.strip_suffix('*') {
+        Some(prefix) => key.get(..prefix.len()).is_some_and(|start| name_eq(start, prefix)),
+        None => name_eq(name, "_all") || name_eq(key, name),
+    };
+    let mut ctx = CTX::get_mut()?;
+    let mut changed = Vec::new();
+    // one facade per ident, see Submodule::facade_ident
+    let mut facades: Vec<(Option<String>, FacadeScope)> = Vec::new();
+    for lm in ctx.logmods_mut().filter(|lm| lm.initialized()) {
+        if scope.is_some_and(|scope| !name_eq(lm.name(), scope)) {
+            continue;
+        }
+        let (key, ident) = (lm.key(), lm.name().to_owned());
+        for submod in lm.submodules_mut().filter(|s| s.initialized() && matches(s.name())) {
+            let facade_ident = submod.facade_ident(&ident);
+            let logdest = match facades.iter().find(|(i, _)| i.as_deref() == facade_ident) {
+                Some((_, logdest)) => logdest,
+                None => {
+                    let logdest = FacadeScope::new(&facade, facade_ident);
+                    facades.push((facade_ident.map(str::to_owned), logdest));
+                    &facades[facades.len() - 1].1
+                }
+            };
+            submod.share_logdest(logdest);
+            changed.push((key, submod.key()));
+        }
+    }
+    for key in &changed {
+        ctx.mirror(*key, Mirror::Facade);
+    }
+    drop(ctx);
+    if changed.is_empty() {
+        return Err(KeyNotInitialized);
+    }
+    audit(format_args!("facade of {} keys matching '{}' changed to {}",
+        changed.len(), pattern, facade));
+    Ok(changed.len())
+}
+
+/// Set the terminator written after each message of a LogKey `K`
+///
+/// The [`Newline`] policy is applied by the [`StdOut`](FacadeVariant::StdOut),
+/// [`StdErr`](FacadeVariant::StdErr) and [`File`](FacadeVariant::File) facades and kept if the
+/// facade is changed via [`set_logdest`]. With [`Newline::CrLf`] the lines within a multi-line
+/// message are terminated with `\r\n` as well. The [`Syslog`](FacadeVariant::Syslog) facade
+/// ignores the policy. The default is [`Newline::Lf`].
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::Newline;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum SomeKey { NL }
+///
+/// # SomeKey::init_with_defaults("test").unwrap();
+/// hclog::set_newline(SomeKey::NL, Newline::CrLf).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn set_newline<K: LogKey>(k: K, newline: Newline) -> Result<()> {
+    CTX::call_mut(|ctx| {
+        ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k).ok_or(KeyNotInitialized)?
+            .set_newline(newline);
+        Ok(())
+    })
+}
+
+/// Set when the facade of a LogKey `K` writes its buffered messages
+///
+/// The [`FlushPolicy`] is applied by the [`File`](FacadeVariant::File) facade and kept if the
+/// facade is changed via [`set_logdest`]. The default [`FlushPolicy::EveryMessage`] flushes
+/// after every message, which costs a syscall per message. Under high throughput
+/// [`FlushPolicy::EveryN`] or [`FlushPolicy::Interval`] coalesce the writes, the messages still
+/// buffered are written by [`flush`]. Switching back to [`FlushPolicy::EveryMessage`] writes
+/// them immediately.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::FlushPolicy;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum SomeKey { Access }
+///
+/// # SomeKey::init_with_defaults("test").unwrap();
+/// hclog::set_flush_policy(SomeKey::Access, FlushPolicy::EveryN(128)).unwrap();
+/// // ... log a lot
+/// hclog::flush().unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn set_flush_policy<K: LogKey>(k: K, policy: FlushPolicy) -> Result<()> {
+    CTX::call_mut(|ctx| {
+        ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k).ok_or(KeyNotInitialized)?
+            .set_flush_policy(policy);
+        Ok(())
+    })
+}
+
+/// Set the LogKey used by the default log macros
+///
+/// The default log macros [`dEM`](macro@crate::dEM) to [`dD10`](macro@crate::dD10) log without
+/// naming a LogKey, e.g. `hclog::dI!("connected to {}", addr)`. They route the message to the
+/// LogKey set here, which is useful while migrating from crates logging without a key: most
+/// call sites log to the default key while the important ones get their own keys. Until a
+/// default key is set the messages go to
+/// [`InternalLogKeys::Internal`](enum@InternalLogKeys#variant.Internal) with the same level.
+///
+/// The key is looked up by its indices on every message, like the keys passed to the other log
+/// macros. So within a task scope (see [`scope`]) the task-local copy of the key is used.
+/// Calling the function again changes the default key for all later messages. When the Scope
+/// of the default key is removed by [`reinitialize`] the messages go to the Internal key again.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Keys { Misc }
+///
+/// # Keys::init_with_defaults("test").unwrap();
+/// hclog::set_default_key(Keys::Misc).unwrap();
+/// hclog::dI!("logged via {}", "misc");
+/// assert_eq!(hclog::default_key().unwrap().as_deref(), Some("Misc"));
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn set_default_key<K: LogKey>(k: K) -> Result<()> {
+    let mut ctx = CTX::get_mut()?;
+    if !ctx.get_mod(K::logscope())?.has_submodule(k) {
+        return Err(KeyNotInitialized);
+    }
+    let name = k.to_string().into();
+    ctx.set_default_key(DefaultKey { scope: K::logscope(), key: k.log_key(), name });
+    Ok(())
+}
+
+/// Name of the LogKey set via [`set_default_key`]
+///
+/// Returns `None` as long as no default key is set.
+///
+/// # Errors
+///
+/// Returns an Error if the context can't be accessed ([`ContextLock`])
+pub fn default_key() -> Result<Option<String>> {
+    Ok(CTX::get()?.default_key().map(|d| d.name.to_string()))
+}
+
+/// Set a `Level` for a single LogKey `K`
+///
+/// Alters the currently set [`Level`] for a given LogKey at runtime. The LogKey `K` is an
+/// initialized logkey as passed to the `init_modules` or `add_submodules` function. The `Level`
+/// can be any valid [`Level`].
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::Level;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum SomeKey { IM }
+///
+/// # SomeKey::init_with_defaults("test").unwrap();
+/// hclog::set_level(SomeKey::IM, Level::Debug9).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+///
+pub fn set_level<K: LogKey>(k: K, level: Level) -> Result<()> {
+    set_level_at(K::logscope(), k.log_key(), level)
+}
+
+// set_level with the key resolved by its indices (shared with the C API)
+pub (crate) fn set_level_at(scope: ScopeKey, key: ContextKey, level: Level) -> Result<()> {
+    let (name, old) = CTX::call_mut(|ctx| {
+        let submod = ctx.get_mod_mut(scope)?.get_submodule_at_mut(key)
+            .ok_or(KeyNotInitialized)?;
+        let old = submod.logsev();
+        submod.set_logsev(level);
+        let name = submod.name().to_owned();
+        ctx.mirror((scope, key), Mirror::Level);
+        Ok((name, old))
+    })?;
+    audit(format_args!("level of '{}' changed {} -> {}", name, old, level));
+    Ok(())
+}
+
+/// Set a `Level` for a single LogKey `K` for a limited time
+///
+/// Like [`set_level`], but the level only applies until `duration` passed. Afterwards the
+/// level configured before (explicit or inherited) applies again and a notice about the
+/// reversion is logged once via the [`Internal`] key. This is intended for temporarily
+/// increasing the verbosity, e.g. during an incident, without the risk of forgetting to turn
+/// it down again.
+///
+/// No background thread is involved: the expiry is checked whenever a message is logged or
+/// tested (e.g. via [`tX`](macro@crate::tX)) for the LogKey. Setting the level again via
+/// [`set_level`] or [`set_mod_level`] cancels the pending reversion. Calling `set_level_for`
+/// again while a level is active only extends it, the level configured before the first call
+/// is restored.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::Level;
+/// use std::time::Duration;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum SomeKey { Incident }
+///
+/// # SomeKey::init_with_defaults("test").unwrap();
+/// hclog::set_level_for(SomeKey::Incident, Level::Debug10, Duration::from_secs(3600)).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn set_level_for<K: LogKey>(k: K, level: Level, duration: Duration) -> Result<()> {
+    let old = CTX::call_mut(|ctx| {
+        let submod = ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k)
+            .ok_or(KeyNotInitialized)?;
+        let old = submod.logsev();
+        submod.set_logsev_for(level, duration);
+        ctx.mirror((K::logscope(), k.log_key()), Mirror::Level);
+        Ok(old)
+    })?;
+    audit(format_args!("level of '{}' changed {} -> {} for {:?}", k, old, level, duration));
+    Ok(())
+}
+
+/*
+ * a level set via set_level_for which expired, noticed while the context is read. The boost
+ * is cleared and the reversion logged once the context is released again.
+ */
+struct ExpiredLevel {
+    scope: ScopeKey,
+    key: ContextKey,
+    name: String,
+    boosted: Level,
+    level: Level,
+}
+type ExpiredSlot = Cell<Option<ExpiredLevel>>;
+
+// note the expiry of the level of `submod` once, see report_expired_level
+fn note_expired_level(slot: &ExpiredSlot, scope: ScopeKey, submod: &Submodule) {
+    if let Some((boosted, level)) = submod.take_expired_boost() {
+        let name = submod.name().to_owned();
+        slot.set(Some(ExpiredLevel { scope, key: submod.key(), name, boosted, level }));
+    }
+}
+
+// must be called without holding the context
+fn report_expired_level(slot: ExpiredSlot) {
+    let Some(expired) = slot.into_inner() else {
+        return;
+    };
+    // fails if called by a nested log call, the expired boost is ignored anyway
+    let _ = CTX::call_mut(|ctx| {
+        if let Some(submod) = ctx.get_mod_mut(expired.scope)?.get_submodule_at_mut(expired.key) {
+            submod.clear_expired_boost();
+        }
+        Ok(())
+    });
+    lN!(Internal, "level of '{}' reverted {} -> {} after expiry",
+        expired.name, expired.boosted, expired.level);
+}
+
+/// Set the parent of a LogKey `K` in the level hierarchy
+///
+/// A LogKey whose level was never set explicitly inherits the effective level of its parent.
+/// The level is explicit if it is given via the `level` attribute of the derive macro or set
+/// at runtime via [`set_level`] or [`set_mod_level`]. Otherwise the default level of the
+/// [`Scope`] is only used if there is no parent. This allows hierarchical subsystems like
+/// `net`, `net.tls` and `net.http` where setting `net` to [`Level::Debug3`] also affects
+/// `net.tls` unless it has an explicit override.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::Level;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Net { Net, Tls }
+///
+/// # Net::init_with_defaults("test").unwrap();
+/// hclog::set_parent(Net::Tls, Net::Net).unwrap();
+/// hclog::set_level(Net::Net, Level::Debug3).unwrap();
+/// assert_eq!(hclog::test_log(Net::Tls, Level::Debug3), Ok(true));
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the child or the parent is not initialized ([`KeyNotInitialized`])
+/// * the parent is the child itself or one of its descendants ([`ParentCycle`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn set_parent<K: LogKey>(child: K, parent: K) -> Result<()> {
+    CTX::call_mut(|ctx| ctx.get_mod_mut(K::logscope())?.set_parent(child, parent))
+}
+
+/// Mirror the configuration changes of a LogKey `from` onto the LogKey `to`
+///
+/// From now on every change of the level, facade or options of `from` is copied to `to`,
+/// no matter if `from` is addressed by its type (e.g. [`set_level`]) or by its name (e.g.
+/// [`set_mod_level`] or [`apply_config_map`]). The keys may belong to different [`Scope`]s, so
+/// e.g. the configuration of `net` in a plugin host also applies to the `net` key of a plugin.
+/// Only the changed part is copied at the time of the change, the current configuration of
+/// `to` is kept until then. A changed facade is shared by both keys. Changes applied to `to`
+/// directly are not copied back. Aliases are followed, so `to` may be aliased itself.
+///
+/// The copied level is the effective level of `from`, including a level inherited from its
+/// parent. It is set explicitly on `to` after passing [`Scope::normalize_level`] of the Scope
+/// of `to`. Aliases from or to a Scope are removed when it is initialized again via
+/// [`reinitialize`].
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::Level;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Host { Net }
+/// # #[derive(Copy, Clone, HCLog)]
+/// # #[hclog(scope = hclog::ScopeKey::Lib)]
+/// enum Plugin { PluginNet }
+///
+/// # Host::init_with_defaults("host").unwrap();
+/// # Plugin::init_with_defaults("plugin").unwrap();
+/// hclog::alias_key(Host::Net, Plugin::PluginNet).unwrap();
+/// hclog::set_mod_level(["net:debug3"]).unwrap();
+/// assert_eq!(hclog::test_log(Plugin::PluginNet, Level::Debug3), Ok(true));
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the Scope of a key is not initialized ([`ScopeNotInitialized`])
+/// * one of the keys is not initialized ([`KeyNotInitialized`])
+/// * `to` is `from` itself or its changes are already copied to `from` ([`AliasCycle`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn alias_key<A: LogKey, B: LogKey>(from: A, to: B) -> Result<()> {
+    CTX::get_mut()?.add_alias((A::logscope(), from.log_key()), (B::logscope(), to.log_key()))
+}
+
+/// Stop mirroring the configuration changes of `from` onto `to`
+///
+/// Removes an alias added via [`alias_key`]. The configuration of `to` stays as it is but
+/// doesn't follow `from` anymore. Returns `false` if there is no such alias.
+///
+/// # Errors
+///
+/// Returns an Error if the context can't be accessed ([`ContextLock`])
+pub fn unalias_key<A: LogKey, B: LogKey>(from: A, to: B) -> Result<bool> {
+    Ok(CTX::get_mut()?.remove_alias((A::logscope(), from.log_key()), (B::logscope(), to.log_key())))
+}
+
+/// Reset the options of a given LogKey `K`
+///
+/// This will reset the Options for a given `K` which implements the [`LogKey`] trait.
+/// When reseting the options, the default options will be used and the environment variables
+/// will be checked. This will not affect the log level or the log destination.
+///
+/// Resetting the options might be usefull when the options where changed at runtime for some
+/// reason and you want restore the original state. Note that this will not restore the state
+/// on initialization of the module. Instead the default is used and the environment variables
+/// are respected.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Key { RM }
+///
+/// # Key::init_with_defaults("test").unwrap();
+/// hclog::reset_module_options(Key::RM).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * parsing the environment variables fails ([`ParseArg`])
+/// * the context can't be accessed ([`ContextLock`])
+///
+pub fn reset_module_options<K: LogKey>(k: K) -> Result<()> {
+    let (old, new) = CTX::call_mut(|ctx| {
+        let submod = ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k)
+            .ok_or(KeyNotInitialized)?;
+        let old = submod.options();
+        let new = submod.reset_options()?.options();
+        ctx.mirror((K::logscope(), k.log_key()), Mirror::Options);
+        Ok((old, new))
+    })?;
+    audit(format_args!("options of '{}' changed {} -> {}", k, old, new));
+    Ok(())
+}
+
+/// Unset one or multiple [`Options`] for a given LogKey
+///
+/// The first argument must be a valid `K` which implements the [`LogKey`] trait.
+///
+/// The second argument is a list of [`Options`] which will be unset for the given `K`.
+/// The options can be arithmetically combined using the `+` operator or, if using the
+/// default [`Options`], unset via the `-` operator. For a list of valid Options see the
+/// [`Options`] documentation.
+///
+/// Unsetting [`LOGCOMPAT`] on the
+/// [`InteralLogKeys::LogCompat`](enum@InternalLogKeys#variant@LogCompat) key mutes the
+/// compatibility layer to the `log` crate if the option was set on the key before. The layer
+/// itself can't be uninstalled.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{ErrorKind, options::{LOGCOMPAT, TIMESTAMP}};
+/// # use hclog_macros::HCLog;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum SomeKey { UM }
+///
+/// match hclog::unset_module_options(SomeKey::UM, LOGCOMPAT + TIMESTAMP) {
+///    Ok(_) => (),
+///    Err(e) if e == ErrorKind::ScopeNotInitialized => println!("Scope not initialized"),
+///    Err(e) if e == ErrorKind::KeyNotInitialized => println!("Module not initialized"),
+///    Err(_) => panic!("Unexpected error"),
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+///
+pub fn unset_module_options<K: LogKey>(k: K, options: Options) -> Result<()> {
+    let (old, new) = CTX::call_mut(|ctx| {
+        let submod = ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k)
+            .ok_or(KeyNotInitialized)?;
+        let old = submod.options();
+        let new = submod.unset_options(options).options();
+        ctx.mirror((K::logscope(), k.log_key()), Mirror::Options);
+        Ok((old, new))
+    })?;
+    audit(format_args!("options of '{}' changed {} -> {}", k, old, new));
+    if old.has(LOGCOMPAT) && !new.has(LOGCOMPAT) {
+        crate::compat::disable_log_compat(k);
+    }
+    Ok(())
+}
+
+/// Set one or multiple [`Options`] for a given LogKey
+///
+/// The first argument must be a valid `K` which implements the [`LogKey`] trait.
+///
+/// The second argument is a list of [`Options`] which will be set for the given `K`.
+/// The options can be arithmetically combined using the `+` operator or, if using the
+/// default [`Options`], unset via the `-` operator. For a list of valid Options see the
+/// [`Options`] documentation.
+///
+/// If [`LOGCOMPAT`] wasn't set on `K` before, setting it initializes the compatibility layer
+/// to the `log` crate with the default [`Level`] and [`FacadeVariant`] of the [`Scope`] of `K`
+/// if it's not initialized yet, see [`init_log_compat()`]. A muted layer is unmuted. This
+/// installs the process wide logger of the `log` crate, which can't be uninstalled again. Note
+/// that [`Options::default()`] and the presets like [`OPTIONS_VERBOSE`] contain [`LOGCOMPAT`]
+/// as well, subtract it (e.g. `Options::default() - LOGCOMPAT`) to keep the `log` crate alone.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{ErrorKind, options::{LOGCOMPAT, TIMESTAMP}};
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Key { SM }
+///
+/// # Key::init_with_defaults("test").unwrap();
+/// match hclog::set_module_options(Key::SM, LOGCOMPAT + TIMESTAMP) {
+///    Ok(_) => (),
+///    Err(e) if e == ErrorKind::ScopeNotInitialized => println!("Scope not initialized"),
+///    Err(e) if e == ErrorKind::KeyNotInitialized => println!("Module not initialized"),
+///    Err(_) => panic!("Unexpected error"),
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+/// * another logger is already installed in the `log` crate ([`LogCompatInitialized`])
+pub fn set_module_options<K: LogKey>(k: K, options: Options) -> Result<()> {
+    let (old, new) = CTX::call_mut(|ctx| {
+        let submod = ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k)
+            .ok_or(KeyNotInitialized)?;
+        let old = submod.options();
+        let new = submod.set_options(options).options();
+        ctx.mirror((K::logscope(), k.log_key()), Mirror::Options);
+        Ok((old, new))
+    })?;
+    audit(format_args!("options of '{}' changed {} -> {}", k, old, new));
+    if !old.has(LOGCOMPAT) && new.has(LOGCOMPAT) {
+        crate::compat::enable_log_compat::<K>()?;
+    }
+    Ok(())
+}
+
+/// Set the maximum interval a message is suppressed by the [`DEDUP`] option
+///
+/// With [`DEDUP`] set, identical consecutive messages are only counted. By default the
+/// counter is written once a different message arrives or [`flush`] is called. With an
+/// interval set the counter is also written if the next identical message arrives after the
+/// interval elapsed since the counting started. Passing `None` removes the interval.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Key { DI }
+///
+/// # Key::init_with_defaults("test").unwrap();
+/// hclog::set_module_options(Key::DI, hclog::options::DEDUP).unwrap();
+/// hclog::set_dedup_interval(Key::DI, Some(Duration::from_secs(30))).unwrap();
 /// ```
 ///
 /// # Errors
 ///
 /// Returns an Error if:
-/// * [ContextLock]: the internal context can't be accessed
-/// * [ScopeNotInitialized]: the scope is not initialized
-///
-pub fn has_module<K: LogKey>(k: K) -> Result<bool> {
-    CTX::call(|ctx| { Ok(ctx.get_mod(K::logscope())?.has_submodule(k)) })
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn set_dedup_interval<K: LogKey>(k: K, interval: Option<Duration>) -> Result<()> {
+    CTX::call_mut(|ctx| {
+        ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k).ok_or(KeyNotInitialized)?
+            .set_dedup_interval(interval);
+        Ok(())
+    })
 }
 
-/// Set the log destination `FacadeVariant` for a given LogKey `K`
+/// Write only every n-th message of a LogKey `K` at a verbose level
 ///
-/// Alter the currently set [`FacadeVariant`] for a given LogKey at runtime. The LogKey `K` is an
-/// initialized logkey as passed to the `init_modules` or `add_submodules` function.
-/// The `FacadeVariant` can be any valid [`FacadeVariant`].
+/// Messages at `level` or any more verbose level which pass the [`Level`] of the LogKey are
+/// sampled: only the first of every `rate` messages is written. The suppressed messages are
+/// counted as well to keep the ratio and show up as `dropped` in [`health_summary`]. Messages
+/// at a less verbose level than `level` are not affected. This allows to characterize a hot
+/// path with e.g. [`Debug10`](Level::Debug10) without writing every single message.
+///
+/// A `rate` of `0` or `1` or `level` [`Off`](Level::Off) disables the sampling. Setting the
+/// sampling again resets the counters.
 ///
 /// # Examples
 ///
 /// ```rust
-/// use hclog::FacadeVariant;
+/// use hclog::Level;
 /// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
 ///
 /// # #[derive(Copy, Clone, HCLog)]
-/// enum SomeKey { SL }
+/// enum Key { HotPath }
 ///
-/// # SomeKey::init_with_defaults("test").unwrap();
-/// hclog::set_logdest(SomeKey::SL, FacadeVariant::StdErr).unwrap();
+/// # Key::init_with_defaults("test").unwrap();
+/// hclog::set_level(Key::HotPath, Level::Debug10).unwrap();
+/// // every 1000th debug message, info and above are still written
+/// hclog::set_sampling(Key::HotPath, Level::Debug1, 1000).unwrap();
 /// ```
 ///
 /// # Errors
@@ -429,31 +2373,40 @@ pub fn has_module<K: LogKey>(k: K) -> Result<bool> {
 /// * the module is not initialized ([`ScopeNotInitialized`])
 /// * the submodule is not initialized ([`KeyNotInitialized`])
 /// * the context can't be accessed ([`ContextLock`])
-pub fn set_logdest<K: LogKey>(k: K, facade: FacadeVariant) -> Result<()> {
+pub fn set_sampling<K: LogKey>(k: K, level: Level, rate: u32) -> Result<()> {
     CTX::call_mut(|ctx| {
         ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k).ok_or(KeyNotInitialized)?
-            .set_logdest(&facade);
+            .set_sampling(level, rate);
         Ok(())
     })
 }
 
-/// Set a `Level` for a single LogKey `K`
+/// Subscribe to the lines logged to a given LogKey `K`
 ///
-/// Alters the currently set [`Level`] for a given LogKey at runtime. The LogKey `K` is an
-/// initialized logkey as passed to the `init_modules` or `add_submodules` function. The `Level`
-/// can be any valid [`Level`].
+/// Every line passing the [`Level`] of the LogKey is passed to the returned [`Subscription`]
+/// in addition to the facade of the LogKey. Multiple subscriptions of the same LogKey receive
+/// the same lines. Each subscription buffers up to `capacity` lines (at least one) and drops the
+/// oldest line if its consumer doesn't keep up. Dropping the subscription unsubscribes.
+///
+/// This allows to show recent log lines in-process, e.g. in a TUI pane, without reading them
+/// back from the facade.
 ///
 /// # Examples
 ///
 /// ```rust
-/// use hclog::Level;
+/// use hclog::{Level, lI};
+/// use std::time::Duration;
 /// # use hclog_macros::HCLog;
 ///
 /// # #[derive(Copy, Clone, HCLog)]
-/// enum SomeKey { IM }
+/// enum Key { Tui }
+/// use Key::Tui;
+/// # use hclog::Scope;
 ///
-/// # SomeKey::init_with_defaults("test").unwrap();
-/// hclog::set_level(SomeKey::IM, Level::Debug9).unwrap();
+/// # Key::init_with_defaults("test").unwrap();
+/// let sub = hclog::subscribe(Tui, 100).unwrap();
+/// lI!(Tui, "shown in the pane");
+/// assert!(sub.recv_timeout(Duration::from_secs(1)).unwrap().contains("shown in the pane"));
 /// ```
 ///
 /// # Errors
@@ -462,36 +2415,410 @@ pub fn set_logdest<K: LogKey>(k: K, facade: FacadeVariant) -> Result<()> {
 /// * the module is not initialized ([`ScopeNotInitialized`])
 /// * the submodule is not initialized ([`KeyNotInitialized`])
 /// * the context can't be accessed ([`ContextLock`])
+pub fn subscribe<K: LogKey>(k: K, capacity: usize) -> Result<Subscription> {
+    CTX::call(|ctx| {
+        ctx.get_mod(K::logscope())?.get_submodule(k).ok_or(KeyNotInitialized)
+            .map(|m| m.subscribe(capacity))
+    })
+}
+
+/// Take all lines kept by the [`RingBuffer`](FacadeVariant::RingBuffer) of a LogKey `K`
 ///
-pub fn set_level<K: LogKey>(k: K, level: Level) -> Result<()> {
-    CTX::call_mut(|ctx| {
-        ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k).ok_or(KeyNotInitialized)?
-            .set_logsev(level);
-        Ok(())
+/// Returns the lines in the order they were logged, the oldest first. The buffer is empty
+/// afterwards. Within a task scope the buffer of the task-local copy is drained, which is shared
+/// with the global LogKey unless the facade was changed in the task.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{Level, FacadeVariant, lD1};
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(HCLog, Copy, Clone)]
+/// enum Keys { Recorder }
+/// # use Keys::*;
+///
+/// # Keys::init_with_defaults("test").unwrap();
+/// hclog::set_level(Recorder, Level::Debug1).unwrap();
+/// hclog::set_logdest(Recorder, FacadeVariant::RingBuffer(2)).unwrap();
+/// for i in 0..3 {
+///     lD1!(Recorder, "step {}", i);
+/// }
+/// let lines = hclog::drain_ring(Recorder).unwrap();
+/// assert_eq!(lines.len(), 2);
+/// assert!(lines[0].ends_with("step 1"));
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the module is not initialized ([`ScopeNotInitialized`])
+/// * the submodule is not initialized ([`KeyNotInitialized`])
+/// * the facade of the LogKey is not a ring buffer ([`NoRingBuffer`])
+/// * the context can't be accessed ([`ContextLock`])
+pub fn drain_ring<K: LogKey>(k: K) -> Result<Vec<String>> {
+    CTX::call(|ctx| {
+        let submod = ctx.get_mod(K::logscope())?.get_submodule(k).ok_or(KeyNotInitialized)?;
+        submod.ring_buffer().ok_or(NoRingBuffer).map(|ring| ring.drain())
     })
 }
 
-/// Reset the options of a given LogKey `K`
+/// Write all lines kept by the [`RingBuffer`](FacadeVariant::RingBuffer) of a LogKey `K` to `w`
 ///
-/// This will reset the Options for a given `K` which implements the [`LogKey`] trait.
-/// When reseting the options, the default options will be used and the environment variables
-/// will be checked. This will not affect the log level or the log destination.
+/// Same as [`drain_ring`] but writes the lines, each terminated by a newline, to the supplied
+/// writer. Returns the number of lines written. The context isn't locked while writing.
 ///
-/// Resetting the options might be usefull when the options where changed at runtime for some
-/// reason and you want restore the original state. Note that this will not restore the state
-/// on initialization of the module. Instead the default is used and the environment variables
-/// are respected.
+/// # Examples
+///
+/// ```rust,no_run
+/// # use hclog_macros::HCLog;
+/// # #[derive(HCLog, Copy, Clone)]
+/// # enum Keys { Recorder }
+/// # use Keys::*;
+/// let mut crash = std::fs::File::create("/tmp/crash.log").unwrap();
+/// hclog::dump_ring_to(Recorder, &mut crash).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// See [`drain_ring`]. Additionally [`IoError`] is returned if writing fails. The lines are
+/// drained in any case.
+pub fn dump_ring_to<K: LogKey, W: Write>(k: K, w: &mut W) -> Result<usize> {
+    let lines = drain_ring(k)?;
+    for line in lines.iter() {
+        w.write_fmt(format_args!("{}\n", line))?;
+    }
+    Ok(lines.len())
+}
+
+/// Register a handler called for every message at or above a given severity
+///
+/// The `handler` is called with the name of the LogKey, the [`Level`] and the formatted message
+/// for every message with a level of `threshold` or more severe (e.g. [`Crit`](Level::Crit)
+/// also covers [`Alert`](Level::Alert) and [`Emerg`](Level::Emerg)), logged via any LogKey of
+/// any [`Scope`]. Only messages passing the level of their LogKey are reported, regardless of
+/// the facade. Messages suppressed by the `DEDUP` option or [`set_sampling`] are reported as
+/// well.
+///
+/// The handlers are called after the message was passed to the facade, in the order of their
+/// registration. They are called on the logging thread while the context is read, so changing
+/// the configuration (e.g. via [`set_level`]) fails with [`ContextLock`]. Logging from a handler
+/// is limited by [`set_max_log_depth`] like any other nested log call.
+///
+/// Returns an id to remove the handler via [`remove_on_severe`].
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{Level, lC};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(HCLog, Copy, Clone)]
+/// enum Keys { Db }
+/// # use Keys::*;
+///
+/// static SEVERE: AtomicUsize = AtomicUsize::new(0);
+///
+/// # Keys::init_with_defaults("test").unwrap();
+/// let id = hclog::on_severe(Level::Crit, Box::new(|_, _, _| {
+///     SEVERE.fetch_add(1, Ordering::Relaxed);
+/// })).unwrap();
+/// lC!(Db, "database is gone");
+/// assert_eq!(SEVERE.load(Ordering::Relaxed), 1);
+/// hclog::remove_on_severe(id).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns [`ContextLock`] if the list of handlers can't be accessed.
+pub fn on_severe(threshold: Level, handler: SevereHandler) -> Result<SevereHandlerId> {
+    crate::severe::register(threshold, handler)
+}
+
+/// Remove a handler registered via [`on_severe`]
+///
+/// Returns `false` if the handler was already removed.
+///
+/// # Errors
+///
+/// Returns [`ContextLock`] if the list of handlers can't be accessed.
+pub fn remove_on_severe(id: SevereHandlerId) -> Result<bool> {
+    crate::severe::remove(id)
+}
+
+/// Set the maximum depth of nested log calls
+///
+/// A [`Display`] implementation or a facade may log itself while a message is logged. Up to
+/// `depth` log calls may be nested on a thread (default: 2). A message beyond the limit is not
+/// logged via its LogKey but written to stderr prefixed with `recursive logging suppressed:`,
+/// deeper messages are dropped. This stops endless recursion, e.g. of a `Display` impl logging
+/// its own value. A `depth` of 0 redirects all messages to stderr.
+///
+/// Independent of the depth, changing the configuration (e.g. via [`set_level`]) while a
+/// message is logged fails with [`ContextLock`] instead of deadlocking.
+///
+/// # Examples
+///
+/// ```rust
+/// hclog::set_max_log_depth(1);
+/// ```
+pub fn set_max_log_depth(depth: usize) {
+    MAX_LOG_DEPTH.store(depth, Ordering::Relaxed);
+}
+
+/// Suppress all logging of the current thread until the returned guard is dropped
+///
+/// While a [`SuppressGuard`] exists, [`test_log`] returns `false` and [`log`] returns `Ok(())`
+/// without accessing the context, so the log macros neither lock, allocate nor write anything.
+/// This makes log calls safe in signal handlers, allocator hooks or while holding locks the
+/// facades might need. The check is a read of a thread-local counter, logging of other threads
+/// continues as usual.
+///
+/// Guards can be nested, logging is restored when the last one is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::lI;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(HCLog, Copy, Clone)]
+/// enum Keys { Alloc }
+/// # use Keys::*;
+///
+/// # Keys::init_with_defaults("test").unwrap();
+/// {
+///     let _guard = hclog::suppress_guard();
+///     assert!(hclog::is_suppressed());
+///     lI!(Alloc, "never written");
+/// }
+/// assert!(!hclog::is_suppressed());
+/// ```
+pub fn suppress_guard() -> SuppressGuard {
+    crate::context::suppress_enter();
+    SuppressGuard { _thread: std::marker::PhantomData }
+}
+
+/// Check if logging is suppressed on the current thread via [`suppress_guard`]
+pub fn is_suppressed() -> bool {
+    crate::context::suppressed()
+}
+
+/// Guard returned by [`suppress_guard`]
+///
+/// Logging of the current thread is suppressed as long as a guard exists. The guard is bound to
+/// the thread which created it and can't be sent to another one.
+#[derive(Debug)]
+#[must_use = "logging is only suppressed until the guard is dropped"]
+pub struct SuppressGuard {
+    // !Send, the counter is per thread
+    _thread: std::marker::PhantomData<*const ()>,
+}
+impl Drop for SuppressGuard {
+    fn drop(&mut self) {
+        crate::context::suppress_leave();
+    }
+}
+
+/// Reset the base of the elapsed time printed by the [`UPTIME`] option
+///
+/// The base is taken when the first [`Scope`] is initialized. Resetting it at a meaningful point,
+/// e.g. at the end of the startup, makes the elapsed times of later messages easier to compare.
+/// The base is shared by all Scopes (of the thread in [single-thread
+/// mode](crate#single-thread-mode)).
+///
+/// # Examples
+///
+/// ```rust
+/// hclog::reset_uptime_base();
+/// ```
+pub fn reset_uptime_base() {
+    crate::message::reset_uptime_base();
+}
+
+/// Set the separator written between prefix and message by the [`PREFIX_SEP`] option
+///
+/// The default separator is `" | "`, passing `None` restores it. The separator is shared by
+/// all Scopes (of the thread in [single-thread mode](crate#single-thread-mode)). Occurrences
+/// of the separator within the prefix, e.g. in the name of a LogKey, are escaped, so a line can
+/// always be split at the first separator. The message itself is written unchanged and may
+/// contain the separator.
+///
+/// # Examples
+///
+/// ```rust
+/// hclog::set_prefix_separator(Some(" :: ")).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the separator is empty or contains a line break or backslash ([`ParseArg`])
+pub fn set_prefix_separator(sep: Option<&str>) -> Result<()> {
+    if let Some(sep) = sep {
+        if sep.is_empty() || sep.contains(['\n', '\r', '\\']) {
+            return Err(ParseArg);
+        }
+    }
+    crate::message::set_prefix_separator(sep.map(str::to_owned));
+    Ok(())
+}
+
+/// Remove `prefix` from the file paths written by the [`FILE`] option
+///
+/// Paths starting with `prefix` are written relative to it, e.g. with the workspace root taken
+/// from `env!("CARGO_MANIFEST_DIR")` by the application the build directory doesn't show up in
+/// the messages. The prefix only matches whole components, `/` and `\` are both accepted as
+/// separators. If several prefixes match, the longest one is removed. The list is shared by
+/// all Scopes (of the thread in [single-thread mode](crate#single-thread-mode)) and takes
+/// precedence over the [`FILE_BASENAME`] option, which only applies to paths without a
+/// matching prefix.
+///
+/// # Examples
+///
+/// ```rust
+/// hclog::add_file_prefix_strip(env!("CARGO_MANIFEST_DIR")).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the prefix is empty or only consists of separators ([`ParseArg`])
+pub fn add_file_prefix_strip(prefix: &str) -> Result<()> {
+    let prefix = prefix.trim_end_matches(crate::message::is_separator);
+    if prefix.is_empty() {
+        return Err(ParseArg);
+    }
+    crate::message::add_file_prefix(prefix);
+    Ok(())
+}
+
+/// Set the instance id written by the [`INSTANCE`] option for all LogKeys of the Scope `K`
+///
+/// The id is written within the brackets of the process and thread id, e.g.
+/// `[pod-7f9c/1/3]`, so replicas of a container whose process id is always the same can be
+/// told apart. It's initialized from the environment variable `HCLOG_INSTANCE_ID` and also
+/// written to syslog. Task local copies of the Scope created afterwards (see [`scope`] and
+/// [`async_scope_for`]) keep the id.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{Scope, Level, FacadeVariant, options::INSTANCE};
+/// # use hclog_macros::HCLog;
+///
+/// # #[derive(HCLog, Copy, Clone)]
+/// enum Keys { Web }
+///
+/// Keys::init("app", Level::Info, FacadeVariant::StdErr, INSTANCE).unwrap();
+/// hclog::set_instance_id::<Keys>("pod-7f9c").unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * [ContextLock]: the internal context can't be accessed
+/// * [ScopeNotInitialized]: the Scope is not initialized
+/// * [ParseArg]: the id is empty or contains whitespace, a slash or a bracket
+pub fn set_instance_id<K: Scope>(id: &str) -> Result<()> {
+    let id = crate::logmod::valid_instance_id(id)?;
+    CTX::get_mut()?.get_mod_mut(K::logscope())?.set_instance_id(id);
+    Ok(())
+}
+
+/// Limit the length of the function name written by the [`FUNC`] option for the Scope `K`
+///
+/// Names longer than `len` characters are cut and end with `…`, so the line isn't dominated by
+/// a deeply nested or generic function. The limit applies after [`FUNC_SHORT`] shortened the
+/// name. `0` removes the limit, which is the default. Task local copies of the Scope created
+/// afterwards (see [`scope`] and [`async_scope_for`]) keep the limit.
 ///
 /// # Examples
 ///
 /// ```rust
+/// use hclog::{Scope, Level, FacadeVariant, options::{FUNC, FUNC_SHORT}};
 /// # use hclog_macros::HCLog;
 ///
+/// # #[derive(HCLog, Copy, Clone)]
+/// enum Keys { Web }
+///
+/// Keys::init("app", Level::Info, FacadeVariant::StdErr, FUNC + FUNC_SHORT).unwrap();
+/// hclog::set_func_max_len::<Keys>(24).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * [ContextLock]: the internal context can't be accessed
+/// * [ScopeNotInitialized]: the Scope is not initialized
+pub fn set_func_max_len<K: Scope>(len: usize) -> Result<()> {
+    CTX::get_mut()?.get_mod_mut(K::logscope())?.set_func_max_len((len > 0).then_some(len));
+    Ok(())
+}
+
+/// Write all pending repeat counters and buffered messages
+///
+/// Writes the `last message repeated N times` line for every LogKey which currently
+/// suppresses messages due to the [`DEDUP`] option and the messages held back by a
+/// [`FlushPolicy`] other than [`FlushPolicy::EveryMessage`]. This should be called before the
+/// application exits to not lose the last counter or messages.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
 /// # #[derive(Copy, Clone, HCLog)]
-/// enum Key { RM }
+/// enum Key { FL }
 ///
 /// # Key::init_with_defaults("test").unwrap();
-/// hclog::reset_module_options(Key::RM).unwrap();
+/// hclog::flush().unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the context can't be accessed ([`ContextLock`])
+/// * writing to a facade fails ([`IoError`])
+pub fn flush() -> Result<()> {
+    let task = crate::context::TASK_CONTEXT.try_with(|ctx| {
+        ctx.logmods().filter(|lm| lm.initialized()).try_for_each(|lm| lm.flush())
+    });
+    if let Ok(Err(e)) = task {
+        return Err(e);
+    }
+    CTX::get()?.logmods().filter(|lm| lm.initialized()).try_for_each(|lm| lm.flush())
+}
+
+/// Let the next `n` writes of a given LogKey `K` fail
+///
+/// Arms the facade of the given LogKey to return an [`IoError`] with the given
+/// [`std::io::ErrorKind`] for the next `n` messages passed to it. Afterwards the messages are
+/// written to the original facade again. Messages filtered by the [`Level`] of the LogKey don't
+/// reach the facade and therefore don't count.
+///
+/// This is intended to test how an application behaves if logging fails (e.g. because the disk
+/// is full). It is only available with the `test-util` feature enabled.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::{Level, ErrorKind};
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(Copy, Clone, HCLog)]
+/// enum Key { FW }
+///
+/// # Key::init_with_defaults("test").unwrap();
+/// hclog::fail_next_writes(Key::FW, 1, std::io::ErrorKind::Other).unwrap();
+/// let res = hclog::log(Key::FW, Level::Emerg, file!(), None, line!(), &format_args!("fails"));
+/// assert_eq!(res, Err(ErrorKind::IoError(std::io::ErrorKind::Other)));
 /// ```
 ///
 /// # Errors
@@ -499,82 +2826,316 @@ pub fn set_level<K: LogKey>(k: K, level: Level) -> Result<()> {
 /// Returns an Error if:
 /// * the module is not initialized ([`ScopeNotInitialized`])
 /// * the submodule is not initialized ([`KeyNotInitialized`])
-/// * parsing the environment variables fails ([`ParseArg`])
 /// * the context can't be accessed ([`ContextLock`])
+#[cfg(feature = "test-util")]
+pub fn fail_next_writes<K: LogKey>(k: K, n: usize, kind: std::io::ErrorKind) -> Result<()> {
+    CTX::call_mut(|ctx| {
+        ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k).ok_or(KeyNotInitialized)?
+            .inject_failures(n, kind);
+        Ok(())
+    })
+}
+
+/// Remove all failures armed by [`fail_next_writes`]
+///
+/// Restores the original facades of all LogKeys, regardless of the number of remaining
+/// failures. It is only available with the `test-util` feature enabled.
+///
+/// # Errors
+///
+/// Returns an Error if:
+/// * the context can't be accessed ([`ContextLock`])
+#[cfg(feature = "test-util")]
+pub fn clear_failures() -> Result<()> {
+    let _ = crate::context::TASK_CONTEXT.try_with_mut(|ctx| ctx.clear_failures());
+    CTX::get_mut()?.clear_failures();
+    Ok(())
+}
+
+/// Replace the clock used for all time based features
+///
+/// The timestamps of messages, the elapsed time of the [`UPTIME`] option, the interval of the
+/// [`DEDUP`] option and the duration of [`set_level_for`] are read from `clock` instead of the
+/// system clock. The clock is process wide. This is intended to test time based behavior
+/// without sleeping, e.g. with a [`MockClock`](crate::testing::MockClock). It is only
+/// available with the `test-util` feature enabled.
+///
+/// If the clock goes back, only the timestamps follow it. Durations continue from the latest
+/// time read from the clock, so a boost or an interval is neither shortened nor extended by
+/// the step back.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::testing::MockClock;
+/// use std::{sync::Arc, time::{Duration, UNIX_EPOCH}};
+///
+/// let clock = MockClock::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+/// hclog::set_clock(Arc::new(clock.clone()));
+/// clock.advance(Duration::from_secs(60));
+/// hclog::reset_clock();
+/// ```
+#[cfg(feature = "test-util")]
+pub fn set_clock(clock: std::sync::Arc<dyn crate::testing::Clock>) {
+    crate::clock::set_clock(Some(clock));
+}
+
+/// Restore the system clock replaced by [`set_clock`]
 ///
-pub fn reset_module_options<K: LogKey>(k: K) -> Result<()> {
-    CTX::call_mut(|ctx| {
-        ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k).ok_or(KeyNotInitialized)?
-            .reset_options()?;
-        Ok(())
-    })
+/// It is only available with the `test-util` feature enabled.
+#[cfg(feature = "test-util")]
+pub fn reset_clock() {
+    crate::clock::set_clock(None);
 }
 
-/// Unset one or multiple [`Options`] for a given LogKey
+/// Get a [`std::io::Write`] adapter logging to a given LogKey `K`
 ///
-/// The first argument must be a valid `K` which implements the [`LogKey`] trait.
+/// The returned [`LogWriter`] buffers all bytes written to it until a newline is encountered
+/// and passes every complete line as a single message with the given [`Level`] to the
+/// LogKey. Calling [`flush`](std::io::Write::flush) logs whatever is buffered, even without a
+/// trailing newline. A partial line left in the buffer is logged when the writer is dropped.
 ///
-/// The second argument is a list of [`Options`] which will be unset for the given `K`.
-/// The options can be arithmetically combined using the `+` operator or, if using the
-/// default [`Options`], unset via the `-` operator. For a list of valid Options see the
-/// [`Options`] documentation.
+/// This allows to pass log output through code which expects a [`std::io::Write`], e.g.
+/// serializers. Non UTF-8 sequences are replaced by `U+FFFD`. The location of the call to
+/// `into_writer` is used as the file and line of the messages.
 ///
 /// # Examples
 ///
 /// ```rust
-/// use hclog::{ErrorKind, options::{LOGCOMPAT, TIMESTAMP}};
+/// use hclog::Level;
+/// use std::io::Write;
 /// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
 ///
 /// # #[derive(Copy, Clone, HCLog)]
-/// enum SomeKey { UM }
+/// enum Key { Db }
 ///
-/// match hclog::unset_module_options(SomeKey::UM, LOGCOMPAT + TIMESTAMP) {
-///    Ok(_) => (),
-///    Err(e) if e == ErrorKind::ScopeNotInitialized => println!("Scope not initialized"),
-///    Err(e) if e == ErrorKind::KeyNotInitialized => println!("Module not initialized"),
-///    Err(_) => panic!("Unexpected error"),
-/// }
+/// # Key::init_with_defaults("test").unwrap();
+/// let mut writer = hclog::into_writer(Key::Db, Level::Debug3);
+/// writeln!(writer, "logged as a single message").unwrap();
 /// ```
 ///
 /// # Errors
 ///
-/// Returns an Error if:
-/// * the module is not initialized ([`ScopeNotInitialized`])
-/// * the submodule is not initialized ([`KeyNotInitialized`])
-/// * the context can't be accessed ([`ContextLock`])
-///
-pub fn unset_module_options<K: LogKey>(k: K, options: Options) -> Result<()> {
-    CTX::call_mut(|ctx| {
-        ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k).ok_or(KeyNotInitialized)?
-            .unset_options(options);
-        Ok(())
-    })
+/// [`write`](std::io::Write::write) never fails: the bytes are always taken, even if logging a
+/// complete line fails. The first error is kept and returned by the next
+/// [`flush`](std::io::Write::flush) instead, so `write_all` never writes a line twice. An
+/// [`IoError`] of the facade is passed as is, any other [`ErrorKind`](crate::ErrorKind) is
+/// wrapped into [`std::io::ErrorKind::Other`].
+#[track_caller]
+pub fn into_writer<K: LogKey>(k: K, level: Level) -> LogWriter<K> {
+    let caller = Location::caller();
+    LogWriter {
+        key: k,
+        level,
+        file: caller.file(),
+        line: caller.line(),
+        buf: Vec::new(),
+        error: None,
+    }
 }
 
-/// Set one or multiple [`Options`] for a given LogKey
+/// A [`std::io::Write`] adapter logging every line to a LogKey
+///
+/// Created by [`into_writer`]. See its documentation for details.
+#[derive(Debug)]
+pub struct LogWriter<K: LogKey> {
+    key: K,
+    level: Level,
+    file: &'static str,
+    line: u32,
+    buf: Vec<u8>,
+    // first failure of a line logged by write, returned by flush
+    error: Option<std::io::Error>,
+}
+impl<K: LogKey> LogWriter<K> {
+    fn log_line(&self, line: &[u8]) -> std::io::Result<()> {
+        let line = String::from_utf8_lossy(line);
+        log(self.key, self.level, self.file, None, self.line, &format_args!("{}", line))
+            .map_err(|e| match e {
+                IoError(kind) => kind.into(),
+                e => std::io::Error::other(e),
+            })
+    }
+}
+impl<K: LogKey> Write for LogWriter<K> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        while let Some(pos) = self.buf.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=pos).collect();
+            if let Err(e) = self.log_line(&line[..pos]) {
+                self.error.get_or_insert(e);
+            }
+        }
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        if !self.buf.is_empty() {
+            let line = std::mem::take(&mut self.buf);
+            if let Err(e) = self.log_line(&line) {
+                self.error.get_or_insert(e);
+            }
+        }
+        self.error.take().map_or(Ok(()), Err)
+    }
+}
+impl<K: LogKey> Drop for LogWriter<K> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/*
+ * Don't document this function. It's only used for internal by the macros
+ */
+#[doc(hidden)]
+pub fn log<K: LogKey>(
+    k: K, lvl: Level, file: &str, func: Option<&str>, line: u32, fmt: &Arguments
+) -> Result<()> {
+    log_at(K::logscope(), k.log_key(), &k, lvl, file, func, line, fmt)
+}
+
+/*
+ * Don't document this function. It's only used by the macros to panic with a message telling
+ * how to fix the error instead of the plain ErrorKind
+ */
+#[doc(hidden)]
+pub fn failure_message<K: LogKey>(k: K, err: ErrorKind) -> String {
+    let ty = std::any::type_name::<K>();
+    let ty = ty.rsplit("::").next().unwrap_or(ty);
+    match err {
+        KeyNotInitialized => {
+            let scope = CTX::call(|ctx| Ok(ctx.get_mod(K::logscope())?.name().to_owned()))
+                .unwrap_or_else(|_| "?".to_owned());
+            // the path of the key in the code, the Display name may differ
+            let path = match k.variant_name() {
+                Some(variant) => format!("{}::{}", ty, variant),
+                None => ty.to_owned(),
+            };
+            format!("hclog: LogKey '{}' (index {}) is not initialized in scope '{}'; did you \
+                forget to call {}::init(...) or add_submodules(&[{}])?", k, k.log_key(), scope,
+                ty, path)
+        }
+        ScopeNotInitialized => format!("hclog: LogKey '{}' (index {}) belongs to a scope which \
+            is not initialized; did you forget to call {}::init(...)?", k, k.log_key(), ty),
+        e => format!("hclog: failed to log via LogKey '{}': {}", k, e),
+    }
+}
+
+/*
+ * Don't document this function. Same as failure_message for the default log macros
+ */
+#[doc(hidden)]
+pub fn default_failure_message(err: ErrorKind) -> String {
+    let key = CTX::call(|ctx| Ok(ctx.default_key().map(|d| d.name.to_string())));
+    match key {
+        Ok(Some(key)) => format!("hclog: failed to log via the default key '{}': {}", key, err),
+        _ => format!("hclog: failed to log via the default key: {}", err),
+    }
+}
+
+/*
+ * log and test_log with the key resolved by its indices. This is shared with the C API which
+ * has no LogKey type. `name` is only used for messages about the key itself.
+ */
+#[allow(clippy::too_many_arguments)]
+pub (crate) fn log_at(
+    scope: ScopeKey, key: ContextKey, name: &dyn Display,
+    lvl: Level, file: &str, func: Option<&str>, line: u32, fmt: &Arguments
+) -> Result<()> {
+    log_batch_at(scope, key, name, lvl, file, func, line, std::slice::from_ref(fmt))
+}
+
+/*
+ * Don't document this function. It's only used for internal by the batch! macro
+ */
+#[doc(hidden)]
+pub fn log_batch<K: LogKey>(
+    k: K, lvl: Level, file: &str, func: Option<&str>, line: u32, fmts: &[Arguments]
+) -> Result<()> {
+    log_batch_at(K::logscope(), k.log_key(), &k, lvl, file, func, line, fmts)
+}
+
+// log all messages with a single access to the context
+#[allow(clippy::too_many_arguments)]
+fn log_batch_at(
+    scope: ScopeKey, key: ContextKey, name: &dyn Display,
+    lvl: Level, file: &str, func: Option<&str>, line: u32, fmts: &[Arguments]
+) -> Result<()> {
+    if lvl == Level::Off {
+        return Err(InvalidLevel);
+    }
+    if crate::context::suppressed() {
+        return Ok(());
+    }
+    let depth = LogDepth::enter();
+    match depth.outer().cmp(&MAX_LOG_DEPTH.load(Ordering::Relaxed)) {
+        cmp::Ordering::Less => (),
+        cmp::Ordering::Equal => {
+            // formatting may recurse once more, this is dropped by the arm below
+            for fmt in fmts {
+                eprintln!("recursive logging suppressed: {} {}:{} {}", name, file, line, fmt);
+            }
+            return Ok(());
+        }
+        cmp::Ordering::Greater => return Ok(()),
+    }
+    let expired = ExpiredSlot::default();
+    let res = CTX::call(|ctx| {
+        let lm = ctx.get_mod(scope)?;
+        match lm.get_submodule_at(key) {
+            Some(m) => {
+                note_expired_level(&expired, scope, m);
+                if !lm.will_log(m, lvl) {
+                    return Ok(());
+                }
+                let scope = lm.env();
+                let (ident, instance, max) = (lm.env_ident(), lm.instance_id(), lm.func_max_len());
+                fmts.iter().try_for_each(|fmt| {
+                    m.do_log(lm.name(), scope, ident, instance, max, lvl, file, func, line, fmt)
+                })
+            }
+            None => {
+                lE!(Internal, "Module {} not initialized", name);
+                Err(KeyNotInitialized)
+            }
+        }
+    });
+    drop(depth);
+    report_expired_level(expired);
+    res
+}
+
+// messages skipped by try_log because the context or a facade was locked
+static CONTENDED: AtomicU64 = AtomicU64::new(0);
+
+/// Log a message via LogKey `K` without waiting for a lock
 ///
-/// The first argument must be a valid `K` which implements the [`LogKey`] trait.
+/// Works like the log macros (e.g. [`lI`](macro@crate::lI)) but never blocks on the internal
+/// context or the lock of the facade. If another thread holds one of them, e.g. while the
+/// configuration is changed, the message is skipped and `Ok(false)` is returned, so soft
+/// real-time threads can drop or defer it. Otherwise `Ok(true)` is returned, also if the
+/// message is filtered by the [`Level`]. The message carries the file and line of the caller.
+/// Skipped messages show up as `dropped` in [`health_summary`].
 ///
-/// The second argument is a list of [`Options`] which will be set for the given `K`.
-/// The options can be arithmetically combined using the `+` operator or, if using the
-/// default [`Options`], unset via the `-` operator. For a list of valid Options see the
-/// [`Options`] documentation.
+/// Only the [`File`](FacadeVariant::File) and [`StdOut`](FacadeVariant::StdOut) facades can
+/// skip a message, all others write it as usual. The [`StdOut`](FacadeVariant::StdOut) facade
+/// only notices other writers of this crate, not e.g. a `println!` of the application.
+/// [`lI_try`](macro@crate::lI_try) is a shortcut for the level [`Info`](Level::Info).
 ///
 /// # Examples
 ///
 /// ```rust
-/// use hclog::{ErrorKind, options::{LOGCOMPAT, TIMESTAMP}};
+/// use hclog::Level;
 /// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
 ///
 /// # #[derive(Copy, Clone, HCLog)]
-/// enum Key { SM }
+/// enum Key { Audio }
 ///
 /// # Key::init_with_defaults("test").unwrap();
-/// match hclog::set_module_options(Key::SM, LOGCOMPAT + TIMESTAMP) {
-///    Ok(_) => (),
-///    Err(e) if e == ErrorKind::ScopeNotInitialized => println!("Scope not initialized"),
-///    Err(e) if e == ErrorKind::KeyNotInitialized => println!("Module not initialized"),
-///    Err(_) => panic!("Unexpected error"),
+/// if !hclog::try_log(Key::Audio, Level::Warn, format_args!("buffer underrun")).unwrap() {
+///     // the context is busy, try again with the next period
 /// }
 /// ```
 ///
@@ -583,47 +3144,138 @@ pub fn unset_module_options<K: LogKey>(k: K, options: Options) -> Result<()> {
 /// Returns an Error if:
 /// * the module is not initialized ([`ScopeNotInitialized`])
 /// * the submodule is not initialized ([`KeyNotInitialized`])
-/// * the context can't be accessed ([`ContextLock`])
-pub fn set_module_options<K: LogKey>(k: K, options: Options) -> Result<()> {
-    CTX::call_mut(|ctx| {
-        ctx.get_mod_mut(K::logscope())?.get_submodule_mut(k).ok_or(KeyNotInitialized)?
-            .set_options(options);
-        Ok(())
-    })
+/// * `level` is [`Off`](Level::Off) ([`InvalidLevel`])
+/// * the message can't be written by the facade
+#[track_caller]
+pub fn try_log<K: LogKey>(k: K, level: Level, args: Arguments) -> Result<bool> {
+    let caller = Location::caller();
+    try_log_at(K::logscope(), k.log_key(), level, caller.file(), None, caller.line(), &args)
 }
 
 /*
- * Don't document this function. It's only used for internal by the macros
+ * Don't document this function. It's only used by the lI_try! macro to pass the function name
  */
 #[doc(hidden)]
-pub fn log<K: LogKey>(
-    k: K, lvl: Level, file: &str, func: &str, line: u32, fmt: &Arguments
-) -> Result<()> {
-    CTX::call(|ctx| {
-        let lm = ctx.get_mod(K::logscope())?;
-        match lm.get_submodule(k) {
-            Some(m) if m.will_log(lvl) => {
-                let scope = lm.env();
-                let ident = lm.env_ident();
-                m.do_log(&lm.name(), scope, ident, lvl, file, func, line, fmt)
-            }
-            Some(_) => Ok(()),
-            None => {
-                lE!(Internal, "Module {} not initialized", k);
-                Err(KeyNotInitialized)
-            }
+pub fn try_log_from<K: LogKey>(
+    k: K, lvl: Level, file: &str, func: Option<&str>, line: u32, fmt: &Arguments
+) -> Result<bool> {
+    try_log_at(K::logscope(), k.log_key(), lvl, file, func, line, fmt)
+}
+
+fn try_log_at(
+    scope: ScopeKey, key: ContextKey, lvl: Level, file: &str, func: Option<&str>, line: u32,
+    fmt: &Arguments,
+) -> Result<bool> {
+    if lvl == Level::Off {
+        return Err(InvalidLevel);
+    }
+    if crate::context::suppressed() {
+        return Ok(true);
+    }
+    // recursion is dropped silently, there is no lock free way to report it
+    let depth = LogDepth::enter();
+    if depth.outer() >= MAX_LOG_DEPTH.load(Ordering::Relaxed) {
+        return Ok(true);
+    }
+    let written = CTX::try_call(|ctx| {
+        let lm = ctx.get_mod(scope)?;
+        let m = lm.get_submodule_at(key).ok_or(KeyNotInitialized)?;
+        if !lm.will_log(m, lvl) {
+            return Ok(true);
         }
-    })
+        let (ident, instance, max) = (lm.env_ident(), lm.instance_id(), lm.func_max_len());
+        m.try_do_log(lm.name(), lm.env(), ident, instance, max, lvl, file, func, line, fmt)
+    })?;
+    if written != Some(true) {
+        CONTENDED.fetch_add(1, Ordering::Relaxed);
+    }
+    Ok(written == Some(true))
+}
+
+/// Defer the computation of a value until it's formatted
+///
+/// Returns a value implementing [`Display`] which calls `f` every time it's formatted and
+/// displays the result. Passed as argument to a log macro `f` only runs if the message is
+/// logged, so expensive computations (e.g. serializing a big struct) cost nothing while the
+/// level is disabled. The log macros provide the shortcut `lazy = || expr` for this.
+///
+/// # Examples
+///
+/// ```rust
+/// use hclog::lD5;
+/// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
+///
+/// # #[derive(HCLog, Copy, Clone)]
+/// enum Keys { State }
+/// # use Keys::*;
+///
+/// # Keys::init_with_defaults("test").unwrap();
+/// let state = vec![1, 2, 3];
+/// lD5!(State, "state: {}", hclog::lazy(|| format!("{:?}", state)));
+/// assert_eq!(hclog::lazy(|| 6 * 7).to_string(), "42");
+/// ```
+pub fn lazy<F, D>(f: F) -> impl Display
+where
+    F: Fn() -> D,
+    D: Display,
+{
+    struct Lazy<F>(F);
+    impl<F: Fn() -> D, D: Display> Display for Lazy<F> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            (self.0)().fmt(f)
+        }
+    }
+    Lazy(f)
+}
+
+/*
+ * Don't document these functions. They are only used by the default log macros (dI! etc.)
+ *
+ * The default key lives in the global context only, so it's the same within task scopes. The
+ * guard is dropped before the key is resolved via the usual (task-local first) path.
+ */
+#[doc(hidden)]
+pub fn log_default(
+    lvl: Level, file: &str, func: Option<&str>, line: u32, fmt: &Arguments
+) -> Result<()> {
+    let default = CTX::get()?.default_key().cloned();
+    match default {
+        Some(d) => log_at(d.scope, d.key, &d.name, lvl, file, func, line, fmt),
+        None => log(Internal, lvl, file, func, line, fmt),
+    }
+}
+#[doc(hidden)]
+pub fn test_log_default(lvl: Level) -> Result<bool> {
+    let default = CTX::get()?.default_key().cloned();
+    match default {
+        Some(d) => test_log_at(d.scope, d.key, lvl),
+        None => test_log(Internal, lvl),
+    }
 }
 
 #[doc(hidden)]
 pub fn test_log<K: LogKey>(k: K, lvl: Level) -> Result<bool> {
-    CTX::call(|ctx| {
-        match ctx.get_mod(K::logscope())?.get_submodule(k) {
-            Some(m) => Ok(m.will_log(lvl)),
+    test_log_at(K::logscope(), k.log_key(), lvl)
+}
+
+pub (crate) fn test_log_at(scope: ScopeKey, key: ContextKey, lvl: Level) -> Result<bool> {
+    if crate::context::suppressed() {
+        return Ok(false);
+    }
+    let expired = ExpiredSlot::default();
+    let res = CTX::call(|ctx| {
+        let lm = ctx.get_mod(scope)?;
+        match lm.get_submodule_at(key) {
+            Some(m) => {
+                note_expired_level(&expired, scope, m);
+                Ok(lm.will_log(m, lvl))
+            }
             None => Ok(false),
         }
-    })
+    });
+    report_expired_level(expired);
+    res
 }
 
 #[cfg(test)]
@@ -641,16 +3293,20 @@ pub (crate) mod libtest {
                                 Options::default())
     }
 
+    fn arg_err(arg: &str, e: crate::ErrorKind) -> Result<()> {
+        Err(InvalidLevelArg(arg.to_owned(), Box::new(e)))
+    }
+
     #[test]
     #[serial]
     fn set_mod_level_no_module() {
-        assert_eq!(crate::api::set_mod_level([":warn"]), Err(ParseArg));
+        assert_eq!(crate::api::set_mod_level([":warn"]), arg_err(":warn", ParseArg));
     }
 
     #[test]
     #[serial]
     fn set_mod_level_no_level() {
-        assert_eq!(crate::api::set_mod_level(["_all:"]), Err(ParseArg));
+        assert_eq!(crate::api::set_mod_level(["_all:"]), arg_err("_all:", ParseArg));
     }
 
     #[test]
@@ -662,7 +3318,25 @@ pub (crate) mod libtest {
     #[test]
     #[serial]
     fn set_mod_level_inval_level() {
-        assert_eq!(crate::api::set_mod_level(["_all:warning"]), Err(UnknownLogLevel));
+        assert_eq!(crate::api::set_mod_level(["_all:warning"]),
+                   arg_err("_all:warning", UnknownLogLevel));
+        let err = crate::api::set_mod_level(["libtestfoo:debgu3"]).unwrap_err();
+        assert_eq!(err.to_string(), concat!("Level argument 'libtestfoo:debgu3': ",
+                                            "Loglevel is unknown (did you mean debug3?)"));
+        let err = crate::api::set_mod_level(["libtestfoo:loud"]).unwrap_err();
+        assert_eq!(err.to_string(), "Level argument 'libtestfoo:loud': Loglevel is unknown");
+    }
+
+    #[test]
+    #[serial]
+    fn set_mod_level_syslog_names() {
+        init_libtest_mod().unwrap();
+        assert!(crate::api::set_mod_level(["libtestfoo:debug,libtestbar:3"]).is_ok());
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Debug1), Ok(true));
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Debug2), Ok(false));
+        assert_eq!(crate::api::test_log(LIBTESTBAR, Error), Ok(true));
+        assert_eq!(crate::api::test_log(LIBTESTBAR, Warn), Ok(false));
+        assert!(crate::api::set_mod_level(["_all:debug9"]).is_ok());
     }
 
     #[test]
@@ -692,4 +3366,291 @@ pub (crate) mod libtest {
         assert_eq!(crate::api::test_log(LIBTESTFOO, Debug10), Ok(true));
         assert_eq!(crate::api::test_log(LIBTESTBAR, Debug10), Ok(true));
     }
+
+    #[test]
+    #[serial]
+    fn set_mod_level_relative() {
+        init_libtest_mod().unwrap();
+        assert!(crate::api::set_mod_level(["_all:info", "libtestfoo:+2"]).is_ok());
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Debug2), Ok(true));
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Debug3), Ok(false));
+        assert!(crate::api::set_mod_level(["libtestfoo:-3,libtestbar:-1"]).is_ok());
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Notice), Ok(true));
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Info), Ok(false));
+        assert_eq!(crate::api::test_log(LIBTESTBAR, Notice), Ok(true));
+        assert_eq!(crate::api::test_log(LIBTESTBAR, Info), Ok(false));
+
+        // clamped to off and debug10
+        assert!(crate::api::set_mod_level(["libtestfoo:-42,_all:+0"]).is_ok());
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Emerg), Ok(false));
+        assert!(crate::api::set_mod_level(["_all:+200"]).is_ok());
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Debug10), Ok(true));
+        assert_eq!(crate::api::test_log(LIBTESTBAR, Debug10), Ok(true));
+
+        assert_eq!(crate::api::set_mod_level(["libtestfoo:+"]), arg_err("libtestfoo:+", ParseArg));
+        assert_eq!(crate::api::set_mod_level(["libtestfoo:+x"]),
+                   arg_err("libtestfoo:+x", ParseArg));
+        assert_eq!(crate::api::set_mod_level(["libtestfoo:--1"]),
+                   arg_err("libtestfoo:--1", ParseArg));
+        assert_eq!(crate::api::set_mod_level(["libtestfoo:+300"]),
+                   arg_err("libtestfoo:+300", ParseArg));
+        assert!(crate::api::set_mod_level(["_all:debug9"]).is_ok());
+    }
+
+    #[test]
+    #[serial]
+    fn set_mod_level_no_partial_apply() {
+        init_libtest_mod().unwrap();
+        assert!(crate::api::set_mod_level(["_all:info"]).is_ok());
+        // the failing chunk is in the middle of the second element
+        assert_eq!(crate::api::set_mod_level(["libtestfoo:debug3",
+                                              "libtestbar:warn,libtestbaz:error,_all:off"]),
+                   arg_err("libtestbaz:error", KeyNotInitialized));
+        assert_eq!(crate::api::set_mod_level(["_all:+2", "libtestfoo:loud"]),
+                   arg_err("libtestfoo:loud", UnknownLogLevel));
+        assert_eq!(crate::api::set_mod_level(["libtestfoo.*:debug1,libtestbar"]),
+                   arg_err("libtestbar", ParseArg));
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Info), Ok(true));
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Debug1), Ok(false));
+        assert_eq!(crate::api::test_log(LIBTESTBAR, Info), Ok(true));
+        assert_eq!(crate::api::test_log(LIBTESTBAR, Debug1), Ok(false));
+    }
+
+    #[test]
+    #[serial]
+    fn set_mod_level_plan_order() {
+        init_libtest_mod().unwrap();
+        // relative levels apply on top of the levels set before within the same call
+        assert!(crate::api::set_mod_level(["libtestfoo:error,_all:warn",
+                                           "libtestfoo:+1,libtestfoo:+1"]).is_ok());
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Info), Ok(true));
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Debug1), Ok(false));
+        assert_eq!(crate::api::test_log(LIBTESTBAR, Warn), Ok(true));
+        assert_eq!(crate::api::test_log(LIBTESTBAR, Notice), Ok(false));
+    }
+
+    #[test]
+    #[serial]
+    fn init_try_facade_names() {
+        init_libtest_mod().unwrap();
+        assert_eq!(crate::api::init_try::<TestKeys, _>("libtest", Debug9, "StdOut",
+                                                      Options::default()), Ok(()));
+        assert_eq!(crate::api::init_try::<TestKeys, _>("libtest", Debug9, String::from("none"),
+                                                      Options::default()), Ok(()));
+        assert_eq!(crate::api::init_try::<TestKeys, _>("libtest", Debug9, "stdin",
+                                                      Options::default()), Err(ParseArg));
+    }
+
+    #[test]
+    #[serial]
+    fn into_writer_buffers_lines() {
+        use std::io::Write;
+        init_libtest_mod().unwrap();
+        crate::api::set_logdest(LIBTESTFOO, FacadeVariant::RingBuffer(8)).unwrap();
+        let line = line!() + 1;
+        let mut writer = crate::api::into_writer(LIBTESTFOO, Info);
+        assert_eq!((writer.file, writer.line), (file!(), line));
+        assert_eq!(writer.write(b"first line\nsecond").unwrap(), 17);
+        assert_eq!(writer.buf, b"second");
+        writer.write_all(b" line\nthird\n").unwrap();
+        assert!(writer.buf.is_empty());
+        writer.write_all(b"partial").unwrap();
+        writer.flush().unwrap();
+        assert!(writer.buf.is_empty());
+        let logged = crate::api::drain_ring(LIBTESTFOO).unwrap();
+        let expected = ["first line", "second line", "third", "partial"];
+        assert_eq!(logged.len(), expected.len(), "{:?}", logged);
+        for (msg, expected) in logged.iter().zip(expected) {
+            let suffix = format!(" {}:{} {}", file!(), line, expected);
+            assert!(msg.ends_with(&suffix), "{:?}", logged);
+        }
+
+        // a failing line consumes its bytes, the error is returned by flush
+        let mut writer = crate::api::into_writer(LIBTESTFOO, Off);
+        assert_eq!(writer.write(b"dropped\nkept").unwrap(), 12);
+        assert_eq!(writer.buf, b"kept");
+        let err = writer.flush().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+        assert!(writer.buf.is_empty());
+        writer.flush().unwrap();
+        assert!(crate::api::drain_ring(LIBTESTFOO).unwrap().is_empty());
+        crate::api::set_logdest(LIBTESTFOO, FacadeVariant::StdOut).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn log_level_off() {
+        init_libtest_mod().unwrap();
+        let fmt = format_args!("never written");
+        assert_eq!(crate::api::log(LIBTESTFOO, Off, file!(), None, line!(), &fmt),
+            Err(InvalidLevel));
+        assert_eq!(crate::api::test_log(LIBTESTFOO, Off), Ok(false));
+        assert_eq!(crate::api::log(LIBTESTFOO, Emerg, file!(), None, line!(), &fmt), Ok(()));
+    }
+
+    #[test]
+    #[serial]
+    fn health_summary_fields() {
+        init_libtest_mod().unwrap();
+        let summary = crate::api::health_summary();
+        let fields = summary.split(' ').map(|f| f.split_once('=').unwrap()).collect::<Vec<_>>();
+        let names = fields.iter().map(|(n, _)| *n).collect::<Vec<_>>();
+        assert_eq!(names, ["scopes", "keys", "active", "errors_last_min", "facade_failures",
+            "dropped"]);
+        // internal scope (hclog, logcompat) and libtest (libtestfoo, libtestbar, libtestrec). In
+        // single-thread mode logcompat is only added to the context of the thread initializing it
+        // first.
+        let compat = crate::api::has_module(crate::InternalLogKeys::LogCompat).unwrap();
+        let keys = 4 + compat as usize;
+        assert_eq!(fields[0], ("scopes", "2"));
+        assert_eq!(fields[1], ("keys", keys.to_string().as_str()));
+        assert!(summary.ends_with("errors_last_min=0 facade_failures=0 dropped=0"));
+
+        // pin down every key to get an exact active count: one active libtest key, one without
+        // facade, one turned off and the internal keys muted
+        let internal = [crate::InternalLogKeys::Internal, crate::InternalLogKeys::LogCompat];
+        let internal = &internal[..1 + compat as usize];
+        let was_muted = internal.iter().map(|k| crate::api::is_muted(*k).unwrap())
+            .collect::<Vec<_>>();
+        internal.iter().for_each(|k| crate::api::mute(*k).unwrap());
+        crate::api::set_logdest(LIBTESTBAR, FacadeVariant::None).unwrap();
+        crate::api::set_level(LIBTESTREC, Level::Off).unwrap();
+        let summary = crate::api::health_summary();
+        internal.iter().zip(was_muted).filter(|(_, m)| !m)
+            .for_each(|(k, _)| crate::api::unmute(*k).unwrap());
+        assert_eq!(summary, format!("scopes=2 keys={} active=1 errors_last_min=0 \
+            facade_failures=0 dropped=0", keys));
+        crate::api::set_logdest(LIBTESTBAR, FacadeVariant::StdOut).unwrap();
+        crate::api::set_level(LIBTESTREC, Level::Debug9).unwrap();
+
+        std::env::set_var("HCLOG_DUMP_MODULES", "1");
+        let mut out = Vec::new();
+        crate::api::dump(&mut out).unwrap();
+        std::env::remove_var("HCLOG_DUMP_MODULES");
+        assert!(String::from_utf8(out).unwrap().starts_with(&format!("scopes=2 keys={} ", keys)));
+
+        let mut out = Vec::new();
+        crate::api::dump(&mut out).unwrap();
+        assert!(out.is_empty());
+        crate::api::dump_always(&mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with(&format!("scopes=2 keys={} ", keys)));
+    }
+
+    #[test]
+    #[serial]
+    fn sampling_counts_dropped() {
+        init_libtest_mod().unwrap();
+        crate::api::set_logdest(LIBTESTBAR, FacadeVariant::None).unwrap();
+        crate::api::set_level(LIBTESTBAR, Debug10).unwrap();
+        crate::api::set_sampling(LIBTESTBAR, Debug1, 10).unwrap();
+        for _ in 0..100 {
+            crate::api::log(LIBTESTBAR, Debug3, file!(), None, line!(), &format_args!("x"))
+                .unwrap();
+        }
+        // no facade at all - nothing is sampled
+        assert!(crate::api::health_summary().ends_with(" dropped=0"));
+        let sub = crate::api::subscribe(LIBTESTBAR, 100).unwrap();
+        for _ in 0..100 {
+            crate::api::log(LIBTESTBAR, Debug3, file!(), None, line!(), &format_args!("x"))
+                .unwrap();
+        }
+        assert_eq!(sub.try_iter().count(), 10);
+        assert!(crate::api::health_summary().ends_with(" dropped=90"));
+        crate::api::set_sampling(LIBTESTBAR, Debug1, 1).unwrap();
+        assert!(crate::api::health_summary().ends_with(" dropped=0"));
+        crate::api::set_logdest(LIBTESTBAR, FacadeVariant::StdOut).unwrap();
+        crate::api::set_level(LIBTESTBAR, Debug9).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn list_modules_detailed_lines() {
+        init_libtest_mod().unwrap();
+        crate::api::set_mod_level(["libtestfoo:debug3"]).unwrap();
+        let mut out = Vec::new();
+        crate::api::list_modules_detailed(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        // facade and options may have been changed at runtime by other tests
+        let line = out.lines().find(|l| l.starts_with("  libtestfoo ")).unwrap();
+        let prefix = "  libtestfoo [level=debug3 (runtime)] [facade=stdout (";
+        assert!(line.starts_with(prefix), "{}", out);
+        assert!(line.contains(&format!("[opts={} (", Options::default())), "{}", out);
+        assert!(out.lines().any(|l| l.starts_with("  libtestbar [level=")));
+        assert!(out.lines().all(|l| l.starts_with("  ") && l.ends_with(']')));
+    }
+
+    #[test]
+    fn file_prefix_rejected() {
+        for prefix in ["", "/", "\\", "//"] {
+            assert_eq!(crate::api::add_file_prefix_strip(prefix), Err(ParseArg));
+        }
+    }
+
+    #[test]
+    fn prefix_separator_rejected() {
+        for sep in ["", "\n", " |\r", "\\"] {
+            assert_eq!(crate::api::set_prefix_separator(Some(sep)), Err(ParseArg));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn add_submodule_index_bound() {
+        #[derive(Copy, Clone)]
+        struct HugeKey(usize);
+        impl Scope for HugeKey {
+            fn init<S: std::fmt::Display>(_: S, _: Level, _: FacadeVariant, _: Options)
+                -> Result<()> { Ok(()) }
+        }
+        impl crate::LogKey for HugeKey {
+            fn log_key(&self) -> crate::ContextKey { self.0 }
+        }
+        impl std::fmt::Display for HugeKey {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "huge{}", self.0)
+            }
+        }
+        init_libtest_mod().unwrap();
+        // HugeKey shares the default scope with the TestKeys
+        for key in [crate::MAX_SUBMODULES, usize::MAX] {
+            assert_eq!(crate::api::add_submodules(&[HugeKey(key)]), Err(KeyNotInitialized));
+        }
+        let msg = crate::api::failure_message(HugeKey(40), KeyNotInitialized);
+        assert_eq!(msg, "hclog: LogKey 'huge40' (index 40) is not initialized in scope \
+            'libtest'; did you forget to call HugeKey::init(...) or add_submodules(&[HugeKey])?");
+        assert_eq!(crate::api::log(HugeKey(40), Info, file!(), None, line!(), &format_args!("x")),
+            Err(KeyNotInitialized));
+    }
+
+    // facade logging once more to its own key for every message
+    #[derive(Debug, Default)]
+    struct Recursive {
+        calls: std::sync::atomic::AtomicUsize,
+        set_level: std::sync::Mutex<Option<Result<()>>>,
+    }
+    impl crate::facades::LogFacade for Recursive {
+        fn log(&self, _: Level, _: crate::message::Message) -> Result<()> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            *self.set_level.lock().unwrap() = Some(crate::api::set_level(LIBTESTREC, Debug1));
+            crate::api::log(LIBTESTREC, Error, file!(), None, line!(), &format_args!("again"))
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn recursive_facade() {
+        init_libtest_mod().unwrap();
+        let facade = crate::facades::FacadeRc::new(Recursive::default());
+        crate::context::CTX::get_mut().unwrap().get_mod_mut(TestKeys::logscope()).unwrap()
+            .get_submodule_mut(LIBTESTREC).unwrap()
+            .set_facade(crate::facades::FacadeScope::Local(facade.clone()));
+
+        let res = crate::api::log(LIBTESTREC, Error, file!(), None, line!(), &format_args!("a"));
+        assert_eq!(res, Ok(()));
+        // the default depth allows two nested calls, the third one goes to stderr
+        assert_eq!(facade.calls.load(std::sync::atomic::Ordering::Relaxed), 2);
+        // a write access from within the facade fails instead of deadlocking
+        assert_eq!(*facade.set_level.lock().unwrap(), Some(Err(ContextLock)));
+        assert_eq!(crate::api::set_level(LIBTESTREC, Debug1), Ok(()));
+    }
 }
@@ -0,0 +1,51 @@
+/*
+ * flush the buffered facades when the process exits
+ *
+ * The global context lives in a static and is never dropped, so the BufWriter of a file facade
+ * never writes the messages held back by its FlushPolicy if the application doesn't call flush.
+ * The first file facade registers an atexit handler which flushes all Scopes on a best-effort
+ * basis: the context lock and the locks of the submodules and facades are only tried. If
+ * another thread keeps holding one of them the handler gives up after EXIT_LOCK_TIMEOUT instead
+ * of hanging the exit, the other Scopes and submodules are flushed anyway. Disable the
+ * `flush-at-exit` feature to not register the handler at all.
+ */
+use crate::context::{LogDepth, CTX};
+use std::{
+    sync::Once,
+    thread,
+    time::{Duration, Instant},
+};
+
+const EXIT_LOCK_TIMEOUT: Duration = Duration::from_millis(100);
+static REGISTER: Once = Once::new();
+
+extern "C" fn flush_at_exit() {
+    // exit called while a message is logged, the facade locks might be held by this thread
+    if LogDepth::active() {
+        return;
+    }
+    let deadline = Instant::now() + EXIT_LOCK_TIMEOUT;
+    loop {
+        match CTX::try_get() {
+            Ok(Some(ctx)) => {
+                let mut pending = ctx.logmods().filter(|lm| lm.initialized()).collect::<Vec<_>>();
+                loop {
+                    pending.retain(|lm| !lm.try_flush());
+                    if pending.is_empty() || Instant::now() >= deadline {
+                        return;
+                    }
+                    thread::sleep(Duration::from_millis(1));
+                }
+            }
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(1)),
+            _ => return,
+        }
+    }
+}
+
+// register flush_at_exit once per process
+pub (crate) fn register() {
+    REGISTER.call_once(|| unsafe {
+        libc::atexit(flush_at_exit);
+    });
+}
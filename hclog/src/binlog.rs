@@ -0,0 +1,274 @@
+//! Compact binary log written by [`FacadeVariant::Binary`](crate::FacadeVariant::Binary)
+//!
+//! The binary facade stores the raw properties of a message instead of the rendered line. The
+//! file is read back with [`Reader`], each [`Record`] can be rendered with any [`Options`]
+//! later on.
+//!
+//! # Format
+//!
+//! The file is a plain sequence of records without a header, so appending to an existing file
+//! is fine. Every record is prefixed with its length, all integers are little endian:
+//!
+//! | field   | size                                             |
+//! |---------|--------------------------------------------------|
+//! | length  | `u32`, number of bytes following                 |
+//! | version | `u8`, currently `1`                              |
+//! | level   | `u8`, index of the [`Level`] variant             |
+//! | time    | `u64`, microseconds since the unix epoch         |
+//! | module  | `u32` length followed by the UTF-8 name          |
+//! | message | `u32` length followed by the UTF-8 message       |
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use hclog::{binlog::Reader, options::OPTIONS_COMPACT};
+//!
+//! for record in Reader::open("/tmp/hclog.bin").unwrap() {
+//!     match record {
+//!         Ok(record) => println!("{}", record.render(&OPTIONS_COMPACT)),
+//!         Err(e) => eprintln!("damaged tail: {}", e),
+//!     }
+//! }
+//! ```
+use crate::{level::Level, message::Message, options::*, ErrorKind, Result};
+use chrono::{DateTime, Utc};
+use std::{
+    fmt,
+    fs::File,
+    io::{BufRead, BufReader, ErrorKind as IoErrorKind, Read},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use strum::IntoEnumIterator;
+
+const VERSION: u8 = 1;
+
+// encode a single record including the length prefix
+pub (crate) fn encode(level: Level, time: DateTime<Utc>, module: &str, message: &str) -> Vec<u8> {
+    let body_len = 1 + 1 + 8 + 4 + module.len() + 4 + message.len();
+    let mut buf = Vec::with_capacity(4 + body_len);
+    buf.extend_from_slice(&(body_len as u32).to_le_bytes());
+    buf.push(VERSION);
+    buf.push(level as u8);
+    buf.extend_from_slice(&(time.timestamp_micros().max(0) as u64).to_le_bytes());
+    buf.extend_from_slice(&(module.len() as u32).to_le_bytes());
+    buf.extend_from_slice(module.as_bytes());
+    buf.extend_from_slice(&(message.len() as u32).to_le_bytes());
+    buf.extend_from_slice(message.as_bytes());
+    buf
+}
+
+/// A single message read from a binary log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    level: Level,
+    micros: u64,
+    module: String,
+    message: String,
+}
+impl Record {
+    /// The [`Level`] the message was logged with
+    pub fn level(&self) -> Level {
+        self.level
+    }
+    /// The time the message was logged at, with microsecond precision
+    pub fn time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_micros(self.micros)
+    }
+    /// The name of the LogKey the message was logged with
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+    /// The formatted message without any prefix
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    /// Render the record the same way a text facade renders a message
+    ///
+    /// Only the properties stored in the record are rendered: [`DATESTAMP`], [`TIMESTAMP`],
+    /// [`NANOSEC`], [`SEVERITY`], [`MODULE`] and [`RAW`]. All other options are ignored.
+    pub fn render(&self, options: &Options) -> String {
+        // anything else isn't stored in the record
+        let recorded = DATESTAMP + TIMESTAMP + NANOSEC + SEVERITY + MODULE + RAW;
+        self.render_args(*options & recorded, format_args!("{}", self.message))
+    }
+    fn render_args(&self, options: Options, fmt: fmt::Arguments) -> String {
+        let mut msg = Message::new(&options, "", "", None, 0, &fmt);
+        msg.set_time(DateTime::<Utc>::from(self.time()));
+        msg.set_severity(&self.level);
+        msg.set_modname(&self.module);
+        msg.to_string()
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self> {
+        let mut cursor = Cursor(buf);
+        if cursor.u8()? != VERSION {
+            return Err(invalid_data());
+        }
+        let level = Level::iter().nth(cursor.u8()? as usize).ok_or_else(invalid_data)?;
+        let micros = u64::from_le_bytes(cursor.array()?);
+        let module = cursor.string()?;
+        let message = cursor.string()?;
+        if !cursor.0.is_empty() {
+            return Err(invalid_data());
+        }
+        Ok(Self { level, micros, module, message })
+    }
+}
+
+fn invalid_data() -> ErrorKind {
+    ErrorKind::IoError(IoErrorKind::InvalidData)
+}
+
+// the not yet decoded part of a record
+struct Cursor<'a>(&'a [u8]);
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(invalid_data());
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut a = [0; N];
+        a.copy_from_slice(self.take(N)?);
+        Ok(a)
+    }
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+    fn string(&mut self) -> Result<String> {
+        let len = u32::from_le_bytes(self.array()?) as usize;
+        let s = self.take(len)?;
+        String::from_utf8(s.to_vec()).map_err(|_| invalid_data())
+    }
+}
+
+/// Iterator over the [`Record`]s of a binary log
+///
+/// A damaged or truncated record is returned as the last item, e.g. if the writing process
+/// died while writing it. The error is [`IoError`](ErrorKind::IoError) with
+/// [`UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) for a truncated record and
+/// [`InvalidData`](std::io::ErrorKind::InvalidData) for a malformed one. The iteration ends
+/// after the first error.
+#[derive(Debug)]
+pub struct Reader<R = BufReader<File>> {
+    inner: R,
+    done: bool,
+}
+impl Reader {
+    /// Open the binary log at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IoError`](ErrorKind::IoError) if the file can't be opened.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+impl<R: BufRead> Reader<R> {
+    /// Read the records from any buffered reader, e.g. a `&[u8]`
+    pub fn new(inner: R) -> Self {
+        Self { inner, done: false }
+    }
+    fn read_record(&mut self) -> Result<Option<Record>> {
+        if self.inner.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let mut len = [0; 4];
+        self.inner.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as usize;
+        // don't trust the length of a damaged record for the allocation
+        let mut buf = Vec::new();
+        (&mut self.inner).take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() < len {
+            return Err(ErrorKind::IoError(IoErrorKind::UnexpectedEof));
+        }
+        Record::decode(&buf).map(Some)
+    }
+}
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let record = self.read_record();
+        self.done = !matches!(record, Ok(Some(_)));
+        record.transpose()
+    }
+}
+
+#[cfg(test)]
+mod binlog_tests {
+    use super::*;
+
+    fn record(level: Level, micros: u64, module: &str, message: &str) -> Record {
+        Record { level, micros, module: module.to_owned(), message: message.to_owned() }
+    }
+
+    fn encode_record(r: &Record) -> Vec<u8> {
+        encode(r.level, DateTime::<Utc>::from(r.time()), &r.module, &r.message)
+    }
+
+    #[test]
+    fn round_trip() {
+        let levels = Level::iter().skip(1).collect::<Vec<_>>();
+        let records = (0..3000u64)
+            .map(|i| record(levels[i as usize % levels.len()], 1_700_000_000_000_000 + i * 1001,
+                &format!("mod{}", i % 7), &"x".repeat(i as usize % 300)))
+            .chain([record(Level::Info, 0, "", ""), record(Level::Debug10, 1, "ünï", "🦀\n✓")])
+            .collect::<Vec<_>>();
+        let buf = records.iter().flat_map(encode_record).collect::<Vec<_>>();
+        let read = Reader::new(buf.as_slice()).collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(read, records);
+    }
+
+    #[test]
+    fn render() {
+        // 2023-11-14 22:13:20.000123 UTC
+        let r = record(Level::Warn, 1_700_000_000_000_123, "db", "connection lost");
+        assert_eq!(r.render(&(DATESTAMP + TIMESTAMP + NANOSEC + SEVERITY + MODULE)),
+            "2023-11-14 22:13:20.000123000 warn db connection lost");
+        assert_eq!(r.render(&OPTIONS_COMPACT), "22:13:20 warn db connection lost");
+        assert_eq!(r.render(&(Options::default() + RAW)), "connection lost");
+        assert_eq!(r.render(&(PID + TID + FILE + LINE + BINNAME + SCOPE)), "connection lost");
+    }
+
+    #[test]
+    fn damaged_tail() {
+        let r = record(Level::Info, 42, "m", "msg");
+        let good = encode_record(&r);
+        let err = |kind| Err(ErrorKind::IoError(kind));
+
+        assert_eq!(Reader::new(&[][..]).count(), 0);
+        for cut in 1..good.len() {
+            let buf = [&good[..], &good[..cut]].concat();
+            let read = Reader::new(buf.as_slice()).collect::<Vec<_>>();
+            assert_eq!(read, [Ok(r.clone()), err(IoErrorKind::UnexpectedEof)], "cut at {}", cut);
+        }
+
+        let mut bad_version = good.clone();
+        bad_version[4] = 2;
+        let mut bad_level = good.clone();
+        bad_level[5] = 18;
+        let mut bad_utf8 = good.clone();
+        *bad_utf8.last_mut().unwrap() = 0xff;
+        let mut bad_len = good.clone();
+        bad_len[0] += 1;
+        bad_len.push(0);
+        let mut huge_len = good.clone();
+        huge_len[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let inval = IoErrorKind::InvalidData;
+        for (buf, kind) in [(bad_version, inval), (bad_level, inval), (bad_utf8, inval),
+            (bad_len, inval), (huge_len, IoErrorKind::UnexpectedEof)]
+        {
+            let buf = [&good[..], &buf[..], &good[..]].concat();
+            let read = Reader::new(buf.as_slice()).collect::<Vec<_>>();
+            assert_eq!(read, [Ok(r.clone()), err(kind)]);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! C ABI for applications logging from C and Rust into the same scopes
+//!
+//! This module is only available with the `capi` feature enabled. The declarations for C are
+//! in `include/hclog.h`, an example is in `examples/capi.c`.
+//!
+//! C has no [`LogKey`](trait@crate::LogKey) type, so the scope and the key are addressed by
+//! their indices: the [`ScopeKey`](crate::ScopeKey) and the index returned by
+//! [`LogKey::log_key`](crate::LogKey::log_key) (the discriminant for derived LogKeys). The
+//! level is the index of the [`Level`] variant, e.g. `4` for [`Level::Error`].
+//!
+//! The scopes and keys are initialized on the Rust side. Messages logged from C use the same
+//! facades and options as the ones logged from Rust, without a function name. Every function
+//! validates its arguments and catches panics, errors are reported as negative return codes.
+//!
+//! To link the functions into a C application, depend on `hclog` with the `capi` feature from
+//! a crate built as `staticlib` or `cdylib`.
+
+use crate::{
+    api::{log_at, test_log_at, set_level_at},
+    context::CTX,
+    logmod::ScopeKey,
+    ContextKey, ErrorKind, Level, Result,
+};
+use libc::{c_char, c_int};
+use std::{ffi::CStr, fmt, panic};
+use strum::IntoEnumIterator;
+
+/// The call succeeded
+pub const HCLOG_OK: c_int = 0;
+/// The scope index is invalid or the scope is not initialized
+pub const HCLOG_ERR_SCOPE: c_int = -1;
+/// The key index is invalid or the key is not initialized
+pub const HCLOG_ERR_KEY: c_int = -2;
+/// The level is not a valid `Level`
+pub const HCLOG_ERR_LEVEL: c_int = -3;
+/// A string argument is NULL or not valid UTF-8
+pub const HCLOG_ERR_ARG: c_int = -4;
+/// The context can't be accessed
+pub const HCLOG_ERR_LOCK: c_int = -5;
+/// Writing the message to the facade failed
+pub const HCLOG_ERR_WRITE: c_int = -6;
+/// Any other error, including a panic caught at the boundary
+pub const HCLOG_ERR_OTHER: c_int = -7;
+
+fn code(e: ErrorKind) -> c_int {
+    match e {
+        ErrorKind::ScopeNotInitialized | ErrorKind::InvalidScope => HCLOG_ERR_SCOPE,
+        ErrorKind::KeyNotInitialized => HCLOG_ERR_KEY,
+        ErrorKind::UnknownLogLevel | ErrorKind::InvalidLevel => HCLOG_ERR_LEVEL,
+        ErrorKind::InvalFmtString => HCLOG_ERR_ARG,
+        ErrorKind::ContextLock => HCLOG_ERR_LOCK,
+        ErrorKind::IoError(_) | ErrorKind::WriteFailed => HCLOG_ERR_WRITE,
+        _ => HCLOG_ERR_OTHER,
+    }
+}
+
+// name of a key in messages about the key itself
+#[derive(Copy, Clone)]
+struct KeyIndex(ScopeKey, ContextKey);
+impl fmt::Display for KeyIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]", self.0, self.1)
+    }
+}
+
+// resolve the indices to an initialized key - unused entries of a scope are rejected as well
+fn resolve(scope_key: usize, context_key: usize) -> Result<KeyIndex> {
+    let scope = ScopeKey::from_index(scope_key).ok_or(ErrorKind::InvalidScope)?;
+    CTX::call(|ctx| {
+        match ctx.get_mod(scope)?.get_submodule_at(context_key) {
+            Some(submod) if submod.initialized() => Ok(KeyIndex(scope, context_key)),
+            _ => Err(ErrorKind::KeyNotInitialized),
+        }
+    })
+}
+
+fn level(level: u32) -> Result<Level> {
+    Level::iter().nth(level as usize).ok_or(ErrorKind::UnknownLogLevel)
+}
+
+// messages can't be logged with Level::Off
+fn msg_level(lvl: u32) -> Result<Level> {
+    level(lvl).and_then(|l| if l == Level::Off { Err(ErrorKind::InvalidLevel) } else { Ok(l) })
+}
+
+/*
+ * SAFETY: the caller guarantees that a non NULL pointer references a NUL terminated string
+ * which stays valid for the duration of the call.
+ */
+unsafe fn str_arg<'a>(s: *const c_char) -> Result<&'a str> {
+    if s.is_null() {
+        return Err(ErrorKind::InvalFmtString);
+    }
+    CStr::from_ptr(s).to_str().map_err(|_| ErrorKind::InvalFmtString)
+}
+
+fn ffi_call<F: FnOnce() -> Result<c_int> + panic::UnwindSafe>(f: F) -> c_int {
+    match panic::catch_unwind(f) {
+        Ok(Ok(ret)) => ret,
+        Ok(Err(e)) => code(e),
+        Err(_) => HCLOG_ERR_OTHER,
+    }
+}
+
+/// Log the message `msg` with `level` via the key `context_key` of scope `scope_key`
+///
+/// `file` may be NULL, `msg` must not. The message is logged as is, it is not interpreted as a
+/// format string. Returns [`HCLOG_OK`] or one of the negative `HCLOG_ERR_*` codes.
+///
+/// # Safety
+///
+/// `file` and `msg` must be NULL or point to NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn hclog_log(
+    scope_key: usize, context_key: usize, level: u32,
+    file: *const c_char, line: u32, msg: *const c_char,
+) -> c_int {
+    ffi_call(|| {
+        let lvl = msg_level(level)?;
+        let file = if file.is_null() { "" } else { str_arg(file)? };
+        let msg = str_arg(msg)?;
+        let key = resolve(scope_key, context_key)?;
+        log_at(key.0, key.1, &key, lvl, file, None, line, &format_args!("{}", msg))?;
+        Ok(HCLOG_OK)
+    })
+}
+
+/// Set the level of the key `context_key` of scope `scope_key`
+///
+/// Returns [`HCLOG_OK`] or one of the negative `HCLOG_ERR_*` codes.
+#[no_mangle]
+pub extern "C" fn hclog_set_level(scope_key: usize, context_key: usize, level: u32) -> c_int {
+    ffi_call(|| {
+        let lvl = self::level(level)?;
+        let key = resolve(scope_key, context_key)?;
+        set_level_at(key.0, key.1, lvl)?;
+        Ok(HCLOG_OK)
+    })
+}
+
+/// Test if a message with `level` would be logged via key `context_key` of scope `scope_key`
+///
+/// Returns `false` for any invalid argument.
+#[no_mangle]
+pub extern "C" fn hclog_test_log(scope_key: usize, context_key: usize, level: u32) -> bool {
+    ffi_call(|| {
+        let lvl = msg_level(level)?;
+        let key = resolve(scope_key, context_key)?;
+        Ok(test_log_at(key.0, key.1, lvl)? as c_int)
+    }) == 1
+}
+
+#[cfg(test)]
+mod capi_tests {
+    use super::*;
+    use crate::{
+        libtest::init_libtest_mod,
+        log_internal::test::TestKeys::{self, *},
+        LogKey, Scope,
+    };
+    use serial_test::serial;
+    use std::ptr;
+
+    fn scope() -> usize {
+        TestKeys::logscope() as usize
+    }
+
+    fn c_str(s: &[u8]) -> *const c_char {
+        s.as_ptr().cast()
+    }
+
+    #[test]
+    fn log_and_validate() {
+        init_libtest_mod().unwrap();
+        let (file, msg) = (c_str(b"capi.c\0"), c_str(b"from C\0"));
+        let key = LIBTESTFOO.log_key();
+        unsafe {
+            assert_eq!(hclog_log(scope(), key, Level::Emerg as u32, file, 1, msg), HCLOG_OK);
+            assert_eq!(hclog_log(scope(), key, Level::Emerg as u32, ptr::null(), 1, msg), HCLOG_OK);
+            assert_eq!(hclog_log(scope(), key, Level::Emerg as u32, file, 1, ptr::null()),
+                HCLOG_ERR_ARG);
+            assert_eq!(hclog_log(scope(), key, Level::Emerg as u32, file, 1,
+                c_str(b"\xff\xfe\0")), HCLOG_ERR_ARG);
+            assert_eq!(hclog_log(scope(), key, 18, file, 1, msg), HCLOG_ERR_LEVEL);
+            assert_eq!(hclog_log(scope(), key, Level::Off as u32, file, 1, msg), HCLOG_ERR_LEVEL);
+            assert_eq!(hclog_log(scope(), 4095, Level::Emerg as u32, file, 1, msg), HCLOG_ERR_KEY);
+            assert_eq!(hclog_log(scope(), usize::MAX, Level::Emerg as u32, file, 1, msg),
+                HCLOG_ERR_KEY);
+            assert_eq!(hclog_log(ScopeKey::MAX as usize, key, Level::Emerg as u32, file, 1, msg),
+                HCLOG_ERR_SCOPE);
+            assert_eq!(hclog_log(usize::MAX, key, Level::Emerg as u32, file, 1, msg),
+                HCLOG_ERR_SCOPE);
+        }
+    }
+
+    // every #define of the hand-written header matches the value on the Rust side
+    #[test]
+    fn header_constants() {
+        let defines = include_str!("../include/hclog.h").lines()
+            .filter_map(|l| l.strip_prefix("#define HCLOG_"))
+            .filter_map(|l| {
+                let mut parts = l.split_whitespace();
+                Some((parts.next()?.to_owned(), parts.next()?.parse::<i64>().ok()?))
+            })
+            .collect::<Vec<_>>();
+        let scopes = (0..ScopeKey::MAX as usize).filter_map(ScopeKey::from_index)
+            .map(|s| (format!("SCOPE_{:?}", s).to_uppercase(), s as i64));
+        let levels = Level::iter().map(|l| (format!("LEVEL_{}", l).to_uppercase(), l as i64));
+        let codes = [
+            ("OK", HCLOG_OK), ("ERR_SCOPE", HCLOG_ERR_SCOPE), ("ERR_KEY", HCLOG_ERR_KEY),
+            ("ERR_LEVEL", HCLOG_ERR_LEVEL), ("ERR_ARG", HCLOG_ERR_ARG),
+            ("ERR_LOCK", HCLOG_ERR_LOCK), ("ERR_WRITE", HCLOG_ERR_WRITE),
+            ("ERR_OTHER", HCLOG_ERR_OTHER),
+        ].map(|(n, c)| (n.to_owned(), c as i64));
+        let expected = scopes.chain(levels).chain(codes).collect::<Vec<_>>();
+        assert_eq!(defines, expected);
+    }
+
+    #[test]
+    #[serial]
+    fn set_and_test_level() {
+        init_libtest_mod().unwrap();
+        let key = LIBTESTBAR.log_key();
+        assert_eq!(hclog_set_level(scope(), key, Level::Notice as u32), HCLOG_OK);
+        assert!(hclog_test_log(scope(), key, Level::Notice as u32));
+        assert!(!hclog_test_log(scope(), key, Level::Info as u32));
+        assert!(!hclog_test_log(scope(), key, Level::Off as u32));
+        assert!(!hclog_test_log(scope(), key, 42));
+        assert!(!hclog_test_log(scope(), 4095, Level::Emerg as u32));
+        assert_eq!(hclog_set_level(scope(), key, 18), HCLOG_ERR_LEVEL);
+        assert_eq!(hclog_set_level(scope(), 4095, Level::Info as u32), HCLOG_ERR_KEY);
+        assert_eq!(hclog_set_level(7, key, Level::Info as u32), HCLOG_ERR_SCOPE);
+        assert_eq!(crate::api::test_log(LIBTESTBAR, Level::Notice), Ok(true));
+    }
+}
@@ -0,0 +1,160 @@
+/*
+ * source of the current time for all time based features
+ *
+ * Everything reading the time (timestamps of messages, UPTIME, DEDUP intervals, levels set via
+ * set_level_for) asks this module instead of the std/chrono functions, so tests can replace
+ * the clock via set_clock. The clock is process wide, even in single-thread mode. As long as
+ * no clock is set the system clock is read without taking the lock.
+ *
+ * The wall clock may jump back (e.g. after restoring a VM snapshot), so it's only used for the
+ * timestamps. All intervals are measured with instants, which are monotonic for the system
+ * clock. The instants of a replaced clock are kept monotonic here, see monotonic().
+ */
+use chrono::{DateTime, Utc};
+use std::{
+    fmt::Debug,
+    sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+#[cfg(any(test, feature = "test-util"))]
+use std::time::SystemTime;
+
+/// Source of the current time
+///
+/// Replaces the system clock for all time based features via
+/// [`set_clock`](fn@crate::set_clock), e.g. with a [`MockClock`] in tests.
+pub trait Clock: Debug + Send + Sync {
+    /// The current wall clock time, used for the timestamps of messages
+    fn now_utc(&self) -> DateTime<Utc>;
+    /// The current monotonic time, used to measure durations
+    ///
+    /// If the returned time goes back, the step is ignored: the time measured by the library
+    /// continues from the latest instant returned before.
+    fn instant(&self) -> Instant;
+}
+
+/// The system clock used by default
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now_utc(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+static REPLACED: AtomicBool = AtomicBool::new(false);
+static CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+
+fn with_clock<R>(f: impl FnOnce(&dyn Clock) -> R) -> R {
+    if !REPLACED.load(Ordering::Acquire) {
+        return f(&SystemClock);
+    }
+    match *CLOCK.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(ref clock) => f(clock.as_ref()),
+        None => f(&SystemClock),
+    }
+}
+pub (crate) fn now_utc() -> DateTime<Utc> {
+    with_clock(|c| c.now_utc())
+}
+pub (crate) fn instant() -> Instant {
+    if !REPLACED.load(Ordering::Acquire) {
+        return Instant::now();
+    }
+    monotonic(with_clock(|c| c.instant()))
+}
+
+/*
+ * a step back of a replaced clock is added to the skew, so the time continues from the last
+ * instant handed out. Neither is an interval stalled until the clock catches up nor does any
+ * duration turn negative.
+ */
+struct Monotonic {
+    last: Option<Instant>,
+    skew: Duration,
+}
+static MONOTONIC: Mutex<Monotonic> = Mutex::new(Monotonic { last: None, skew: Duration::ZERO });
+
+fn monotonic(raw: Instant) -> Instant {
+    let mut mono = MONOTONIC.lock().unwrap_or_else(|e| e.into_inner());
+    let mut now = raw.checked_add(mono.skew).unwrap_or(raw);
+    if let Some(last) = mono.last.filter(|last| now < *last) {
+        mono.skew += last - now;
+        now = last;
+    }
+    mono.last = Some(now);
+    now
+}
+// None restores the system clock
+#[cfg(any(test, feature = "test-util"))]
+pub (crate) fn set_clock(clock: Option<Arc<dyn Clock>>) {
+    let mut current = CLOCK.write().unwrap_or_else(|e| e.into_inner());
+    REPLACED.store(clock.is_some(), Ordering::Release);
+    *current = clock;
+    // a new clock starts its own time line
+    *MONOTONIC.lock().unwrap_or_else(|e| e.into_inner()) =
+        Monotonic { last: None, skew: Duration::ZERO };
+}
+
+/// A clock which only moves when told so
+///
+/// The clock starts at the given time and stays there until it's moved via
+/// [`advance`](MockClock::advance) or [`rewind`](MockClock::rewind). Clones share the time, so
+/// the clone passed to [`set_clock`](fn@crate::set_clock) can be moved via the original.
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    start_utc: SystemTime,
+    start: Instant,
+    offset: Arc<Mutex<Offset>>,
+}
+// the distance from the start, the clock may be rewound behind it
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Debug, Default, Clone, Copy)]
+struct Offset {
+    ahead: Duration,
+    behind: Duration,
+}
+#[cfg(any(test, feature = "test-util"))]
+impl MockClock {
+    /// Create a new `MockClock` with the wall clock time `start`
+    pub fn new(start: SystemTime) -> Self {
+        Self { start_utc: start, start: Instant::now(), offset: Arc::default() }
+    }
+    /// Move the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        self.shift(duration, Duration::ZERO);
+    }
+    /// Move the clock back by `duration`
+    ///
+    /// Both the wall clock and the monotonic time go back, like a restored VM snapshot with a
+    /// broken clock source. The timestamps of messages show the earlier time, while durations
+    /// measured by the library continue from the latest time (see [`Clock::instant`]).
+    pub fn rewind(&self, duration: Duration) {
+        self.shift(Duration::ZERO, duration);
+    }
+    fn shift(&self, ahead: Duration, behind: Duration) {
+        let mut offset = self.offset.lock().unwrap_or_else(|e| e.into_inner());
+        let (ahead, behind) = (offset.ahead + ahead, offset.behind + behind);
+        let common = ahead.min(behind);
+        *offset = Offset { ahead: ahead - common, behind: behind - common };
+    }
+    fn offset(&self) -> Offset {
+        *self.offset.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+#[cfg(any(test, feature = "test-util"))]
+impl Clock for MockClock {
+    fn now_utc(&self) -> DateTime<Utc> {
+        let offset = self.offset();
+        (self.start_utc + offset.ahead - offset.behind).into()
+    }
+    // an instant before the start of the process may not be representable
+    fn instant(&self) -> Instant {
+        let offset = self.offset();
+        (self.start + offset.ahead).checked_sub(offset.behind).unwrap_or(self.start)
+    }
+}
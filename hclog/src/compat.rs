@@ -4,14 +4,15 @@
  * Define an implicit "module" as LogCompat to redirect each call to info! and so
  * on to this module.
  */
-use log::{Level as LogLevel, Metadata, Record};
+use log::{Level as LogLevel, LevelFilter, Metadata, Record};
 use crate::{
     level::Level,
     facades::FacadeVariant,
     log_internal::InternalLogKeys::{self, Internal, LogCompat},
     options::Options,
+    logmod::Provenance,
     context::CTX,
-    Scope, lD1, Result,
+    Scope, LogKey, Result,
 };
 use std::sync::atomic::{AtomicBool, Ordering};
 
@@ -27,15 +28,63 @@ impl From<LogLevel> for Level {
     }
 }
 
+/*
+ * compare with the levels of crate log via the mapping above, e.g. Debug1 equals log::Debug
+ * and Debug5 is greater (more verbose)
+ */
+impl PartialEq<LogLevel> for Level {
+    fn eq(&self, other: &LogLevel) -> bool {
+        *self == Level::from(*other)
+    }
+}
+impl PartialEq<Level> for LogLevel {
+    fn eq(&self, other: &Level) -> bool {
+        Level::from(*self) == *other
+    }
+}
+impl PartialOrd<LogLevel> for Level {
+    fn partial_cmp(&self, other: &LogLevel) -> Option<std::cmp::Ordering> {
+        self.partial_cmp(&Level::from(*other))
+    }
+}
+impl PartialOrd<Level> for LogLevel {
+    fn partial_cmp(&self, other: &Level) -> Option<std::cmp::Ordering> {
+        Level::from(*self).partial_cmp(other)
+    }
+}
+
+// every level between two mapped levels is passed by the filter of the less verbose one
+impl From<Level> for LevelFilter {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Off => LevelFilter::Off,
+            Level::Emerg | Level::Alert | Level::Crit | Level::Error => LevelFilter::Error,
+            Level::Warn => LevelFilter::Warn,
+            Level::Notice | Level::Info => LevelFilter::Info,
+            Level::Debug10 => LevelFilter::Trace,
+            _ => LevelFilter::Debug,
+        }
+    }
+}
+
 static INITIALIZED: AtomicBool = AtomicBool::new(false);
+/*
+ * the boxed logger can't be removed from crate log once installed. Unsetting LOGCOMPAT on
+ * the LogCompat key mutes it instead.
+ */
+static MUTED: AtomicBool = AtomicBool::new(false);
 
 struct CLogLogger;
 impl log::Log for CLogLogger {
     fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        // a suppress_guard doesn't even look at the context
+        if crate::context::suppressed() || MUTED.load(Ordering::Acquire) {
+            return false;
+        }
         if !crate::api::has_module(LogCompat).unwrap_or(false) {
             return false;
         }
-        if !crate::api::test_log(LogCompat, metadata.level().into()).unwrap() {
+        if !crate::api::test_log(LogCompat, metadata.level().into()).unwrap_or(false) {
             return false;
         }
         true
@@ -46,7 +95,7 @@ impl log::Log for CLogLogger {
                 LogCompat,
                 record.level().into(),
                 record.file().unwrap_or(""),
-                "", // record has no function name metadata
+                None, // record has no function name metadata
                 record.line().unwrap_or(0u32),
                 record.args(),
             )
@@ -63,16 +112,81 @@ pub(crate) fn init_log_compat(
         return Ok(());
     }
     {
-        CTX::get_mut()?.get_mod_mut(InternalLogKeys::logscope())?
-            .add_submodule(LogCompat)?
+        let mut ctx = CTX::get_mut()?;
+        let lm = ctx.get_mod_mut(InternalLogKeys::logscope())?;
+        let ident = lm.name().to_owned();
+        lm.add_submodule(LogCompat)?
             .set_logsev(level)
-            .set_logdest(&facade);
+            .set_logdest(&facade, &ident)
+            .set_provenance(Provenance::default());
     }
     lD1!(Internal, "initializing crate log compatibility support");
 
     log::set_boxed_logger(Box::new(CLogLogger))?;
     // always set the highest level Filter. Filtering is handled by hclog
-    log::set_max_level(log::LevelFilter::Trace);
+    log::set_max_level(LevelFilter::Trace);
     INITIALIZED.store(true, Ordering::Release);
     Ok(())
 }
+
+/*
+ * hooks for runtime changes of the LOGCOMPAT option
+ *
+ * Newly setting the option on any key unmutes the layer and initializes it with the defaults
+ * of the keys scope if necessary. Unsetting it only has an effect on the LogCompat key.
+ */
+pub(crate) fn enable_log_compat<K: LogKey>() -> Result<()> {
+    MUTED.store(false, Ordering::Release);
+    if INITIALIZED.load(Ordering::Acquire) {
+        return Ok(());
+    }
+    let (level, facade) = {
+        let ctx = CTX::get()?;
+        let lm = ctx.get_mod(K::logscope())?;
+        (lm.default_level(), lm.default_facade().clone())
+    };
+    init_log_compat(level, facade, None)
+}
+pub(crate) fn disable_log_compat<K: LogKey>(k: K) {
+    if K::logscope() == InternalLogKeys::logscope() && k.log_key() == LogCompat.log_key() {
+        MUTED.store(true, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod compat_tests {
+    use super::CLogLogger;
+    use crate::Level;
+    use log::{Level as LogLevel, LevelFilter, Log, Metadata};
+
+    #[test]
+    fn compare_log_level() {
+        assert!(Level::Debug1 == LogLevel::Debug);
+        assert!(LogLevel::Debug == Level::Debug1);
+        assert!(Level::Debug5 >= LogLevel::Debug);
+        assert!(Level::Debug5 < LogLevel::Trace);
+        assert!(Level::Notice > LogLevel::Warn && Level::Notice < LogLevel::Info);
+        assert!(Level::Crit < LogLevel::Error);
+        assert!(LogLevel::Error > Level::Crit);
+        assert!(Level::Off < LogLevel::Error);
+        assert!(LogLevel::Trace <= Level::Debug10);
+    }
+
+    #[test]
+    fn level_filter() {
+        assert_eq!(LevelFilter::from(Level::Off), LevelFilter::Off);
+        assert_eq!(LevelFilter::from(Level::Emerg), LevelFilter::Error);
+        assert_eq!(LevelFilter::from(Level::Warn), LevelFilter::Warn);
+        assert_eq!(LevelFilter::from(Level::Notice), LevelFilter::Info);
+        assert_eq!(LevelFilter::from(Level::Debug1), LevelFilter::Debug);
+        assert_eq!(LevelFilter::from(Level::Debug9), LevelFilter::Debug);
+        assert_eq!(LevelFilter::from(Level::Debug10), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn enabled_while_suppressed() {
+        let metadata = Metadata::builder().level(LogLevel::Error).build();
+        let _guard = crate::api::suppress_guard();
+        assert!(!CLogLogger.enabled(&metadata));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    options::{flag_by_name, Options, NONE},
+    ErrorKind,
+};
+
+/// Result of [`apply_config_map`](fn@crate::apply_config_map)
+///
+/// Each entry of the map is listed once by its key, e.g. `level.db`, in the order the entries
+/// were applied.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ConfigReport {
+    applied: Vec<String>,
+    skipped: Vec<String>,
+    failed: Vec<(String, ErrorKind)>,
+}
+impl ConfigReport {
+    /// The entries which were applied
+    pub fn applied(&self) -> &[String] {
+        &self.applied
+    }
+    /// The entries with an unknown setting or LogKey, nothing was changed for them
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+    /// The entries which couldn't be applied and the reason
+    pub fn failed(&self) -> &[(String, ErrorKind)] {
+        &self.failed
+    }
+    /// `true` if every entry was applied
+    pub fn is_complete(&self) -> bool {
+        self.skipped.is_empty() && self.failed.is_empty()
+    }
+    pub (crate) fn add(&mut self, entry: &str, res: crate::Result<()>) {
+        match res {
+            Ok(()) => self.applied.push(entry.to_owned()),
+            Err(ErrorKind::KeyNotInitialized) => self.skipped.push(entry.to_owned()),
+            Err(e) => self.failed.push((entry.to_owned(), e)),
+        }
+    }
+    pub (crate) fn skip(&mut self, entry: &str) {
+        self.skipped.push(entry.to_owned());
+    }
+}
+
+// value of an `options.<key>` entry
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub (crate) enum OptionsArg {
+    // replaces the options of the LogKey, e.g. `TIMESTAMP,PID`
+    Replace(Options),
+    // flags added and removed relative to the current options, e.g. `+PID-FILE`
+    Change { set: Options, unset: Options },
+}
+impl OptionsArg {
+    pub (crate) fn parse(s: &str) -> crate::Result<Self> {
+        let s = s.trim();
+        if !s.starts_with(['+', '-']) {
+            return s.parse().map(Self::Replace).map_err(|_| ErrorKind::ParseArg);
+        }
+        let (mut set, mut unset) = (NONE, NONE);
+        let mut rest = s;
+        while let Some(sign) = rest.chars().next() {
+            let end = rest[1..].find(['+', '-']).map_or(rest.len(), |i| i + 1);
+            let flag = flag_by_name(rest[1..end].trim()).ok_or(ErrorKind::ParseArg)?;
+            if sign == '+' {
+                set += flag;
+                unset -= flag;
+            } else {
+                unset += flag;
+                set -= flag;
+            }
+            rest = &rest[end..];
+        }
+        Ok(Self::Change { set, unset })
+    }
+    pub (crate) fn apply(self, current: Options) -> Options {
+        match self {
+            Self::Replace(options) => options,
+            Self::Change { set, unset } => current + set - unset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::OptionsArg;
+    use crate::{options::*, ErrorKind};
+
+    #[test]
+    fn options_arg() {
+        let current = TIMESTAMP + FILE;
+        let apply = |s: &str| OptionsArg::parse(s).map(|arg| arg.apply(current));
+        assert_eq!(apply("PID"), Ok(PID));
+        assert_eq!(apply("+pid-FILE"), Ok(TIMESTAMP + PID));
+        assert_eq!(apply("-FILE+FILE"), Ok(current));
+        assert_eq!(apply("-TIMESTAMP"), Ok(FILE));
+        assert_eq!(apply("+"), Err(ErrorKind::ParseArg));
+        assert_eq!(apply("+NOPE"), Err(ErrorKind::ParseArg));
+        assert_eq!(apply("NOPE"), Err(ErrorKind::ParseArg));
+    }
+}
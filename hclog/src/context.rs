@@ -1,67 +1,298 @@
 #![allow(unused_variables)] // TMP
 
+#[cfg(not(feature = "single-thread"))]
 use once_cell::sync::Lazy;
 use crate::{
-    submodule::Submodule,
-    logmod::{LogScope, ScopeKey},
+    ContextKey,
+    logmod::{LogScope, ScopeEnv, ScopeKey},
+    submodule::{name_eq, Mirror, Submodule},
     task::TaskLocalErr,
-    InternalLogKeys::Internal,
+    InternalLogKeys::{Internal, LogCompat},
     Scope, LogKey, ErrorKind, Result, Level,
     FacadeVariant, Options,
 };
+#[cfg(not(feature = "single-thread"))]
+use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+#[cfg(feature = "single-thread")]
+use std::cell::{Ref, RefCell, RefMut};
 use std::{
     ops::{Index, IndexMut},
-    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
     fmt::Display,
     future::Future,
+    cell::Cell,
+    sync::{atomic::AtomicUsize, Arc},
 };
 
 /*
  * with msrv > 1.63 we can remove the outer lazy because RwLock will also work
  * in const/static context.
  */
+#[cfg(not(feature = "single-thread"))]
 pub static GLOBAL_CONTEXT: Lazy<RwLock<Context>> = Lazy::new(||RwLock::new(Context::default()));
+#[cfg(not(feature = "single-thread"))]
+static STAGING_CONTEXT: Lazy<RwLock<Context>> = Lazy::new(||RwLock::new(Context::default()));
+// the context CTX hands out on this thread, see Staging
+#[cfg(not(feature = "single-thread"))]
+fn context_lock() -> &'static RwLock<Context> {
+    if staging() { &STAGING_CONTEXT } else { &GLOBAL_CONTEXT }
+}
+#[cfg(not(feature = "single-thread"))]
+pub (crate) type ContextRef = RwLockReadGuard<'static, Context>;
+#[cfg(not(feature = "single-thread"))]
+pub (crate) type ContextMut = RwLockWriteGuard<'static, Context>;
+
+/*
+ * single-thread mode: the "global" context lives in a RefCell owned by the thread which
+ * touches it first. Other threads see their own (empty) context and therefore get
+ * ScopeNotInitialized on access.
+ *
+ * The RefCell is deliberately leaked once per thread which touches the context, to hand out
+ * 'static guards with the same signature as the RwLock guards above. Only the cell itself
+ * leaks: ContextCell takes the Context out of it when the thread exits, so the Scopes and
+ * their facades are dropped (and files flushed) like the ones of a dropped Context.
+ */
+#[cfg(feature = "single-thread")]
+struct ContextCell(&'static RefCell<Context>);
+#[cfg(feature = "single-thread")]
+impl Drop for ContextCell {
+    fn drop(&mut self) {
+        // a guard can't outlive the thread, the borrow only fails if it's leaked itself
+        if let Ok(mut ctx) = self.0.try_borrow_mut() {
+            drop(std::mem::take(&mut *ctx));
+        }
+    }
+}
+#[cfg(feature = "single-thread")]
+std::thread_local! {
+    static GLOBAL_CONTEXT: ContextCell =
+        ContextCell(Box::leak(Box::new(RefCell::new(Context::default()))));
+    static STAGING_CONTEXT: ContextCell =
+        ContextCell(Box::leak(Box::new(RefCell::new(Context::default()))));
+}
+// the cell of this thread, ContextLock once the thread local is destroyed on thread exit
+#[cfg(feature = "single-thread")]
+fn context_cell() -> Result<&'static RefCell<Context>> {
+    let cell = if staging() { &STAGING_CONTEXT } else { &GLOBAL_CONTEXT };
+    cell.try_with(|c| c.0).map_err(|_| ErrorKind::ContextLock)
+}
+#[cfg(feature = "single-thread")]
+pub (crate) type ContextRef = Ref<'static, Context>;
+#[cfg(feature = "single-thread")]
+pub (crate) type ContextMut = RefMut<'static, Context>;
+
 crate::task_local! {
     pub static TASK_CONTEXT: Context;
 }
+
+/*
+ * re-entrancy guard for logging
+ *
+ * A Display impl or a facade may log itself while a message is logged. Reading the context
+ * again is fine, but nesting is limited to MAX_LOG_DEPTH calls to stop endless recursion (e.g.
+ * via the Internal key). Writing the context while it's borrowed for logging would deadlock
+ * (or panic for the task context), so CTX::get_mut and CTX::call_mut fail with ContextLock.
+ */
+pub (crate) static MAX_LOG_DEPTH: AtomicUsize = AtomicUsize::new(2);
+std::thread_local! {
+    static LOG_DEPTH: Cell<usize> = const { Cell::new(0) };
+    // number of active SuppressGuards on this thread
+    static SUPPRESSED: Cell<usize> = const { Cell::new(0) };
+    // a Staging guard is alive on this thread
+    static STAGING: Cell<bool> = const { Cell::new(false) };
+}
+// plain read of a const initialized thread-local, no lock or allocation
+#[inline]
+pub (crate) fn suppressed() -> bool {
+    SUPPRESSED.try_with(|s| s.get() > 0).unwrap_or(false)
+}
+pub (crate) fn suppress_enter() {
+    let _ = SUPPRESSED.try_with(|s| s.set(s.get() + 1));
+}
+pub (crate) fn suppress_leave() {
+    let _ = SUPPRESSED.try_with(|s| s.set(s.get().saturating_sub(1)));
+}
+pub (crate) struct LogDepth(usize);
+impl LogDepth {
+    pub fn enter() -> Self {
+        Self(LOG_DEPTH.with(|d| d.replace(d.get() + 1)))
+    }
+    // number of log calls already running on this thread
+    pub fn outer(&self) -> usize {
+        self.0
+    }
+    pub fn active() -> bool {
+        LOG_DEPTH.try_with(|d| d.get() > 0).unwrap_or(false)
+    }
+}
+impl Drop for LogDepth {
+    fn drop(&mut self) {
+        let _ = LOG_DEPTH.try_with(|d| d.set(self.0));
+    }
+}
+fn staging() -> bool {
+    STAGING.try_with(Cell::get).unwrap_or(false)
+}
+
+/*
+ * staging context of reinitialize
+ *
+ * Scope::init is implemented by the user and changes the context via the public functions. To
+ * build a new scope without touching the one in use, reinitialize runs it against a copy of the
+ * context: as long as the guard is alive CTX hands out the staging context instead of the global
+ * one on this thread. Other threads keep using the global context and wait for each other to
+ * stage.
+ */
+pub (crate) struct Staging {
+    #[cfg(not(feature = "single-thread"))]
+    _lock: MutexGuard<'static, ()>,
+}
+#[cfg(not(feature = "single-thread"))]
+static STAGING_LOCK: Mutex<()> = Mutex::new(());
+impl Staging {
+    pub fn enter(ctx: Context) -> Result<Self> {
+        // Scope::init calling reinitialize itself
+        if staging() {
+            return Err(ErrorKind::ContextLock);
+        }
+        let staging = Self {
+            #[cfg(not(feature = "single-thread"))]
+            _lock: STAGING_LOCK.lock().unwrap_or_else(|e| e.into_inner()),
+        };
+        STAGING.with(|s| s.set(true));
+        *CTX::get_mut()? = ctx;
+        Ok(staging)
+    }
+    // the staged context, CTX hands out the global context again
+    pub fn finish(self) -> Result<Context> {
+        Ok(std::mem::take(&mut *CTX::get_mut()?))
+    }
+}
+impl Drop for Staging {
+    fn drop(&mut self) {
+        if let Ok(mut ctx) = CTX::get_mut() {
+            drop(std::mem::take(&mut *ctx));
+        }
+        let _ = STAGING.try_with(|s| s.set(false));
+    }
+}
+
 // dummy struct to encapsulate access to the static context - at least for now (Maybe tmp)
 #[allow(clippy::upper_case_acronyms)]
 pub (crate) struct CTX;
 impl CTX {
-    pub (crate) fn get() -> Result<RwLockReadGuard<'static, Context>> {
-        Ok(GLOBAL_CONTEXT.read()?)
+    #[cfg(not(feature = "single-thread"))]
+    pub (crate) fn get() -> Result<ContextRef> {
+        Ok(context_lock().read()?)
+    }
+    #[cfg(not(feature = "single-thread"))]
+    pub (crate) fn get_mut() -> Result<ContextMut> {
+        if LogDepth::active() {
+            return Err(ErrorKind::ContextLock);
+        }
+        Ok(context_lock().write()?)
+    }
+    // None if the context is write locked instead of waiting for the lock
+    #[cfg(not(feature = "single-thread"))]
+    pub (crate) fn try_get() -> Result<Option<ContextRef>> {
+        match context_lock().try_read() {
+            Ok(ctx) => Ok(Some(ctx)),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Poisoned(e)) => Err(e.into()),
+        }
     }
-    pub (crate) fn get_mut() -> Result<RwLockWriteGuard<'static, Context>> {
-        Ok(GLOBAL_CONTEXT.write()?)
+    #[cfg(feature = "single-thread")]
+    pub (crate) fn get() -> Result<ContextRef> {
+        context_cell()?.try_borrow().map_err(|_| ErrorKind::ContextLock)
+    }
+    #[cfg(feature = "single-thread")]
+    pub (crate) fn get_mut() -> Result<ContextMut> {
+        if LogDepth::active() {
+            return Err(ErrorKind::ContextLock);
+        }
+        context_cell()?.try_borrow_mut().map_err(|_| ErrorKind::ContextLock)
+    }
+    // the context can only be borrowed mutably by the same thread, e.g. from a Display impl
+    #[cfg(feature = "single-thread")]
+    pub (crate) fn try_get() -> Result<Option<ContextRef>> {
+        Ok(context_cell()?.try_borrow().ok())
     }
 
     /*
      * scoped access
      * NOTE: This is currently just a temp impl and will be replaced */
-    pub (crate) fn new_scoped<I, K, F>(ident: I, key: K, future: F) -> Result<impl Future>
+    pub (crate) fn new_scoped<I, K, F>(ident: I, key: K, future: F)
+        -> Result<impl Future<Output = F::Output>>
     where
         I: Display + Send, K: LogKey, F: Future + Send
     {
-        let global = GLOBAL_CONTEXT.read()?;
-        let logmod = global.get_mod(K::logscope())?;
-        let modname = logmod.name();
-        // initialize task local struct
-        let mut local = Context::default();
-        // just init with the requested module(s)
-        local[logmod.key()] = logmod.to_scoped(ident);
-        local[logmod.key()].add_submodule(key)?; //, None, None, None)?;
+        let mut local = Self::scoped_context(ident, K::logscope())?;
+        local[K::logscope()].add_submodule(key)?;
 
-        lD1!(Internal, "new_scoped: {} with key: {}", modname, key);
+        lD1!(Internal, "new_scoped: {} with key: {}", local[K::logscope()].name(), key);
 
         Ok(TASK_CONTEXT.scope(local, future))
     }
+    pub (crate) fn new_scoped_for<I, F>(ident: I, scope: ScopeKey, future: F)
+        -> Result<impl Future<Output = F::Output>>
+    where
+        I: Display + Send, F: Future + Send
+    {
+        let local = Self::scoped_context(ident, scope)?;
+
+        lD1!(Internal, "new_scoped_for: {}", local[scope].name());
+
+        Ok(TASK_CONTEXT.scope(local, future))
+    }
+    /*
+     * task local context with a copy of all keys of the given scope
+     *
+     * The CLog scope is copied as well if the compatibility layer is set up. Messages of the
+     * log crate are written via its LogCompat key, which would be missing in the task context
+     * and the messages wouldn't carry the task ident.
+     */
+    fn scoped_context<I: Display>(ident: I, scope: ScopeKey) -> Result<Context> {
+        let global = Self::get()?;
+        let logmod = global.get_mod(scope)?;
+        let ident = ident.to_string();
+        let mut local = Context::default();
+        local[scope] = logmod.to_scoped(&ident);
+        if scope != ScopeKey::CLog {
+            if let Ok(clog) = global.get_mod(ScopeKey::CLog) {
+                if clog.has_submodule(LogCompat) {
+                    local[ScopeKey::CLog] = clog.to_scoped(&ident);
+                }
+            }
+        }
+        Ok(local)
+    }
+    // ident of the task local scope, None outside of a task scope
+    pub (crate) fn task_ident() -> Option<String> {
+        TASK_CONTEXT.try_with(|ctx| {
+            ctx.logmods().find(|lm| lm.initialized() && lm.env() == ScopeEnv::Task)
+                .and_then(|lm| lm.env_ident().map(str::to_owned))
+        }).ok().flatten()
+    }
+    // copy of the task local context to be installed in another thread, None outside of it
+    pub (crate) fn snapshot() -> Option<ScopeSnapshot> {
+        TASK_CONTEXT.try_with(|ctx| ScopeSnapshot { context: ctx.clone() }).ok()
+    }
+    pub (crate) fn in_task_scope() -> bool {
+        TASK_CONTEXT.try_with(|_| ()).is_ok()
+    }
+    // None outside of a task scope
+    pub (crate) fn with_task_context<F, R>(f: F) -> Option<R>
+    where
+        F: FnOnce(&Context) -> R,
+    {
+        TASK_CONTEXT.try_with(f).ok()
+    }
     pub (crate) fn call<F, R>(f: F) -> Result<R>
     where
         F: FnOnce(&Context) -> Result<R> + Copy,
     {
         match TASK_CONTEXT.try_with(|ctx| { f(ctx) }) {
             Err(TaskLocalErr::AccessError) | Ok(Err(ErrorKind::KeyNotInitialized)) => {
-                let ctx = GLOBAL_CONTEXT.read()?;
+                let ctx = Self::get()?;
                 f(&ctx)
             }
             Err(e) => Err(e.into()),
@@ -69,13 +300,33 @@ impl CTX {
             Ok(o) => o,
         }
     }
-    pub (crate) fn call_mut<F>(f: F) -> Result<()>
+    // like call but None if the global context is locked, the task context is never contended
+    pub (crate) fn try_call<F, R>(f: F) -> Result<Option<R>>
+    where
+        F: FnOnce(&Context) -> Result<R> + Copy,
+    {
+        match TASK_CONTEXT.try_with(|ctx| { f(ctx) }) {
+            Err(TaskLocalErr::AccessError) | Ok(Err(ErrorKind::KeyNotInitialized)) => {
+                match Self::try_get()? {
+                    Some(ctx) => f(&ctx).map(Some),
+                    None => Ok(None),
+                }
+            }
+            Err(e) => Err(e.into()),
+            Ok(Err(e)) => Err(e),
+            Ok(o) => o.map(Some),
+        }
+    }
+    pub (crate) fn call_mut<F, R>(f: F) -> Result<R>
     where
-        F: FnOnce(&mut Context) -> Result<()> + Copy,
+        F: FnOnce(&mut Context) -> Result<R> + Copy,
     {
+        if LogDepth::active() {
+            return Err(ErrorKind::ContextLock);
+        }
         match TASK_CONTEXT.try_with_mut(|v| { f(v) }) {
             Err(TaskLocalErr::AccessError) | Ok(Err(ErrorKind::KeyNotInitialized)) => {
-                let mut ctx = GLOBAL_CONTEXT.write()?;
+                let mut ctx = Self::get_mut()?;
                 f(&mut ctx)
             }
             Err(e) => Err(e.into()),
@@ -85,9 +336,53 @@ impl CTX {
     }
 }
 
-#[derive(Debug, Default)]
+/// Copy of the task local context of a [`scope`](fn@crate::scope)
+///
+/// Taken via [`current_scope_snapshot`](fn@crate::current_scope_snapshot) and installed in
+/// another thread with [`install_for`](ScopeSnapshot::install_for), so the messages logged
+/// there carry the same ident as the ones of the task. The snapshot is a copy: changes made
+/// on either side after it was taken aren't visible on the other one.
+///
+/// With the `single-thread` feature enabled the snapshot can't be sent to another thread, but
+/// it can still be installed on the thread it was taken on, e.g. outside of the task.
+#[derive(Debug, Clone)]
+pub struct ScopeSnapshot {
+    context: Context,
+}
+impl ScopeSnapshot {
+    /// Run `f` with the snapshot as task local context of the calling thread
+    ///
+    /// The previous task local context (if any) is restored when `f` returns.
+    pub fn install_for<F, R>(self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        TASK_CONTEXT.sync_scope(self.context, f)
+    }
+}
+
+/*
+ * LogKey used by the default log macros (dI! etc.), set via set_default_key
+ *
+ * Only the indices are stored, the key is looked up on every message like any other key. The
+ * name is shared to not allocate per message.
+ */
+#[derive(Debug, Clone)]
+pub (crate) struct DefaultKey {
+    pub scope: ScopeKey,
+    pub key: ContextKey,
+    pub name: Arc<str>,
+}
+
+// a LogKey addressed by its indices, e.g. as end of an alias
+pub (crate) type KeyRef = (ScopeKey, ContextKey);
+
+#[derive(Debug, Default, Clone)]
 pub (crate) struct Context {
     log_modules: [LogScope; ScopeKey::MAX as usize],
+    default_key: Option<DefaultKey>,
+    // (from, to) pairs set via alias_key, changes of `from` are copied to `to`
+    aliases: Vec<(KeyRef, KeyRef)>,
 }
 // impl index for easier access - not really necessary but avoids some bloat
 impl Index<ScopeKey> for Context {
@@ -109,43 +404,282 @@ impl Context {
     pub fn logmods_mut(&mut self) -> impl Iterator<Item = &mut LogScope> {
         self.log_modules.iter_mut()
     }
+    pub fn default_key(&self) -> Option<&DefaultKey> {
+        self.default_key.as_ref()
+    }
+    pub fn set_default_key(&mut self, key: DefaultKey) {
+        self.default_key = Some(key);
+    }
+    /*
+     * aliases
+     *
+     * An alias is no live reference: every function changing a LogKey calls mirror afterwards
+     * under the same lock. Chains are followed, cycles are refused when the alias is added so
+     * the walk terminates.
+     */
+    pub fn add_alias(&mut self, from: KeyRef, to: KeyRef) -> Result<()> {
+        for (scope, key) in [from, to] {
+            self.get_mod(scope)?.get_submodule_at(key).filter(|s| s.initialized())
+                .ok_or(ErrorKind::KeyNotInitialized)?;
+        }
+        if from == to || self.alias_targets(to).contains(&from) {
+            return Err(ErrorKind::AliasCycle);
+        }
+        if !self.aliases.contains(&(from, to)) {
+            self.aliases.push((from, to));
+        }
+        Ok(())
+    }
+    // false if there was no such alias
+    pub fn remove_alias(&mut self, from: KeyRef, to: KeyRef) -> bool {
+        let len = self.aliases.len();
+        self.aliases.retain(|alias| *alias != (from, to));
+        self.aliases.len() != len
+    }
+    // all LogKeys reached from `from` via one or more aliases
+    fn alias_targets(&self, from: KeyRef) -> Vec<KeyRef> {
+        let mut targets = Vec::new();
+        let mut next = vec![from];
+        while let Some(cur) = next.pop() {
+            for &(_, to) in self.aliases.iter().filter(|(f, _)| *f == cur) {
+                if !targets.contains(&to) {
+                    targets.push(to);
+                    next.push(to);
+                }
+            }
+        }
+        targets
+    }
+    // copy the changed part of `from` to its alias targets, uninitialized targets are skipped
+    pub fn mirror(&mut self, from: KeyRef, what: Mirror) {
+        self.mirror_where(from, what, |_| true);
+    }
+    // mirror every aliased LogKey after all LogKeys changed, targets exempt from `_all` keep
+    // their level
+    pub fn mirror_all(&mut self, what: Mirror) {
+        let mut sources = self.aliases.iter().map(|(from, _)| *from).collect::<Vec<_>>();
+        sources.dedup();
+        for from in sources {
+            self.mirror_where(from, what, |dst| what != Mirror::Level || !dst.all_exempt());
+        }
+    }
+    fn mirror_where(&mut self, from: KeyRef, what: Mirror, keep: impl Fn(&Submodule) -> bool) {
+        if self.aliases.is_empty() {
+            return;
+        }
+        let Some(src) = self.get_mod(from.0).ok().and_then(|lm| lm.mirrored(from.1, what))
+        else {
+            return;
+        };
+        for (scope, key) in self.alias_targets(from) {
+            if let Ok(lm) = self.get_mod_mut(scope) {
+                lm.mirror(key, &src, &keep);
+            }
+        }
+    }
     #[inline(always)]
     pub fn has(&self, key: ScopeKey) -> bool {
-        self.log_modules.len() < key as usize || self[key].initialized()
+        (key as usize) < self.log_modules.len() && self[key].initialized()
+    }
+    /*
+     * ScopeKey::MAX (or any future key without a slot) can't be indexed. logscope() is
+     * implemented by the user, so don't trust its return value.
+     */
+    #[inline(always)]
+    fn check_scope(&self, key: ScopeKey) -> Result<()> {
+        if key as usize >= self.log_modules.len() {
+            return Err(ErrorKind::InvalidScope);
+        }
+        Ok(())
     }
     pub fn init_mod<I: Scope, S: Display>(
         &mut self, name: S, level: Level, facade: FacadeVariant, options: Options,
     ) -> Result<&mut LogScope> {
         let lm = I::logscope();
+        self.check_scope(lm)?;
+        crate::message::init_uptime_base();
         if !self.has(lm) {
             self[lm] = LogScope::init::<I, S>(name, level, facade, options)?;
         }
         Ok(&mut self[lm])
     }
+    /*
+     * put the scope `lm` of a staged context in place of the current one, which is returned.
+     * Scopes and keys initialized along with it, e.g. the Internal scope or the LogCompat key,
+     * are taken over if they are missing here.
+     */
+    pub fn replace_mod(&mut self, lm: ScopeKey, mut staged: Context) -> Result<LogScope> {
+        let old = self.clear_mod(lm)?;
+        self[lm] = std::mem::take(&mut staged[lm]);
+        for (logmod, new) in self.log_modules.iter_mut().zip(staged.log_modules) {
+            logmod.adopt(new);
+        }
+        if self.default_key.is_none() {
+            self.default_key = staged.default_key;
+        }
+        Ok(old)
+    }
+    // take a scope including all submodules out of the context, leaving it uninitialized
+    pub fn clear_mod(&mut self, lm: ScopeKey) -> Result<LogScope> {
+        self.check_scope(lm)?;
+        // the default macros fall back to the Internal key
+        if self.default_key.as_ref().is_some_and(|d| d.scope == lm) {
+            self.default_key = None;
+        }
+        // the keys of a scope initialized again are new keys
+        self.aliases.retain(|(from, to)| from.0 != lm && to.0 != lm);
+        Ok(std::mem::take(&mut self[lm]))
+    }
     pub fn get_mod(&self, lm: ScopeKey) -> Result<&LogScope> {
+        self.check_scope(lm)?;
         if !self.has(lm) {
             return Err(ErrorKind::ScopeNotInitialized);
         }
         Ok(&self[lm])
     }
     pub fn get_mod_mut(&mut self, lm: ScopeKey) -> Result<&mut LogScope> {
+        self.check_scope(lm)?;
         if !self.has(lm) {
             return Err(ErrorKind::ScopeNotInitialized);
         }
         Ok(&mut self[lm])
     }
 
-    /* search a submodule by it's name in every existing lockmod */
-    pub fn get_submod_by_name(&mut self, key: &str) -> Option<&mut Submodule> {
-        if self.log_modules.is_empty() {
-            return None;
+    #[cfg(feature = "test-util")]
+    pub fn clear_failures(&mut self) {
+        for logmod in self.logmods_mut() {
+            for submod in logmod.submodules_mut() {
+                submod.clear_failures();
+            }
         }
-        for cmod in self.logmods_mut() {
-            if !cmod.initialized() { continue; }
-            if let Some(m) = cmod.get_submod_by_name(key) {
-                return Some(m);
+    }
+
+    /*
+     * search a submodule by it's name in every existing logmod
+     *
+     * names are not unique across scopes - refuse to guess if more than one matches.
+     * Returns the logmod as well since levels set by name are normalized by the Scope.
+     */
+    // `key` may be qualified with the name of its scope as `scope/key`
+    pub fn get_submod_by_name(&mut self, key: &str) -> Result<(&mut LogScope, ContextKey)> {
+        let (scope, name) = match key.split_once('/') {
+            Some((scope, name)) => (Some(scope), name),
+            None => (None, key),
+        };
+        let (lm, submod) = {
+            let mut found = self.logmods()
+                .filter(|cmod| cmod.initialized())
+                .filter(|cmod| scope.map_or(true, |scope| name_eq(cmod.name(), scope)))
+                .flat_map(|cmod| cmod.get_submods_by_name(name).map(|key| (cmod.key(), key)));
+            let first = found.next().ok_or(ErrorKind::KeyNotInitialized)?;
+            if found.next().is_some() {
+                return Err(ErrorKind::AmbiguousKeyName(key.to_owned()));
             }
+            first
+        };
+        Ok((&mut self[lm], submod))
+    }
+}
+
+#[cfg(test)]
+mod context_tests {
+    use crate::{
+        logmod::ScopeKey,
+        Scope, LogKey, Result, ErrorKind, Level, FacadeVariant, Options,
+    };
+    use super::Context;
+
+    // Scope returning the ScopeKey with index N - or MAX if there is none
+    #[derive(Copy, Clone)]
+    struct ScopeOf<const N: usize>;
+    impl<const N: usize> Scope for ScopeOf<N> {
+        fn init<S: std::fmt::Display>(_: S, _: Level, _: FacadeVariant, _: Options)
+            -> Result<()> { Ok(()) }
+        fn logscope() -> ScopeKey {
+            ScopeKey::from_index(N).unwrap_or(ScopeKey::MAX)
+        }
+    }
+
+    fn init_mod<const N: usize>(ctx: &mut Context) -> Result<ScopeKey> {
+        ctx.init_mod::<ScopeOf<N>, _>("ctx", Level::Info, FacadeVariant::None, Options::new())
+            .map(|lm| lm.key())
+    }
+
+    #[test]
+    fn every_scope_key() {
+        let mut ctx = Context::default();
+        let keys = [ScopeKey::Application, ScopeKey::CLog, ScopeKey::Lib, ScopeKey::MAX];
+        for key in keys {
+            assert!(!ctx.has(key));
+        }
+        assert_eq!(ctx.get_mod(ScopeKey::MAX).err(), Some(ErrorKind::InvalidScope));
+        assert_eq!(ctx.get_mod_mut(ScopeKey::MAX).err(), Some(ErrorKind::InvalidScope));
+        for key in &keys[..3] {
+            assert_eq!(ctx.get_mod(*key).err(), Some(ErrorKind::ScopeNotInitialized));
+            assert_eq!(ctx.get_mod_mut(*key).err(), Some(ErrorKind::ScopeNotInitialized));
+        }
+
+        assert_eq!(init_mod::<0>(&mut ctx), Ok(ScopeKey::Application));
+        assert_eq!(init_mod::<1>(&mut ctx), Ok(ScopeKey::CLog));
+        assert_eq!(init_mod::<2>(&mut ctx), Ok(ScopeKey::Lib));
+        assert_eq!(init_mod::<3>(&mut ctx), Err(ErrorKind::InvalidScope));
+        assert_eq!(init_mod::<{ usize::MAX }>(&mut ctx), Err(ErrorKind::InvalidScope));
+
+        for key in &keys[..3] {
+            assert!(ctx.has(*key));
+            assert_eq!(ctx.get_mod(*key).map(|lm| lm.key()), Ok(*key));
+            assert_eq!(ctx.get_mod_mut(*key).map(|lm| lm.key()), Ok(*key));
+        }
+        assert!(!ctx.has(ScopeKey::MAX));
+        assert_eq!(ctx.get_mod(ScopeKey::MAX).err(), Some(ErrorKind::InvalidScope));
+        assert_eq!(ctx.logmods().filter(|lm| lm.initialized()).count(), 3);
+    }
+
+    #[test]
+    fn init_mod_keeps_initialized_scope() {
+        let mut ctx = Context::default();
+        init_mod::<2>(&mut ctx).unwrap();
+        ctx.init_mod::<ScopeOf<2>, _>("other", Level::Debug1, FacadeVariant::None, Options::new())
+            .unwrap();
+        assert_eq!(ctx.get_mod(ScopeKey::Lib).map(|lm| lm.name()), Ok("ctx"));
+    }
+
+    // LogKey with the index 0 in the Application scope and the given name
+    #[derive(Copy, Clone)]
+    struct NamedKey(&'static str);
+    impl Scope for NamedKey {
+        fn init<S: std::fmt::Display>(_: S, _: Level, _: FacadeVariant, _: Options)
+            -> Result<()> { Ok(()) }
+        fn logscope() -> ScopeKey { ScopeKey::Application }
+    }
+    impl std::fmt::Display for NamedKey {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+    impl LogKey for NamedKey {
+        fn log_key(&self) -> crate::ContextKey { 0 }
+    }
+
+    #[test]
+    fn blank_names() {
+        let mut ctx = Context::default();
+        for name in ["", " ", "\t \n"] {
+            let res = ctx.init_mod::<ScopeOf<0>, _>(name, Level::Info, FacadeVariant::None,
+                Options::new());
+            assert_eq!(res.err(), Some(ErrorKind::ParseArg), "{:?}", name);
+        }
+        assert!(!ctx.has(ScopeKey::Application));
+        ctx.init_mod::<ScopeOf<0>, _>(" padded\t", Level::Info, FacadeVariant::None,
+            Options::new()).unwrap();
+        let lm = ctx.get_mod_mut(ScopeKey::Application).unwrap();
+        assert_eq!(lm.name(), "padded");
+
+        for name in ["", "  "] {
+            assert_eq!(lm.add_submodule(NamedKey(name)).err(), Some(ErrorKind::ParseArg));
         }
-        None
+        assert!(!lm.get_submodule(NamedKey("")).is_some_and(|s| s.initialized()));
+        assert_eq!(lm.add_submodule(NamedKey(" db ")).map(|s| s.name().to_owned()),
+            Ok("db".to_owned()));
     }
 }
@@ -0,0 +1,130 @@
+//! Helpers to read the `hclog` configuration from the process environment
+//!
+//! The library reads some of its configuration from environment variables on initialization
+//! of a [`Scope`](trait@crate::Scope). The functions in this module expose the same logic to
+//! users implementing the [`Scope`](trait@crate::Scope) trait manually or reading their own
+//! configuration values from the environment. They read the environment even if the library
+//! ignores it (see [`ignore_env`](fn@crate::ignore_env)).
+//!
+//! # Example
+//!
+//! ```rust
+//! use hclog::{Level, env::{read_env_var, read_level_env}};
+//!
+//! std::env::set_var("MYAPP_WORKERS", "4");
+//! assert_eq!(read_env_var::<u32>("MYAPP_WORKERS").unwrap(), Some(4));
+//!
+//! // fall back to a default if the variable is not set
+//! let level = read_level_env("MYAPP_LEVEL").unwrap().unwrap_or(Level::Info);
+//! ```
+use crate::{
+    Result, Level, FacadeVariant,
+    error::ErrorKind::EnvType,
+};
+use std::{
+    env,
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/*
+ * known environment variables
+ *
+ * This variables can be used to alter or define the logging behaviour
+ *
+ * The syntax for HCLOG_FACADE and HCLOG_LEVEL is always:
+ *      HCLOG_ENVVAR="<module>:<value>"
+ * where <module> is the module to set <value> for. To set a <value> for
+ * all known Modules in Context _all can be used.
+ * <value> is restricted to known values of the addressed config (see level.rs
+ * or facades.rs)
+ */
+/// Prefix of the environment variables to set or unset single [`Options`](crate::options::Options)
+///
+/// The name of the option is appended in uppercase, e.g. `HCLOG_OPT_TIMESTAMP=0`.
+pub const ENV_OPT_PREFIX: &str = "HCLOG_OPT_";
+/// Environment variable to override the default [`FacadeVariant`] of a [`Scope`](trait@crate::Scope)
+pub const ENV_OPT_FACADE: &str = "HCLOG_FACADE";
+/// Environment variable to override the default [`Level`] of a [`Scope`](trait@crate::Scope)
+pub const ENV_OPT_LEVEL: &str = "HCLOG_LEVEL";
+/// Environment variable to set the instance id of a [`Scope`](trait@crate::Scope)
+///
+/// The id is written with the [`INSTANCE`](const@crate::options::INSTANCE) option, see
+/// [`set_instance_id`](fn@crate::set_instance_id). An invalid id is ignored.
+pub const ENV_INSTANCE_ID: &str = "HCLOG_INSTANCE_ID";
+
+// set via ignore_env, the readers below are meant for users and don't check it
+static IGNORE_ENV: AtomicBool = AtomicBool::new(false);
+
+pub (crate) fn ignored() -> bool {
+    IGNORE_ENV.load(Ordering::Relaxed)
+}
+pub (crate) fn set_ignored(ignore: bool) {
+    IGNORE_ENV.store(ignore, Ordering::Relaxed);
+}
+
+/// Read the environment variable `key` and parse it as `T`
+///
+/// Returns `Ok(None)` if the variable is not set or does not contain valid unicode.
+///
+/// # Errors
+///
+/// Returns [`EnvType`](crate::ErrorKind::EnvType) if the value can't be parsed as `T`.
+pub fn read_env_var<T>(key: &str) -> Result<Option<T>>
+where
+    T: FromStr + Sized,
+{
+    if let Some(ref var) = env::var_os(key) {
+        if let Some(v) = var.to_str() {
+            match v.parse::<T>() {
+                Ok(v) => return Ok(Some(v)),
+                Err(_) => {
+                    return Err(EnvType);
+                }
+            }
+        }
+    }
+    Ok(None::<T>)
+}
+
+/// Read the environment variable `key` and parse it as [`Level`]
+///
+/// The level names are case insensitive, see [`Level`] for the accepted values.
+///
+/// # Errors
+///
+/// Returns [`EnvType`](crate::ErrorKind::EnvType) if the value is not a known [`Level`].
+pub fn read_level_env(key: &str) -> Result<Option<Level>> {
+    read_env_var::<Level>(key)
+}
+
+/// Read the environment variable `key` and parse it as [`FacadeVariant`]
+///
+/// The facade names are case insensitive, see [`FacadeVariant`] for the accepted values.
+///
+/// # Errors
+///
+/// Returns [`EnvType`](crate::ErrorKind::EnvType) if the value is not a known [`FacadeVariant`].
+pub fn read_facade_env(key: &str) -> Result<Option<FacadeVariant>> {
+    read_env_var::<FacadeVariant>(key)
+}
+
+#[cfg(test)]
+mod env_tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    #[test]
+    fn read_level_and_facade() {
+        std::env::set_var("HCLOG_TEST_ENV_LEVEL", "Debug3");
+        assert_eq!(read_level_env("HCLOG_TEST_ENV_LEVEL"), Ok(Some(Level::Debug3)));
+        std::env::set_var("HCLOG_TEST_ENV_LEVEL", "verbose");
+        assert_eq!(read_level_env("HCLOG_TEST_ENV_LEVEL"), Err(ErrorKind::EnvType));
+        assert_eq!(read_level_env("HCLOG_TEST_ENV_UNSET"), Ok(None));
+
+        std::env::set_var("HCLOG_TEST_ENV_FACADE", "stderr");
+        assert!(matches!(read_facade_env("HCLOG_TEST_ENV_FACADE"), Ok(Some(FacadeVariant::StdErr))));
+        std::env::set_var("HCLOG_TEST_ENV_FACADE", "pipe");
+        assert!(read_facade_env("HCLOG_TEST_ENV_FACADE").is_err());
+    }
+}
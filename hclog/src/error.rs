@@ -1,5 +1,6 @@
 use crate::{
     task::TaskLocalErr,
+    Level,
 };
 use log::SetLoggerError;
 use std::{
@@ -47,16 +48,49 @@ pub enum ErrorKind {
     ContextInconsistent,
     /// Log-Module isn't initialized
     ScopeNotInitialized,
+    /// ScopeKey doesn't belong to a usable Scope (e.g. `ScopeKey::MAX`)
+    InvalidScope,
     /// Submodule is not initialized
     KeyNotInitialized,
+    /// LogKey index is already used by a LogKey with a different name
+    ///
+    /// Also returned if the name of the LogKey only differs by case from the name of another
+    /// LogKey in the same Scope. Names are case insensitive when a LogKey is addressed by name.
+    KeyCollision,
+    /// LogKey name is used by multiple initialized LogKeys
+    ///
+    /// Contains the ambiguous name.
+    AmbiguousKeyName(String),
+    /// Setting the parent of a LogKey would create a cycle in the hierarchy
+    ParentCycle,
+    /// Aliasing a LogKey would mirror its changes back onto itself
+    ///
+    /// Returned for a LogKey aliased to itself or if the target already mirrors onto the
+    /// source, see [`alias_key`](fn@crate::alias_key).
+    AliasCycle,
+    /// The facade of the LogKey is not a [`RingBuffer`](crate::FacadeVariant::RingBuffer)
+    NoRingBuffer,
     /// Parse environment variable failed
     ParseEnv,
     /// Parse commandline argument string failed
     ParseArg,
+    /// A `key:level` argument of [`set_mod_level`](fn@crate::set_mod_level) failed
+    ///
+    /// Contains the failing argument and the error it failed with, e.g. [`ParseArg`] or
+    /// [`KeyNotInitialized`]. No level was changed by the call.
+    ///
+    /// [`ParseArg`]: ErrorKind::ParseArg
+    /// [`KeyNotInitialized`]: ErrorKind::KeyNotInitialized
+    InvalidLevelArg(String, Box<ErrorKind>),
     /// Environment variable has unexpected type
     EnvType,
     /// Loglevel is unknown
     UnknownLogLevel,
+    /// Level can't be used for a message
+    ///
+    /// Returned when a message is logged with [`Level::Off`](crate::Level::Off), which only
+    /// disables a LogKey.
+    InvalidLevel,
     /// Failed to write logstring via [`Facade`](type@crate::FacadeVariant)
     WriteFailed,
     /// Logging string contained non utf8 characters
@@ -70,18 +104,44 @@ pub enum ErrorKind {
     /// Wraps the [`std::io::ErrorKind`] thrown by the underlying I/O operation.
     IoError(IoErrorKind),
 }
-impl StdError for ErrorKind {}
+impl StdError for ErrorKind {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            Self::InvalidLevelArg(_, ref e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
 impl fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             Self::ContextLock => write!(f, "Failed to lock Context"),
             Self::ContextInconsistent => write!(f, "Context is inconsistent"),
             Self::ScopeNotInitialized => f.write_str("Scope not initialized"),
+            Self::InvalidScope => f.write_str("ScopeKey is not a valid Scope"),
             Self::KeyNotInitialized => write!(f, "LogKey is not initialized"),
+            Self::KeyCollision => write!(f, "LogKey collides with an initialized LogKey"),
+            Self::AmbiguousKeyName(ref n) => write!(f, "LogKey name '{}' is ambiguous", n),
+            Self::ParentCycle => write!(f, "LogKey parent would create a cycle"),
+            Self::AliasCycle => f.write_str("LogKey alias would create a cycle"),
+            Self::NoRingBuffer => f.write_str("LogKey does not log to a ring buffer"),
             Self::ParseEnv => write!(f, "Parse environment variable failed"),
             Self::ParseArg => write!(f, "Parse argument string failed"),
+            Self::InvalidLevelArg(ref arg, ref e) => {
+                write!(f, "Level argument '{}': {}", arg, e)?;
+                let close = match **e {
+                    Self::UnknownLogLevel =>
+                        arg.rsplit_once(':').and_then(|(_, level)| Level::suggest(level)),
+                    _ => None,
+                };
+                match close {
+                    Some(level) => write!(f, " (did you mean {}?)", level),
+                    None => Ok(()),
+                }
+            }
             Self::EnvType => write!(f, "Environment variable has unexpected type"),
             Self::UnknownLogLevel => write!(f, "Loglevel is unknown"),
+            Self::InvalidLevel => f.write_str("Level can't be used for a message"),
             Self::WriteFailed => write!(f, "Failed to write logstring"),
             Self::InvalFmtString => write!(f, "Logging string contained non utf8 characters"),
             Self::LogCompatInitialized => write!(f, "Log Compat is already initialized"),
@@ -136,14 +196,14 @@ mod test {
 
     #[test]
     fn error_eq() {
-        assert_eq!(ParseEnv == ParseEnv, true);
-        assert_eq!(TaskLocal(BorrowError) == TaskLocal(BorrowError), true);
+        assert!(ParseEnv == ParseEnv);
+        assert!(TaskLocal(BorrowError) == TaskLocal(BorrowError));
     }
 
     #[test]
     fn error_ne() {
-        assert_eq!(ParseEnv == EnvType, false);
-        assert_eq!(TaskLocal(AccessError) == TaskLocal(BorrowError), false);
+        assert!(ParseEnv != EnvType);
+        assert!(TaskLocal(AccessError) != TaskLocal(BorrowError));
     }
 
     #[test]
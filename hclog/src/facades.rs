@@ -1,30 +1,81 @@
-use crate::{level::Level, message::Message, Result, ErrorKind::*};
+use crate::{binlog, clock, level::Level, message::Message, Result, ErrorKind::*};
 use libc::{self, c_int};
 use strum_macros::Display;
+#[cfg(feature = "net-syslog")]
+use crate::netsyslog::{SyslogTcp, SyslogUdp};
 use std::{
-    ffi::CString,
-    fmt::Debug,
+    collections::VecDeque,
+    ffi::{CStr, CString},
+    fmt::{self, Debug},
     str::FromStr,
-    sync::{Arc, Mutex},
+    sync::{
+        Arc, Mutex, MutexGuard, TryLockError,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    },
     io::{BufWriter, Write},
     fs::File as StdFile,
     path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
+/*
+ * Send + Sync of the facades, dropped in single-thread mode
+ *
+ * In single-thread mode the facades are never shared between threads, so the bounds are dropped
+ * and the facades are reference counted via Rc instead of Arc.
+ */
+#[doc(hidden)]
+#[cfg(not(feature = "single-thread"))]
+pub trait MaybeSendSync: Send + Sync {}
+#[cfg(not(feature = "single-thread"))]
+impl<T: Send + Sync + ?Sized> MaybeSendSync for T {}
+#[doc(hidden)]
+#[cfg(feature = "single-thread")]
+pub trait MaybeSendSync {}
+#[cfg(feature = "single-thread")]
+impl<T: ?Sized> MaybeSendSync for T {}
+
 /*
  * base trait describing how a log facade should work
  */
 #[doc(hidden)]
-pub trait LogFacade: Debug + Send + Sync {
+pub trait LogFacade: Debug + MaybeSendSync {
     fn log(&self, level: Level, msg: Message) -> Result<()>;
 
     // helper
     fn is_syslog(&self) -> bool { false }
+    // name of the FacadeVariant as accepted by FacadeVariant::from_str
+    fn variant_name(&self) -> &'static str { "custom" }
+    fn ring_buffer(&self) -> Option<&RingBuffer> { None }
+    // only facades buffering their writes care about the policy and flushing
+    fn set_flush_policy(&self, _: FlushPolicy) {}
+    fn flush(&self) -> Result<()> { Ok(()) }
+    // false instead of waiting for a lock held by another writer, see try_log
+    fn try_flush(&self) -> Result<bool> {
+        self.flush().map(|()| true)
+    }
+    // false if the facade is locked by another writer, facades without a lock of their own
+    // just write
+    fn try_log(&self, level: Level, msg: Message) -> Result<bool> {
+        self.log(level, msg).map(|()| true)
+    }
 }
+#[cfg(not(feature = "single-thread"))]
+pub (crate) type FacadeRc<T> = Arc<T>;
+#[cfg(feature = "single-thread")]
+pub (crate) type FacadeRc<T> = std::rc::Rc<T>;
+// Send + Sync via MaybeSendSync unless in single-thread mode
+pub (crate) type SharedFacade = FacadeRc<dyn LogFacade>;
 
 #[derive(Debug, Default, Display, Clone)]
 /// Declaration of the different available log facacdes (log targets).
 ///
+/// A [`FacadeVariant`] can be parsed from its case insensitive name. The `file`, `binary`,
+/// `syslog` and `ringbuffer` variants take their argument after a colon, e.g.
+/// `file:/var/log/app.log` or `ringbuffer:500`, and use a default without it. The
+/// `syslog-udp` and `syslog-tcp` variants require the address and the facility, e.g.
+/// `syslog-udp:loghost:514:local0`.
+///
 /// # Errors
 ///
 /// When parsing a [`FacadeVariant`] from a string, the following errors can occur:
@@ -32,6 +83,8 @@ pub trait LogFacade: Debug + Send + Sync {
 ///   message.
 /// - If the string matches a known variant but the variant is not implemented, a [`String`] is
 ///   returned with an error message.
+/// - If the argument of the variant is invalid, e.g. an unknown syslog facility or a capacity
+///   which isn't a number, a [`String`] is returned with an error message.
 ///
 /// # Panics
 ///
@@ -47,6 +100,11 @@ pub trait LogFacade: Debug + Send + Sync {
 ///
 /// ## Panic on logging
 ///
+/// # Features
+///
+/// The enum is `#[non_exhaustive]`: the features `test-util` and `net-syslog` add variants, so
+/// a `match` outside of this crate needs a wildcard arm to compile with any set of features.
+#[non_exhaustive]
 pub enum FacadeVariant {
     #[default]
     /// Logging is disabled
@@ -72,84 +130,450 @@ pub enum FacadeVariant {
     /// The first argument is the filename
     /// The second argument is a boolean that indicates whether the file should be truncated
     File(PathBuf, bool), // Filename, truncate-option
+    /// Log to a binary file
+    ///
+    /// The argument is the filename. Instead of the rendered line the level, time, LogKey name
+    /// and message are appended as a compact record, which can be read and rendered later on
+    /// via [`binlog::Reader`](crate::binlog::Reader). The [`Options`](crate::options::Options)
+    /// are not applied when writing.
+    Binary(PathBuf),
+    /// Log to a buffer in memory keeping the last lines only
+    ///
+    /// The argument is the number of lines kept. If the buffer is full, the oldest line is
+    /// dropped for every new one. The lines are taken out via
+    /// [`drain_ring`](fn@crate::drain_ring) or [`dump_ring_to`](fn@crate::dump_ring_to), e.g.
+    /// to write the debug messages preceding an error to disk ("flight recorder").
+    RingBuffer(usize),
+    /// Log to a [`MockFacade`](crate::testing::MockFacade)
+    ///
+    /// Only available with the `test-util` feature enabled.
+    #[cfg(feature = "test-util")]
+    Mock(crate::testing::MockFacade),
+    /// Log to a remote syslog collector via UDP
+    ///
+    /// The arguments are the address of the collector as `host:port` and the syslog facility
+    /// (e.g. "user"). The address is resolved with the first message. All LogKeys logging to
+    /// the same address share one socket, regardless of the facility. Each message is sent
+    /// as a RFC 5424 frame with the name of the [`Scope`](crate::Scope) as APP-NAME and the name
+    /// of the LogKey as MSGID. The [`Options`](crate::options::Options) are not applied, the
+    /// frame carries the time and the host itself. A message which can't be sent is dropped and
+    /// counted as `facade_failures` in [`health_summary`](fn@crate::health_summary), as is every
+    /// message if the facility is unknown or the address can't be resolved.
+    ///
+    /// Only available with the `net-syslog` feature enabled.
+    #[cfg(feature = "net-syslog")]
+    SyslogUdp(String, String),
+    /// Log to a remote syslog collector via TCP
+    ///
+    /// Like [`SyslogUdp`](FacadeVariant::SyslogUdp), but the frames are sent with octet-counted
+    /// framing (RFC 6587). The frames are sent by a background thread, so a slow or stalled
+    /// collector never blocks the log call. Up to 1024 frames are queued, further messages are
+    /// dropped. The address is resolved and the connection is opened with the first message.
+    /// All LogKeys logging to the same address share the thread and the connection.
+    /// After a failure the facade reconnects with an increasing delay of up to 30 seconds
+    /// between the attempts, messages logged meanwhile are dropped.
+    /// [`flush`](fn@crate::flush) waits a few seconds at most for the queued frames.
+    ///
+    /// Only available with the `net-syslog` feature enabled.
+    #[cfg(feature = "net-syslog")]
+    SyslogTcp(String, String),
+    /// Log to a primary facade and mirror the messages to a secondary one on a best effort basis
+    ///
+    /// The first argument is the primary facade. It behaves exactly as if it was used alone:
+    /// its errors are returned to the caller and it's flushed via [`flush`](fn@crate::flush).
+    /// The second argument is the secondary facade, which never fails or slows down the log
+    /// call. If it's locked by another writer the message is skipped instead of waiting for it
+    /// and counted as `dropped`, a message it fails to write is counted as `facade_failures`
+    /// in [`health_summary`](fn@crate::health_summary). The first failure is reported once via
+    /// the [`Internal`](crate::InternalLogKeys::Internal) key. This variant can't be parsed
+    /// from a string.
+    ///
+    /// ```rust
+    /// use hclog::FacadeVariant;
+    ///
+    /// let facade = FacadeVariant::WithBestEffort(
+    ///     Box::new(FacadeVariant::File("/var/log/app.log".into(), false)),
+    ///     Box::new(FacadeVariant::StdErr),
+    /// );
+    /// ```
+    WithBestEffort(Box<FacadeVariant>, Box<FacadeVariant>),
+}
+
+/// Terminator written by the facades after each message
+///
+/// Set per LogKey via [`set_newline`](fn@crate::set_newline), the default is
+/// [`Lf`](Newline::Lf). The [`StdOut`](FacadeVariant::StdOut),
+/// [`StdErr`](FacadeVariant::StdErr) and [`File`](FacadeVariant::File) facades honor it, the
+/// [`Syslog`](FacadeVariant::Syslog) facade ignores it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Newline {
+    /// Lines end with `\n`
+    #[default]
+    Lf,
+    /// Lines end with `\r\n`, also the lines within a multi-line message
+    CrLf,
+    /// Messages are written without terminator
+    ///
+    /// The message has to contain its own terminator, e.g. with the [`RAW`](crate::RAW)
+    /// option.
+    None,
+}
+impl Newline {
+    pub (crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+            Self::None => "",
+        }
+    }
+}
+
+/// When the [`File`](FacadeVariant::File) facade writes its buffered messages to the file
+///
+/// Set per LogKey via [`set_flush_policy`](fn@crate::set_flush_policy), the default is
+/// [`EveryMessage`](FlushPolicy::EveryMessage). Flushing less often saves a syscall per message
+/// under high throughput. Messages still buffered are written by [`flush`](fn@crate::flush) and
+/// once the facade is dropped, e.g. replaced via [`set_logdest`](fn@crate::set_logdest).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// Flush after every message
+    #[default]
+    EveryMessage,
+    /// Flush after every `n` messages, `0` behaves like `1`
+    EveryN(u32),
+    /// Flush with the first message written after the interval elapsed since the last flush
+    ///
+    /// There is no background thread, so the last messages stay buffered until the next
+    /// message is written or [`flush`](fn@crate::flush) is called.
+    Interval(Duration),
 }
 
 impl FromStr for FacadeVariant {
     type Err = String;
 
+    // the argument of `name:arg` replaces the default, e.g. `file:/var/log/app.log`
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        match s.to_ascii_lowercase().as_str() {
-            "none" => Ok(Self::None),
-            "stdout" => Ok(Self::StdOut),
-            "stderr" => Ok(Self::StdErr),
-            "syslog" => Ok(Self::Syslog("user".to_string())),
-            "file" => Ok(Self::File("/tmp/hclog.log".into(), false)),
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (s, None),
+        };
+        match (name.to_ascii_lowercase().as_str(), arg) {
+            ("none", None) => Ok(Self::None),
+            ("stdout", None) => Ok(Self::StdOut),
+            ("stderr", None) => Ok(Self::StdErr),
+            // an unknown facility would panic once the facade is created
+            ("syslog", Some(arg)) if try_facility(arg).is_none() =>
+                Err(format!("Invalid argument '{}' of facade '{}'", arg, name)),
+            ("syslog", arg) => Ok(Self::Syslog(arg.unwrap_or("user").to_string())),
+            ("file", arg) => Ok(Self::File(arg.unwrap_or("/tmp/hclog.log").into(), false)),
+            ("binary", arg) => Ok(Self::Binary(arg.unwrap_or("/tmp/hclog.bin").into())),
+            ("ringbuffer", None) => Ok(Self::RingBuffer(10_000)),
+            ("ringbuffer", Some(n)) => n.parse().map(Self::RingBuffer)
+                .map_err(|_| format!("Invalid capacity '{}' of facade '{}'", n, name)),
+            #[cfg(feature = "net-syslog")]
+            ("syslog-udp", Some(arg)) => parse_remote(arg)
+                .map(|(addr, facility)| Self::SyslogUdp(addr, facility))
+                .ok_or_else(|| format!("Invalid argument '{}' of facade '{}'", arg, name)),
+            #[cfg(feature = "net-syslog")]
+            ("syslog-tcp", Some(arg)) => parse_remote(arg)
+                .map(|(addr, facility)| Self::SyslogTcp(addr, facility))
+                .ok_or_else(|| format!("Invalid argument '{}' of facade '{}'", arg, name)),
             _ => Err(format!("Facade '{}' not exists or not implemented", s)),
         }
     }
 }
 
+impl FacadeVariant {
+    // the facade opens a file, which may fail
+    pub (crate) fn opens_file(&self) -> bool {
+        match self {
+            Self::File(..) | Self::Binary(_) => true,
+            Self::WithBestEffort(p, s) => p.opens_file() || s.opens_file(),
+            _ => false,
+        }
+    }
+
+    /// The string which parses back into this facade, e.g. `file:/var/log/app.log`
+    ///
+    /// Returns `None` for the facades which can't be parsed from a string: a
+    /// [`File`](FacadeVariant::File) truncated on open or with a path which isn't valid unicode,
+    /// [`WithBestEffort`](FacadeVariant::WithBestEffort) and the `Mock` facade.
+    ///
+    /// ```rust
+    /// use hclog::FacadeVariant;
+    ///
+    /// let facade: FacadeVariant = "SYSLOG".parse().unwrap();
+    /// assert_eq!(facade.spec().as_deref(), Some("syslog:user"));
+    /// let parsed: FacadeVariant = facade.spec().unwrap().parse().unwrap();
+    /// assert_eq!(parsed.spec(), facade.spec());
+    /// ```
+    pub fn spec(&self) -> Option<String> {
+        match self {
+            Self::None => Some("none".to_owned()),
+            Self::StdOut => Some("stdout".to_owned()),
+            Self::StdErr => Some("stderr".to_owned()),
+            Self::Syslog(facility) => Some(format!("syslog:{}", facility)),
+            Self::File(path, false) => path.to_str().map(|p| format!("file:{}", p)),
+            Self::File(_, true) => None,
+            Self::Binary(path) => path.to_str().map(|p| format!("binary:{}", p)),
+            Self::RingBuffer(capacity) => Some(format!("ringbuffer:{}", capacity)),
+            #[cfg(feature = "test-util")]
+            Self::Mock(_) => None,
+            #[cfg(feature = "net-syslog")]
+            Self::SyslogUdp(addr, facility) => Some(format!("syslog-udp:{}:{}", addr, facility)),
+            #[cfg(feature = "net-syslog")]
+            Self::SyslogTcp(addr, facility) => Some(format!("syslog-tcp:{}:{}", addr, facility)),
+            Self::WithBestEffort(..) => None,
+        }
+    }
+}
+
+// `host:port:facility`, the host is resolved by the facade, not while parsing
+#[cfg(feature = "net-syslog")]
+fn parse_remote(arg: &str) -> Option<(String, String)> {
+    let (addr, name) = arg.rsplit_once(':')?;
+    try_facility(name)?;
+    let (host, port) = addr.rsplit_once(':')?;
+    if host.is_empty() || port.parse::<u16>().is_err() {
+        return None;
+    }
+    Some((addr.to_owned(), name.to_owned()))
+}
+
+impl TryFrom<&str> for FacadeVariant {
+    type Error = String;
+
+    fn try_from(s: &str) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+impl TryFrom<String> for FacadeVariant {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 /*
  * Both fields are currently the same but may change in the future
+ *
+ * Inject is a decorator around the original FacadeScope used to simulate failing writes
+ * in tests. It only exists with the test-util feature enabled.
  */
 #[derive(Clone, Debug)]
 #[allow(dead_code)]
 pub enum FacadeScope {
     None,
-    Global(Arc<(dyn 'static + LogFacade + Send + Sync)>),
-    Local(Arc<(dyn LogFacade + Send + Sync)>),
+    Global(SharedFacade),
+    Local(SharedFacade),
+    #[cfg(feature = "test-util")]
+    Inject(FacadeRc<FailInjector>),
 }
 impl Default for FacadeScope {
     fn default() -> Self {
-        Self::new(&FacadeVariant::None)
+        Self::new(&FacadeVariant::None, None)
     }
 }
 #[allow(dead_code)]
 impl FacadeScope {
-    pub fn new(variant: &FacadeVariant) -> Self {
+    // ident is only used by syslog (see Syslog::init_with_ident)
+    pub fn new(variant: &FacadeVariant, ident: Option<&str>) -> Self {
         match variant {
             FacadeVariant::None => Self::None,
-            FacadeVariant::StdOut => Self::Global(Arc::new(self::StdOut::init())),
-            FacadeVariant::StdErr => Self::Global(Arc::new(self::StdErr::init())),
-            FacadeVariant::Syslog(s) => Self::Global(Arc::new(self::Syslog::init(s))),
-            FacadeVariant::File(p, t) => Self::Global(Arc::new(self::File::init(p, *t))),
+            FacadeVariant::StdOut => Self::Global(FacadeRc::new(self::StdOut::init())),
+            FacadeVariant::StdErr => Self::Global(FacadeRc::new(self::StdErr::init())),
+            FacadeVariant::Syslog(s) => Self::Global(FacadeRc::new(match ident {
+                Some(ident) => self::Syslog::init_with_ident(s, ident),
+                None => self::Syslog::init(s),
+            })),
+            FacadeVariant::File(p, t) => Self::Global(FacadeRc::new(self::File::init(p, *t))),
+            FacadeVariant::Binary(p) => Self::Global(FacadeRc::new(self::Binary::init(p))),
+            FacadeVariant::RingBuffer(n) => Self::Global(FacadeRc::new(RingBuffer::init(*n))),
+            #[cfg(feature = "test-util")]
+            FacadeVariant::Mock(m) => Self::Global(FacadeRc::new(m.clone())),
+            #[cfg(feature = "net-syslog")]
+            FacadeVariant::SyslogUdp(a, f) => Self::Global(FacadeRc::new(SyslogUdp::init(a, f))),
+            #[cfg(feature = "net-syslog")]
+            FacadeVariant::SyslogTcp(a, f) => Self::Global(FacadeRc::new(SyslogTcp::init(a, f))),
+            FacadeVariant::WithBestEffort(p, s) => Self::Global(FacadeRc::new(BestEffort {
+                primary: Self::new(p, ident),
+                secondary: Self::new(s, ident),
+                reported: AtomicBool::new(false),
+            })),
         }
     }
+    // like new, but opening a file fails instead of panicking
+    pub fn try_new(variant: &FacadeVariant, ident: Option<&str>) -> Result<Self> {
+        Ok(match variant {
+            FacadeVariant::File(p, t) => Self::Global(FacadeRc::new(self::File::try_init(p, *t)?)),
+            FacadeVariant::Binary(p) => Self::Global(FacadeRc::new(self::Binary::try_init(p)?)),
+            FacadeVariant::WithBestEffort(p, s) => Self::Global(FacadeRc::new(BestEffort {
+                primary: Self::try_new(p, ident)?,
+                secondary: Self::try_new(s, ident)?,
+                reported: AtomicBool::new(false),
+            })),
+            variant => Self::new(variant, ident),
+        })
+    }
     pub fn to_local(&self) -> Option<Self> {
         match self {
-            Self::Global(ref a) => Some(Self::Local(Arc::clone(a))),
+            Self::Global(ref a) => Some(Self::Local(FacadeRc::clone(a))),
             Self::None | Self::Local(_) => None,
+            #[cfg(feature = "test-util")]
+            Self::Inject(_) => None,
         }
     }
-    pub fn inner(&self) -> Option<&Arc<dyn LogFacade + Send + Sync>> {
+    pub fn variant_name(&self) -> &'static str {
+        self.inner().map_or("none", |f| f.variant_name())
+    }
+    pub fn ring_buffer(&self) -> Option<&RingBuffer> {
+        self.inner().and_then(|f| f.ring_buffer())
+    }
+    pub fn inner(&self) -> Option<&dyn LogFacade> {
         match *self {
             Self::None => None,
-            Self::Global(ref f) | Self::Local(ref f) => Some(f),
+            Self::Global(ref f) | Self::Local(ref f) => Some(f.as_ref()),
+            #[cfg(feature = "test-util")]
+            Self::Inject(ref f) => Some(f.as_ref()),
+        }
+    }
+    // wrap the current facade to fail the next n writes with the given kind
+    #[cfg(feature = "test-util")]
+    pub fn inject_failures(&self, n: usize, kind: std::io::ErrorKind) -> Self {
+        let original = match *self {
+            Self::Inject(ref f) => f.original.clone(),
+            _ => self.clone(),
+        };
+        Self::Inject(FacadeRc::new(FailInjector {
+            original,
+            remaining: AtomicUsize::new(n),
+            kind,
+        }))
+    }
+    // unwrap the original facade if failures are injected
+    #[cfg(feature = "test-util")]
+    pub fn clear_failures(&self) -> Self {
+        match *self {
+            Self::Inject(ref f) => f.original.clone(),
+            _ => self.clone(),
+        }
+    }
+}
+
+// Fail the next `remaining` writes with `kind`, forward to the original facade afterwards
+#[cfg(feature = "test-util")]
+#[derive(Debug)]
+pub struct FailInjector {
+    original: FacadeScope,
+    remaining: AtomicUsize,
+    kind: std::io::ErrorKind,
+}
+#[cfg(feature = "test-util")]
+impl LogFacade for FailInjector {
+    fn log(&self, level: Level, msg: Message) -> Result<()> {
+        use std::sync::atomic::Ordering;
+        let armed = self.remaining.fetch_update(
+            Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1)
+        );
+        if armed.is_ok() {
+            return Err(IoError(self.kind));
+        }
+        match self.original.inner() {
+            Some(f) => f.log(level, msg),
+            None => Ok(()),
+        }
+    }
+    fn is_syslog(&self) -> bool {
+        self.original.inner().is_some_and(|f| f.is_syslog())
+    }
+    fn variant_name(&self) -> &'static str {
+        self.original.variant_name()
+    }
+    fn ring_buffer(&self) -> Option<&RingBuffer> {
+        self.original.ring_buffer()
+    }
+    fn set_flush_policy(&self, policy: FlushPolicy) {
+        if let Some(f) = self.original.inner() {
+            f.set_flush_policy(policy);
+        }
+    }
+    fn flush(&self) -> Result<()> {
+        self.original.inner().map_or(Ok(()), |f| f.flush())
+    }
+    fn try_flush(&self) -> Result<bool> {
+        self.original.inner().map_or(Ok(true), |f| f.try_flush())
+    }
+    fn try_log(&self, level: Level, msg: Message) -> Result<bool> {
+        use std::sync::atomic::Ordering;
+        let armed = self.remaining.fetch_update(
+            Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1)
+        );
+        if armed.is_ok() {
+            return Err(IoError(self.kind));
         }
+        self.original.inner().map_or(Ok(true), |f| f.try_log(level, msg))
+    }
+}
+
+
+/*
+ * Log to stdout
+ *
+ * The lock of std::io::Stdout can't be tried, so the StdOut facades count their writers in
+ * STDOUT_WRITERS instead. log() never waits for it, try_log only skips the message while
+ * another writer of hclog is busy - not e.g. a println! of the app.
+ */
+static STDOUT_WRITERS: AtomicUsize = AtomicUsize::new(0);
+
+// a writer counted in STDOUT_WRITERS until dropped
+struct StdOutWriter;
+impl StdOutWriter {
+    fn enter() -> Self {
+        STDOUT_WRITERS.fetch_add(1, Ordering::Acquire);
+        Self
+    }
+    fn try_enter() -> Option<Self> {
+        STDOUT_WRITERS.compare_exchange(0, 1, Ordering::Acquire, Ordering::Relaxed).ok()
+            .map(|_| Self)
+    }
+}
+impl Drop for StdOutWriter {
+    fn drop(&mut self) {
+        STDOUT_WRITERS.fetch_sub(1, Ordering::Release);
     }
 }
 
-// Log to stdout
 #[derive(Debug)]
 #[allow(dead_code)]
 pub struct StdOut { handle: std::io::Stdout }
 impl StdOut {
     fn init() -> Self { Self { handle: std::io::stdout() } }
-}
-impl LogFacade for StdOut {
     #[cfg(not(test))]
-    fn log(&self, _lvl: Level, msg: Message) -> Result<()> {
+    fn write(&self, msg: Message) -> Result<()> {
         let mut handle = self.handle.lock();
-        handle.write_all(msg.to_string().as_bytes())?;
-        handle.write_all(b"\n")?;
+        handle.write_fmt(format_args!("{}{}", msg, msg.terminator()))?;
         Ok(())
     }
     #[cfg(test)]
-    fn log(&self, _lvl: Level, msg: Message) -> Result<()> {
-        println!("{}", msg);
+    fn write(&self, msg: Message) -> Result<()> {
+        print!("{}{}", msg, msg.terminator());
         Ok(())
     }
 }
+impl LogFacade for StdOut {
+    fn variant_name(&self) -> &'static str { "stdout" }
+    fn log(&self, _lvl: Level, msg: Message) -> Result<()> {
+        let _writer = StdOutWriter::enter();
+        self.write(msg)
+    }
+    fn try_log(&self, _lvl: Level, msg: Message) -> Result<bool> {
+        let Some(_writer) = StdOutWriter::try_enter() else {
+            return Ok(false);
+        };
+        self.write(msg).map(|()| true)
+    }
+}
 
 // Log to stderr
 #[derive(Debug)]
@@ -158,62 +582,226 @@ impl StdErr {
     fn init() -> Self { Self { handle: std::io::stderr() }}
 }
 impl LogFacade for StdErr {
+    fn variant_name(&self) -> &'static str { "stderr" }
     fn log(&self, _lvl: Level, msg: Message) -> Result<()> {
         let mut handle = self.handle.lock();
-        handle.write_all(msg.to_string().as_bytes())?;
-        handle.write_all(b"\n")?;
+        handle.write_fmt(format_args!("{}{}", msg, msg.terminator()))?;
         Ok(())
     }
 }
 
+// messages dropped by a facade instead of failing the log call, see health_summary
+pub (crate) static FACADE_FAILURES: AtomicU64 = AtomicU64::new(0);
+// messages skipped by the secondary of WithBestEffort because it was locked
+pub (crate) static FACADE_SKIPPED: AtomicU64 = AtomicU64::new(0);
+
+/*
+ * Log to the primary facade as if it was used alone and try the secondary one
+ *
+ * The secondary is always written via try_log, so a locked secondary skips the message. Its
+ * errors are only counted, the first one is reported via the Internal key. The report is
+ * logged while the context is read, just like the expiry of a level in log_batch_at.
+ */
+#[derive(Debug)]
+pub struct BestEffort {
+    primary: FacadeScope,
+    secondary: FacadeScope,
+    reported: AtomicBool,
+}
+impl BestEffort {
+    fn mirror(&self, level: Level, msg: Message, blocking: bool) {
+        let Some(secondary) = self.secondary.inner() else {
+            return;
+        };
+        match secondary.try_log(level, msg) {
+            Ok(true) => (),
+            Ok(false) => {
+                FACADE_SKIPPED.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                FACADE_FAILURES.fetch_add(1, Ordering::Relaxed);
+                if !self.reported.swap(true, Ordering::Relaxed) {
+                    let report = format_args!("best-effort facade '{}' failed: {}, further \
+                        failures are only counted", secondary.variant_name(), e);
+                    let internal = crate::InternalLogKeys::Internal;
+                    // a failing report must not fail the log call either
+                    let _ = match blocking {
+                        true => crate::log(internal, Level::Warn, file!(), None, line!(), &report),
+                        false => crate::try_log(internal, Level::Warn, report).map(|_| ()),
+                    };
+                }
+            }
+        }
+    }
+}
+impl LogFacade for BestEffort {
+    fn log(&self, level: Level, msg: Message) -> Result<()> {
+        let primary = self.primary.inner().map_or(Ok(()), |f| f.log(level, msg.clone()));
+        self.mirror(level, msg, true);
+        primary
+    }
+    fn try_log(&self, level: Level, msg: Message) -> Result<bool> {
+        let primary = self.primary.inner().map_or(Ok(true), |f| f.try_log(level, msg.clone()));
+        self.mirror(level, msg, false);
+        primary
+    }
+    fn is_syslog(&self) -> bool {
+        self.primary.inner().is_some_and(|f| f.is_syslog())
+    }
+    fn variant_name(&self) -> &'static str {
+        self.primary.variant_name()
+    }
+    fn ring_buffer(&self) -> Option<&RingBuffer> {
+        self.primary.ring_buffer()
+    }
+    fn set_flush_policy(&self, policy: FlushPolicy) {
+        for facade in [&self.primary, &self.secondary].into_iter().filter_map(|f| f.inner()) {
+            facade.set_flush_policy(policy);
+        }
+    }
+    fn flush(&self) -> Result<()> {
+        if let Some(Err(_)) = self.secondary.inner().map(|f| f.flush()) {
+            FACADE_FAILURES.fetch_add(1, Ordering::Relaxed);
+        }
+        self.primary.inner().map_or(Ok(()), |f| f.flush())
+    }
+    fn try_flush(&self) -> Result<bool> {
+        let secondary = match self.secondary.inner().map(|f| f.try_flush()) {
+            Some(Err(_)) => {
+                FACADE_FAILURES.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Some(Ok(flushed)) => flushed,
+            None => true,
+        };
+        let primary = self.primary.inner().map_or(Ok(true), |f| f.try_flush())?;
+        Ok(primary && secondary)
+    }
+}
+
+// facility bits of syslog(3), also used for the PRI of the remote syslog facades
+pub (crate) fn try_facility(name: &str) -> Option<c_int> {
+    Some(match name {
+        "kern" => libc::LOG_KERN,
+        "user" => libc::LOG_USER,
+        "mail" => libc::LOG_MAIL,
+        "daemon" => libc::LOG_DAEMON,
+        "auth" => libc::LOG_AUTH,
+        "syslog" => libc::LOG_SYSLOG,
+        "lpr" => libc::LOG_LPR,
+        "news" => libc::LOG_NEWS,
+        "uucp" => libc::LOG_UUCP,
+        "local0" => libc::LOG_LOCAL0,
+        "local1" => libc::LOG_LOCAL1,
+        "local2" => libc::LOG_LOCAL2,
+        "local3" => libc::LOG_LOCAL3,
+        "local4" => libc::LOG_LOCAL4,
+        "local5" => libc::LOG_LOCAL5,
+        "local6" => libc::LOG_LOCAL6,
+        "local7" => libc::LOG_LOCAL7,
+        _ => return None,
+    })
+}
+pub (crate) fn facility(name: &str) -> c_int {
+    try_facility(name).unwrap_or_else(|| panic!("unknown syslog facility '{}'", name))
+}
+pub (crate) fn severity(level: Level) -> Result<c_int> {
+    match level {
+        Level::Off => Err(InvalidLevel),
+        Level::Emerg => Ok(libc::LOG_EMERG),
+        Level::Alert => Ok(libc::LOG_ALERT),
+        Level::Crit => Ok(libc::LOG_CRIT),
+        Level::Error => Ok(libc::LOG_ERR),
+        Level::Warn => Ok(libc::LOG_WARNING),
+        Level::Notice => Ok(libc::LOG_NOTICE),
+        Level::Info => Ok(libc::LOG_INFO),
+        d if d >= Level::Debug1 && d <= Level::Debug10 => Ok(libc::LOG_DEBUG),
+        _ => Err(UnknownLogLevel),
+    }
+}
+
+/*
+ * idents passed to openlog(3) and the number of facades using them
+ *
+ * openlog(3) keeps the pointer to the ident, so the idents are leaked and live as long as the
+ * process. They are interned to keep one copy per name. openlog(3) and closelog(3) are only
+ * called with the lock held, the log is closed when the last facade with an ident is dropped.
+ */
+#[derive(Debug)]
+struct SyslogIdents {
+    interned: Vec<&'static CStr>,
+    active: Option<&'static CStr>,
+    users: usize,
+}
+impl SyslogIdents {
+    fn intern(&mut self, ident: CString) -> &'static CStr {
+        match self.interned.iter().find(|i| **i == ident.as_c_str()) {
+            Some(interned) => interned,
+            None => {
+                let leaked: &'static CStr = Box::leak(ident.into_boxed_c_str());
+                self.interned.push(leaked);
+                leaked
+            }
+        }
+    }
+}
+static SYSLOG_IDENTS: Mutex<SyslogIdents> =
+    Mutex::new(SyslogIdents { interned: Vec::new(), active: None, users: 0 });
+
+fn syslog_idents() -> MutexGuard<'static, SyslogIdents> {
+    SYSLOG_IDENTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 // Log to Syslog
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default)]
 pub struct Syslog {
     facility: c_int,
+    // interned in SYSLOG_IDENTS, counted as user while set
+    ident: Option<&'static CStr>,
 }
 impl Syslog {
+    fn init_with_ident(opt: &str, ident: &str) -> Self {
+        let mut syslog = Self::init(opt);
+        // scope names with a nul byte are rejected by init, strip them for any other caller
+        let ident = CString::new(ident.replace('\0', "")).unwrap_or_default();
+        let mut idents = syslog_idents();
+        let ident = idents.intern(ident);
+        unsafe {
+            libc::openlog(ident.as_ptr(), libc::LOG_PID | libc::LOG_NDELAY, syslog.facility);
+        }
+        idents.active = Some(ident);
+        idents.users += 1;
+        syslog.ident = Some(ident);
+        syslog
+    }
     fn init(opt: &str) -> Self {
-        let facility = match opt {
-            "kern" => libc::LOG_KERN,
-            "user" => libc::LOG_USER,
-            "mail" => libc::LOG_MAIL,
-            "daemon" => libc::LOG_DAEMON,
-            "auth" => libc::LOG_AUTH,
-            "syslog" => libc::LOG_SYSLOG,
-            "lpr" => libc::LOG_LPR,
-            "news" => libc::LOG_NEWS,
-            "uucp" => libc::LOG_UUCP,
-            "local0" => libc::LOG_LOCAL0,
-            "local1" => libc::LOG_LOCAL1,
-            "local2" => libc::LOG_LOCAL2,
-            "local3" => libc::LOG_LOCAL3,
-            "local4" => libc::LOG_LOCAL4,
-            "local5" => libc::LOG_LOCAL5,
-            "local6" => libc::LOG_LOCAL6,
-            "local7" => libc::LOG_LOCAL7,
-            _ => panic!("unknown syslog facility '{}'", opt),
-        };
-        Self { facility }
+        let facility = facility(opt);
+        Self { facility, ident: None }
+    }
+}
+impl Drop for Syslog {
+    fn drop(&mut self) {
+        if self.ident.is_some() {
+            let mut idents = syslog_idents();
+            idents.users -= 1;
+            if idents.users == 0 {
+                idents.active = None;
+                unsafe { libc::closelog() }
+            }
+        }
     }
 }
 impl LogFacade for Syslog {
     fn is_syslog(&self) -> bool {
         true
     }
+    fn variant_name(&self) -> &'static str {
+        "syslog"
+    }
 
     fn log(&self, level: Level, msg: Message) -> Result<()> {
-        let lvl = match level {
-            Level::Off => return Ok(()),
-            Level::Emerg => libc::LOG_EMERG,
-            Level::Alert => libc::LOG_ALERT,
-            Level::Crit => libc::LOG_CRIT,
-            Level::Error => libc::LOG_ERR,
-            Level::Warn => libc::LOG_WARNING,
-            Level::Notice => libc::LOG_NOTICE,
-            Level::Info => libc::LOG_INFO,
-            d if d >= Level::Debug1 && d <= Level::Debug10 => libc::LOG_DEBUG,
-            _ => return Err(UnknownLogLevel),
-        };
+        let lvl = severity(level)?;
+        // syslog(3) needs one contiguous nul terminated buffer
         let msg_raw = CString::new(msg.to_string())?;
         let fmt = CString::new("%s".to_owned())?;
         unsafe {
@@ -230,29 +818,318 @@ impl LogFacade for Syslog {
 // Log to a file
 #[derive(Debug)]
 pub struct File {
-    handle: Arc<Mutex<BufWriter<StdFile>>>,
+    handle: Arc<Mutex<FileWriter>>,
+}
+#[derive(Debug)]
+struct FileWriter {
+    writer: BufWriter<StdFile>,
+    policy: FlushPolicy,
+    // messages written since the last flush
+    pending: u32,
+    last_flush: Instant,
+}
+impl FileWriter {
+    fn write(&mut self, msg: Message) -> Result<()> {
+        self.writer.write_fmt(format_args!("{}{}", msg, msg.terminator()))?;
+        self.written()?;
+        Ok(())
+    }
+    fn written(&mut self) -> std::io::Result<()> {
+        self.pending = self.pending.saturating_add(1);
+        let due = match self.policy {
+            FlushPolicy::EveryMessage => true,
+            FlushPolicy::EveryN(n) => self.pending >= n,
+            FlushPolicy::Interval(interval) =>
+                clock::instant().saturating_duration_since(self.last_flush) >= interval,
+        };
+        if due { self.flush() } else { Ok(()) }
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()?;
+        self.pending = 0;
+        self.last_flush = clock::instant();
+        Ok(())
+    }
 }
 impl File {
     fn init<P: AsRef<Path>>(path: P, truncate: bool) -> Self {
+        Self::try_init(path, truncate).expect("failed to open log file")
+    }
+    fn try_init<P: AsRef<Path>>(path: P, truncate: bool) -> std::io::Result<Self> {
         let handle = std::fs::OpenOptions::new()
             .create(true)
             .append(!truncate)
             .truncate(truncate)
             .write(true)
-            .open(path.as_ref())
-            .expect("failed to open log file");
-        Self { handle: Arc::new(Mutex::new(BufWriter::new(handle))) }
+            .open(path.as_ref())?;
+        // the BufWriter is never dropped while it's part of the global context
+        #[cfg(all(unix, feature = "flush-at-exit"))]
+        crate::atexit::register();
+        Ok(Self { handle: Arc::new(Mutex::new(FileWriter {
+            writer: BufWriter::new(handle),
+            policy: FlushPolicy::EveryMessage,
+            pending: 0,
+            last_flush: clock::instant(),
+        })) })
     }
 }
 impl LogFacade for File {
+    fn variant_name(&self) -> &'static str { "file" }
+    fn log(&self, _: Level, msg: Message) -> Result<()> {
+        let mut file = self.handle.lock().map_err(|_| ContextLock)?;
+        file.write(msg)
+    }
+    fn try_log(&self, _: Level, msg: Message) -> Result<bool> {
+        match self.handle.try_lock() {
+            Ok(mut file) => file.write(msg).map(|()| true),
+            Err(TryLockError::WouldBlock) => Ok(false),
+            Err(TryLockError::Poisoned(_)) => Err(ContextLock),
+        }
+    }
+    fn set_flush_policy(&self, policy: FlushPolicy) {
+        if let Ok(mut file) = self.handle.lock() {
+            file.policy = policy;
+            // switching back writes the messages buffered under the previous policy
+            if policy == FlushPolicy::EveryMessage {
+                let _ = file.flush();
+            }
+        }
+    }
+    fn flush(&self) -> Result<()> {
+        self.handle.lock().map_err(|_| ContextLock)?.flush()?;
+        Ok(())
+    }
+    fn try_flush(&self) -> Result<bool> {
+        match self.handle.try_lock() {
+            Ok(mut file) => file.flush().map(|()| true).map_err(Into::into),
+            Err(TryLockError::WouldBlock) => Ok(false),
+            Err(TryLockError::Poisoned(_)) => Err(ContextLock),
+        }
+    }
+}
+
+// Log records to a binary file, see crate::binlog for the format
+#[derive(Debug)]
+pub struct Binary {
+    handle: Mutex<BufWriter<StdFile>>,
+}
+impl Binary {
+    fn init<P: AsRef<Path>>(path: P) -> Self {
+        Self::try_init(path).expect("failed to open binary log file")
+    }
+    fn try_init<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let handle = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())?;
+        Ok(Self { handle: Mutex::new(BufWriter::new(handle)) })
+    }
+}
+impl LogFacade for Binary {
+    fn variant_name(&self) -> &'static str { "binary" }
+    fn log(&self, level: Level, msg: Message) -> Result<()> {
+        let record = binlog::encode(level, msg.time(), msg.module(), msg.payload());
+        let mut writer = self.handle.lock().unwrap_or_else(|e| e.into_inner());
+        writer.write_all(&record)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+// Keep the last `capacity` rendered lines in memory
+pub struct RingBuffer {
+    lines: Mutex<VecDeque<String>>,
+    capacity: usize,
+}
+impl RingBuffer {
+    fn init(capacity: usize) -> Self {
+        // don't allocate the whole capacity upfront, most buffers never fill up
+        Self { lines: Mutex::new(VecDeque::new()), capacity }
+    }
+    // take all lines, the oldest first
+    pub fn drain(&self) -> Vec<String> {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner()).drain(..).collect()
+    }
+}
+// the lines are left out, they would flood the dump
+impl Debug for RingBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let len = self.lines.lock().unwrap_or_else(|e| e.into_inner()).len();
+        f.debug_struct("RingBuffer").field("capacity", &self.capacity).field("len", &len).finish()
+    }
+}
+impl LogFacade for RingBuffer {
+    fn variant_name(&self) -> &'static str { "ringbuffer" }
+    fn ring_buffer(&self) -> Option<&RingBuffer> { Some(self) }
     fn log(&self, _: Level, msg: Message) -> Result<()> {
-        let handle = Arc::clone(&self.handle);
-        {
-            let mut writer = handle.lock().unwrap();
-            writer.write_all(msg.to_string().as_bytes())?;
-            writer.write_all(b"\n")?;
-            writer.flush()?;
+        if self.capacity == 0 {
+            return Ok(());
         }
+        let line = msg.to_string();
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod facades_tests {
+    use super::{
+        BestEffort, FacadeRc, FacadeScope, FacadeVariant, File, LogFacade, Syslog, FACADE_SKIPPED,
+        StdOutWriter, syslog_idents,
+    };
+    use crate::{level::Level, message::Message, options::Options};
+    use serial_test::serial;
+    use std::{ffi::CStr, sync::atomic::{AtomicBool, Ordering}};
+
+    fn log_to(facade: &FacadeScope, line: std::fmt::Arguments) {
+        let opts = Options::new();
+        let msg = Message::new(&opts, "bin", file!(), None, line!(), &line);
+        facade.inner().unwrap().log(Level::Info, msg).unwrap();
+    }
+
+    #[test]
+    fn ring_buffer_overflow() {
+        let facade = FacadeScope::new(&FacadeVariant::RingBuffer(3), None);
+        for i in 0..5 {
+            log_to(&facade, format_args!("line {}", i));
+        }
+        let ring = facade.ring_buffer().unwrap();
+        assert_eq!(format!("{:?}", ring), "RingBuffer { capacity: 3, len: 3 }");
+        assert_eq!(ring.drain(), ["line 2", "line 3", "line 4"]);
+        assert_eq!(ring.drain(), Vec::<String>::new());
+        log_to(&facade, format_args!("line {}", 5));
+        assert_eq!(ring.drain(), ["line 5"]);
+
+        let facade = FacadeScope::new(&FacadeVariant::RingBuffer(0), None);
+        log_to(&facade, format_args!("dropped"));
+        assert_eq!(facade.ring_buffer().unwrap().drain(), Vec::<String>::new());
+        assert!(FacadeScope::new(&FacadeVariant::StdErr, None).ring_buffer().is_none());
+    }
+
+    #[test]
+    fn try_log_contended() {
+        let opts = Options::new();
+        let try_log = |facade: &dyn LogFacade| facade.try_log(Level::Info,
+            Message::new(&opts, "bin", file!(), None, line!(), &format_args!("try")));
+        let path = std::env::temp_dir()
+            .join(format!("hclog_try_log_facade_{}.log", std::process::id()));
+        let file = File::init(&path, true);
+        let locked = file.handle.lock().unwrap();
+        assert_eq!(try_log(&file), Ok(false));
+        drop(locked);
+        assert_eq!(try_log(&file), Ok(true));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "try\n");
+        std::fs::remove_file(path).unwrap();
+
+        let stdout = FacadeScope::new(&FacadeVariant::StdOut, None);
+        let busy = StdOutWriter::enter();
+        assert_eq!(try_log(stdout.inner().unwrap()), Ok(false));
+        drop(busy);
+        assert_eq!(try_log(stdout.inner().unwrap()), Ok(true));
+    }
+
+    #[test]
+    fn best_effort_skips_locked() {
+        let opts = Options::new();
+        let path = std::env::temp_dir()
+            .join(format!("hclog_best_effort_facade_{}.log", std::process::id()));
+        let file = FacadeRc::new(File::init(&path, true));
+        let facade = BestEffort {
+            primary: FacadeScope::new(&FacadeVariant::RingBuffer(4), None),
+            secondary: FacadeScope::Global(file.clone()),
+            reported: AtomicBool::new(false),
+        };
+        let log = |line| facade.log(Level::Info,
+            Message::new(&opts, "bin", file!(), None, line!(), &format_args!("{}", line)));
+        let skipped = FACADE_SKIPPED.load(Ordering::Relaxed);
+        let locked = file.handle.lock().unwrap();
+        assert_eq!(log("skipped"), Ok(()));
+        drop(locked);
+        assert_eq!(log("mirrored"), Ok(()));
+        assert_eq!(FACADE_SKIPPED.load(Ordering::Relaxed), skipped + 1);
+        assert_eq!(facade.ring_buffer().unwrap().drain(), ["skipped", "mirrored"]);
+        assert_eq!(facade.variant_name(), "ringbuffer");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "mirrored\n");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn parse_with_argument() {
+        let parse = |s: &str| s.parse::<FacadeVariant>().map(|f| format!("{:?}", f));
+        assert_eq!(parse("StdOut").unwrap(), "StdOut");
+        assert_eq!(parse("file:/tmp/Out.log").unwrap(), "File(\"/tmp/Out.log\", false)");
+        assert_eq!(parse("syslog:daemon").unwrap(), "Syslog(\"daemon\")");
+        assert_eq!(parse("Binary:/tmp/a.bin").unwrap(), "Binary(\"/tmp/a.bin\")");
+        assert_eq!(parse("ringbuffer:16").unwrap(), "RingBuffer(16)");
+        assert!(parse("ringbuffer:many").is_err());
+        assert!(parse("syslog:nope").is_err());
+        assert!(parse("syslog:daemon:1").is_err());
+        assert!(parse("stdout:1").is_err());
+        assert!(parse("pipe").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "net-syslog")]
+    fn parse_remote_syslog() {
+        let parse = |s: &str| s.parse::<FacadeVariant>().map(|f| format!("{:?}", f));
+        assert_eq!(parse("syslog-udp:127.0.0.1:514:local0").unwrap(),
+                   "SyslogUdp(\"127.0.0.1:514\", \"local0\")");
+        assert_eq!(parse("Syslog-TCP:[::1]:6514:user").unwrap(),
+                   "SyslogTcp(\"[::1]:6514\", \"user\")");
+        // not resolved while parsing
+        assert_eq!(parse("syslog-tcp:collector.invalid:601:daemon").unwrap(),
+                   "SyslogTcp(\"collector.invalid:601\", \"daemon\")");
+        assert!(parse("syslog-udp:127.0.0.1:514:nope").is_err());
+        assert!(parse("syslog-udp:127.0.0.1:local0").is_err());
+        assert!(parse("syslog-udp::514:local0").is_err());
+        assert!(parse("syslog-udp:loghost:65536:local0").is_err());
+        assert!(parse("syslog-udp").is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn syslog_ident_lifetime() {
+        let active = || syslog_idents().active.map(CStr::as_ptr);
+        let first = Syslog::init_with_ident("user", "hclog_first");
+        let first_ptr = first.ident.unwrap().as_ptr();
+        assert_eq!(active(), Some(first_ptr));
+        assert!(Syslog::init("user").ident.is_none());
+        assert_eq!(active(), Some(first_ptr));
+
+        // the log stays open with the newer ident while any facade with an ident is alive
+        let second = Syslog::init_with_ident("daemon", "hclog_second");
+        let second_ptr = second.ident.unwrap().as_ptr();
+        drop(second);
+        assert_eq!(active(), Some(second_ptr));
+        drop(first);
+        assert_eq!(active(), None);
+
+        // the same name is opened with the same pointer again
+        let again = Syslog::init_with_ident("user", "hclog_first");
+        assert_eq!(again.ident.unwrap().as_ptr(), first_ptr);
+    }
+
+    #[test]
+    #[serial]
+    fn syslog_ident_concurrent() {
+        let threads: Vec<_> = (0..8).map(|t| std::thread::spawn(move || {
+            for i in 0..200 {
+                let syslog = Syslog::init_with_ident("user", &format!("hclog_t{}_{}", t, i % 4));
+                let ident = syslog.ident.unwrap();
+                // the ident of the facade stays valid while other facades are created
+                assert!(ident.to_str().unwrap().starts_with("hclog_"));
+                assert!(syslog_idents().users > 0);
+            }
+        })).collect();
+        threads.into_iter().for_each(|t| t.join().unwrap());
+        let idents = syslog_idents();
+        assert_eq!(idents.users, 0);
+        assert_eq!(idents.active, None);
+        let interned = idents.interned.iter().filter(|i| i.to_bytes().starts_with(b"hclog_t"));
+        assert_eq!(interned.count(), 8 * 4);
+    }
+}
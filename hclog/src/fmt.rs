@@ -0,0 +1,134 @@
+//! Human friendly formatting of durations, sizes and rates
+//!
+//! The functions of this module return wrappers implementing [`Display`] which render the value
+//! with an adaptive unit and three significant digits while the message is formatted, without
+//! any allocation. They are intended as arguments of the log macros:
+//!
+//! ```rust
+//! use hclog::fmt::{bytes, dur, rate};
+//! use std::time::Duration;
+//!
+//! let took = Duration::from_micros(12_345);
+//! assert_eq!(format!("read {} in {} ({})", bytes(3 << 20), dur(took), rate(12_300, took)),
+//!     "read 3.00MiB in 12.3ms (996k/s)");
+//! ```
+//!
+//! The [`lI_timed`](macro@crate::lI_timed) macro logs the duration of a block this way.
+use std::{fmt::{self, Display}, time::Duration};
+
+/*
+ * writes v with three significant digits, values from 999.5 on move to the next unit. With
+ * binary units this leaves a fraction of the next unit, e.g. 0.977MiB for 1000KiB.
+ */
+fn scaled(f: &mut fmt::Formatter<'_>, mut v: f64, units: &[&str], step: f64) -> fmt::Result {
+    let mut unit = 0;
+    while v >= 999.5 && unit + 1 < units.len() {
+        v /= step;
+        unit += 1;
+    }
+    let prec = if v < 0.9995 { 3 } else if v < 9.995 { 2 } else if v < 99.95 { 1 } else { 0 };
+    write!(f, "{:.*}{}", prec, v, units[unit])
+}
+
+/// Format a [`Duration`] with an adaptive unit
+///
+/// Durations below a microsecond are written in whole nanoseconds, longer ones in `µs`, `ms` or
+/// `s` with three significant digits, e.g. `850ns`, `12.3µs`, `1.00ms` or `250s`.
+pub fn dur(d: Duration) -> impl Display {
+    struct Dur(Duration);
+    impl Display for Dur {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.0.as_nanos() {
+                n if n < 1000 => write!(f, "{}ns", n),
+                n => scaled(f, n as f64 / 1000.0, &["µs", "ms", "s"], 1000.0),
+            }
+        }
+    }
+    Dur(d)
+}
+
+/// Format a number of bytes with a binary unit
+///
+/// Sizes below a KiB are written in whole bytes, larger ones in `KiB`, `MiB` or `GiB` with
+/// three significant digits, e.g. `512B`, `1.50KiB` or `12.3GiB`. From 1000 of a unit on the
+/// next one is used, e.g. `0.977MiB`.
+pub fn bytes(n: u64) -> impl Display {
+    struct Bytes(u64);
+    impl Display for Bytes {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.0 {
+                n if n < 1024 => write!(f, "{}B", n),
+                n => scaled(f, n as f64 / 1024.0, &["KiB", "MiB", "GiB"], 1024.0),
+            }
+        }
+    }
+    Bytes(n)
+}
+
+/// Format the rate of `n` events within `d` per second
+///
+/// The rate is written with three significant digits and a decimal prefix, e.g. `5.00/s`,
+/// `12.3k/s` or `1.20M/s`. No events are written as `0/s`, events within no time as `inf/s`.
+pub fn rate(n: u64, d: Duration) -> impl Display {
+    struct Rate(u64, Duration);
+    impl Display for Rate {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match (self.0, self.1) {
+                (0, _) => f.write_str("0/s"),
+                (_, d) if d.is_zero() => f.write_str("inf/s"),
+                (n, d) => scaled(f, n as f64 / d.as_secs_f64(), &["/s", "k/s", "M/s", "G/s"],
+                    1000.0),
+            }
+        }
+    }
+    Rate(n, d)
+}
+
+#[cfg(test)]
+mod fmt_tests {
+    use super::{bytes, dur, rate};
+    use std::time::Duration;
+
+    #[test]
+    fn durations() {
+        let ns = |n| dur(Duration::from_nanos(n)).to_string();
+        assert_eq!(ns(0), "0ns");
+        assert_eq!(ns(999), "999ns");
+        assert_eq!(ns(1000), "1.00µs");
+        assert_eq!(ns(9_994), "9.99µs");
+        assert_eq!(ns(9_996), "10.0µs");
+        assert_eq!(ns(12_345), "12.3µs");
+        assert_eq!(ns(999_499), "999µs");
+        assert_eq!(ns(999_500), "1.00ms");
+        assert_eq!(ns(12_300_000), "12.3ms");
+        assert_eq!(ns(1_234_567_891), "1.23s");
+        assert_eq!(dur(Duration::from_secs(4321)).to_string(), "4321s");
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(bytes(0).to_string(), "0B");
+        assert_eq!(bytes(1023).to_string(), "1023B");
+        assert_eq!(bytes(1024).to_string(), "1.00KiB");
+        assert_eq!(bytes(1536).to_string(), "1.50KiB");
+        assert_eq!(bytes(999 << 10).to_string(), "999KiB");
+        assert_eq!(bytes(1000 << 10).to_string(), "0.977MiB");
+        assert_eq!(bytes((1 << 20) - 1).to_string(), "1.00MiB");
+        assert_eq!(bytes(3 << 20).to_string(), "3.00MiB");
+        assert_eq!(bytes(123 << 30).to_string(), "123GiB");
+        assert_eq!(bytes(2048 << 30).to_string(), "2048GiB");
+    }
+
+    #[test]
+    fn rates() {
+        let sec = Duration::from_secs(1);
+        assert_eq!(rate(0, Duration::ZERO).to_string(), "0/s");
+        assert_eq!(rate(5, Duration::ZERO).to_string(), "inf/s");
+        assert_eq!(rate(5, sec).to_string(), "5.00/s");
+        assert_eq!(rate(1, sec * 2).to_string(), "0.500/s");
+        assert_eq!(rate(999, sec).to_string(), "999/s");
+        assert_eq!(rate(12_345, sec).to_string(), "12.3k/s");
+        assert_eq!(rate(1_200, Duration::from_millis(1)).to_string(), "1.20M/s");
+        assert_eq!(rate(5_000, Duration::from_micros(1)).to_string(), "5.00G/s");
+    }
+}
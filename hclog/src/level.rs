@@ -19,6 +19,7 @@ use crate::ErrorKind;
 /// ```
 /// use hclog::Level;
 /// # use hclog_macros::HCLog;
+/// # use hclog::Scope;
 ///
 /// // the default level is 'off' - if no level is set, logging is disabled
 ///
@@ -35,12 +36,28 @@ use crate::ErrorKind;
 /// }
 /// ```
 ///
+/// A [`Level`] can be compared with a [`log::Level`] directly. The levels of crate `log` are
+/// mapped the same way as messages logged via the `log` compatibility layer: `Debug` equals
+/// [`Debug1`](crate::Level::Debug1) and `Trace` equals [`Debug10`](crate::Level::Debug10).
+/// A [`Level`] also converts into a [`log::LevelFilter`].
+///
+/// ```
+/// use hclog::Level;
+///
+/// assert!(Level::Debug5 >= log::Level::Debug);
+/// assert_eq!(log::LevelFilter::from(Level::Notice), log::LevelFilter::Info);
+/// ```
+///
 /// for more Informations see [`set_level`](fn@crate::set_level)
 #[derive(Copy, Clone, Debug, Default, Display, EnumIter, Hash, Eq, PartialEq, Ord, PartialOrd)]
 #[strum(serialize_all = "lowercase")]
 pub enum Level {
     #[default]  /* logging is disabled by default */
     /// Logging is disabled
+    ///
+    /// Only used as level of a LogKey. Messages logged with `Off` are never written, the log
+    /// macros drop them and the functions taking a message return
+    /// [`InvalidLevel`](ErrorKind::InvalidLevel).
     Off = 0, /* no logging */
     /// The "Emergency" level
     ///
@@ -98,7 +115,22 @@ pub enum Level {
 
 /// Parses a string to a [`Level`]
 ///
-/// The string is compared case-insensitive to the available [`Level`]s.
+/// The string is compared case-insensitive to the names of the available [`Level`]s. For
+/// operators used to syslog a few more forms are accepted:
+///
+/// | Input      | Level                                                       |
+/// |------------|-------------------------------------------------------------|
+/// | `debug`    | [`Debug1`](Level::Debug1)                                   |
+/// | `panic`    | [`Emerg`](Level::Emerg)                                     |
+/// | `err`      | [`Error`](Level::Error)                                     |
+/// | `0` to `7` | the syslog severity, `0` is [`Emerg`] and `7` is [`Debug1`] |
+///
+/// A number is always read as syslog severity and not as the position of the variant in
+/// [`Level`], so `6` is [`Info`](Level::Info) and numbers above `7` are rejected. There is no
+/// number for [`Off`](Level::Off) or the more verbose debug levels, use their names instead.
+///
+/// [`Emerg`]: Level::Emerg
+/// [`Debug1`]: Level::Debug1
 ///
 /// # Examples
 /// ```
@@ -107,6 +139,8 @@ pub enum Level {
 /// fn main() {
 ///     let level = "info".parse::<Level>().unwrap();
 ///     assert_eq!(level, Level::Info);
+///     assert_eq!("6".parse::<Level>().unwrap(), Level::Info);
+///     assert_eq!("debug".parse::<Level>().unwrap(), Level::Debug1);
 /// }
 /// ```
 ///
@@ -118,9 +152,18 @@ impl std::str::FromStr for Level {
     type Err = ErrorKind;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match Level::iter().find(|r| r.to_string().eq_ignore_ascii_case(s)) {
-            Some(l) => Ok(l),
-            None => Err(ErrorKind::UnknownLogLevel),
+        if let Some(l) = Level::iter().find(|r| r.to_string().eq_ignore_ascii_case(s)) {
+            return Ok(l);
+        }
+        match s.to_ascii_lowercase().as_str() {
+            "debug" => Ok(Self::Debug1),
+            "panic" => Ok(Self::Emerg),
+            "err" => Ok(Self::Error),
+            // the syslog severity is one less than the discriminant
+            n => match n.parse::<u8>() {
+                Ok(sev @ 0..=7) if n.len() == 1 => Ok(Self::Emerg.offset(sev)),
+                _ => Err(ErrorKind::UnknownLogLevel),
+            },
         }
     }
 }
@@ -139,9 +182,29 @@ impl Level {
         Self::Off
     }
 
+    // whether a message at level other passes self, a message at Off never does
     #[inline(always)]
     pub fn is_enabled(&self, other: Self) -> bool {
-        other <= *self
+        other != Self::Off && other <= *self
+    }
+
+    pub fn from_u8(v: u8) -> Option<Self> {
+        Level::iter().nth(v as usize)
+    }
+
+    // the level `n` steps more verbose, Debug10 at most
+    fn offset(self, n: u8) -> Self {
+        Self::from_u8((self as u8).saturating_add(n)).unwrap_or(Self::Debug10)
+    }
+
+    // the name of a level close to the unknown `s`, e.g. a typo like "debgu1"
+    pub (crate) fn suggest(s: &str) -> Option<Self> {
+        let s = s.to_ascii_lowercase();
+        Level::iter()
+            .map(|l| (edit_distance(&s, &l.to_string()), l))
+            .filter(|&(d, _)| d <= 2)
+            .min_by_key(|&(d, _)| d)
+            .map(|(_, l)| l)
     }
 
     pub fn debug_level(id: u8) -> Self {
@@ -153,6 +216,21 @@ impl Level {
     }
 }
 
+// Levenshtein distance of two short ASCII strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.bytes().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let subst = prev[j] + usize::from(ca != cb);
+            cur[j + 1] = subst.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
 #[cfg(test)]
 mod level_tests {
     use crate::level::Level;
@@ -181,6 +259,35 @@ mod level_tests {
         assert_eq!("debug9".parse::<Level>(), Ok(Level::Debug9));
         assert_eq!("debug10".parse::<Level>(), Ok(Level::Debug10));
         assert_eq!("debug11".parse::<Level>(), Err(UnknownLogLevel));
+        assert_eq!("debug0".parse::<Level>(), Err(UnknownLogLevel));
+        assert_eq!("off".parse::<Level>(), Ok(Level::Off));
+    }
+    #[test]
+    fn test_level_from_str_syslog() {
+        assert_eq!("debug".parse::<Level>(), Ok(Level::Debug1));
+        assert_eq!("DEBUG".parse::<Level>(), Ok(Level::Debug1));
+        assert_eq!("panic".parse::<Level>(), Ok(Level::Emerg));
+        assert_eq!("Err".parse::<Level>(), Ok(Level::Error));
+        assert_eq!("warning".parse::<Level>(), Err(UnknownLogLevel));
+        let severities = (0..=7).map(|n| n.to_string().parse::<Level>()).collect::<Vec<_>>();
+        assert_eq!(severities, [
+            Ok(Level::Emerg), Ok(Level::Alert), Ok(Level::Crit), Ok(Level::Error),
+            Ok(Level::Warn), Ok(Level::Notice), Ok(Level::Info), Ok(Level::Debug1),
+        ]);
+        // no discriminant mapping, no numbers beyond the syslog severities
+        for n in ["8", "10", "18", "-1", "+1", "07", " 6", "6.0"] {
+            assert_eq!(n.parse::<Level>(), Err(UnknownLogLevel), "{}", n);
+        }
+    }
+    #[test]
+    fn test_level_suggest() {
+        assert_eq!(Level::suggest("debgu1"), Some(Level::Debug1));
+        assert_eq!(Level::suggest("Debug11"), Some(Level::Debug1));
+        assert_eq!(Level::suggest("inf"), Some(Level::Info));
+        assert_eq!(Level::suggest("wran"), Some(Level::Warn));
+        assert_eq!(Level::suggest("eror"), Some(Level::Error));
+        assert_eq!(Level::suggest("loud"), None);
+        assert_eq!(Level::suggest(""), None);
     }
     #[test]
     fn test_level_to_str() {
@@ -209,4 +316,20 @@ mod level_tests {
         assert_eq!(Level::iter().nth(6).unwrap_or(Level::Off), Level::Notice);
         assert_eq!(Level::iter().nth(42).unwrap_or(Level::Off), Level::Off);
     }
+    #[test]
+    fn test_level_from_u8() {
+        assert_eq!(Level::from_u8(0), Some(Level::Off));
+        assert_eq!(Level::from_u8(Level::Info as u8), Some(Level::Info));
+        assert_eq!(Level::from_u8(Level::Debug10 as u8), Some(Level::Debug10));
+        assert_eq!(Level::from_u8(Level::Debug10 as u8 + 1), None);
+    }
+
+    #[test]
+    fn test_level_is_enabled() {
+        assert!(Level::Info.is_enabled(Level::Emerg));
+        assert!(Level::Info.is_enabled(Level::Info));
+        assert!(!Level::Info.is_enabled(Level::Debug1));
+        assert!(!Level::Off.is_enabled(Level::Emerg));
+        assert!(Level::iter().all(|l| !l.is_enabled(Level::Off)));
+    }
 }
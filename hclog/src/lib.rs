@@ -92,6 +92,7 @@
 //! # enum AppScope { AA, AB, AC, AD }
 //! # AppScope::init_with_defaults("Keys").unwrap();
 //! # use AppScope::*;
+//! # use hclog::Scope;
 //! hclog::lI!(AA, "This is an info message");
 //! hclog::lD5!(AB, "This is a debug message with level {}", Level::Debug5);
 //! ```
@@ -267,6 +268,7 @@
 //! }
 //!
 //! use ModBKeys::*;
+//! use hclog::Scope;
 //!
 //! fn main() {
 //!    ModBKeys::init_with_defaults("MyLogKeys").unwrap();
@@ -276,22 +278,68 @@
 //! }
 //! ```
 //!
+//! # Performance
+//!
+//! The log macros check the [`Level`] of the `LogKey` before anything else is evaluated. A
+//! call whose level is disabled only reads the context and doesn't allocate, neither
+//! the format arguments nor the caller location are evaluated. An enabled message allocates
+//! for the function path and the formatted arguments, a message without arguments is
+//! borrowed. The rendered line is written to the facade without another copy, except for
+//! syslog. Both properties are covered by the tests and the `iai` benchmarks of the
+//! repository.
+//!
 //! # Warning
 //!
-//! The library internal context may be init only once.
+//! The library internal context may be init only once. Calling [`Scope::init`] again on an
+//! initialized scope keeps the existing scope, use [`reinitialize`] to start over.
 //!
 //! # Crate Feature Flags
 //!
 //! The following feature flags are available for this crate. They are configured in your Cargo.toml:
 //!
 //! * `std`: Enabled by default. This flag does not enable any additional features.
-//! * `derive`: This flag enables the derive macro for the `HCLog` trait.
+//! * `derive`: This flag enables the derive macro for the `HCLog` trait and re-exports the
+//!   `define_logging!` macro, which defines the LogKeys and the initialization of an
+//!   application in one item.
+//! * `single-thread`: Replaces the `RwLock` protected global context with a `RefCell` based one
+//!   and drops the `Send + Sync` requirement of the internal log facades. This avoids the
+//!   locking overhead in small single threaded tools. See [Single-thread mode](#single-thread-mode) below.
+//! * `test-util`: Enables helpers like `fail_next_writes` to inject write failures into the
+//!   facades of single LogKeys for robustness testing and the [`testing`] module to assert the
+//!   log output in tests or to replace the clock via `set_clock`. Not intended for production
+//!   builds.
+//! * `net-syslog`: Adds the `SyslogUdp` and `SyslogTcp` variants of [`FacadeVariant`] to send
+//!   the messages to a remote syslog collector.
+//! * `flush-at-exit`: Enabled by default (unix only). The first `File` facade registers an
+//!   `atexit` handler which flushes all facades, so messages held back by a [`FlushPolicy`]
+//!   aren't lost if the application exits without calling [`flush`]. Disable the default
+//!   features to not register the handler.
 //!
 //! ```toml
 //! [dependencies]
 //! hclog = { version = "0.1", features = ["derive"] }
 //! ```
 //!
+//! ## Single-thread mode
+//!
+//! With the `single-thread` feature enabled the context is owned by the thread that initializes
+//! the library. The API and the macros are identical to the default build, but the context is
+//! not shared across threads: logging from any other thread behaves as if no [`Scope`] was
+//! initialized and returns [`ScopeNotInitialized`](ErrorKind::ScopeNotInitialized). The
+//! initialization functions like [`init`] or [`Scope::init`] must therefore be called on the
+//! thread which does the logging. Concurrent access from within the same thread, e.g. a log
+//! call from a [`Display`] implementation while the context is modified, returns
+//! [`ContextLock`](ErrorKind::ContextLock) instead of blocking. The context of a thread is
+//! dropped when the thread exits, which flushes and closes its facades. A small cell per thread
+//! that used the library is never freed. The settings shared by all Scopes, like the base of
+//! [`reset_uptime_base`], the [`set_prefix_separator`] and the [`add_file_prefix_strip`] list,
+//! belong to the thread as well.
+//!
+//! ```toml
+//! [dependencies]
+//! hclog = { version = "0.1", features = ["single-thread"] }
+//! ```
+//!
 //! # Version compatibility
 //!
 //! This crate is currently compatible with Rust 1.74.1 and later. We will try to keep this
@@ -318,22 +366,33 @@ pub use crate::error::{Result, ErrorKind};
 #[doc(hidden)]
 pub mod macros;
 
+// used by the code the macros expand to only, not part of the API
+#[doc(hidden)]
+pub mod __private {
+    // the (replaceable) clock read by lI_timed!
+    pub fn instant() -> std::time::Instant {
+        crate::clock::instant()
+    }
+}
+
 mod log_internal;
 pub use crate::log_internal::InternalLogKeys;
 
 #[doc(hidden)]
 mod context;
+#[doc(inline)]
+pub use crate::context::ScopeSnapshot;
 
 mod logmod;
 #[doc(inline)]
-pub use crate::logmod::ScopeKey;
+pub use crate::logmod::{ScopeKey, MergePolicy, Provenance, Source};
 
 #[doc(hidden)]
 mod submodule;
 
 mod facades;
 #[doc(inline)]
-pub use crate::facades::FacadeVariant;
+pub use crate::facades::{FacadeVariant, FlushPolicy, Newline};
 
 mod level;
 #[doc(inline)]
@@ -342,6 +401,8 @@ pub use crate::level::Level;
 #[doc(hidden)]
 mod message;
 
+mod clock;
+
 pub mod options;
 
 #[doc(hidden)]
@@ -350,22 +411,69 @@ mod compat;
 #[doc(hidden)]
 mod task;
 
+mod subscription;
+#[doc(inline)]
+pub use crate::subscription::Subscription;
+
+mod validate;
+#[doc(inline)]
+pub use crate::validate::{ConfigWarning, ConfigWarningKind, KeyMismatch, KeyMismatchKind};
+
+mod config;
+#[doc(inline)]
+pub use crate::config::ConfigReport;
+
+mod metadata;
+#[doc(inline)]
+pub use crate::metadata::MetadataFormat;
+
+mod severe;
+#[doc(inline)]
+pub use crate::severe::{SevereHandler, SevereHandlerId};
+
 mod api;
 #[doc(inline)]
 pub use crate::api::*;
 
-#[doc(hidden)]
-mod util;
+#[cfg(feature = "derive")]
+#[doc(inline)]
+pub use hclog_macros::define_logging;
+
+pub mod env;
+
+pub mod fmt;
+
+pub mod binlog;
+
+#[cfg(feature = "net-syslog")]
+mod netsyslog;
+
+#[cfg(all(unix, feature = "flush-at-exit"))]
+mod atexit;
+
+#[cfg(feature = "test-util")]
+pub mod testing;
+
+#[cfg(feature = "capi")]
+pub mod capi;
 
 // library internal imports
 use crate::options::*;
 
 /// Alias for the Index of the Scope in the context
 ///
-/// This demands that passed [`LogKey`] implementors are `#[repr(usize)]` which is
-/// currently the default.
+/// The index of a [`LogKey`] is its enum discriminant cast to `usize`. The derive macro
+/// accepts enums without `#[repr]` or with `#[repr(u8)]`, `#[repr(u16)]`, `#[repr(u32)]` or
+/// `#[repr(usize)]`, whose discriminants must ascend from `0` without gaps. A [`LogKey`]
+/// implemented by hand has to keep the same contract, e.g. with `*self as usize`.
 pub type ContextKey = usize;
 
+/// Maximum number of [`LogKey`]s per [`Scope`]
+///
+/// The index returned by [`LogKey::log_key`] must be lower than this value. Adding a
+/// [`LogKey`] with a higher index fails with [`KeyNotInitialized`](ErrorKind::KeyNotInitialized).
+pub const MAX_SUBMODULES: ContextKey = 4096;
+
 /// Initialization trait for the log scope
 ///
 /// Trait to initialize to library which is implemented on a set of [`LogKey`]s. It can
@@ -437,11 +545,45 @@ pub type ContextKey = usize;
 /// }
 /// ```
 ///
+/// ### Multiple `LogKey` types in one `Scope`
+///
+/// Calling `init` on a second type with the same [`ScopeKey`] does not create a new [`Scope`].
+/// The name and the defaults of the first initialization are kept and only the [`LogKey`]s of
+/// the second type are added. Since the index of a [`LogKey`] is its enum discriminant, the
+/// keys of both types would share the same indices. Adding a [`LogKey`] to an index which is
+/// used by a [`LogKey`] with a different name fails with [`KeyCollision`](ErrorKind::KeyCollision)
+/// by default. The derive macro allows to choose a different [`MergePolicy`] via the `merge`
+/// attribute, where `merge = "append"` places the keys behind the already initialized ones.
+///
+/// ```rust
+/// use hclog::Scope;
+/// use hclog_macros::HCLog;
+///
+/// mod lib_keys {
+///     # use hclog_macros::HCLog;
+///     #[derive(HCLog, Copy, Clone)]
+///     #[hclog(merge = "append")]
+///     pub enum LibKeys { Db, Net }
+/// }
+///
+/// #[derive(HCLog, Copy, Clone)]
+/// #[hclog(merge = "append")]
+/// enum AppKeys { Main }
+///
+/// AppKeys::init_with_defaults("app").unwrap();
+/// lib_keys::LibKeys::init_with_defaults("lib").unwrap();
+/// use lib_keys::LibKeys::Net;
+/// hclog::lI!(Net, "logged to index 2");
+/// ```
+///
+/// Initializing the same type twice is a noop.
+///
 /// # Errors
 ///
 /// The initialzation of the log scope can fail if:
 /// - the log scope is already initialized
 /// - the initialization of the log scope fails
+/// - a [`LogKey`] collides with a [`LogKey`] of a different type in the same scope
 /// - the [`LOGCOMPAT`] option is set and the initialization of the
 ///   compatibility logger fails
 ///
@@ -471,11 +613,30 @@ pub trait Scope: Send + Sync + Copy {
             Self::default_options())
     }
 
+    /// Initialize the log scope again from scratch
+    ///
+    /// **Destructive**: all [`LogKey`]s of the scope and their runtime changes are replaced by
+    /// the ones [`init`] creates. See [`reinitialize`](fn@crate::reinitialize).
+    fn reinitialize<S: Display>(
+        name: S, level: Level, facade: FacadeVariant, options: Options
+    ) -> Result<()> {
+        crate::api::reinitialize::<Self, S>(name, level, facade, options)
+    }
+
+    /// Shortcut to [`reinitialize`](Scope::reinitialize) with the values of the `default_*`
+    /// functions, like [`init_with_defaults`].
+    fn reinitialize_with_defaults<S: Display>(name: S) -> Result<()> {
+        Self::reinitialize(name, Self::default_level(), Self::default_facade(),
+            Self::default_options())
+    }
+
     /// Returns the [`ScopeKey`] which is used to access the [`LogKey`]s in the context.
     fn logscope() -> ScopeKey { ScopeKey::default() }
     /// default [`Options`] for the log scope
     ///
-    /// if no Options are defined the [`Options::default`] is used.
+    /// if no Options are defined the [`Options::default`] is used. The derive macro returns
+    /// [`Options::auto_for`] the [`default_facade`](Scope::default_facade) with the
+    /// `auto_options` attribute.
     fn default_options() -> Options { Options::default() }
     /// default [`FacadeVariant`] for the log scope
     ///
@@ -485,6 +646,16 @@ pub trait Scope: Send + Sync + Copy {
     ///
     /// if no Level is defined the [`Level::default`] is used.
     fn default_level() -> Level { Level::default() }
+    /// Normalize a [`Level`] assigned by name to a [`LogKey`] of this scope
+    ///
+    /// Applied whenever a level is assigned without naming the [`LogKey`] type, like via
+    /// [`set_mod_level`] (including `_all`) or the `HCLOG_LEVEL` environment variable. This
+    /// allows a scope to clamp or rescale requested levels, e.g. if its `Debug3` is as chatty
+    /// as `Debug8` of other scopes. Explicit calls to [`set_level`] are not normalized.
+    ///
+    /// The default is the identity. The derive macro generates a clamping implementation
+    /// with the `max_level` attribute.
+    fn normalize_level(level: &Level) -> Level { *level }
 }
 
 /// Trait for the LogKey
@@ -511,6 +682,7 @@ pub trait Scope: Send + Sync + Copy {
 /// enum MyLogKeys { LA }
 ///
 /// use MyLogKeys::LA;
+/// # use hclog::Scope;
 ///
 /// # MyLogKeys::init_with_defaults("MyLogKeys").unwrap();
 /// let _ = hclog::set_level(LA, Level::Emerg);
@@ -581,6 +753,12 @@ pub trait LogKey: Scope + Display {
     /// Returns the Index key associated with the `LogKey` variant
     ///
     /// Since this trait should be usually implemented on enums this will be the enum discriminant
+    /// (see [`ContextKey`]). It must return the same index for the same `LogKey` every time.
+    ///
+    /// # Warning
+    ///
+    /// The returned value is used as an index into a `Vec` and must be a small zero-based
+    /// index below [`MAX_SUBMODULES`]. Gaps between the indices are filled with unused entries.
     fn log_key(&self) -> ContextKey;
 
     /// Initial [`Level`] of the LogKey
@@ -591,8 +769,63 @@ pub trait LogKey: Scope + Display {
     ///
     /// If no FacadeVariant is defined the [`Scope::default_facade`] is used.
     fn init_facade(&self) -> Option<FacadeVariant> { None }
+    /// Whether the LogKey is exempt from `_all` on initialization
+    ///
+    /// An exempt LogKey keeps its level when the level of all LogKeys is changed via `_all` or
+    /// the `HCLOG_LEVEL` environment variable, see [`set_all_exempt`].
+    fn init_all_exempt(&self) -> bool { false }
+    /// Description of the LogKey
+    ///
+    /// Listed by [`export_metadata`], e.g. to generate the documentation of the LogKeys.
+    fn description(&self) -> Option<&'static str> { None }
     // init_options is reserved right now and not derived automaticaly
     #[doc(hidden)]
     fn init_options(&self) -> Option<Options> { None }
+    // the identifier of the variant, derived for the hints of the panics of the log macros
+    #[doc(hidden)]
+    fn variant_name(&self) -> Option<&'static str> { None }
+}
+
+/// Conversion of the items passed to [`add_submodules`] and [`init_modules`] into a [`LogKey`]
+///
+/// This is implemented for references to any [`LogKey`], so slices of keys can be passed. The
+/// derive macro `HCLog` also implements it for the type itself, which allows to pass owned keys,
+/// e.g. a `Vec` or a filtered iterator. A `LogKey` implemented by hand needs this impl to be
+/// passed by value:
+///
+/// ```rust
+/// # use hclog::{IntoLogKey, LogKey, Scope, ContextKey, Level, FacadeVariant, Result};
+/// # use hclog::options::Options;
+/// #[derive(Copy, Clone)]
+/// enum MyLogKeys { LA, LB }
+/// # impl std::fmt::Display for MyLogKeys {
+/// #     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { f.write_str("k") }
+/// # }
+/// # impl Scope for MyLogKeys {
+/// #     fn init<S: std::fmt::Display>(_: S, _: Level, _: FacadeVariant, _: Options)
+/// #         -> Result<()> { Ok(()) }
+/// # }
+/// # impl LogKey for MyLogKeys {
+/// #     fn log_key(&self) -> ContextKey { *self as ContextKey }
+/// # }
+///
+/// impl IntoLogKey for MyLogKeys {
+///     type Key = Self;
+///     fn into_log_key(self) -> Self { self }
+/// }
+///
+/// hclog::add_submodules(vec![MyLogKeys::LA, MyLogKeys::LB]);
+/// ```
+pub trait IntoLogKey {
+    /// The [`LogKey`] converted to
+    type Key: LogKey;
+    /// Convert into the [`LogKey`]
+    fn into_log_key(self) -> Self::Key;
+}
+impl<K: LogKey> IntoLogKey for &K {
+    type Key = K;
+    fn into_log_key(self) -> K {
+        *self
+    }
 }
 
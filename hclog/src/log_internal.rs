@@ -3,7 +3,7 @@ use crate::{
     level::Level,
     facades::FacadeVariant,
     options::Options,
-    util::read_var_from_env,
+    env::{self, read_env_var},
     Scope, LogKey, ScopeKey, ContextKey, Result,
 };
 use std::fmt::{self, Display};
@@ -58,21 +58,38 @@ impl Scope for InternalLogKeys {
     ) -> Result<()> {
         let mut level = level;
         let mut facade = facade;
-        if let Ok(Some(f)) = read_var_from_env::<u8>("CLOG_DEBUG") {
+        let debug = match env::ignored() {
+            true => None,
+            false => read_env_var::<u8>("CLOG_DEBUG").ok().flatten(),
+        };
+        if let Some(f) = debug {
             facade = FacadeVariant::StdOut;
             level = Level::debug_level(f);
         }
         {
-            CTX::get_mut()?.init_mod::<Self, S>(name, level, facade, options)?
-                .add_submodule(Self::Internal)?;
+            let mut ctx = CTX::get_mut()?;
+            let fresh = !ctx.has(Self::logscope());
+            let lm = ctx.init_mod::<Self, S>(name, level, facade, options)?;
+            if let Some(f) = debug.filter(|_| fresh) {
+                lm.record_env("CLOG_DEBUG", f.to_string(),
+                    format!("internal messages are written to stdout at {}", level));
+            }
+            lm.add_submodule(Self::Internal)?;
         }
         Ok(())
     }
 }
 impl LogKey for InternalLogKeys {
     fn log_key(&self) -> ContextKey { *self as usize }
+    fn description(&self) -> Option<&'static str> {
+        match *self {
+            Self::Internal => Some("messages of hclog itself"),
+            Self::LogCompat => Some("messages of crate log"),
+        }
+    }
 }
 
+#[cfg(test)]
 pub (crate) mod test {
     use crate::{Scope, LogKey, ContextKey, Result, Level, FacadeVariant, options::Options};
     use std::fmt::{self, Display};
@@ -84,9 +101,11 @@ pub (crate) mod test {
      * context is _not_ overwritten.
      */
     #[derive(Copy, Clone, Debug)]
+    #[allow(clippy::upper_case_acronyms)]
     pub enum TestKeys {
         LIBTESTFOO,
         LIBTESTBAR,
+        LIBTESTREC,
     }
     use TestKeys::*;
     impl LogKey for TestKeys {
@@ -97,7 +116,7 @@ pub (crate) mod test {
             name: S, level: Level, facade: FacadeVariant, options: Options
         ) -> Result<()> {
             crate::init::<Self, S>(name, level, facade, options)?;
-            crate::add_submodules(&[LIBTESTFOO, LIBTESTBAR])
+            crate::add_submodules(&[LIBTESTFOO, LIBTESTBAR, LIBTESTREC])
         }
     }
     impl Display for TestKeys {
@@ -105,6 +124,7 @@ pub (crate) mod test {
             match *self {
                 Self::LIBTESTFOO => write!(f, "libtestfoo"),
                 Self::LIBTESTBAR => write!(f, "libtestbar"),
+                Self::LIBTESTREC => write!(f, "libtestrec"),
             }
         }
     }
@@ -1,13 +1,13 @@
 #![allow(unused_variables)] // TMP
 
 use crate::{
-    options::{self, Options},
-    submodule::Submodule,
+    options::Options,
+    submodule::{Mirror, Mirrored, Submodule},
     facades::FacadeVariant,
     level::Level,
-    ErrorKind::ScopeNotInitialized,
-    Scope, LogKey, Result,
-    util::read_var_from_env,
+    ErrorKind::{ScopeNotInitialized, KeyCollision, KeyNotInitialized, ParentCycle, ParseArg},
+    Scope, LogKey, ContextKey, Result, MAX_SUBMODULES,
+    env::{self, read_env_var, read_level_env, read_facade_env},
 };
 use std::{
     vec::Vec,
@@ -44,6 +44,20 @@ pub enum ScopeKey {
     #[doc(hidden)]
     MAX,    // must remain last (for slice initialization)
 }
+impl ScopeKey {
+    /// Get the `ScopeKey` for a given index
+    ///
+    /// Returns `None` if the index doesn't belong to a usable `ScopeKey`. This is a `const fn`
+    /// to allow the derive macro to check the `scope_key` attribute at compile time.
+    pub const fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Self::Application),
+            1 => Some(Self::CLog),
+            2 => Some(Self::Lib),
+            _ => None,
+        }
+    }
+}
 impl Display for ScopeKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -55,6 +69,72 @@ impl Display for ScopeKey {
     }
 }
 
+/// Behavior when adding a [`LogKey`] to an index which is already in use
+///
+/// Multiple [`LogKey`] types can be initialized in the same [`Scope`]. Since the index of a
+/// [`LogKey`] is usually its enum discriminant, the keys of different types share the same index
+/// space. The policy decides what happens if an index is already used by a [`LogKey`] with a
+/// different name. It's used with
+/// [`add_submodules_with`](fn@crate::add_submodules_with) or the `merge` attribute of the
+/// `HCLog` derive macro.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum MergePolicy {
+    /// Fail with [`KeyCollision`](crate::ErrorKind::KeyCollision) (default)
+    #[default]
+    Error,
+    /// Keep the already initialized [`LogKey`] and silently ignore the new one
+    Skip,
+    /// The [`LogKey`]s are placed behind the already initialized ones
+    ///
+    /// The offset must be applied by the [`LogKey::log_key`] implementation, which is done by
+    /// the derive macro. A collision is still reported as
+    /// [`KeyCollision`](crate::ErrorKind::KeyCollision).
+    Append,
+}
+
+/// Origin of a configured value of a [`LogKey`]
+///
+/// Reported by [`provenance`](fn@crate::provenance) and
+/// [`list_modules_detailed`](fn@crate::list_modules_detailed) to find out why a
+/// [`LogKey`] logs with a certain level, facade or options.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Source {
+    /// Passed to the initialization or set via the attributes of the derive macro (default)
+    #[default]
+    Code,
+    /// Taken from an environment variable like `HCLOG_LEVEL`, `HCLOG_FACADE` or `HCLOG_OPT_*`
+    Env,
+    /// Set via [`set_mod_level_cli`](fn@crate::set_mod_level_cli), e.g. from a commandline
+    /// argument
+    Cli,
+    /// Changed by one of the setters at runtime, e.g. [`set_level`](fn@crate::set_level)
+    Runtime,
+}
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Code => f.write_str("code"),
+            Self::Env => f.write_str("env"),
+            Self::Cli => f.write_str("cli"),
+            Self::Runtime => f.write_str("runtime"),
+        }
+    }
+}
+
+/// The [`Source`] of the level, facade and options of a [`LogKey`]
+///
+/// The level of a [`LogKey`] inheriting its level from a parent (see
+/// [`set_parent`](fn@crate::set_parent)) reports the [`Source`] of the inherited level.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Provenance {
+    /// Where the level came from
+    pub level: Source,
+    /// Where the facade came from
+    pub facade: Source,
+    /// Where the options came from
+    pub options: Source,
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub (crate) enum ScopeEnv {
     #[default]
@@ -77,18 +157,30 @@ impl fmt::Display for ScopeEnv {
  * Submodule on initialization if the caller does not provide any. The Submodule
  * options itself can be changed any time
  */
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub (crate) struct LogScope {
     name: String,
     lm: ScopeKey,
     env: ScopeEnv,
     env_ident: Option<String>,
+    // written with the INSTANCE option, kept by the task local copies
+    instance_id: Option<String>,
+    // set via set_func_max_len, kept by the task local copies
+    func_max_len: Option<usize>,
     initialized: bool,
     submodules: Vec<Submodule>,
     // defaults passed down to submodules on init (if not given)
     default_options: Options,
     default_facade: FacadeVariant,
     default_level: Level,
+    // the level passed to init, LogKeys exempt from `_all` ignore HCLOG_LEVEL
+    init_level: Level,
+    // whether the defaults were taken from the environment
+    default_provenance: Provenance,
+    // (variable, value, effect) of every variable which changed something on init
+    env_overrides: Vec<(String, String, String)>,
+    // Scope::normalize_level of the implementing type
+    normalize: Option<fn(&Level) -> Level>,
 }
 impl<K> Index<K> for LogScope where K: LogKey {
     type Output = Submodule;
@@ -102,29 +194,101 @@ impl<K> IndexMut<K> for LogScope where K: LogKey {
     }
 }
 
+/*
+ * names of Scopes and LogKeys without the surrounding whitespace
+ *
+ * An empty name can't be addressed by name (e.g. via set_mod_level) and leaves an empty token
+ * in the output, so it's rejected. A nul byte can't be passed to openlog(3) as syslog ident.
+ */
+pub (crate) fn valid_name(name: impl Display) -> Result<String> {
+    let name = name.to_string();
+    match name.trim() {
+        "" => Err(ParseArg),
+        _ if name.contains('\0') => Err(ParseArg),
+        trimmed if trimmed.len() == name.len() => Ok(name),
+        trimmed => Ok(trimmed.to_owned()),
+    }
+}
+
+/*
+ * instance ids are written within the brackets of pid and tid, so the separator and the
+ * brackets would make the prefix ambiguous
+ */
+pub (crate) fn valid_instance_id(id: &str) -> Result<String> {
+    let id = valid_name(id)?;
+    if id.contains(|c: char| c == '/' || c == '[' || c == ']' || c.is_whitespace()) {
+        return Err(ParseArg);
+    }
+    Ok(id)
+}
+
+// nothing is read if the environment is ignored, invalid values are ignored as well
+fn env_value<T>(read: impl FnOnce(&str) -> Result<Option<T>>, var: &str) -> Option<T> {
+    match env::ignored() {
+        true => None,
+        false => read(var).ok().flatten(),
+    }
+}
+
 impl LogScope {
     pub (crate) fn init<I: Scope, S: Display>(
         name: S, level: Level, facade: FacadeVariant, options: Options
     ) -> Result<Self> {
+        let name = valid_name(name)?;
+        let mut default_provenance = Provenance::default();
+        let mut env_overrides = Vec::new();
+        // the internal scope is named like the application
+        let scope = format!("{} ({})", name, I::logscope());
+        let mut record = |var: &str, effect: String| {
+            let value = std::env::var(var).unwrap_or_default();
+            env_overrides.push((var.to_owned(), value, effect));
+        };
         let mut default_level = level;
-        if let Ok(Some(l)) = read_var_from_env::<Level>(options::ENV_OPT_LEVEL) {
-            default_level = l;
+        if let Some(l) = env_value(read_level_env, env::ENV_OPT_LEVEL) {
+            default_level = I::normalize_level(&l);
+            default_provenance.level = Source::Env;
+            if default_level != level {
+                record(env::ENV_OPT_LEVEL, format!("default level of scope {} is {} \
+                    instead of {}", scope, default_level, level));
+            }
         };
         let mut default_facade = facade;
-        if let Ok(Some(f)) = read_var_from_env::<FacadeVariant>(options::ENV_OPT_FACADE) {
+        if let Some(f) = env_value(read_facade_env, env::ENV_OPT_FACADE) {
+            if format!("{:?}", f) != format!("{:?}", default_facade) {
+                record(env::ENV_OPT_FACADE, format!("default facade of scope {} is {:?} \
+                    instead of {:?}", scope, f, default_facade));
+            }
             default_facade = f;
+            default_provenance.facade = Source::Env;
         }
         let mut default_options = options;
-        default_options.parse_from_env()?;
+        for (key, value) in default_options.apply_env()? {
+            let state = if value == 0 { "disabled" } else { "enabled" };
+            record(&format!("{}{}", env::ENV_OPT_PREFIX, key),
+                format!("option {} of scope {} {}", key, scope, state));
+        }
+        if default_options != options {
+            default_provenance.options = Source::Env;
+        }
+        let instance_id = env_value(read_env_var::<String>, env::ENV_INSTANCE_ID)
+            .and_then(|id| valid_instance_id(&id).ok());
+        if let Some(ref id) = instance_id {
+            record(env::ENV_INSTANCE_ID, format!("instance id of scope {} is {}", scope, id));
+        }
 
         Ok(Self {
-            name: name.to_string(),
+            name,
             lm: I::logscope(),
             env: ScopeEnv::Global,
+            instance_id,
             initialized: true,
             default_options,
             default_facade,
             default_level,
+            init_level: level,
+            default_provenance,
+            env_overrides,
+            normalize: Some(I::normalize_level),
             ..Default::default()
         })
     }
@@ -132,12 +296,18 @@ impl LogScope {
         Self {
             env: ScopeEnv::Task,
             env_ident: Some(ident.to_string()),
+            instance_id: self.instance_id.clone(),
+            func_max_len: self.func_max_len,
             name: self.name.clone(),
             lm: self.lm,
             initialized: self.initialized,
             default_options: self.default_options,
             default_facade: self.default_facade.clone(),
             default_level: self.default_level,
+            init_level: self.init_level,
+            default_provenance: self.default_provenance,
+            env_overrides: self.env_overrides.clone(),
+            normalize: self.normalize,
             submodules: self.submodules.clone(),
         }
     }
@@ -147,15 +317,42 @@ impl LogScope {
     pub (crate) fn name(&self) -> &str {
         &self.name
     }
+    pub (crate) fn env_overrides(&self) -> &[(String, String, String)] {
+        &self.env_overrides
+    }
+    pub (crate) fn record_env(&mut self, var: &str, value: String, effect: String) {
+        self.env_overrides.push((var.to_owned(), value, effect));
+    }
     pub (crate) fn key(&self) -> ScopeKey {
         self.lm
     }
+    pub (crate) fn default_level(&self) -> Level {
+        self.default_level
+    }
+    pub (crate) fn default_options(&self) -> Options {
+        self.default_options
+    }
+    pub (crate) fn default_facade(&self) -> &FacadeVariant {
+        &self.default_facade
+    }
     pub (crate) fn env(&self) -> ScopeEnv {
         self.env
     }
     pub (crate) fn env_ident(&self) -> Option<&str> {
         self.env_ident.as_deref()
     }
+    pub (crate) fn instance_id(&self) -> Option<&str> {
+        self.instance_id.as_deref()
+    }
+    pub (crate) fn set_instance_id(&mut self, id: String) {
+        self.instance_id = Some(id);
+    }
+    pub (crate) fn func_max_len(&self) -> Option<usize> {
+        self.func_max_len
+    }
+    pub (crate) fn set_func_max_len(&mut self, max: Option<usize>) {
+        self.func_max_len = max;
+    }
     pub (crate) fn has_submodule<K: LogKey>(&self, key: K) -> bool {
         let ckey = key.log_key();
         if !self.initialized || self.submodules.len() <= ckey {
@@ -169,13 +366,42 @@ impl LogScope {
     }
     pub (crate) fn add_submodule<K: LogKey>(&mut self, submod: K)
         -> Result<&mut Submodule>
+    {
+        self.merge_submodule(submod, MergePolicy::Error)
+    }
+    pub (crate) fn merge_submodule<K: LogKey>(&mut self, submod: K, policy: MergePolicy)
+        -> Result<&mut Submodule>
     {
         if !self.initialized {
             return Err(ScopeNotInitialized);
         }
-        let level = submod.init_level().unwrap_or(self.default_level);
+        // the index is used to pad the Vec - refuse huge allocations
+        if submod.log_key() >= MAX_SUBMODULES {
+            return Err(KeyNotInitialized);
+        }
+        let name = valid_name(submod)?;
+        let level = submod.init_level().unwrap_or(match submod.init_all_exempt() {
+            true => self.init_level,
+            false => self.default_level,
+        });
         let facade = submod.init_facade().unwrap_or(self.default_facade.clone());
         let opts = submod.init_options().unwrap_or(self.default_options);
+        let from_code = |code: bool, default: Source| if code { Source::Code } else { default };
+        let provenance = Provenance {
+            level: from_code(submod.init_level().is_some() || submod.init_all_exempt(),
+                self.default_provenance.level),
+            facade: from_code(submod.init_facade().is_some(), self.default_provenance.facade),
+            options: from_code(submod.init_options().is_some(), self.default_provenance.options),
+        };
+        // another index with a name only differing by case couldn't be addressed by name
+        let case_variant = self.submodules.iter().position(|sub| sub.initialized()
+            && sub.key() != submod.log_key() && sub.name_matches(&name) && sub.name() != name);
+        if let Some(existing) = case_variant {
+            return match policy {
+                MergePolicy::Skip => Ok(&mut self.submodules[existing]),
+                _ => Err(KeyCollision),
+            };
+        }
         match self.submodules.get_mut(submod.log_key()) {
             Some(sub) => {
                 // in case the added module was not initialized and is later
@@ -183,7 +409,16 @@ impl LogScope {
                 //
                 // silently ignore if the module is already initialized
                 if !sub.initialized() {
-                    *sub = Submodule::new(submod, level, &facade, opts);
+                    *sub = Submodule::new(submod, &name, level, &facade, opts, &self.name);
+                    sub.set_provenance(provenance);
+                } else {
+                    // a LogKey implemented by hand has to return the same index every time
+                    debug_assert_eq!(sub.key(), submod.log_key(),
+                        "log_key() of LogKey '{}' doesn't match its index in the scope", name);
+                    if !sub.name_matches(&name) && policy != MergePolicy::Skip {
+                        // the index is used by a LogKey of a different type
+                        return Err(KeyCollision);
+                    }
                 }
             }
             None => {
@@ -194,11 +429,33 @@ impl LogScope {
                         self.submodules.push(Submodule::default());
                     }
                 }
-                self.submodules.push(Submodule::new(submod, level, &facade, opts));
+                let mut new = Submodule::new(submod, &name, level, &facade, opts, &self.name);
+                new.set_provenance(provenance);
+                self.submodules.push(new);
             }
         }
         Ok(&mut self.submodules[submod.log_key()])
     }
+    // take over `other` if this scope isn't initialized, otherwise the keys missing here
+    pub (crate) fn adopt(&mut self, other: LogScope) {
+        if !self.initialized {
+            *self = other;
+            return;
+        }
+        for submod in other.submodules.into_iter().filter(|s| s.initialized()) {
+            let key = submod.key();
+            if self.submodules.get(key).is_some_and(|s| s.initialized()) {
+                continue;
+            }
+            if self.submodules.len() <= key {
+                self.submodules.resize_with(key + 1, Submodule::default);
+            }
+            self.submodules[key] = submod;
+        }
+    }
+    pub (crate) fn submodule_count(&self) -> usize {
+        self.submodules.len()
+    }
     pub (crate) fn submodules(&self) -> impl Iterator<Item = &Submodule> {
         self.submodules.iter()
     }
@@ -211,10 +468,143 @@ impl LogScope {
     pub (crate) fn get_submodule_mut<K: LogKey>(&mut self, key: K) -> Option<&mut Submodule> {
         self.submodules.get_mut(key.log_key())
     }
-    pub (crate) fn get_submod_by_name(&mut self, key: &str) -> Option<&mut Submodule> {
-        if self.submodules.is_empty() {
-            return None;
+    // keys of all initialized submodules with the given name (case-insensitive)
+    pub (crate) fn get_submods_by_name<'a>(&'a self, name: &'a str)
+        -> impl Iterator<Item = ContextKey> + 'a
+    {
+        self.submodules.iter()
+            .filter(move |submod| submod.initialized() && submod.name_matches(name))
+            .map(|submod| submod.key())
+    }
+    pub (crate) fn get_submodule_at(&self, key: ContextKey) -> Option<&Submodule> {
+        self.submodules.get(key)
+    }
+    pub (crate) fn get_submodule_at_mut(&mut self, key: ContextKey) -> Option<&mut Submodule> {
+        self.submodules.get_mut(key)
+    }
+    // the part of the configuration of `key` copied to its alias targets, an inherited level
+    // is resolved via the parents
+    pub (crate) fn mirrored(&self, key: ContextKey, what: Mirror) -> Option<Mirrored> {
+        let submod = self.submodules.get(key)?;
+        let mut mirrored = submod.mirrored(what);
+        if let Mirrored::Level { ref mut level, ref mut source, .. } = mirrored {
+            *level = self.effective_level(submod);
+            *source = self.provenance(submod).level;
+        }
+        Some(mirrored)
+    }
+    // apply the configuration of an alias source to `key` if initialized and kept, the level
+    // is normalized like a level set by name
+    pub (crate) fn mirror(
+        &mut self, key: ContextKey, src: &Mirrored, keep: impl Fn(&Submodule) -> bool,
+    ) {
+        let normalize = self.normalize;
+        if let Some(dst) = self.submodules.get_mut(key).filter(|s| s.initialized() && keep(s)) {
+            dst.mirror(src, |level| normalize.map_or(level, |normalize| normalize(&level)));
+        }
+    }
+    // applied to all levels set by name (set_mod_level, HCLOG_LEVEL)
+    pub (crate) fn normalize_level(&self, level: Level) -> Level {
+        self.normalize.map_or(level, |normalize| normalize(&level))
+    }
+    pub (crate) fn set_parent<K: LogKey>(&mut self, child: K, parent: K) -> Result<()> {
+        if !self.has_submodule(child) || !self.has_submodule(parent) {
+            return Err(KeyNotInitialized);
+        }
+        // walk up from the new parent - meeting the child again would close a cycle
+        let mut next = Some(parent.log_key());
+        while let Some(key) = next {
+            if key == child.log_key() {
+                return Err(ParentCycle);
+            }
+            next = self.submodules[key].parent();
+        }
+        self[child].set_parent(Some(parent.log_key()));
+        Ok(())
+    }
+    // the first submodule up the hierarchy with an explicitly set level
+    fn level_origin<'a>(&'a self, submod: &'a Submodule) -> &'a Submodule {
+        let mut cur = submod;
+        while let (false, Some(parent)) = (cur.explicit_level(), cur.parent()) {
+            cur = &self.submodules[parent];
+        }
+        cur
+    }
+    pub (crate) fn effective_level(&self, submod: &Submodule) -> Level {
+        self.level_origin(submod).logsev()
+    }
+    // an inherited level reports the source of the level of the parent
+    pub (crate) fn provenance(&self, submod: &Submodule) -> Provenance {
+        Provenance { level: self.level_origin(submod).provenance().level, ..submod.provenance() }
+    }
+    pub (crate) fn will_log(&self, submod: &Submodule, logsev: Level) -> bool {
+        if submod.explicit_level() || submod.parent().is_none() {
+            return submod.will_log(logsev);
+        }
+        submod.will_log_at(self.effective_level(submod), logsev)
+    }
+    fn is_descendant(&self, key: ContextKey, ancestor: ContextKey) -> bool {
+        let mut next = self.submodules[key].parent();
+        while let Some(parent) = next {
+            if parent == ancestor {
+                return true;
+            }
+            next = self.submodules[parent].parent();
         }
-        self.submodules.iter_mut().find(|submod| submod.name() == key)
+        false
+    }
+    // set the level of root and let all its descendants inherit it, returns the changed keys
+    pub (crate) fn set_subtree_level(&mut self, root: ContextKey, level: Level)
+        -> Vec<ContextKey>
+    {
+        let mut changed = Vec::new();
+        for key in 0..self.submodules.len() {
+            if key == root {
+                self.submodules[key].set_logsev(level);
+            } else if self.is_descendant(key, root) {
+                self.submodules[key].inherit_level();
+            } else {
+                continue;
+            }
+            changed.push(key);
+        }
+        changed
+    }
+    // (un)mute the key `root` and all its descendants
+    pub (crate) fn set_subtree_muted(&mut self, root: ContextKey, muted: bool)
+        -> Vec<ContextKey>
+    {
+        let keys = (0..self.submodules.len())
+            .filter(|&key| key == root || self.is_descendant(key, root))
+            .collect::<Vec<_>>();
+        for &key in &keys {
+            self.submodules[key].set_muted(muted);
+        }
+        keys
+    }
+    // emit the pending repeat counters of all submodules, a failing submodule doesn't keep the
+    // others from flushing. Returns the first error.
+    pub (crate) fn flush(&self) -> Result<()> {
+        let mut res = Ok(());
+        for submod in self.submodules.iter().filter(|s| s.initialized()) {
+            let flushed = submod.flush(
+                &self.name, self.env, self.env_ident(), self.instance_id(), self.func_max_len,
+            );
+            if res.is_ok() {
+                res = flushed;
+            }
+        }
+        res
+    }
+    // like flush but skip the submodules locked by another writer, false if any was skipped.
+    // Errors are ignored, only used at exit.
+    #[cfg(all(unix, feature = "flush-at-exit"))]
+    pub (crate) fn try_flush(&self) -> bool {
+        self.submodules.iter().filter(|s| s.initialized()).fold(true, |done, submod| {
+            let flushed = submod.try_flush(
+                &self.name, self.env, self.env_ident(), self.instance_id(), self.func_max_len,
+            );
+            !matches!(flushed, Ok(false)) && done
+        })
     }
 }
@@ -13,27 +13,74 @@ macro_rules! fn_name {
     () => {{
         fn hclog_fn() {}
         fn type_name_of<T>(_: T) -> &'static str {
-            std::any::type_name::<T>()
+            ::core::any::type_name::<T>()
         }
+        // skip the segments generated for closures and async blocks/fns, e.g. `{{closure}}`,
+        // so these report the function they are defined in
         type_name_of(hclog_fn)
             .rsplit("::")
-            .find(|&part| part != "hclog_fn" && part != "{{closure}}")
+            .find(|&part| part != "hclog_fn" && !(part.starts_with("{{") && part.ends_with("}}")))
             .unwrap_or("")
     }};
 }
 #[macro_export]
 #[doc(hidden)]
 macro_rules! fn_path {
-    () => {{&format!("{}::{}", std::module_path!(), $crate::fn_name!())}}
+    () => {{&::std::format!("{}::{}", ::core::module_path!(), $crate::fn_name!())}}
 }
 
 #[cfg(doctest)]
 use hclog_macros::HCLog;
 
+/*
+ * fails to compile if $key isn't a variant (or constant) of the LogKey type $kty. The check
+ * is evaluated at compile time only, the expanded log call is the same as without it.
+ */
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __hclog_check_key {
+    ($kty:ty, $key:ident) => {
+        const _: fn() = || {
+            let _: $kty = $key;
+        };
+    };
+}
+
+/*
+ * format_args! with lazily evaluated arguments
+ *
+ * Arguments written as `lazy = || expr` are wrapped into crate::lazy, so the closure only runs
+ * if the message is formatted. All other arguments are passed through unchanged, named ones
+ * included. The arguments are taken one at a time to keep the recursion depth low.
+ */
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __hclog_format {
+    (@[$($out:tt)*] , lazy = || $lazy:expr $(, $($rest:tt)*)?) => {
+        $crate::__hclog_format!(@[$($out)* , $crate::lazy(|| $lazy)] $(, $($rest)*)?)
+    };
+    (@[$($out:tt)*] , $name:ident = $arg:expr $(, $($rest:tt)*)?) => {
+        $crate::__hclog_format!(@[$($out)* , $name = $arg] $(, $($rest)*)?)
+    };
+    (@[$($out:tt)*] , $arg:expr $(, $($rest:tt)*)?) => {
+        $crate::__hclog_format!(@[$($out)* , $arg] $(, $($rest)*)?)
+    };
+    (@[$($out:tt)*] $(,)?) => {
+        ::core::format_args!($($out)*)
+    };
+    ($fmt:literal $($rest:tt)*) => {
+        $crate::__hclog_format!(@[$fmt] $($rest)*)
+    };
+    // e.g. concat!() as format string
+    ($($arg:tt)*) => {
+        ::core::format_args!($($arg)*)
+    };
+}
+
 /*
  * base log macro, needs to be exported to be found by other macros
  *
- * unwrap here to cause a panic in the calling frame with a msg thrown by hclog::api::log
+ * panic here to cause a panic in the calling frame with the msg built by hclog::api::failure_message
  * It's also possible to panic! in log() itself but would add this as a frame in the resulting trace
  *
  */
@@ -47,6 +94,9 @@ use hclog_macros::HCLog;
 /// Additionally it logs the file, line and function name where the log was called from.
 /// File and line are resolved using the rust compiler built-in macros `std::file!()` and
 /// `std::line!()`. The function name is resolved via a macro taken from the [`crate-stdext`] crate.
+/// Closures, `async fn`s and `async` blocks report the function they are written in, no matter
+/// which function calls or polls them. The name is prefixed with the module path only, so
+/// methods don't show the type they're implemented for.
 ///
 /// It is not intended to call this macro directly because its implementation might change. Use the
 /// shortcut macros provided instead:
@@ -68,6 +118,7 @@ use hclog_macros::HCLog;
 /// enum HclogKeys { Foo }
 ///
 /// use HclogKeys::*;
+/// # use hclog::Scope;
 ///
 /// fn main() {
 ///    # HclogKeys::init("foo", Level::Info, FacadeVariant::StdOut, Options::default()).unwrap();
@@ -77,18 +128,141 @@ use hclog_macros::HCLog;
 /// }
 /// ```
 ///
+/// # Checking the type of the key
+///
+/// All macros accept any [`LogKey`](crate::LogKey). After a glob import of the keys of two
+/// crates it's easy to pass the identically named key of the wrong crate, which only shows up
+/// as unexpected filtering at runtime. Prefixing the key with `Scope = <LogKey type>` makes
+/// this a compile error. This works with all log and test macros, e.g.
+/// `lI!(Scope = HclogKeys, Foo, "...")` or `tI!(Scope = HclogKeys, Foo)`. The key must be a
+/// variant or a constant, not a local variable. Apart from the check the expanded code is
+/// the same.
+///
+/// ```rust
+/// # use hclog_macros::HCLog;
+/// # use hclog::{Level, FacadeVariant, options::Options};
+/// # #[derive(HCLog, Copy, Clone)]
+/// # enum HclogKeys { Foo }
+/// # use HclogKeys::*;
+/// # use hclog::Scope;
+/// # fn main() {
+/// #    HclogKeys::init("foo", Level::Info, FacadeVariant::StdOut, Options::default()).unwrap();
+/// hclog::hclog!(Level::Info, Scope = HclogKeys, Foo, "Hello World");
+/// hclog::lI!(Scope = HclogKeys, Foo, "Hello World");
+/// # }
+/// ```
+///
+/// # Lazy arguments
+///
+/// The arguments are only evaluated if the level is enabled. Values computed before the call,
+/// e.g. a `String` serialized from a big struct, are computed in any case. An argument written
+/// as `lazy = || expr` is computed when the message is formatted, that is only if it is
+/// actually logged. The closure must return a value implementing
+/// [`Display`](std::fmt::Display), it's the same as passing [`lazy(|| expr)`](fn@crate::lazy).
+/// Lazy arguments can be mixed with other arguments and are used like positional ones.
+///
+/// ```rust
+/// # use hclog_macros::HCLog;
+/// # use hclog::{Level, FacadeVariant, options::Options};
+/// # #[derive(HCLog, Copy, Clone)]
+/// # enum HclogKeys { Foo }
+/// # use HclogKeys::*;
+/// # use hclog::Scope;
+/// # fn main() {
+/// #    HclogKeys::init("foo", Level::Info, FacadeVariant::StdOut, Options::default()).unwrap();
+/// let state = vec![1, 2, 3];
+/// // the closure isn't called because Debug5 is disabled
+/// hclog::lD5!(Foo, "{} items: {}", state.len(), lazy = || format!("{:?}", state));
+/// # }
+/// ```
+///
+/// A message with the level [`Level::Off`](crate::Level::Off) is never logged, this also
+/// applies to a level computed at runtime.
+///
 /// # Panics
 ///
 /// This macro panics if the given `$key` is not initialized or `$lvl` is not a valid
-/// [`Level`](crate::Level).
+/// [`Level`](crate::Level). The panic message names the `LogKey` and its Scope and hints at
+/// the missing initialization.
 /// See [`init_modules`](crate::init_modules) for more information.
 ///
 macro_rules! hclog {
+    ($lvl:path, Scope = $kty:ty, $key:ident, $($arg:tt)*) => {{
+        $crate::__hclog_check_key!($kty, $key);
+        $crate::hclog!($lvl, $key, $($arg)*)
+    }};
     ($lvl:path, $key:ident, $($arg:tt)*) => {{
+        // everything that costs more than the level check, like the function path which is
+        // formatted into a String and the arguments, is evaluated inside of the branch only.
+        // A disabled call must not allocate, see tests/alloc_count.rs
         if $crate::tX!($key, $lvl) {
-            $crate::log(
-                $key, $lvl, std::file!(), $crate::fn_path!(), std::line!(), &format_args!($($arg)*)
-            ).unwrap();
+            if let ::core::result::Result::Err(e) = $crate::log(
+                $key, $lvl, ::core::file!(), ::core::option::Option::Some($crate::fn_path!()),
+                ::core::line!(), &$crate::__hclog_format!($($arg)*)
+            ) {
+                ::core::panic!("{}", $crate::failure_message($key, e));
+            }
+        }
+    }};
+}
+
+/// Log several messages with the same [`Level`](crate::Level) via `LogKey` at once
+///
+/// Each message in the block is a format string with its arguments, the messages are separated
+/// by `;`. The level of the `LogKey` is checked once for all messages and all messages are
+/// written in order with a single access to the internal context, instead of one access per
+/// message as with repeated calls of [`hclog`](macro@crate::hclog). This is useful to dump a
+/// data structure field by field. The messages carry the file and line of the macro call.
+///
+/// The arguments are only evaluated if the level is enabled. Like with the log macros the key
+/// can be prefixed with `Scope = <LogKey type>` to check its type.
+///
+/// The block takes the bare format strings instead of log macros like
+/// `{ lI!(key, "a={}", a); lI!(key, "b={}", b); }`: each of those carries its own key and level
+/// which may differ from the ones of the batch and would check the level and access the context
+/// again when expanded, which is what the batch avoids.
+///
+/// # Example
+///
+/// ```rust
+/// # use hclog_macros::HCLog;
+/// use hclog::{Level, FacadeVariant, options::Options};
+///
+/// # #[derive(HCLog, Copy, Clone)]
+/// enum HclogKeys { Foo }
+///
+/// use HclogKeys::*;
+/// # use hclog::Scope;
+///
+/// fn main() {
+///    # HclogKeys::init("foo", Level::Debug1, FacadeVariant::StdOut, Options::default()).unwrap();
+///    let (host, port) = ("localhost", 8080);
+///    hclog::batch!(Foo, Level::Debug1, {
+///        "config:";
+///        "  host={}", host;
+///        "  port={}", port;
+///    });
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Like [`hclog`](macro@crate::hclog) this macro panics if the given `$key` is not
+/// initialized.
+#[macro_export]
+macro_rules! batch {
+    (Scope = $kty:ty, $key:ident, $lvl:path, { $($fmt:literal $(, $args:expr)*);+ $(;)? }) => {{
+        $crate::__hclog_check_key!($kty, $key);
+        $crate::batch!($key, $lvl, { $($fmt $(, $args)*);+ })
+    }};
+    ($key:ident, $lvl:path, { $($fmt:literal $(, $args:expr)*);+ $(;)? }) => {{
+        if $crate::tX!($key, $lvl) {
+            if let ::core::result::Result::Err(e) = $crate::log_batch(
+                $key, $lvl, ::core::file!(), ::core::option::Option::Some($crate::fn_path!()),
+                ::core::line!(), &[$(::core::format_args!($fmt $(, $args)*)),+]
+            ) {
+                ::core::panic!("{}", $crate::failure_message($key, e));
+            }
         }
     }};
 }
@@ -101,103 +275,498 @@ macro_rules! hclog {
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lEM {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Emerg, $key, $($args)+)}}
+macro_rules! lEM {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Emerg, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Emerg, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Alert`](crate::Level::Alert) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lA {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Alert, $key, $($args)+)}}
+macro_rules! lA {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Alert, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Alert, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Crit`](crate::Level::Crit) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lC {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Crit, $key, $($args)+)}}
+macro_rules! lC {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Crit, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Crit, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Error`](crate::Level::Error) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lE {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Error, $key, $($args)+)}}
+macro_rules! lE {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Error, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Error, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Warn`](crate::Level::Warn) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lW {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Warn, $key, $($args)+)}}
+macro_rules! lW {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Warn, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Warn, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Notice`](crate::Level::Notice) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lN {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Notice, $key, $($args)+)}}
+macro_rules! lN {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Notice, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Notice, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Info`](crate::Level::Info) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lI {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Info, $key, $($args)+)}}
+macro_rules! lI {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Info, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Info, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Debug1`](crate::Level::Debug1) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lD1 {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug1, $key, $($args)+)}}
+macro_rules! lD1 {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Debug1, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug1, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Debug2`](crate::Level::Debug2) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lD2 {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug2, $key, $($args)+)}}
+macro_rules! lD2 {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Debug2, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug2, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Debug3`](crate::Level::Debug3) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lD3 {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug3, $key, $($args)+)}}
+macro_rules! lD3 {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Debug3, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug3, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Debug4`](crate::Level::Debug4) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lD4 {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug4, $key, $($args)+)}}
+macro_rules! lD4 {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Debug4, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug4, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Debug5`](crate::Level::Debug5) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lD5 {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug5, $key, $($args)+)}}
+macro_rules! lD5 {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Debug5, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug5, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Debug6`](crate::Level::Debug6) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lD6 {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug6, $key, $($args)+)}}
+macro_rules! lD6 {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Debug6, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug6, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Debug7`](crate::Level::Debug7) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lD7 {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug7, $key, $($args)+)}}
+macro_rules! lD7 {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Debug7, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug7, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Debug8`](crate::Level::Debug8) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lD8 {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug8, $key, $($args)+)}}
+macro_rules! lD8 {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Debug8, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug8, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Debug9`](crate::Level::Debug9) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lD9 {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug9, $key, $($args)+)}}
+macro_rules! lD9 {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Debug9, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug9, $key, $($args)+)};
+}
 
 /// Log a message with severity [`Debug10`](crate::Level::Debug10) via `LogKey`
 ///
 /// For more details see [`hclog`](macro@crate::hclog)
 #[macro_export]
-macro_rules! lD10 {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug10, $key, $($args)+)}}
+macro_rules! lD10 {
+    (Scope = $kty:ty, $key:ident, $($args:tt)+) => {
+        $crate::hclog!($crate::Level::Debug10, Scope = $kty, $key, $($args)+)
+    };
+    ($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::Debug10, $key, $($args)+)};
+}
+
+/*
+ * log via the LogKey set by set_default_key (or the Internal key), see api::log_default
+ */
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __hclog_default {
+    ($lvl:path, $($arg:tt)+) => {{
+        let enabled = match $crate::test_log_default($lvl) {
+            ::core::result::Result::Ok(enabled) => enabled,
+            ::core::result::Result::Err(e) => {
+                ::core::panic!("{}", $crate::default_failure_message(e))
+            },
+        };
+        if enabled {
+            if let ::core::result::Result::Err(e) = $crate::log_default(
+                $lvl, ::core::file!(), ::core::option::Option::Some($crate::fn_path!()),
+                ::core::line!(), &$crate::__hclog_format!($($arg)+)
+            ) {
+                ::core::panic!("{}", $crate::default_failure_message(e));
+            }
+        }
+    }};
+}
+
+/// Log a message with severity [`Emerg`](crate::Level::Emerg) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dEM {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Emerg, $($args)+)};
+}
+
+/// Log a message with severity [`Alert`](crate::Level::Alert) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dA {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Alert, $($args)+)};
+}
+
+/// Log a message with severity [`Crit`](crate::Level::Crit) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dC {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Crit, $($args)+)};
+}
+
+/// Log a message with severity [`Error`](crate::Level::Error) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dE {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Error, $($args)+)};
+}
+
+/// Log a message with severity [`Warn`](crate::Level::Warn) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dW {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Warn, $($args)+)};
+}
+
+/// Log a message with severity [`Notice`](crate::Level::Notice) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dN {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Notice, $($args)+)};
+}
+
+/// Log a message with severity [`Info`](crate::Level::Info) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dI {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Info, $($args)+)};
+}
+
+/// Log a message with severity [`Debug1`](crate::Level::Debug1) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dD1 {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Debug1, $($args)+)};
+}
+
+/// Log a message with severity [`Debug2`](crate::Level::Debug2) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dD2 {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Debug2, $($args)+)};
+}
+
+/// Log a message with severity [`Debug3`](crate::Level::Debug3) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dD3 {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Debug3, $($args)+)};
+}
+
+/// Log a message with severity [`Debug4`](crate::Level::Debug4) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dD4 {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Debug4, $($args)+)};
+}
+
+/// Log a message with severity [`Debug5`](crate::Level::Debug5) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dD5 {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Debug5, $($args)+)};
+}
+
+/// Log a message with severity [`Debug6`](crate::Level::Debug6) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dD6 {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Debug6, $($args)+)};
+}
+
+/// Log a message with severity [`Debug7`](crate::Level::Debug7) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dD7 {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Debug7, $($args)+)};
+}
+
+/// Log a message with severity [`Debug8`](crate::Level::Debug8) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dD8 {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Debug8, $($args)+)};
+}
+
+/// Log a message with severity [`Debug9`](crate::Level::Debug9) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dD9 {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Debug9, $($args)+)};
+}
+
+/// Log a message with severity [`Debug10`](crate::Level::Debug10) via the default key
+///
+/// For more details see [`set_default_key`](fn@crate::set_default_key)
+#[macro_export]
+macro_rules! dD10 {
+    ($($args:tt)+) => {$crate::__hclog_default!($crate::Level::Debug10, $($args)+)};
+}
+
+/*
+ * error propagation with logging
+ *
+ * The context is logged via hclog! so file!(), line!() and fn_path!() resolve to the caller of
+ * error_context!/warn_context! and not to this file.
+ */
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __hclog_context {
+    ($lvl:path, $key:ident, $expr:expr, $($ctx:tt)+) => {
+        match $expr {
+            ::core::result::Result::Err(e) => {
+                $crate::hclog!($lvl, $key, "{}: {}", ::core::format_args!($($ctx)+), e);
+                ::core::result::Result::Err(e)
+            }
+            ok => ok,
+        }
+    };
+}
+
+/// Log the error of a `Result` with severity [`Error`](crate::Level::Error) and a context
+///
+/// Evaluates `$expr` and passes the `Result` through unchanged. If it is an `Err(e)`, the error
+/// is logged via `LogKey` as `"<context>: <e>"` before, which requires `e` to implement
+/// [`Display`](std::fmt::Display). The context takes a format string and arguments like the
+/// log macros. File, line and function of the message are the ones of the macro call.
+///
+/// This replaces the common `.map_err(|e| { lE!(KEY, "...: {}", e); e })` pattern.
+///
+/// # Example
+///
+/// ```rust
+/// # use hclog_macros::HCLog;
+/// use hclog::{Level, FacadeVariant, options::Options};
+///
+/// # #[derive(HCLog, Copy, Clone)]
+/// enum CtxKeys { Cfg }
+///
+/// use CtxKeys::*;
+/// # use hclog::Scope;
+///
+/// fn read_config(path: &str) -> std::io::Result<String> {
+///     hclog::error_context!(Cfg, std::fs::read_to_string(path), "failed to read {}", path)
+/// }
+///
+/// fn main() {
+///     # CtxKeys::init("foo", Level::Info, FacadeVariant::StdOut, Options::default()).unwrap();
+///     // logs "failed to read /nonexistent: No such file or directory (os error 2)"
+///     assert!(read_config("/nonexistent").is_err());
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Like all log macros this panics if the given `$key` is not initialized.
+#[macro_export]
+macro_rules! error_context {
+    ($key:ident, $expr:expr, $($ctx:tt)+) => {
+        $crate::__hclog_context!($crate::Level::Error, $key, $expr, $($ctx)+)
+    };
+}
+
+/// Log the error of a `Result` with severity [`Warn`](crate::Level::Warn) and a context
+///
+/// For more details see [`error_context`](macro@crate::error_context)
+#[macro_export]
+macro_rules! warn_context {
+    ($key:ident, $expr:expr, $($ctx:tt)+) => {
+        $crate::__hclog_context!($crate::Level::Warn, $key, $expr, $($ctx)+)
+    };
+}
+
+/// Log the duration of a block with severity [`Info`](crate::Level::Info) via `LogKey`
+///
+/// Runs the block, logs `"<operation> took <duration>"` once it completed and returns the value
+/// of the block. The duration is formatted by [`fmt::dur`](fn@crate::fmt::dur), e.g.
+/// `"load took 12.3ms"`. The block always runs, the level only decides whether the message is
+/// logged. A block left early via `return` or `?` isn't logged.
+///
+/// # Example
+///
+/// ```rust
+/// # use hclog_macros::HCLog;
+/// use hclog::{Level, FacadeVariant, options::Options};
+///
+/// # #[derive(HCLog, Copy, Clone)]
+/// enum TimedKeys { Load }
+///
+/// use TimedKeys::*;
+/// # use hclog::Scope;
+///
+/// # TimedKeys::init("foo", Level::Info, FacadeVariant::StdOut, Options::default()).unwrap();
+/// // logs e.g. "sum took 2.10µs"
+/// let sum = hclog::lI_timed!(Load, "sum", { (1..=4).sum::<u32>() });
+/// assert_eq!(sum, 10);
+/// ```
+///
+/// # Panics
+///
+/// Like all log macros this panics if the given `$key` is not initialized.
+#[macro_export]
+macro_rules! lI_timed {
+    ($key:ident, $op:expr, $block:block) => {{
+        let start = $crate::__private::instant();
+        let ret = $block;
+        let took = $crate::__private::instant().saturating_duration_since(start);
+        $crate::lI!($key, "{} took {}", $op, $crate::fmt::dur(took));
+        ret
+    }};
+}
+
+/// Log a message with severity [`Info`](crate::Level::Info) via `LogKey` without waiting for a lock
+///
+/// The message is skipped if the internal context or the facade is locked by another thread,
+/// see [`try_log`](fn@crate::try_log). Whether it was written isn't reported, call
+/// [`try_log`](fn@crate::try_log) directly to find out. The arguments are the same as for
+/// [`lI`](macro@crate::lI), including lazy arguments.
+///
+/// # Example
+///
+/// ```rust
+/// # use hclog_macros::HCLog;
+/// use hclog::{Level, FacadeVariant, options::Options};
+///
+/// # #[derive(HCLog, Copy, Clone)]
+/// enum RtKeys { Audio }
+///
+/// use RtKeys::*;
+/// # use hclog::Scope;
+///
+/// # RtKeys::init("foo", Level::Info, FacadeVariant::StdOut, Options::default()).unwrap();
+/// hclog::lI_try!(Audio, "period {} done", 42);
+/// ```
+///
+/// # Panics
+///
+/// Like all log macros this panics if the given `$key` is not initialized.
+#[macro_export]
+macro_rules! lI_try {
+    ($key:ident, $($arg:tt)+) => {
+        if let ::core::result::Result::Err(e) = $crate::try_log_from(
+            $key, $crate::Level::Info, ::core::file!(),
+            ::core::option::Option::Some($crate::fn_path!()), ::core::line!(),
+            &$crate::__hclog_format!($($arg)+)
+        ) {
+            ::core::panic!("{}", $crate::failure_message($key, e));
+        }
+    };
+}
 
 // exported test macros
 /// Test if a given [`Level`](enum@crate::Level) is enabled for a given [`LogKey`](trait@crate::LogKey)
@@ -223,15 +792,16 @@ macro_rules! lD10 {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::
 /// enum TxKey { AA, }
 ///
 /// use TxKey::AA;
+/// # use hclog::Scope;
 ///
 /// fn main() {
 ///     # TxKey::init("foo", Level::Debug9, FacadeVariant::StdOut, Options::default()).unwrap();
 ///
 ///     assert!(hclog::tX!(AA, Level::Debug9));            // Debug9 is enabled
-///     assert_eq!(hclog::tX!(AA, Level::Debug10), false); // Debug10 is not enabled
+///     assert!(!hclog::tX!(AA, Level::Debug10));          // Debug10 is not enabled
 ///     // the lines below are actually shortcuts for the above lines
 ///     assert!(hclog::tD9!(AA));
-///     assert_eq!(hclog::tD10!(AA), false);               // Debug10 is not enabled
+///     assert!(!hclog::tD10!(AA));                        // Debug10 is not enabled
 /// }
 /// ```
 ///
@@ -242,8 +812,17 @@ macro_rules! lD10 {($key:ident, $($args:tt)+) => {$crate::hclog!($crate::Level::
 ///
 #[macro_export]
 macro_rules! tX {
+    (Scope = $kty:ty, $key:ident, $level:path) => {{
+        $crate::__hclog_check_key!($kty, $key);
+        $crate::tX!($key, $level)
+    }};
     ($key:ident, $level:path) => {
-        $crate::test_log($key, $level).unwrap()
+        match $crate::test_log($key, $level) {
+            ::core::result::Result::Ok(enabled) => enabled,
+            ::core::result::Result::Err(e) => {
+                ::core::panic!("{}", $crate::failure_message($key, e))
+            },
+        }
     };
 }
 
@@ -251,103 +830,154 @@ macro_rules! tX {
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tEM {($key:ident) => {$crate::tX!($key, $crate::Level::Emerg)};}
+macro_rules! tEM {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Emerg)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Emerg)};
+}
 
 /// Test if severity [`Alert`](crate::Level::Alert) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tA {($key:ident) => {$crate::tX!($key, $crate::Level::Alert)};}
+macro_rules! tA {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Alert)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Alert)};
+}
 
 /// Test if severity [`Crit`](crate::Level::Crit) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tC {($key:ident) => {$crate::tX!($key, $crate::Level::Crit)};}
+macro_rules! tC {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Crit)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Crit)};
+}
 
 /// Test if severity [`Error`](crate::Level::Error) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tE {($key:ident) => {$crate::tX!($key, $crate::Level::Error)};}
+macro_rules! tE {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Error)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Error)};
+}
 
 /// Test if severity [`Warn`](crate::Level::Warn) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tW {($key:ident) => {$crate::tX!($key, $crate::Level::Warn)};}
+macro_rules! tW {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Warn)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Warn)};
+}
 
 /// Test if severity [`Notice`](crate::Level::Notice) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tN {($key:ident) => {$crate::tX!($key, $crate::Level::Notice)};}
+macro_rules! tN {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Notice)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Notice)};
+}
 
 /// Test if severity [`Info`](crate::Level::Info) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tI {($key:ident) => {$crate::tX!($key, $crate::Level::Info)};}
+macro_rules! tI {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Info)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Info)};
+}
 
 /// Test if severity [`Debug1`](crate::Level::Debug1) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tD1 {($key:ident) => {$crate::tX!($key, $crate::Level::Debug1)};}
+macro_rules! tD1 {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Debug1)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Debug1)};
+}
 
 /// Test if severity [`Debug2`](crate::Level::Debug2) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tD2 {($key:ident) => {$crate::tX!($key, $crate::Level::Debug2)};}
+macro_rules! tD2 {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Debug2)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Debug2)};
+}
 
 /// Test if severity [`Debug3`](crate::Level::Debug3) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tD3 {($key:ident) => {$crate::tX!($key, $crate::Level::Debug3)};}
+macro_rules! tD3 {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Debug3)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Debug3)};
+}
 
 /// Test if severity [`Debug4`](crate::Level::Debug4) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tD4 {($key:ident) => {$crate::tX!($key, $crate::Level::Debug4)};}
+macro_rules! tD4 {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Debug4)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Debug4)};
+}
 
 /// Test if severity [`Debug5`](crate::Level::Debug5) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tD5 {($key:ident) => {$crate::tX!($key, $crate::Level::Debug5)};}
+macro_rules! tD5 {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Debug5)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Debug5)};
+}
 
 /// Test if severity [`Debug6`](crate::Level::Debug6) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tD6 {($key:ident) => {$crate::tX!($key, $crate::Level::Debug6)};}
+macro_rules! tD6 {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Debug6)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Debug6)};
+}
 
 /// Test if severity [`Debug7`](crate::Level::Debug7) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tD7 {($key:ident) => {$crate::tX!($key, $crate::Level::Debug7)};}
+macro_rules! tD7 {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Debug7)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Debug7)};
+}
 
 /// Test if severity [`Debug8`](crate::Level::Debug8) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tD8 {($key:ident) => {$crate::tX!($key, $crate::Level::Debug8)};}
+macro_rules! tD8 {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Debug8)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Debug8)};
+}
 
 /// Test if severity [`Debug9`](crate::Level::Debug9) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tD9 {($key:ident) => {$crate::tX!($key, $crate::Level::Debug9)};}
+macro_rules! tD9 {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Debug9)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Debug9)};
+}
 
 /// Test if severity [`Debug10`](crate::Level::Debug10) is enabled for `LogKey`
 ///
 /// For more details see [`tX`](macro@crate::tX)
 #[macro_export]
-macro_rules! tD10 {($key:ident) => {$crate::tX!($key, $crate::Level::Debug10)};}
+macro_rules! tD10 {
+    (Scope = $kty:ty, $key:ident) => {$crate::tX!(Scope = $kty, $key, $crate::Level::Debug10)};
+    ($key:ident) => {$crate::tX!($key, $crate::Level::Debug10)};
+}
 
 // Test below just ensure that macros are expanded as expected
 #[cfg(test)]
@@ -369,6 +999,38 @@ mod macro_test {
         hclog!(Debug3, LIBTESTFOO, "ensure $path works");
     }
 
+    #[test]
+    fn runtime_level_off() {
+        init_libtest_mod().unwrap();
+        // the macro checks the level first, log() would fail with InvalidLevel
+        let lvl = [Emerg, Off][std::hint::black_box(1)];
+        hclog!(lvl, LIBTESTFOO, "never written");
+        assert!(!tX!(LIBTESTFOO, lvl));
+    }
+
+    #[test]
+    fn timed_passthrough() {
+        init_libtest_mod().unwrap();
+        let sum = lI_timed!(LIBTESTFOO, "sum", { (1..=4).sum::<u32>() });
+        assert_eq!(sum, 10);
+        let parsed: Result<u8, _> = lI_timed!(LIBTESTFOO, format_args!("parse {}", 1), {
+            "42".parse()
+        });
+        assert_eq!(parsed, Ok(42));
+        assert_eq!(lI_timed!(LIBTESTFOO, "unit", {}), ());
+    }
+
+    #[test]
+    fn lazy_args() {
+        init_libtest_mod().unwrap();
+        let (a, b) = (1, "b");
+        lEM!(LIBTESTFOO, "{}", lazy = || 42);
+        lEM!(LIBTESTFOO, "{} {} {:>4}", a, lazy = || a + 1, lazy = || b,);
+        lEM!(LIBTESTFOO, "{x} {} {b}", lazy = || a, x = a + 1);
+        lEM!(LIBTESTFOO, "{lazy}", lazy = 3);
+        lEM!(LIBTESTFOO, concat!("{}", "{}"), a, b);
+    }
+
     #[test]
     fn log_macros() {
         init_libtest_mod().unwrap();
@@ -391,6 +1053,15 @@ mod macro_test {
         lD10!(LIBTESTFOO, "Foo");
     }
 
+    #[test]
+    fn batch_macro() {
+        init_libtest_mod().unwrap();
+        batch!(LIBTESTFOO, Emerg, { "foo" });
+        batch!(LIBTESTFOO, Emerg, { "foo"; "Foo {} {:?}", 42, Debug10; });
+        batch!(LIBTESTFOO, crate::Level::Debug10, { "foo_{} baz {:?}", "bar", (42u32, -1) });
+        batch!(Scope = crate::log_internal::test::TestKeys, LIBTESTFOO, Info, { "{}", 1; "2" });
+    }
+
     #[test]
     #[serial]
     fn test_macros() {
@@ -408,10 +1079,10 @@ mod macro_test {
         assert!(tD3!(LIBTESTFOO));
         assert!(tD4!(LIBTESTFOO));
         assert!(tD5!(LIBTESTFOO));
-        assert_eq!(tD6!(LIBTESTFOO), false, "Debug6 is enabled");
-        assert_eq!(tD7!(LIBTESTFOO), false);
-        assert_eq!(tD8!(LIBTESTFOO), false);
-        assert_eq!(tD9!(LIBTESTFOO), false);
-        assert_eq!(tD10!(LIBTESTFOO), false);
+        assert!(!tD6!(LIBTESTFOO), "Debug6 is enabled");
+        assert!(!tD7!(LIBTESTFOO));
+        assert!(!tD8!(LIBTESTFOO));
+        assert!(!tD9!(LIBTESTFOO));
+        assert!(!tD10!(LIBTESTFOO));
     }
 }
@@ -1,52 +1,422 @@
-use crate::{level::Level, logmod::ScopeEnv, options::*};
+use crate::{clock, facades::Newline, level::Level, logmod::ScopeEnv, options::*};
 use chrono::{DateTime, Utc};
 use std::{
+    backtrace::{Backtrace, BacktraceStatus},
     fmt::{self, Display, Debug, Arguments},
     borrow::Cow,
     process,
+    sync::{atomic::{AtomicBool, Ordering}, OnceLock},
     thread,
+    time::{Duration, Instant},
 };
+#[cfg(not(feature = "single-thread"))]
+use std::sync::RwLock;
+#[cfg(feature = "single-thread")]
+use std::{cell::RefCell, thread::LocalKey};
 
-#[derive(Debug)]
+/*
+ * settings shared by all scopes and read for every message. They are process wide behind a
+ * RwLock, in single-thread mode they belong to the thread like the context and are read
+ * without a lock.
+ */
+macro_rules! setting {
+    ($name:ident: $ty:ty = $init:expr) => {
+        #[cfg(not(feature = "single-thread"))]
+        static $name: Setting<$ty> = RwLock::new($init);
+        #[cfg(feature = "single-thread")]
+        std::thread_local! {
+            static $name: RefCell<$ty> = const { RefCell::new($init) };
+        }
+    };
+}
+#[cfg(not(feature = "single-thread"))]
+type Setting<T> = RwLock<T>;
+#[cfg(feature = "single-thread")]
+type Setting<T> = LocalKey<RefCell<T>>;
+
+#[cfg(not(feature = "single-thread"))]
+fn read_setting<T: Default, R>(setting: &'static Setting<T>, f: impl FnOnce(&T) -> R) -> R {
+    f(&setting.read().unwrap_or_else(|e| e.into_inner()))
+}
+#[cfg(not(feature = "single-thread"))]
+fn write_setting<T>(setting: &'static Setting<T>, f: impl FnOnce(&mut T)) {
+    f(&mut setting.write().unwrap_or_else(|e| e.into_inner()))
+}
+// the default is read once the thread local is destroyed on thread exit
+#[cfg(feature = "single-thread")]
+fn read_setting<T: Default, R>(setting: &'static Setting<T>, f: impl FnOnce(&T) -> R) -> R {
+    let mut f = Some(f);
+    setting.try_with(|v| f.take().map(|f| f(&v.borrow())))
+        .ok()
+        .flatten()
+        .or_else(|| f.take().map(|f| f(&T::default())))
+        .expect("f is called exactly once")
+}
+#[cfg(feature = "single-thread")]
+fn write_setting<T>(setting: &'static Setting<T>, f: impl FnOnce(&mut T)) {
+    let _ = setting.try_with(|v| f(&mut v.borrow_mut()));
+}
+
+/*
+ * base of the UPTIME option, set on the first initialization of a scope (or the first message
+ * rendered with UPTIME) and moved by reset_uptime_base. The epoch of MONOTONIC_TS is the
+ * initial base and never moves, it's process wide even in single-thread mode.
+ */
+setting!(UPTIME_BASE: Option<Instant> = None);
+static MONOTONIC_EPOCH: OnceLock<Instant> = OnceLock::new();
+
+pub (crate) fn init_uptime_base() -> Instant {
+    if let Some(base) = read_setting(&UPTIME_BASE, |base| *base) {
+        return base;
+    }
+    let mut base = monotonic_epoch();
+    write_setting(&UPTIME_BASE, |b| base = *b.get_or_insert(base));
+    base
+}
+fn monotonic_epoch() -> Instant {
+    *MONOTONIC_EPOCH.get_or_init(clock::instant)
+}
+pub (crate) fn reset_uptime_base() {
+    write_setting(&UPTIME_BASE, |base| *base = Some(clock::instant()));
+}
+
+/*
+ * stack appended to messages with the BACKTRACE option, starting with a line break. If the
+ * platform can't capture it the first message says so and all later ones get nothing.
+ */
+static BACKTRACE_NOTED: AtomicBool = AtomicBool::new(false);
+
+pub (crate) fn backtrace() -> String {
+    let trace = Backtrace::force_capture();
+    match trace.status() {
+        BacktraceStatus::Captured => format!("\n{}", trace.to_string().trim_end()),
+        _ if BACKTRACE_NOTED.swap(true, Ordering::Relaxed) => String::new(),
+        status => format!("\nbacktrace unavailable: {:?}", status),
+    }
+}
+
+/*
+ * sequence written between the prefix and the payload with the PREFIX_SEP option, shared like
+ * the UPTIME base. None is the default separator.
+ */
+setting!(PREFIX_SEPARATOR: Option<String> = None);
+const DEFAULT_PREFIX_SEPARATOR: &str = " | ";
+
+pub (crate) fn set_prefix_separator(sep: Option<String>) {
+    write_setting(&PREFIX_SEPARATOR, |s| *s = sep);
+}
+fn with_prefix_separator<R>(f: impl FnOnce(&str) -> R) -> R {
+    read_setting(&PREFIX_SEPARATOR, |sep| f(sep.as_deref().unwrap_or(DEFAULT_PREFIX_SEPARATOR)))
+}
+/*
+ * break every occurrence of the separator with a backslash after its first character. This
+ * includes an occurrence completed by the separator written after the prefix, e.g. a prefix
+ * ending with " |" followed by " | ".
+ */
+fn escape_separator(prefix: &mut String, sep: &str) {
+    let split = sep.chars().next().map_or(0, char::len_utf8);
+    prefix.push_str(sep);
+    while let Some(pos) = prefix.find(sep).filter(|pos| pos + sep.len() < prefix.len()) {
+        prefix.insert(pos + split, '\\');
+    }
+    prefix.truncate(prefix.len() - sep.len());
+}
+/*
+ * escapes the prefix like escape_separator while it's written, so no String of the prefix is
+ * needed. The chars which may still become a separator are held back, at the end they are
+ * passed to escape_separator. A single trailing space is dropped like in render_split.
+ */
+struct EscapeSeparator<'s, W> {
+    out: W,
+    sep: &'s str,
+    // sep[..held] is held back
+    held: usize,
+    space: bool,
+}
+impl<'s, W: fmt::Write> EscapeSeparator<'s, W> {
+    fn new(out: W, sep: &'s str) -> Self {
+        Self { out, sep, held: 0, space: false }
+    }
+    fn push(&mut self, c: char) -> fmt::Result {
+        let sep = self.sep;
+        while !sep[self.held..].starts_with(c) {
+            if self.held == 0 {
+                return self.out.write_char(c);
+            }
+            // keep the longest tail of the held chars which may still start a separator
+            let held = &sep[..self.held];
+            let keep = (1..self.held).rev()
+                .find(|n| held.is_char_boundary(self.held - n)
+                    && sep.starts_with(&held[self.held - n..]))
+                .unwrap_or(0);
+            self.out.write_str(&held[..self.held - keep])?;
+            self.held = keep;
+        }
+        self.held += c.len_utf8();
+        if self.held == sep.len() {
+            let split = sep.chars().next().map_or(0, char::len_utf8);
+            self.out.write_str(&sep[..split])?;
+            self.out.write_char('\\')?;
+            self.held = 0;
+            sep[split..].chars().try_for_each(|c| self.push(c))?;
+        }
+        Ok(())
+    }
+    fn finish(mut self) -> fmt::Result {
+        if self.held == 0 {
+            return Ok(());
+        }
+        let mut tail = self.sep[..self.held].to_owned();
+        escape_separator(&mut tail, self.sep);
+        self.out.write_str(&tail)
+    }
+}
+impl<W: fmt::Write> fmt::Write for EscapeSeparator<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if std::mem::take(&mut self.space) {
+                self.push(' ')?;
+            }
+            match c {
+                ' ' => self.space = true,
+                c => self.push(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/*
+ * prefixes removed from the path written with the FILE option, shared like the separator.
+ * The longest matching prefix wins, it only matches whole components.
+ */
+setting!(FILE_PREFIXES: Vec<String> = Vec::new());
+
+pub (crate) fn is_separator(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+// the trailing separators are removed by the caller
+pub (crate) fn add_file_prefix(prefix: &str) {
+    write_setting(&FILE_PREFIXES, |prefixes| {
+        if !prefixes.iter().any(|p| p == prefix) {
+            prefixes.push(prefix.to_owned());
+        }
+    });
+}
+fn strip_file_prefix(file: &str) -> Option<&str> {
+    read_setting(&FILE_PREFIXES, |prefixes| {
+        prefixes.iter()
+            .filter_map(|p| file.strip_prefix(p.as_str()).map(|rest| (p.len(), rest)))
+            .filter(|(_, rest)| rest.starts_with(is_separator))
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, rest)| rest.trim_start_matches(is_separator))
+    })
+}
+// the path as written with FILE, see FILE_BASENAME
+fn display_file(file: &str, basename: bool) -> &str {
+    match strip_file_prefix(file) {
+        Some(rest) => rest,
+        None if basename => file.rsplit(is_separator).next().unwrap_or(file),
+        None => file,
+    }
+}
+
+/*
+ * function name as written with FUNC_SHORT: the generic arguments are removed, then the last
+ * two segments are kept. Segments generated for closures and async blocks (e.g. `{{closure}}`)
+ * stay but don't count. A `<` at the start of the path or of a type opens a trait-qualified
+ * path like `<a::Conn as core::fmt::Display>`, which is kept as one segment with its paths
+ * shortened to the last segment each.
+ */
+pub (crate) fn short_func(func: &str) -> String {
+    keep_segments(&strip_generics(func), 2)
+}
+// `->` (e.g. in `fn() -> T`) doesn't close a bracket
+fn strip_generics(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        // a bracket at the start of the path or a type is a qualified path, not generics
+        if c != '<' || out.is_empty() || out.ends_with(['<', ' ']) {
+            out.push(c);
+            continue;
+        }
+        // turbofish
+        if out.ends_with("::") {
+            out.truncate(out.len() - 2);
+        }
+        let (mut depth, mut prev) = (1, c);
+        while depth > 0 {
+            let Some(c) = chars.next() else { break };
+            match c {
+                '<' => depth += 1,
+                '>' if prev != '-' => depth -= 1,
+                _ => (),
+            }
+            prev = c;
+        }
+    }
+    out
+}
+// split at the `::` outside of brackets
+fn split_segments(path: &str) -> Vec<&str> {
+    let (mut segments, mut depth, mut start, mut prev) = (Vec::new(), 0usize, 0, ' ');
+    let bytes = path.as_bytes();
+    for (pos, c) in path.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' if prev != '-' => depth = depth.saturating_sub(1),
+            ':' if depth == 0 && pos >= start && bytes.get(pos + 1) == Some(&b':') => {
+                segments.push(&path[start..pos]);
+                start = pos + 2;
+            }
+            _ => (),
+        }
+        prev = c;
+    }
+    segments.push(&path[start.min(path.len())..]);
+    segments
+}
+fn keep_segments(path: &str, keep: usize) -> String {
+    let mut kept = Vec::new();
+    let mut counted = 0;
+    for segment in split_segments(path).into_iter().rev() {
+        if counted == keep {
+            break;
+        }
+        if !(segment.starts_with("{{") && segment.ends_with("}}")) {
+            counted += 1;
+        }
+        kept.push(shorten_qualified(segment));
+    }
+    kept.reverse();
+    kept.join("::")
+}
+// `<a::Conn as core::fmt::Display>` -> `<Conn as Display>`
+fn shorten_qualified(segment: &str) -> String {
+    match segment.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+        Some(inner) => {
+            let types = inner.split(" as ").map(|ty| keep_segments(ty, 1)).collect::<Vec<_>>();
+            format!("<{}>", types.join(" as "))
+        }
+        None => segment.to_owned(),
+    }
+}
+// at most `max` chars, the last one replaced by `…` if cut
+pub (crate) fn truncate_func(func: &str, max: usize) -> Cow<'_, str> {
+    match func.char_indices().nth(max.saturating_sub(1)) {
+        Some((pos, _)) if func[pos..].chars().nth(1).is_some() => {
+            Cow::Owned(format!("{}…", &func[..pos]))
+        }
+        _ => Cow::Borrowed(func),
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Message<'a> {
     options: &'a Options,
     time: DateTime<Utc>,
+    uptime: Option<Duration>,
+    monotonic: Option<Duration>,
     binname: &'a str,
     severity: Option<&'a Level>,
     modname: Option<Cow<'a, str>>,
+    name: &'a str,
     scope: Option<&'a ScopeEnv>,
     scope_ident: Option<&'a str>,
+    instance: Option<&'a str>,
     file: &'a str,
-    func: &'a str,
+    func: Option<&'a str>,
+    func_max_len: Option<usize>,
     line: u32,
     fmt: Cow<'a, str>,
+    newline: Newline,
 }
 /*
- * NOTE: fmt::Display is always buffered and options.line_buffered = false is not implemented.
- * To implement we would need to use std::io::Write and a custom (or different) formatter."
+ * NOTE: the facades render a Message straight into their io handle via write_fmt, so no
+ * intermediate String of the whole line is built (except for syslog which needs a CString).
+ * The payload itself is formatted into a String by Message::new unless it's a plain str: the
+ * Display impls of the arguments may log themselves, which has to be done before the line
+ * is written.
+ * With PREFIX_SEP the separator is escaped while the prefix is written.
+ * options.line_buffered = false is not implemented, the handles keep their own buffering.
  */
 impl<'a> Display for Message<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.options.has(DATESTAMP) {
+        if self.options.has(RAW) {
+            return self.write_payload(f);
+        }
+        if self.options.has(PREFIX_SEP) {
+            with_prefix_separator(|sep| {
+                let mut prefix = EscapeSeparator::new(&mut *f, sep);
+                self.write_prefix(&mut prefix)?;
+                prefix.finish()?;
+                f.write_str(sep)
+            })?;
+        } else {
+            self.write_prefix(f)?;
+        }
+        self.write_payload(f)
+    }
+}
+impl<'a> Message<'a> {
+    /// Render the prefix and return it along with the unchanged payload
+    ///
+    /// The rendered message is the prefix, the separator if [`PREFIX_SEP`] is set and the
+    /// payload. With [`PREFIX_SEP`] the trailing space of the prefix is dropped and the
+    /// separator is escaped within the prefix. With [`RAW`] the prefix is empty.
+    #[allow(dead_code)] // Message isn't public yet, the Display impl escapes the same way
+    pub fn render_split(&self) -> (String, &str) {
+        let mut prefix = String::new();
+        // writing to a String doesn't fail
+        let _ = match (self.options.has(RAW), self.options.has(PREFIX_SEP)) {
+            (true, _) => Ok(()),
+            (false, true) => with_prefix_separator(|sep| {
+                let mut escaped = EscapeSeparator::new(&mut prefix, sep);
+                self.write_prefix(&mut escaped)?;
+                escaped.finish()
+            }),
+            (false, false) => self.write_prefix(&mut prefix),
+        };
+        (prefix, &self.fmt)
+    }
+    fn write_prefix<W: fmt::Write>(&self, f: &mut W) -> fmt::Result {
+        // replaces the time of day
+        if let Some(mt) = self.monotonic {
+            write!(f, "MT+{:07}.{:09} ", mt.as_secs(), mt.subsec_nanos())?;
+        }
+        if self.options.has(DATESTAMP) && self.monotonic.is_none() {
             write!(f, "{} ", self.time.format("%F"))?;
         }
-        if self.options.has(TIMESTAMP) {
+        if self.options.has(TIMESTAMP) && self.monotonic.is_none() {
             match self.options.has(NANOSEC) {
                 true => write!(f, "{} ", self.time.format("%X.%f"))?,
                 false => write!(f, "{} ", self.time.format("%X"))?,
             };
         }
+        if let Some(uptime) = self.uptime {
+            write!(f, "[+{}.{:06}s] ", uptime.as_secs(), uptime.subsec_micros())?;
+        }
         if self.options.has(BINNAME) {
             write!(f, "{}", self.binname)?;
         }
-        if self.options.has(PID) {
-            if self.options.has(TID) {
-                write!(f, "[{}/{}] ", process::id(), Self::get_current_thread_id())?;
-            } else {
-                write!(f, "[{}] ", process::id())?;
+        // [instance/pid/tid] with the parts not set left out
+        let pid = self.options.has(PID).then(process::id);
+        let tid = self.options.has(TID).then(Self::get_current_thread_id);
+        if self.instance.is_some() || pid.is_some() || tid.is_some() {
+            let mut sep = "[";
+            if let Some(instance) = self.instance {
+                write!(f, "{}{}", sep, instance)?;
+                sep = "/";
+            }
+            if let Some(pid) = pid {
+                write!(f, "{}{}", sep, pid)?;
+                sep = "/";
             }
-        } else if self.options.has(TID) {
-            write!(f, "[{}] ", Self::get_current_thread_id())?;
+            if let Some(tid) = tid {
+                write!(f, "{}{}", sep, tid)?;
+            }
+            write!(f, "] ")?;
         } else if self.options.has(BINNAME) {
                 write!(f, " ")?;
         }
@@ -66,24 +436,27 @@ impl<'a> Display for Message<'a> {
         }
         // file=false implicitly also disables line
         if self.options.has(FILE) {
+            let file = display_file(self.file, self.options.has(FILE_BASENAME));
             if self.options.has(LINE) {
-                write!(f, "{}:{} ", self.file, self.line)?;
+                write!(f, "{}:{} ", file, self.line)?;
             } else {
-                write!(f, "{} ", self.file)?;
+                write!(f, "{} ", file)?;
             }
         }
-        if self.options.has(FUNC) {
-            if !self.func.is_empty() {
-                write!(f, "{} ", self.func)?;
+        if let (true, Some(func)) = (self.options.has(FUNC), self.func) {
+            let func = match self.options.has(FUNC_SHORT) {
+                true => Cow::Owned(short_func(func)),
+                false => Cow::Borrowed(func),
+            };
+            match self.func_max_len {
+                Some(max) => write!(f, "{} ", truncate_func(&func, max))?,
+                None => write!(f, "{} ", func)?,
             }
         }
-
-        write!(f, "{}", self.fmt)
+        Ok(())
     }
-}
-impl<'a> Message<'a> {
     pub (crate) fn new(
-        options: &'a Options, binname: &'a str, file: &'a str, func: &'a str,
+        options: &'a Options, binname: &'a str, file: &'a str, func: Option<&'a str>,
         line: u32, fmt: &'a Arguments,
     ) -> Self {
         let fmt = match fmt.as_str() {
@@ -92,18 +465,63 @@ impl<'a> Message<'a> {
         };
         Self {
             options,
-            time: Utc::now(),
+            time: clock::now_utc(),
+            uptime: options.has(UPTIME)
+                .then(|| clock::instant().saturating_duration_since(init_uptime_base())),
+            monotonic: options.has(MONOTONIC_TS)
+                .then(|| clock::instant().saturating_duration_since(monotonic_epoch())),
             binname,
             severity: None,
             modname: None,
+            name: "",
             scope: None,
             scope_ident: None,
+            instance: None,
             file,
             func,
+            func_max_len: None,
             line,
             fmt,
+            newline: Newline::Lf,
         }
     }
+    // with CrLf the lines within the payload are terminated with \r\n as well
+    fn write_payload(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.newline != Newline::CrLf || !self.fmt.contains('\n') {
+            return f.write_str(&self.fmt);
+        }
+        for (i, line) in self.fmt.split('\n').enumerate() {
+            if i > 0 {
+                f.write_str("\r\n")?;
+            }
+            f.write_str(line.strip_suffix('\r').unwrap_or(line))?;
+        }
+        Ok(())
+    }
+    // written by the facades after the message
+    pub (crate) fn terminator(&self) -> &'static str {
+        self.newline.as_str()
+    }
+    // the formatted user arguments without any prefix
+    pub (crate) fn payload(&self) -> &str {
+        &self.fmt
+    }
+    // the raw properties, independent of the options
+    pub (crate) fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
+    pub (crate) fn module(&self) -> &str {
+        self.name
+    }
+    #[cfg(feature = "net-syslog")]
+    pub (crate) fn scope_name(&self) -> &str {
+        self.binname
+    }
+    // messages read back from a binary log keep their original time
+    pub (crate) fn set_time(&mut self, time: DateTime<Utc>) -> &mut Self {
+        self.time = time;
+        self
+    }
     // those fields are set by the module (if set in config)
     pub (crate) fn set_severity(&mut self, lvl: &'a Level) -> &mut Self {
         if self.options.has(SEVERITY) {
@@ -112,11 +530,16 @@ impl<'a> Message<'a> {
         self
     }
     pub (crate) fn set_modname(&mut self, name: &'a str) -> &mut Self {
+        self.name = name;
         if self.options.has(MODULE) {
             self.modname = Some(Cow::Borrowed(name));
         }
         self
     }
+    pub (crate) fn set_newline(&mut self, newline: Newline) -> &mut Self {
+        self.newline = newline;
+        self
+    }
     pub (crate) fn set_scope(&mut self, scope: &'a ScopeEnv, ident: Option<&'a str>) -> &mut Self {
         if self.options.has(SCOPE) {
             self.scope = Some(scope);
@@ -124,6 +547,16 @@ impl<'a> Message<'a> {
         }
         self
     }
+    pub (crate) fn set_instance(&mut self, instance: Option<&'a str>) -> &mut Self {
+        if self.options.has(INSTANCE) {
+            self.instance = instance;
+        }
+        self
+    }
+    pub (crate) fn set_func_max_len(&mut self, max: Option<usize>) -> &mut Self {
+        self.func_max_len = max;
+        self
+    }
 
     /*
      * ThreadId has no display, to_str or to_u64 method. Since str::parse would
@@ -140,3 +573,334 @@ impl<'a> Message<'a> {
     }
 }
 
+#[cfg(test)]
+mod message_tests {
+    use crate::{clock::{self, MockClock}, level::Level, logmod::ScopeEnv, options::*};
+    use super::Message;
+    use serial_test::serial;
+    use std::{sync::Arc, time::{Duration, UNIX_EPOCH}};
+
+    fn render(options: Options, fmt: std::fmt::Arguments) -> String {
+        let mut msg = Message::new(&options, "bin", file!(), Some("func"), line!(), &fmt);
+        msg.set_severity(&Level::Info);
+        msg.set_modname("mod");
+        msg.set_scope(&ScopeEnv::Global, Some("ident"));
+        msg.to_string()
+    }
+
+    #[test]
+    fn raw_is_undecorated() {
+        let all = Options::default() + SCOPE + EXACT_LVL_MATCH + RAW;
+        assert_eq!(render(all, format_args!("static")), "static");
+        assert_eq!(render(all, format_args!("{}-{:>4}", 1, "x")), format!("{}-{:>4}", 1, "x"));
+        assert_eq!(render(RAW, format_args!("a\nb\r\n")), "a\nb\r\n");
+        assert_eq!(render(RAW, format_args!("{} ünïcödé ✓", "🦀")), "🦀 ünïcödé ✓");
+        assert_eq!(render(RAW, format_args!("")), "");
+        assert_ne!(render(all - RAW, format_args!("static")), "static");
+    }
+
+    #[test]
+    fn absent_func() {
+        let fmt = format_args!("x");
+        let absent = Message::new(&FUNC, "bin", file!(), None, line!(), &fmt).to_string();
+        let empty = Message::new(&FUNC, "bin", file!(), Some(""), line!(), &fmt).to_string();
+        let named = Message::new(&FUNC, "bin", file!(), Some("f"), line!(), &fmt).to_string();
+        assert_eq!(absent, "x");
+        assert_eq!(empty, " x");
+        assert_eq!(named, "f x");
+    }
+
+    // replaces the clock until dropped
+    struct Mocked(MockClock);
+    impl Mocked {
+        fn new() -> Self {
+            // 2023-11-14 22:13:20 UTC
+            let clock = MockClock::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+            clock::set_clock(Some(Arc::new(clock.clone())));
+            Self(clock)
+        }
+    }
+    impl Drop for Mocked {
+        fn drop(&mut self) {
+            clock::set_clock(None);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn pinned_time() {
+        let clock = Mocked::new();
+        super::reset_uptime_base();
+        clock.0.advance(Duration::from_micros(1_500_123));
+        let opts = DATESTAMP + TIMESTAMP + NANOSEC + UPTIME;
+        assert_eq!(render(opts, format_args!("x")), "2023-11-14 22:13:21.500123000 [+1.500123s] x");
+        assert_eq!(render(TIMESTAMP + SEVERITY, format_args!("x")), "22:13:21 info x");
+        clock.0.advance(Duration::from_secs(3600));
+        assert_eq!(render(opts, format_args!("x")),
+            "2023-11-14 23:13:21.500123000 [+3601.500123s] x");
+    }
+
+    #[test]
+    #[serial]
+    fn rewound_time() {
+        let clock = Mocked::new();
+        super::reset_uptime_base();
+        clock.0.advance(Duration::from_secs(10));
+        let opts = TIMESTAMP + UPTIME;
+        assert_eq!(render(opts, format_args!("x")), "22:13:30 [+10.000000s] x");
+        // the uptime continues where it was, only the timestamp goes back
+        clock.0.rewind(Duration::from_secs(60));
+        assert_eq!(render(opts, format_args!("x")), "22:12:30 [+10.000000s] x");
+        clock.0.advance(Duration::from_secs(1));
+        assert_eq!(render(opts, format_args!("x")), "22:12:31 [+11.000000s] x");
+    }
+
+    // seconds and nanoseconds of the MONOTONIC_TS field leading `msg`
+    fn monotonic(msg: &str) -> Duration {
+        let (mt, _) = msg.strip_prefix("MT+").unwrap().split_once(' ').unwrap();
+        let (secs, nanos) = mt.split_once('.').unwrap();
+        assert_eq!((secs.len(), nanos.len()), (7, 9), "{}", msg);
+        Duration::new(secs.parse().unwrap(), nanos.parse().unwrap())
+    }
+
+    #[test]
+    #[serial]
+    fn monotonic_ts() {
+        let clock = Mocked::new();
+        let fmt = format_args!("x");
+        let first = render(MONOTONIC_TS, fmt);
+        assert!(first.ends_with(" x"), "{}", first);
+        clock.0.advance(Duration::new(1, 500_000_123));
+        let second = render(MONOTONIC_TS, fmt);
+        assert_eq!(monotonic(&second) - monotonic(&first), Duration::new(1, 500_000_123));
+
+        // the time of day is replaced, the uptime restarts but the epoch doesn't move
+        let all = DATESTAMP + TIMESTAMP + NANOSEC + MONOTONIC_TS + UPTIME;
+        super::reset_uptime_base();
+        clock.0.advance(Duration::from_secs(2));
+        let rendered = render(all, fmt);
+        assert_eq!(monotonic(&rendered) - monotonic(&second), Duration::from_secs(2));
+        assert!(rendered.ends_with(" [+2.000000s] x"), "{}", rendered);
+        assert_eq!(rendered.split(' ').count(), 3);
+
+        // a clock going back doesn't decrease it
+        clock.0.rewind(Duration::from_secs(60));
+        assert_eq!(monotonic(&render(all, fmt)), monotonic(&rendered));
+        let mut opts = all;
+        opts.for_syslog();
+        assert_eq!(render(opts, fmt), "[+2.000000s] x");
+        assert_eq!(render(RAW + MONOTONIC_TS, fmt), "x");
+    }
+
+    #[test]
+    #[serial]
+    fn monotonic_ts_never_decreases() {
+        let fmt = format_args!("x");
+        let values = (0..1000).map(|_| monotonic(&render(MONOTONIC_TS, fmt))).collect::<Vec<_>>();
+        assert!(values.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    #[serial]
+    fn uptime_prefix() {
+        let fmt = format_args!("x");
+        super::reset_uptime_base();
+        let msg = Message::new(&UPTIME, "bin", file!(), None, line!(), &fmt).to_string();
+        let (uptime, rest) = msg.strip_prefix("[+").unwrap().split_once("s] ").unwrap();
+        let (secs, micros) = uptime.split_once('.').unwrap();
+        assert_eq!((secs, micros.len(), rest), ("0", 6, "x"));
+
+        let opts = TIMESTAMP + UPTIME;
+        let msg = Message::new(&opts, "bin", file!(), None, line!(), &fmt);
+        let rendered = msg.to_string();
+        let mut fields = rendered.split(' ');
+        assert_eq!(fields.next().unwrap().len(), "00:00:00".len());
+        assert!(fields.next().unwrap().starts_with("[+0."));
+        assert_eq!(msg.uptime.map(|u| u < Duration::from_secs(1)), Some(true));
+        assert_eq!(render(Options::default(), fmt).find("[+"), None);
+        assert_eq!(render(RAW + UPTIME, fmt), "x");
+    }
+
+    #[test]
+    #[serial]
+    fn prefix_separator() {
+        let opts = SEVERITY + MODULE + PREFIX_SEP;
+        let fmt = format_args!("{{\"a\": \"x | y\"}}");
+        let mut msg = Message::new(&opts, "bin", file!(), None, line!(), &fmt);
+        msg.set_severity(&Level::Info).set_modname("mod");
+        assert_eq!(msg.to_string(), "info mod | {\"a\": \"x | y\"}");
+        let mut named = Message::new(&opts, "bin", file!(), None, line!(), &fmt);
+        named.set_severity(&Level::Info).set_modname("a | b | | c");
+        assert_eq!(named.to_string(), "info a \\| b \\| \\| c | {\"a\": \"x | y\"}");
+
+        super::set_prefix_separator(Some("::".to_owned()));
+        let fmt = format_args!("payload::with::sep");
+        for opts in [NONE, SEVERITY, SEVERITY + MODULE + FILE, RAW] {
+            for opts in [opts, opts + PREFIX_SEP] {
+                let mut msg = Message::new(&opts, "bin", "src/a::b.rs", None, 7, &fmt);
+                msg.set_severity(&Level::Info).set_modname("x::y:::z");
+                let (prefix, payload) = msg.render_split();
+                assert_eq!(payload, "payload::with::sep");
+                let sep = if opts.has(PREFIX_SEP) && !opts.has(RAW) { "::" } else { "" };
+                assert!(sep.is_empty() || !prefix.contains(sep), "{}", prefix);
+                assert_eq!(msg.to_string(), format!("{}{}{}", prefix, sep, payload));
+            }
+        }
+        super::set_prefix_separator(None);
+    }
+
+    #[test]
+    #[serial]
+    fn split_at_first_separator() {
+        let opts = SEVERITY + MODULE + PREFIX_SEP;
+        let fmt = format_args!("a | b::c");
+        for (sep, names) in [
+            (None, ["x |", "x | ", "x  |", "|x", "x ||  | y"]),
+            (Some("::"), ["x:", "x::", "x:::", ":x", "x:y:"]),
+            (Some("aab"), ["xa", "xaa", "xaab", "aaa", "xaaba"]),
+        ] {
+            super::set_prefix_separator(sep.map(str::to_owned));
+            let sep = sep.unwrap_or(super::DEFAULT_PREFIX_SEPARATOR);
+            for name in names {
+                let mut msg = Message::new(&opts, "bin", file!(), None, line!(), &fmt);
+                msg.set_severity(&Level::Info).set_modname(name);
+                let (prefix, payload) = msg.render_split();
+                let rendered = msg.to_string();
+                let (head, tail) = rendered.split_once(sep).unwrap();
+                assert_eq!((head, tail), (prefix.as_str(), payload), "{:?} {:?}", sep, name);
+                assert_eq!(head.replace('\\', ""), format!("info {}", name));
+            }
+        }
+        super::set_prefix_separator(None);
+    }
+
+    #[test]
+    fn instance_brackets() {
+        let fmt = format_args!("x");
+        let (pid, tid) = (std::process::id(), Message::get_current_thread_id());
+        let render = |opts: Options, instance| {
+            let mut msg = Message::new(&opts, "bin", file!(), None, line!(), &fmt);
+            msg.set_instance(instance);
+            msg.to_string()
+        };
+        let cases = [
+            (NONE, "x".to_owned()),
+            (INSTANCE, "[pod-1] x".to_owned()),
+            (PID, format!("[{}] x", pid)),
+            (TID, format!("[{}] x", tid)),
+            (INSTANCE + PID, format!("[pod-1/{}] x", pid)),
+            (INSTANCE + TID, format!("[pod-1/{}] x", tid)),
+            (PID + TID, format!("[{}/{}] x", pid, tid)),
+            (INSTANCE + PID + TID, format!("[pod-1/{}/{}] x", pid, tid)),
+        ];
+        for (opts, expected) in cases {
+            assert_eq!(render(opts, Some("pod-1")), expected, "{}", opts);
+            // without an id INSTANCE has no effect
+            assert_eq!(render(opts, None), render(opts - INSTANCE, Some("pod-1")), "{}", opts);
+        }
+        assert_eq!(render(BINNAME + INSTANCE, Some("pod-1")), "bin[pod-1] x");
+        assert_eq!(render(BINNAME + INSTANCE, None), "bin x");
+        assert_eq!(render(RAW + INSTANCE, Some("pod-1")), "x");
+
+        let mut opts = Options::default() + INSTANCE;
+        opts.for_syslog();
+        assert_eq!(render(opts - FILE - FUNC, Some("pod-1")), format!("[pod-1/{}] x", tid));
+    }
+
+    #[test]
+    #[serial]
+    fn file_paths() {
+        let fmt = format_args!("x");
+        let render = |opts: Options, file| Message::new(&opts, "bin", file, None, 42, &fmt)
+            .to_string();
+        let unix = "/home/ci/build/workspace/crates/netstack/src/conn/tcp.rs";
+        let windows = "C:\\ci\\workspace\\crates\\netstack\\src\\conn\\tcp.rs";
+        assert_eq!(render(FILE, unix), format!("{} x", unix));
+        assert_eq!(render(FILE + FILE_BASENAME, unix), "tcp.rs x");
+        assert_eq!(render(FILE + LINE + FILE_BASENAME, unix), "tcp.rs:42 x");
+        assert_eq!(render(FILE + LINE + FILE_BASENAME, windows), "tcp.rs:42 x");
+        assert_eq!(render(FILE + FILE_BASENAME, "src/main.rs"), "main.rs x");
+        assert_eq!(render(FILE + FILE_BASENAME, "main.rs"), "main.rs x");
+        // LINE without FILE writes nothing
+        assert_eq!(render(LINE + FILE_BASENAME, unix), "x");
+
+        super::add_file_prefix("/home/ci/build/workspace");
+        super::add_file_prefix("/home/ci/build/workspace/crates/netstack");
+        super::add_file_prefix("C:\\ci\\workspace");
+        super::add_file_prefix("/home/ci/build/work");
+        // the longest prefix wins and the remaining path is kept with FILE_BASENAME
+        assert_eq!(render(FILE + LINE, unix), "src/conn/tcp.rs:42 x");
+        assert_eq!(render(FILE + LINE + FILE_BASENAME, unix), "src/conn/tcp.rs:42 x");
+        assert_eq!(render(FILE, "/home/ci/build/workspace/build.rs"), "build.rs x");
+        assert_eq!(render(FILE + LINE, windows), "crates\\netstack\\src\\conn\\tcp.rs:42 x");
+        // only whole components match
+        let sibling = "/home/ci/build/workspace2/a.rs";
+        assert_eq!(render(FILE, sibling), format!("{} x", sibling));
+        assert_eq!(render(FILE + FILE_BASENAME, sibling), "a.rs x");
+        assert_eq!(render(FILE, "/home/ci/build/workspace"), "/home/ci/build/workspace x");
+        super::write_setting(&super::FILE_PREFIXES, Vec::clear);
+    }
+
+    #[test]
+    fn short_func() {
+        use super::short_func;
+        assert_eq!(short_func("myapp::server::handle"), "server::handle");
+        assert_eq!(short_func("handle"), "handle");
+        assert_eq!(short_func(""), "");
+        // nested generics
+        assert_eq!(short_func("myapp::server::handle<hyper::Body, tower::ServiceFn<F>>"),
+            "server::handle");
+        assert_eq!(short_func("a::Pool<Vec<Option<u8>>>::get<'_, T>"), "Pool::get");
+        assert_eq!(short_func("a::b::run<dyn Fn(u8) -> Vec<u8>>"), "b::run");
+        assert_eq!(short_func("a::b::collect::<Vec<_>>"), "b::collect");
+        // trait-qualified paths
+        assert_eq!(short_func("<myapp::conn::Conn<T> as core::fmt::Display>::fmt"),
+            "<Conn as Display>::fmt");
+        assert_eq!(short_func("<std::vec::Vec<u8> as myapp::Encode>::encode::{{closure}}"),
+            "<Vec as Encode>::encode::{{closure}}");
+        // closures and async blocks don't count as segments
+        assert_eq!(short_func("myapp::server::handle::{{closure}}"),
+            "server::handle::{{closure}}");
+        assert_eq!(short_func("myapp::server::handle<S>::{{closure}}::{{closure}}"),
+            "server::handle::{{closure}}::{{closure}}");
+        assert_eq!(short_func("{{closure}}"), "{{closure}}");
+        // unbalanced input doesn't panic
+        assert_eq!(short_func("a::b::c<d::e"), "b::c");
+        assert_eq!(short_func("a::b>::c"), "b>::c");
+    }
+
+    #[test]
+    fn truncate_func() {
+        use super::truncate_func;
+        assert_eq!(truncate_func("server::handle", 14), "server::handle");
+        assert_eq!(truncate_func("server::handle", 20), "server::handle");
+        assert_eq!(truncate_func("server::handle", 8), "server:…");
+        assert_eq!(truncate_func("äöü::ß", 3), "äö…");
+        assert_eq!(truncate_func("handle", 1), "…");
+        assert_eq!(truncate_func("", 1), "");
+    }
+
+    #[test]
+    fn func_rendering() {
+        let fmt = format_args!("x");
+        let func = "myapp::server::handle<hyper::Body>::{{closure}}";
+        let render = |opts: Options, max| {
+            let mut msg = Message::new(&opts, "bin", file!(), Some(func), 42, &fmt);
+            msg.set_func_max_len(max);
+            msg.to_string()
+        };
+        // the full string by default
+        assert_eq!(render(FUNC, None), format!("{} x", func));
+        assert_eq!(render(FUNC + FUNC_SHORT, None), "server::handle::{{closure}} x");
+        assert_eq!(render(FUNC + FUNC_SHORT, Some(10)), "server::h… x");
+        assert_eq!(render(FUNC, Some(8)), "myapp::… x");
+        assert_eq!(render(FUNC_SHORT, Some(8)), "x");
+    }
+
+    #[test]
+    fn syslog_keeps_raw() {
+        let mut opts = Options::default() + RAW;
+        opts.for_syslog();
+        assert!(opts.has(RAW));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{context::Context, level::Level};
+use std::fmt::Write;
+
+/// Format of the document generated by [`export_metadata`](fn@crate::export_metadata)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MetadataFormat {
+    /// A section per [`Scope`](trait@crate::Scope) with a table of its LogKeys
+    Markdown,
+    /// A roff subsection per [`Scope`](trait@crate::Scope) with a tagged paragraph per LogKey,
+    /// meant to be included in the man page of the application
+    Man,
+    /// A heading per [`Scope`](trait@crate::Scope) with an indented line per LogKey, e.g. for
+    /// `--help-logging`
+    PlainText,
+}
+
+// a LogKey as listed in the document
+struct KeyMeta<'a> {
+    name: &'a str,
+    level: Level,
+    facade: &'static str,
+    description: Option<&'static str>,
+}
+
+/*
+ * the document is built from the values recorded at registration only, so it doesn't change
+ * with the configuration at runtime. Scopes are sorted by name and ScopeKey (the internal
+ * scope is named like the application), LogKeys by name.
+ */
+pub (crate) fn render(ctx: &Context, format: MetadataFormat) -> String {
+    let mut scopes = ctx.logmods().filter(|lm| lm.initialized()).map(|lm| {
+        let mut keys = lm.submodules().filter(|s| s.initialized()).map(|s| KeyMeta {
+            name: s.name(),
+            level: s.init_level(),
+            facade: s.init_facade(),
+            description: s.description(),
+        }).collect::<Vec<_>>();
+        // names are case insensitive, the exact name only decides between equal ones
+        keys.sort_by_cached_key(|k| (k.name.to_lowercase(), k.name));
+        (lm.name(), lm.key(), keys)
+    }).collect::<Vec<_>>();
+    scopes.sort_by(|a, b| (a.0, a.1 as usize).cmp(&(b.0, b.1 as usize)));
+
+    let mut out = String::new();
+    for (idx, (name, scope, keys)) in scopes.iter().enumerate() {
+        let heading = format!("{} ({})", name, scope);
+        // writing to a String can't fail
+        let _ = match format {
+            MetadataFormat::Markdown => markdown(&mut out, idx == 0, &heading, keys),
+            MetadataFormat::Man => man(&mut out, &heading, keys),
+            MetadataFormat::PlainText => plain(&mut out, idx == 0, &heading, keys),
+        };
+    }
+    out
+}
+
+fn markdown(out: &mut String, first: bool, heading: &str, keys: &[KeyMeta]) -> std::fmt::Result {
+    let cell = |s: &str| s.replace('|', "\\|");
+    if !first {
+        out.push('\n');
+    }
+    writeln!(out, "## {}\n", cell(heading))?;
+    writeln!(out, "| LogKey | Level | Facade | Description |")?;
+    writeln!(out, "|--------|-------|--------|-------------|")?;
+    for key in keys {
+        writeln!(out, "| {} | {} | {} | {} |", cell(key.name), key.level, key.facade,
+            key.description.map_or("-".to_owned(), cell))?;
+    }
+    Ok(())
+}
+
+fn man(out: &mut String, heading: &str, keys: &[KeyMeta]) -> std::fmt::Result {
+    // a leading dot or quote would start a request
+    let text = |s: &str| {
+        let s = s.replace('\\', "\\e").replace('-', "\\-");
+        match s.starts_with(['.', '\'']) {
+            true => format!("\\&{}", s),
+            false => s,
+        }
+    };
+    writeln!(out, ".SS {}", text(heading))?;
+    for key in keys {
+        writeln!(out, ".TP\n.B {}", text(key.name))?;
+        write!(out, "level {}, facade {}", key.level, key.facade)?;
+        match key.description {
+            Some(d) => writeln!(out, "\n.br\n{}", text(d))?,
+            None => out.push('\n'),
+        }
+    }
+    Ok(())
+}
+
+fn plain(out: &mut String, first: bool, heading: &str, keys: &[KeyMeta]) -> std::fmt::Result {
+    if !first {
+        out.push('\n');
+    }
+    writeln!(out, "{}:", heading)?;
+    let width = keys.iter().map(|k| k.name.chars().count()).max().unwrap_or_default();
+    for key in keys {
+        let defaults = format!("level={} facade={}", key.level, key.facade);
+        match key.description {
+            Some(d) => writeln!(out, "  {:width$}  {:28}  {}", key.name, defaults, d)?,
+            None => writeln!(out, "  {:width$}  {}", key.name, defaults)?,
+        }
+    }
+    Ok(())
+}
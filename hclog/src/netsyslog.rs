@@ -0,0 +1,408 @@
+/*
+ * Send messages as RFC 5424 frames to a remote syslog collector
+ *
+ * Both facades never fail or panic a log call: a message which can't be sent is dropped and
+ * counted in the facade_failures of health_summary, as is every message of a facade with an
+ * unknown facility. The address is kept as given and resolved when the first message is sent,
+ * so neither parsing a FacadeVariant nor creating the facade waits for a DNS lookup.
+ *
+ * The facades of all LogKeys sending to the same address share one UDP socket or TCP sender
+ * thread, looked up by the address as given. The facility is part of every frame, so it doesn't
+ * need a connection of its own. The socket or thread goes away with the last facade using it.
+ *
+ * The TCP facade hands the frames to a background thread via a bounded queue, a frame which
+ * doesn't fit is dropped. The thread connects with the first frame and reconnects after a
+ * failure, but waits for a backoff doubling up to MAX_BACKOFF between the attempts. Frames
+ * queued while waiting are dropped as well. So a stalled collector never blocks the logging
+ * thread, which still holds the context.
+ */
+use crate::{
+    clock,
+    facades::{severity, try_facility, LogFacade, FACADE_FAILURES},
+    level::Level,
+    message::Message,
+    Result,
+};
+use libc::c_int;
+use std::{
+    io::Write,
+    net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket},
+    sync::{atomic::Ordering, mpsc::{self, Receiver, SyncSender}, Arc, Mutex, OnceLock, Weak},
+    thread,
+    time::{Duration, Instant},
+};
+
+const MIN_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+// frames queued for the sender thread of the TCP facade
+const QUEUE_LEN: usize = 1024;
+
+// header fields are printable US-ASCII without spaces, limited to the lengths of RFC 5424
+fn header_field(out: &mut Vec<u8>, value: &str, max: usize) {
+    let start = out.len();
+    out.extend(value.bytes().take(max).map(|b| if b.is_ascii_graphic() { b } else { b'_' }));
+    if out.len() == start {
+        out.push(b'-');
+    }
+}
+
+// name of this host, `-` if it's unknown
+fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    let res = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    match std::str::from_utf8(&buf[..len]) {
+        Ok(name) if res == 0 && !name.is_empty() => name.to_owned(),
+        _ => "-".to_owned(),
+    }
+}
+
+/*
+ * <PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID - MSG
+ *
+ * APP-NAME is the name of the Scope, MSGID the name of the LogKey. There is no structured
+ * data and the message is the payload without the prefix of the Options.
+ */
+pub (crate) fn frame(facility: c_int, hostname: &str, level: Level, msg: &Message)
+    -> Result<Vec<u8>>
+{
+    let pri = facility | severity(level)?;
+    let mut out = format!("<{}>1 {} ", pri, msg.time().format("%Y-%m-%dT%H:%M:%S%.6fZ"))
+        .into_bytes();
+    header_field(&mut out, hostname, 255);
+    out.push(b' ');
+    header_field(&mut out, msg.scope_name(), 48);
+    let _ = write!(out, " {} ", std::process::id());
+    header_field(&mut out, msg.module(), 32);
+    out.extend_from_slice(b" - ");
+    out.extend_from_slice(msg.payload().as_bytes());
+    Ok(out)
+}
+
+fn failed() {
+    FACADE_FAILURES.fetch_add(1, Ordering::Relaxed);
+}
+
+// UDP sockets and TCP sender threads by their address, owned by the facades using them
+type Registry<T> = Mutex<Vec<(String, Weak<T>)>>;
+static UDP_TARGETS: Registry<UdpTarget> = Mutex::new(Vec::new());
+static TCP_SENDERS: Registry<TcpSender> = Mutex::new(Vec::new());
+
+// the entry of `addr` if a facade still uses it, a new one otherwise
+fn shared<T>(registry: &Registry<T>, addr: &str, new: impl FnOnce() -> T) -> Arc<T> {
+    let mut entries = registry.lock().unwrap_or_else(|e| e.into_inner());
+    entries.retain(|(_, entry)| entry.strong_count() > 0);
+    if let Some(entry) = entries.iter().filter(|(a, _)| a == addr).find_map(|(_, e)| e.upgrade()) {
+        return entry;
+    }
+    let entry = Arc::new(new());
+    entries.push((addr.to_owned(), Arc::downgrade(&entry)));
+    entry
+}
+
+// a UDP socket connected with the first message
+#[derive(Debug)]
+struct UdpTarget {
+    addr: String,
+    // None if the address can't be resolved or no socket could be bound
+    socket: OnceLock<Option<UdpSocket>>,
+}
+impl UdpTarget {
+    fn socket(&self) -> Option<&UdpSocket> {
+        let connect = |addr: SocketAddr| {
+            let local: SocketAddr = match addr {
+                SocketAddr::V4(_) => ([0, 0, 0, 0], 0).into(),
+                SocketAddr::V6(_) => ([0u16; 8], 0).into(),
+            };
+            UdpSocket::bind(local).and_then(|s| s.connect(addr).map(|()| s)).ok()
+        };
+        self.socket.get_or_init(|| {
+            self.addr.to_socket_addrs().ok().into_iter().flatten().find_map(connect)
+        }).as_ref()
+    }
+}
+
+// Log to a remote syslog via UDP, one datagram per message
+#[derive(Debug)]
+pub struct SyslogUdp {
+    facility: Option<c_int>,
+    hostname: String,
+    target: Arc<UdpTarget>,
+}
+impl SyslogUdp {
+    pub (crate) fn init(addr: &str, opt: &str) -> Self {
+        let target = shared(&UDP_TARGETS, addr, || UdpTarget {
+            addr: addr.to_owned(),
+            socket: OnceLock::new(),
+        });
+        Self { facility: try_facility(opt), hostname: hostname(), target }
+    }
+}
+impl LogFacade for SyslogUdp {
+    fn variant_name(&self) -> &'static str { "syslog-udp" }
+    fn log(&self, level: Level, msg: Message) -> Result<()> {
+        let (Some(facility), Some(socket)) = (self.facility, self.target.socket()) else {
+            failed();
+            return Ok(());
+        };
+        let frame = frame(facility, &self.hostname, level, &msg)?;
+        if socket.send(&frame).is_err() {
+            failed();
+        }
+        Ok(())
+    }
+}
+
+// Log to a remote syslog via TCP with octet-counted framing (RFC 6587)
+#[derive(Debug)]
+pub struct SyslogTcp {
+    facility: Option<c_int>,
+    hostname: String,
+    sender: Arc<TcpSender>,
+}
+// the queue of a sender thread, the thread ends once the queue is dropped
+#[derive(Debug)]
+struct TcpSender {
+    // None if the thread couldn't be started
+    queue: Option<SyncSender<Command>>,
+}
+impl TcpSender {
+    fn start(addr: &str) -> Self {
+        let (queue, frames) = mpsc::sync_channel(QUEUE_LEN);
+        let conn = TcpConn::new(addr);
+        let sender = thread::Builder::new()
+            .name("hclog-syslog-tcp".to_owned())
+            .spawn(move || conn.run(frames));
+        Self { queue: sender.ok().map(|_| queue) }
+    }
+}
+enum Command {
+    Frame(Vec<u8>),
+    // answered once the frames queued before are handled
+    Flush(SyncSender<()>),
+}
+#[derive(Debug)]
+struct TcpConn {
+    addr: String,
+    stream: Option<TcpStream>,
+    // no connection attempt before this point in time
+    retry_at: Option<Instant>,
+    backoff: Duration,
+}
+impl TcpConn {
+    fn new(addr: &str) -> Self {
+        Self { addr: addr.to_owned(), stream: None, retry_at: None, backoff: MIN_BACKOFF }
+    }
+    fn connect(&self) -> Option<TcpStream> {
+        let addrs = self.addr.to_socket_addrs().ok()?;
+        let stream = addrs.into_iter()
+            .find_map(|addr| TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).ok())?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT)).ok()?;
+        Some(stream)
+    }
+    fn stream(&mut self) -> Option<&mut TcpStream> {
+        if self.stream.is_none() {
+            let now = clock::instant();
+            if self.retry_at.is_some_and(|at| now < at) {
+                return None;
+            }
+            match self.connect() {
+                Some(stream) => {
+                    self.stream = Some(stream);
+                    self.retry_at = None;
+                    self.backoff = MIN_BACKOFF;
+                }
+                None => {
+                    self.retry_at = now.checked_add(self.backoff);
+                    self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        }
+        self.stream.as_mut()
+    }
+    fn send(&mut self, frame: &[u8]) {
+        let sent = self.stream().is_some_and(|stream| {
+            write!(stream, "{} ", frame.len()).and_then(|()| stream.write_all(frame)).is_ok()
+        });
+        if !sent {
+            // reconnect with the next frame
+            self.stream = None;
+            failed();
+        }
+    }
+    // runs until the facade is dropped and the frames queued before are sent
+    fn run(mut self, queue: Receiver<Command>) {
+        for cmd in queue {
+            match cmd {
+                Command::Frame(frame) => self.send(&frame),
+                Command::Flush(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+}
+impl SyslogTcp {
+    pub (crate) fn init(addr: &str, opt: &str) -> Self {
+        Self {
+            facility: try_facility(opt),
+            hostname: hostname(),
+            sender: shared(&TCP_SENDERS, addr, || TcpSender::start(addr)),
+        }
+    }
+}
+impl LogFacade for SyslogTcp {
+    fn variant_name(&self) -> &'static str { "syslog-tcp" }
+    fn log(&self, level: Level, msg: Message) -> Result<()> {
+        let (Some(facility), Some(ref queue)) = (self.facility, &self.sender.queue) else {
+            failed();
+            return Ok(());
+        };
+        let frame = frame(facility, &self.hostname, level, &msg)?;
+        if queue.try_send(Command::Frame(frame)).is_err() {
+            failed();
+        }
+        Ok(())
+    }
+    // wait for the queued frames, but not longer than a connect and a write may take. Skipped
+    // if the queue is full.
+    fn flush(&self) -> Result<()> {
+        if let Some(ref queue) = self.sender.queue {
+            let (done, wait) = mpsc::sync_channel(1);
+            if queue.try_send(Command::Flush(done)).is_ok() {
+                let _ = wait.recv_timeout(CONNECT_TIMEOUT + WRITE_TIMEOUT);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod netsyslog_tests {
+    use super::{SyslogTcp, SyslogUdp, TcpConn, MIN_BACKOFF};
+    use crate::{
+        facades::{LogFacade, FACADE_FAILURES},
+        level::Level,
+        message::Message,
+        options::Options,
+    };
+    use std::{
+        io::Read,
+        net::{TcpListener, UdpSocket},
+        sync::{atomic::Ordering, Arc},
+    };
+
+    fn log_to(facade: &dyn LogFacade, level: Level, line: std::fmt::Arguments) {
+        let opts = Options::default();
+        let mut msg = Message::new(&opts, "net app", file!(), None, line!(), &line);
+        msg.set_modname("netkey");
+        facade.log(level, msg).unwrap();
+    }
+
+    // everything but the timestamp and the hostname, which differ between the runs
+    fn strip_header(frame: &str) -> (String, String) {
+        let parts = frame.splitn(4, ' ').collect::<Vec<_>>();
+        let time = parts[1];
+        assert_eq!(time.len(), "2024-01-01T00:00:00.000000Z".len(), "{}", frame);
+        assert!(time.ends_with('Z') && time.as_bytes()[10] == b'T', "{}", frame);
+        assert!(!parts[2].is_empty() && !parts[2].contains(' '), "{}", frame);
+        (parts[0].to_owned(), parts[3].to_owned())
+    }
+
+    #[test]
+    fn udp_frame() {
+        let collector = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let facade = SyslogUdp::init(&collector.local_addr().unwrap().to_string(), "local0");
+        log_to(&facade, Level::Warn, format_args!("disk {} full", "/var"));
+        log_to(&facade, Level::Debug7, format_args!("debug"));
+
+        let mut buf = [0u8; 512];
+        let len = collector.recv(&mut buf).unwrap();
+        let (pri, rest) = strip_header(std::str::from_utf8(&buf[..len]).unwrap());
+        // local0 (16) * 8 + warning (4)
+        assert_eq!(pri, "<132>1");
+        assert_eq!(rest, format!("net_app {} netkey - disk /var full", std::process::id()));
+        let len = collector.recv(&mut buf).unwrap();
+        let (pri, _) = strip_header(std::str::from_utf8(&buf[..len]).unwrap());
+        assert_eq!(pri, "<135>1");
+    }
+
+    #[test]
+    fn tcp_octet_counting() {
+        let collector = TcpListener::bind("127.0.0.1:0").unwrap();
+        let facade = SyslogTcp::init(&collector.local_addr().unwrap().to_string(), "user");
+        log_to(&facade, Level::Error, format_args!("first"));
+        log_to(&facade, Level::Info, format_args!("second message"));
+
+        let (mut conn, _) = collector.accept().unwrap();
+        drop(facade);
+        let mut frames = String::new();
+        conn.read_to_string(&mut frames).unwrap();
+        let mut rest = frames.as_str();
+        let mut received = Vec::new();
+        while !rest.is_empty() {
+            let (len, tail) = rest.split_once(' ').unwrap();
+            let (frame, tail) = tail.split_at(len.parse().unwrap());
+            received.push(strip_header(frame));
+            rest = tail;
+        }
+        let pid = std::process::id();
+        assert_eq!(received, [
+            ("<11>1".to_owned(), format!("net_app {} netkey - first", pid)),
+            ("<14>1".to_owned(), format!("net_app {} netkey - second message", pid)),
+        ]);
+    }
+
+    #[test]
+    fn shared_per_address() {
+        let collector = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = collector.local_addr().unwrap().to_string();
+        let first = SyslogTcp::init(&addr, "user");
+        let second = SyslogTcp::init(&addr, "local0");
+        assert!(Arc::ptr_eq(&first.sender, &second.sender));
+        log_to(&first, Level::Error, format_args!("first"));
+        log_to(&second, Level::Error, format_args!("second"));
+        let (mut conn, _) = collector.accept().unwrap();
+        drop((first, second));
+        let mut frames = String::new();
+        conn.read_to_string(&mut frames).unwrap();
+        assert!(frames.contains("<11>1") && frames.contains("<131>1"), "{}", frames);
+
+        // the address is resolved with the first message
+        let udp = SyslogUdp::init("collector.invalid:514", "user");
+        assert!(udp.target.socket.get().is_none());
+        assert!(Arc::ptr_eq(&udp.target, &SyslogUdp::init("collector.invalid:514", "user").target));
+        assert!(!Arc::ptr_eq(&udp.target, &SyslogUdp::init("127.0.0.1:514", "user").target));
+    }
+
+    #[test]
+    fn tcp_backoff() {
+        // nothing listens on the port of the dropped listener
+        let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let mut conn = TcpConn::new(&addr.to_string());
+        let before = FACADE_FAILURES.load(Ordering::Relaxed);
+        conn.send(b"refused");
+        conn.send(b"waiting for the backoff");
+        assert!(FACADE_FAILURES.load(Ordering::Relaxed) >= before + 2);
+        assert!(conn.stream.is_none() && conn.retry_at.is_some());
+        assert_eq!(conn.backoff, MIN_BACKOFF * 2);
+    }
+
+    #[test]
+    fn never_panics() {
+        let before = FACADE_FAILURES.load(Ordering::Relaxed);
+        let facades: [Box<dyn LogFacade>; 4] = [
+            Box::new(SyslogUdp::init("127.0.0.1:514", "bogus")),
+            Box::new(SyslogUdp::init("no port", "user")),
+            Box::new(SyslogTcp::init("127.0.0.1:601", "bogus")),
+            Box::new(SyslogTcp::init("no port", "user")),
+        ];
+        for facade in &facades {
+            log_to(facade.as_ref(), Level::Info, format_args!("dropped"));
+            facade.flush().unwrap();
+        }
+        assert!(FACADE_FAILURES.load(Ordering::Relaxed) >= before + 4);
+    }
+}
@@ -8,23 +8,12 @@
 //! [`unset_module_options`](fn@crate::unset_module_options) and
 //! [`reset_module_options`](fn@crate::reset_module_options) functions.
 //!
-use crate::{Result, util::read_var_from_env};
-use std::ops::{Add, AddAssign, Sub, SubAssign};
-/*
- * known environment variables
- *
- * This variables can be used to alter or define the logging behaviour
- *
- * The syntax for HCLOG_FACADE and HCLOG_LEVEL is always:
- *      HCLOG_ENVVAR="<module>:<value>"
- * where <module> is the module to set <value> for. To set a <value> for
- * all known Modules in Context _all can be used.
- * <value> is restricted to known values of the addressed config (see level.rs
- * or facades.rs)
- */
-pub (crate) const ENV_OPT_PREFIX: &str = "HCLOG_OPT_";
-pub (crate) const ENV_OPT_FACADE: &str = "HCLOG_FACADE";
-pub (crate) const ENV_OPT_LEVEL: &str = "HCLOG_LEVEL";
+use crate::{Result, ErrorKind, FacadeVariant, env::{self, read_env_var, ENV_OPT_PREFIX}};
+use std::io::IsTerminal;
+use std::ops::{
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Sub,
+    SubAssign,
+};
 
 /*
  * Output format options:
@@ -83,6 +72,116 @@ pub const LOGCOMPAT: Options = Options(0x2000);
 /// as set on library initialization. If the level is set to `INFO` only messages with the
 /// level `INFO` are printed.
 pub const EXACT_LVL_MATCH: Options = Options(0x4000);
+/// Suppress identical consecutive messages
+///
+/// When DEDUP is set a message which is identical to the previous message of the same LogKey
+/// and logged with the same level is suppressed. Once a different message arrives, the
+/// interval set via [`set_dedup_interval`](fn@crate::set_dedup_interval) elapsed or
+/// [`flush`](fn@crate::flush) is called a single line `last message repeated N times` is
+/// written instead.
+pub const DEDUP: Options = Options(0x8000);
+/// Log messages are written without any decoration
+///
+/// When RAW is set only the formatted message is written. All other formatting options are
+/// ignored. This is intended for output which is consumed by other programs line by line.
+pub const RAW: Options = Options(0x10000);
+/// Set the syslog ident to the name of the Scope
+///
+/// When SYSLOG_IDENT is set the [`Syslog`](enum@crate::FacadeVariant#variant.Syslog) facade
+/// calls `openlog(3)` with the name of the Scope, so the entries show this name instead of the
+/// process name. The ident is process wide, the last facade opened with this option wins.
+pub const SYSLOG_IDENT: Options = Options(0x20000);
+/// Log messages are prefixed with the time elapsed since the uptime base
+///
+/// The elapsed time is written in seconds with microsecond precision, e.g. `[+12.345678s]`,
+/// right after the timestamp. The base is taken when the first [`Scope`](trait@crate::Scope) is
+/// initialized and can be moved with [`reset_uptime_base`](fn@crate::reset_uptime_base), e.g.
+/// at the end of the startup. Syslog doesn't record the elapsed time, so this option is kept
+/// for the [`Syslog`](enum@crate::FacadeVariant#variant.Syslog) facade.
+pub const UPTIME: Options = Options(0x40000);
+/// Messages at [`Error`](crate::Level::Error) or worse are followed by a backtrace
+///
+/// When BACKTRACE is set the stack is captured for messages with the level
+/// [`Error`](crate::Level::Error) or a more severe one and appended to the message, one frame
+/// per line. The capture ignores `RUST_BACKTRACE`, but it's expensive and only done for those
+/// levels and for messages which are written: messages dropped by sampling or suppressed by
+/// [`DEDUP`](const@crate::DEDUP) don't capture the stack, and DEDUP compares the messages
+/// without it. If backtraces aren't supported on the platform the first of these messages notes
+/// this instead.
+pub const BACKTRACE: Options = Options(0x80000);
+/// Separate the prefix from the message with an unambiguous sequence
+///
+/// When PREFIX_SEP is set the separator, `" | "` by default, is written between the prefix and
+/// the message, so consumers can split a line at the first separator. The separator is set via
+/// [`set_prefix_separator`](fn@crate::set_prefix_separator). If it appears within the prefix,
+/// e.g. in the name of a LogKey, it's escaped with a backslash after its first character. The
+/// message itself is written unchanged.
+pub const PREFIX_SEP: Options = Options(0x100000);
+/// Log messages are prefixed with the instance id of the Scope
+///
+/// When INSTANCE is set the id passed to [`set_instance_id`](fn@crate::set_instance_id) or
+/// read from `HCLOG_INSTANCE_ID` is written in the brackets of [`PID`] and [`TID`], e.g.
+/// `[pod-7f9c/42/3]`. Within containers the process id is mostly 1 and meaningless, the
+/// instance id tells the replicas apart. Syslog doesn't know the instance, so this option is
+/// kept for the [`Syslog`](enum@crate::FacadeVariant#variant.Syslog) facade. Without an id
+/// set the option has no effect.
+pub const INSTANCE: Options = Options(0x200000);
+/// Log messages are prefixed with the file name only instead of its path
+///
+/// When FILE_BASENAME is set together with [`FILE`] only the final component of the path is
+/// written, e.g. `tcp.rs` instead of `/home/ci/build/crates/netstack/src/conn/tcp.rs`. Both
+/// `/` and `\` separate the components. If a prefix set via
+/// [`add_file_prefix_strip`](fn@crate::add_file_prefix_strip) was removed from the path, the
+/// remaining path is written instead.
+pub const FILE_BASENAME: Options = Options(0x400000);
+/// The function name is shortened to its last two path segments without generic arguments
+///
+/// When FUNC_SHORT is set together with [`FUNC`] the generic arguments are removed and only the
+/// last two segments of the path are written, e.g. `server::handle` instead of
+/// `myapp::server::handle<hyper::Body, tower::ServiceFn<F>>`. Segments of closures like
+/// `{{closure}}` are kept but not counted, the types of trait-qualified paths are shortened to
+/// their name (`<Conn as Display>::fmt`). The length can be limited further via
+/// [`set_func_max_len`](fn@crate::set_func_max_len).
+pub const FUNC_SHORT: Options = Options(0x800000);
+/// Log messages are prefixed with a monotonic timestamp instead of the time of day
+///
+/// When MONOTONIC_TS is set the time elapsed since a fixed epoch of the process is written
+/// with nanosecond precision and at least seven digits for the seconds, e.g.
+/// `MT+0000123.456789012`. Unlike the time of day it's never adjusted (e.g. by NTP), so the
+/// values of successive messages never decrease. The epoch is taken when the first
+/// [`Scope`](trait@crate::Scope) is initialized and is the initial base of [`UPTIME`], but it
+/// isn't moved by [`reset_uptime_base`](fn@crate::reset_uptime_base). MONOTONIC_TS takes
+/// precedence over [`TIMESTAMP`], [`DATESTAMP`] and [`NANOSEC`], which are ignored while it's
+/// set. Like those it's removed for the
+/// [`Syslog`](enum@crate::FacadeVariant#variant.Syslog) facade.
+pub const MONOTONIC_TS: Options = Options(0x1000000);
+
+/*
+ * Presets
+ *
+ * The operators can't be used in const context, so the presets are assembled from the bits.
+ */
+/// Options removed for messages sent to syslog
+///
+/// Those properties are already part of each syslog message.
+pub const OPTIONS_FOR_SYSLOG_MASK: Options = Options(
+    TIMESTAMP.0 | DATESTAMP.0 | NANOSEC.0 | MONOTONIC_TS.0 | BINNAME.0 | PID.0 | SEVERITY.0
+);
+/// All properties of a message (same as [`Options::default`])
+pub const OPTIONS_VERBOSE: Options = Options(
+    LINEBUFFERED.0 | TIMESTAMP.0 | DATESTAMP.0 | NANOSEC.0 | BINNAME.0 | PID.0 | TID.0 |
+    MODULE.0 | SEVERITY.0 | FUNC.0 | FILE.0 | LINE.0 | LOGCOMPAT.0
+);
+/// Options for the [`Syslog`](enum@crate::FacadeVariant#variant.Syslog) facade
+///
+/// Same as [`OPTIONS_VERBOSE`] without the properties in [`OPTIONS_FOR_SYSLOG_MASK`].
+pub const OPTIONS_FOR_SYSLOG: Options = Options(OPTIONS_VERBOSE.0 & !OPTIONS_FOR_SYSLOG_MASK.0);
+/// Short messages with the time, severity and module name only
+pub const OPTIONS_COMPACT: Options = Options(
+    LINEBUFFERED.0 | TIMESTAMP.0 | SEVERITY.0 | MODULE.0 | LOGCOMPAT.0
+);
+/// All properties of a message including the [`SCOPE`]
+pub const OPTIONS_DEBUG: Options = Options(OPTIONS_VERBOSE.0 | SCOPE.0);
 
 #[allow(clippy::suspicious_arithmetic_impl)]
 impl Add for Options {
@@ -107,8 +206,48 @@ impl SubAssign for Options {
         *self = *self - rhs;
     }
 }
+impl BitOr for Options {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+impl BitAnd for Options {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+impl BitXor for Options {
+    type Output = Self;
+    fn bitxor(self, rhs: Self) -> Self {
+        Self(self.0 ^ rhs.0)
+    }
+}
+impl BitOrAssign for Options {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+impl BitAndAssign for Options {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+impl BitXorAssign for Options {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.0 ^= rhs.0;
+    }
+}
+// limited to the known flags like Options::complement
+impl Not for Options {
+    type Output = Self;
+    fn not(self) -> Self {
+        self.complement()
+    }
+}
 
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Copy, Clone, Eq, PartialEq)]
 /// Configuration options for the log messages
 ///
 /// [Options] is a bitfield struct that can be used to set the format of the log messages.
@@ -121,11 +260,15 @@ impl SubAssign for Options {
 /// manipulated using the arithmetic operators `+` and `-`. Eventhough the Options struct is a
 /// bitfield internaly we decided to use the arithmetic operators because its actually _adding_
 /// (`+`) or _removing_ (`-`) options. This makes the code more readable and easier to understand.
+/// For code used to bitflags the bitwise operators are available as well, see
+/// [Operators](#operators).
 ///
 /// All Options can also be set via environment variables. The environment variables are prefixed
 /// with `HCLOG_OPT_` and the name of the option in uppercase. For example to set the `LINEBUFFERED`
 /// option via environment variable you would set `HCLOG_OPT_LINEBUFFERED=1`. To unset the option
-/// you would set `HCLOG_OPT_LINEBUFFERED=0`.
+/// you would set `HCLOG_OPT_LINEBUFFERED=0`. The variables which changed the options of a
+/// [`Scope`](trait@crate::Scope) are listed by [`env_overrides`](fn@crate::env_overrides),
+/// [`ignore_env`](fn@crate::ignore_env) disables them.
 ///
 /// The default options are:
 /// [`LINEBUFFERED`](const@crate::LINEBUFFERED), [`TIMESTAMP`](const@crate::TIMESTAMP),
@@ -138,6 +281,32 @@ impl SubAssign for Options {
 /// `DATESTAMP`, `NANOSEC`, `BINNAME`, `PID` and `SEVERITY` are ignored and not printed because
 /// they are set by the syslog daemon.
 ///
+/// # Operators
+///
+/// Both styles can be mixed, for each operation there is a method as well:
+///
+/// | arithmetic   | bitwise       | method                        |
+/// |--------------|---------------|-------------------------------|
+/// | `a + b`      | `a \| b`      | `a.union(b)`                  |
+/// | `a - b`      | `a & !b`      | `a.difference(b)`             |
+/// |              | `a & b`       | `a.intersection(b)`           |
+/// |              | `a ^ b`       | `a.symmetric_difference(b)`   |
+/// |              | `!a`          | `a.complement()`              |
+/// | `a.has(b)`   | `a & b == b`  | `a.contains(b)`               |
+///
+/// `!` only yields the options defined in this module. Options are a set, not a number, so
+/// they can be compared for equality but aren't ordered. Whether one contains the other is
+/// checked via [`contains`](Options::contains), whether they share any option via
+/// [`intersects`](Options::intersects).
+///
+/// ```rust
+/// use hclog::options::{Options, PID, TID, TIMESTAMP};
+///
+/// let opts = Options::default();
+/// assert_eq!(opts - PID + TIMESTAMP, (opts & !PID) | TIMESTAMP);
+/// assert!(opts.contains(PID + TID));
+/// ```
+///
 /// # Available options:
 ///
 /// * [`LINEBUFFERED`](const@crate::LINEBUFFERED): log messages are written line buffered
@@ -155,36 +324,191 @@ impl SubAssign for Options {
 /// * [`LINE`](const@crate::LINE): log messages are prefixed with the line number
 /// * [`LOGCOMPAT`](const@crate::LOGCOMPAT): enable compatibility with the log crate
 /// * [`EXACT_LVL_MATCH`](const@crate::EXACT_LVL_MATCH): log messages are prefixed with the exact level match
+/// * [`DEDUP`](const@crate::DEDUP): identical consecutive log messages are suppressed
+/// * [`RAW`](const@crate::RAW): log messages are written without any prefix
+/// * [`SYSLOG_IDENT`](const@crate::SYSLOG_IDENT): the syslog ident is set to the name of the scope
+/// * [`UPTIME`](const@crate::UPTIME): log messages are prefixed with the elapsed time
+/// * [`BACKTRACE`](const@crate::BACKTRACE): error messages are followed by a backtrace
+/// * [`PREFIX_SEP`](const@crate::PREFIX_SEP): the prefix is separated from the message
+/// * [`INSTANCE`](const@crate::INSTANCE): log messages are prefixed with the instance id
+/// * [`FILE_BASENAME`](const@crate::FILE_BASENAME): only the file name is written, not its path
+/// * [`FUNC_SHORT`](const@crate::FUNC_SHORT): the function name is shortened
+/// * [`MONOTONIC_TS`](const@crate::MONOTONIC_TS): log messages are prefixed with a monotonic timestamp
+///
+/// # Presets
+///
+/// * [`OPTIONS_VERBOSE`]: the default options
+/// * [`OPTIONS_FOR_SYSLOG`]: the default options without the properties added by syslog
+/// * [`OPTIONS_COMPACT`]: timestamp, severity and module name only
+/// * [`OPTIONS_DEBUG`]: the default options plus the scope
+///
+/// ```rust
+/// use hclog::{options::OPTIONS_FOR_SYSLOG, Scope};
+/// # use hclog::{Level, FacadeVariant};
+/// # use hclog_macros::HCLog;
+/// # #[derive(HCLog, Copy, Clone)]
+/// # enum Keys { SL }
+///
+/// Keys::init("app", Level::Info, FacadeVariant::Syslog("daemon".into()), OPTIONS_FOR_SYSLOG)
+///     .unwrap();
+/// ```
 ///
-pub struct Options(u16);
+pub struct Options(u32);
 impl Default for Options {
     fn default() -> Self {
-        LINEBUFFERED + TIMESTAMP + DATESTAMP + NANOSEC + BINNAME +
-        PID + TID + MODULE + SEVERITY + FUNC + FILE + LINE + LOGCOMPAT
+        OPTIONS_VERBOSE
     }
 }
+/*
+ * names of all known flags as used by Debug, Display and the flag name conversion
+ * ordered by bit position, OptionsIter relies on that
+ *
+ * NOTE: the name of LOGCOMPAT differs from the environment variable (LOG_COMPAT)
+ */
+const FLAG_NAMES: [(&str, Options); 25] = [
+    ("LINEBUFFERED", LINEBUFFERED),
+    ("TIMESTAMP", TIMESTAMP),
+    ("DATESTAMP", DATESTAMP),
+    ("NANOSEC", NANOSEC),
+    ("BINNAME", BINNAME),
+    ("PID", PID),
+    ("TID", TID),
+    ("MODULE", MODULE),
+    ("SEVERITY", SEVERITY),
+    ("SCOPE", SCOPE),
+    ("FUNC", FUNC),
+    ("FILE", FILE),
+    ("LINE", LINE),
+    ("LOGCOMPAT", LOGCOMPAT),
+    ("EXACT_LVL_MATCH", EXACT_LVL_MATCH),
+    ("DEDUP", DEDUP),
+    ("RAW", RAW),
+    ("SYSLOG_IDENT", SYSLOG_IDENT),
+    ("UPTIME", UPTIME),
+    ("BACKTRACE", BACKTRACE),
+    ("PREFIX_SEP", PREFIX_SEP),
+    ("INSTANCE", INSTANCE),
+    ("FILE_BASENAME", FILE_BASENAME),
+    ("FUNC_SHORT", FUNC_SHORT),
+    ("MONOTONIC_TS", MONOTONIC_TS),
+];
+// all known flags, the complement of an Options value is limited to those
+const ALL_FLAGS: u32 = {
+    let mut bits = 0;
+    let mut i = 0;
+    while i < FLAG_NAMES.len() {
+        bits |= FLAG_NAMES[i].1 .0;
+        i += 1;
+    }
+    bits
+};
+impl IntoIterator for Options {
+    type Item = (&'static str, Options);
+    type IntoIter = OptionsIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the set flags of an [`Options`] value
+///
+/// Created by [`Options::iter`].
+#[derive(Debug, Clone)]
+pub struct OptionsIter {
+    // remaining flags - the lowest set bit is the next one yielded
+    bits: u32,
+}
+impl Iterator for OptionsIter {
+    type Item = (&'static str, Options);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.bits != 0 {
+            let pos = self.bits.trailing_zeros() as usize;
+            self.bits &= self.bits - 1;
+            if let Some(entry) = FLAG_NAMES.get(pos) {
+                return Some(*entry);
+            }
+        }
+        None
+    }
+}
+
 impl std::fmt::Debug for Options {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("[")?;
-        if self.has(LINEBUFFERED) { f.write_str("LINEBUFFERED, ")?; }
-        if self.has(TIMESTAMP) { f.write_str("TIMESTAMP, ")?; }
-        if self.has(DATESTAMP) { f.write_str("DATESTAMP, ")?; }
-        if self.has(NANOSEC) { f.write_str("NANOSEC, ")?; }
-        if self.has(BINNAME) { f.write_str("BINNAME, ")?; }
-        if self.has(PID) { f.write_str("PID, ")?; }
-        if self.has(TID) { f.write_str("TID, ")?; }
-        if self.has(MODULE) { f.write_str("MODULE, ")?; }
-        if self.has(SEVERITY) { f.write_str("SEVERITY, ")?; }
-        if self.has(SCOPE) { f.write_str("SCOPE, ")?; }
-        if self.has(FUNC) { f.write_str("FUNC, ")?; }
-        if self.has(FILE) { f.write_str("FILE, ")?; }
-        if self.has(LINE) { f.write_str("LINE, ")?; }
-        if self.has(LOGCOMPAT) { f.write_str("LOGCOMPAT, ")?; }
-        if self.has(EXACT_LVL_MATCH) { f.write_str("EXACT_LVL_MATCH, ")?; }
+        for name in self.named_flags() {
+            write!(f, "{}, ", name)?;
+        }
         f.write_str("]")?;
         Ok(())
     }
 }
+/// Formats the set flags separated by `+`, e.g. `TIMESTAMP+SEVERITY` or `NONE` if no flag
+/// is set. The output can be parsed back with [`str::parse`].
+impl std::fmt::Display for Options {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.named_flags() {
+            v if v.is_empty() => f.write_str("NONE"),
+            v => f.write_str(&v.join("+")),
+        }
+    }
+}
+/// Parses a list of flag names as written by [`Display`](std::fmt::Display)
+///
+/// The names are case insensitive and separated by `+` or `,` to add a flag or by `-` to remove
+/// it. `NONE` doesn't set any flag and `DEFAULT` adds all flags of [`Options::default`]. If the
+/// string starts with `-` the flags are removed from the default options, so `-PID-TID` is the
+/// same as `DEFAULT-PID-TID`. An empty string results in [`NONE`].
+///
+/// # Example
+/// ```rust
+/// use hclog::options::{Options, PID, TID, TIMESTAMP, SEVERITY};
+///
+/// assert_eq!("timestamp+severity".parse(), Ok(TIMESTAMP + SEVERITY));
+/// assert_eq!("default-PID-TID".parse(), Ok(Options::default() - PID - TID));
+/// assert_eq!((TIMESTAMP + SEVERITY).to_string().parse(), Ok(TIMESTAMP + SEVERITY));
+/// ```
+///
+/// # Errors
+///
+/// Returns [`ParseArg`](ErrorKind::ParseArg) if a name is unknown or empty, e.g. for a stray
+/// separator.
+impl std::str::FromStr for Options {
+    type Err = ErrorKind;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(NONE);
+        }
+        let (mut opts, mut add, mut rest) = match s.strip_prefix('-') {
+            Some(rest) => (Options::default(), false, rest),
+            None => (NONE, true, s),
+        };
+        loop {
+            let end = rest.find(['+', ',', '-']).unwrap_or(rest.len());
+            let flags = match rest[..end].trim() {
+                "" => return Err(ErrorKind::ParseArg),
+                n if n.eq_ignore_ascii_case("DEFAULT") => Options::default(),
+                n => flag_by_name(n).ok_or(ErrorKind::ParseArg)?,
+            };
+            if add { opts += flags } else { opts -= flags }
+            if end == rest.len() {
+                return Ok(opts);
+            }
+            add = !rest[end..].starts_with('-');
+            rest = &rest[end + 1..];
+        }
+    }
+}
+// single flag by its case insensitive name, NONE is accepted as well
+pub (crate) fn flag_by_name(name: &str) -> Option<Options> {
+    if name.eq_ignore_ascii_case("NONE") {
+        return Some(NONE);
+    }
+    FLAG_NAMES.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, flag)| *flag)
+}
 impl Options {
     /// Create a new Options struct with no options set
     ///
@@ -278,61 +602,448 @@ impl Options {
         *self = Self::default();
         self.parse_from_env()
     }
+    /// Get the names of all set flags
+    ///
+    /// The names are equal to the names of the constants in this module and ordered by their
+    /// value. If no flag is set the returned Vec is empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{TIMESTAMP, SEVERITY};
+    ///
+    /// assert_eq!((TIMESTAMP + SEVERITY).named_flags(), ["TIMESTAMP", "SEVERITY"]);
+    /// ```
+    pub fn named_flags(self) -> Vec<&'static str> {
+        self.iter().map(|(name, _)| name).collect()
+    }
+    /// Iterate over all set flags
+    ///
+    /// Yields the name and the value of each set flag, ordered by their value. The names are
+    /// equal to the ones returned by [`named_flags`](Options::named_flags).
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{Options, TIMESTAMP, SEVERITY, PID};
+    ///
+    /// let old = TIMESTAMP + SEVERITY;
+    /// let new = TIMESTAMP + PID;
+    /// for (name, flag) in new.iter().filter(|(_, f)| !old.has(*f)) {
+    ///     println!("added {}: {:?}", name, flag);
+    /// }
+    /// ```
+    pub fn iter(&self) -> OptionsIter {
+        OptionsIter { bits: self.0 }
+    }
+    /// Create Options from a list of flag names
+    ///
+    /// This is the inverse of [`Options::named_flags`]. The names are case insensitive, `NONE`
+    /// is accepted but doesn't set any flag.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{Options, TIMESTAMP, SEVERITY};
+    ///
+    /// let opts = TIMESTAMP + SEVERITY;
+    /// assert_eq!(Options::from_flag_names(&opts.named_flags()), Ok(opts));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseArg`](ErrorKind::ParseArg) if a name is not a known flag.
+    pub fn from_flag_names(names: &[&str]) -> Result<Self> {
+        names.iter().try_fold(NONE, |opts, name| {
+            flag_by_name(name).map(|flag| opts + flag).ok_or(ErrorKind::ParseArg)
+        })
+    }
+    /// Get the flags set in `self` but not in `other`
+    ///
+    /// Same as `self - other`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{PID, TID, TIMESTAMP};
+    ///
+    /// assert_eq!((TIMESTAMP + PID).difference(PID + TID), TIMESTAMP);
+    /// ```
+    pub fn difference(self, other: Options) -> Options {
+        self - other
+    }
+    /// Get the flags set in either `self` or `other` but not in both
+    ///
+    /// Same as `self ^ other`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{PID, TID, TIMESTAMP};
+    ///
+    /// assert_eq!((TIMESTAMP + PID).symmetric_difference(PID + TID), TIMESTAMP + TID);
+    /// ```
+    pub fn symmetric_difference(self, other: Options) -> Options {
+        self ^ other
+    }
+    /// Get all known flags which are not set in `self`
+    ///
+    /// Only the flags defined in this module are set in the result, so the complement of the
+    /// complement is the original value.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{Options, NONE};
+    ///
+    /// let opts = Options::default();
+    /// assert_eq!(opts.complement().complement(), opts);
+    /// assert!(opts.complement().is_disjoint(opts));
+    /// assert_eq!(NONE.complement() - opts, opts.complement());
+    /// ```
+    pub fn complement(self) -> Options {
+        Self(!self.0 & ALL_FLAGS)
+    }
+    /// Check if `self` and `other` have no flag in common
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{PID, TID, TIMESTAMP};
+    ///
+    /// assert!((TIMESTAMP + PID).is_disjoint(TID));
+    /// assert!(!(TIMESTAMP + PID).is_disjoint(PID + TID));
+    /// ```
+    pub fn is_disjoint(self, other: Options) -> bool {
+        self.0 & other.0 == 0
+    }
+    /// Check if all flags of `other` are set in `self`
+    ///
+    /// Same as [`has`](Options::has).
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{NONE, PID, TID, TIMESTAMP};
+    ///
+    /// assert!((TIMESTAMP + PID).contains(PID));
+    /// assert!(!(TIMESTAMP + PID).contains(PID + TID));
+    /// assert!(TIMESTAMP.contains(NONE));
+    /// ```
+    pub fn contains(self, other: Options) -> bool {
+        self.has(other)
+    }
+    /// Check if `self` and `other` have at least one flag in common
+    ///
+    /// The opposite of [`is_disjoint`](Options::is_disjoint).
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{PID, TID, TIMESTAMP};
+    ///
+    /// assert!((TIMESTAMP + PID).intersects(PID + TID));
+    /// assert!(!(TIMESTAMP + PID).intersects(TID));
+    /// ```
+    pub fn intersects(self, other: Options) -> bool {
+        !self.is_disjoint(other)
+    }
+    /// Get the flags set in `self`, `other` or both
+    ///
+    /// Same as `self + other` and `self | other`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{PID, TID, TIMESTAMP};
+    ///
+    /// assert_eq!((TIMESTAMP + PID).union(PID + TID), TIMESTAMP + PID + TID);
+    /// ```
+    pub fn union(self, other: Options) -> Options {
+        self + other
+    }
+    /// Get the flags set in both `self` and `other`
+    ///
+    /// Same as `self & other`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{PID, TID, TIMESTAMP};
+    ///
+    /// assert_eq!((TIMESTAMP + PID).intersection(PID + TID), PID);
+    /// ```
+    pub fn intersection(self, other: Options) -> Options {
+        self & other
+    }
+    /// Check if no flag is set
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{NONE, PID};
+    ///
+    /// assert!(NONE.is_empty());
+    /// assert!((PID - PID).is_empty());
+    /// assert!(!PID.is_empty());
+    /// ```
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+    /// Get the raw bits of the flags, e.g. to store them
+    ///
+    /// The values of the flags are stable, new flags get new bits.
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{Options, PID, TIMESTAMP};
+    ///
+    /// let opts = TIMESTAMP + PID;
+    /// assert_eq!(Options::from_bits_truncate(opts.bits()), opts);
+    /// ```
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+    /// Create Options from raw bits, bits not belonging to a known flag are dropped
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::options::{Options, NONE, PID};
+    ///
+    /// assert_eq!(Options::from_bits_truncate(PID.bits() | 0x8000_0000), PID);
+    /// assert_eq!(Options::from_bits_truncate(u32::MAX), NONE.complement());
+    /// ```
+    pub const fn from_bits_truncate(bits: u32) -> Options {
+        Self(bits & ALL_FLAGS)
+    }
+    /// Pick the Options depending on who reads the output of `facade`
+    ///
+    /// If the [`StdOut`](FacadeVariant::StdOut) or [`StdErr`](FacadeVariant::StdErr) facade
+    /// writes to a terminal a human reads the messages, so only [`OPTIONS_COMPACT`] is
+    /// returned. For all other facades or if the output is redirected the full
+    /// [`Options::default`] is returned. The environment variables are applied on top when the
+    /// Options are passed to [`init`](fn@crate::Scope::init).
+    ///
+    /// # Example
+    /// ```rust
+    /// use hclog::{FacadeVariant, options::Options};
+    ///
+    /// let opts = Options::auto_for(&FacadeVariant::StdErr);
+    /// assert_eq!(Options::auto_for(&FacadeVariant::RingBuffer(8)), Options::default());
+    /// ```
+    pub fn auto_for(facade: &FacadeVariant) -> Options {
+        let terminal = match facade {
+            FacadeVariant::StdOut => std::io::stdout().is_terminal(),
+            FacadeVariant::StdErr => std::io::stderr().is_terminal(),
+            _ => false,
+        };
+        Self::auto(terminal)
+    }
+    // the decision of auto_for without looking at the output
+    fn auto(terminal: bool) -> Options {
+        if terminal { OPTIONS_COMPACT } else { Options::default() }
+    }
     #[doc(hidden)]
     pub fn for_syslog(&mut self) {
-        *self -= TIMESTAMP + DATESTAMP + NANOSEC + BINNAME + PID + SEVERITY;
+        *self -= OPTIONS_FOR_SYSLOG_MASK;
     }
 
-    #[doc(hidden)]
-    fn opt_from_env(&mut self, key: &str, var: Options) -> Result<()> {
+    // the value of the variable if it flipped `var`
+    fn opt_from_env(&mut self, key: &str, var: Options) -> Result<Option<u16>> {
         let envvar = format!("{}{}", ENV_OPT_PREFIX, key);
-        match read_var_from_env::<u16>(&envvar) {
-            Ok(v) => match v {
-                Some(0) => *self -= var,
-                Some(1) => *self += var,
-                Some(_) | None => (),
-            }
-            Err(e) => return Err(e),
+        let before = *self;
+        match read_env_var::<u16>(&envvar)? {
+            Some(0) => *self -= var,
+            Some(1) => *self += var,
+            Some(_) | None => (),
         }
-        Ok(())
+        Ok((*self != before).then(|| u16::from(self.has(var))))
     }
 
     #[doc(hidden)]
     pub fn parse_from_env(&mut self) -> Result<&Self> {
-        self.opt_from_env("LINEBUFFERED", LINEBUFFERED)?;
-        self.opt_from_env("TIMESTAMP", TIMESTAMP)?;
-        self.opt_from_env("DATESTAMP", DATESTAMP)?;
-        self.opt_from_env("NANOSEC", NANOSEC)?;
-        self.opt_from_env("BINNAME", BINNAME)?;
-        self.opt_from_env("PID", PID)?;
-        self.opt_from_env("TID", TID)?;
-        self.opt_from_env("MODULE", MODULE)?;
-        self.opt_from_env("SEVERITY", SEVERITY)?;
-        self.opt_from_env("SCOPE", SCOPE)?;
-        self.opt_from_env("FUNC", FUNC)?;
-        self.opt_from_env("FILE", FILE)?;
-        self.opt_from_env("LINE", LINE)?;
-        self.opt_from_env("LOG_COMPAT", LOGCOMPAT)?;
-        self.opt_from_env("EXACT_LVL_MATCH", EXACT_LVL_MATCH)?;
+        self.apply_env()?;
         Ok(self)
     }
+
+    /*
+     * the variables which actually changed the options as (suffix of the variable, value).
+     * Nothing is read if the environment is ignored, see ignore_env.
+     */
+    pub (crate) fn apply_env(&mut self) -> Result<Vec<(&'static str, u16)>> {
+        let mut flipped = Vec::new();
+        if env::ignored() {
+            return Ok(flipped);
+        }
+        for (key, var) in ENV_OPTIONS {
+            if let Some(value) = self.opt_from_env(key, var)? {
+                flipped.push((key, value));
+            }
+        }
+        Ok(flipped)
+    }
+}
+
+// the suffixes of the HCLOG_OPT_* variables
+const ENV_OPTIONS: [(&str, Options); 25] = [
+    ("LINEBUFFERED", LINEBUFFERED),
+    ("TIMESTAMP", TIMESTAMP),
+    ("DATESTAMP", DATESTAMP),
+    ("NANOSEC", NANOSEC),
+    ("BINNAME", BINNAME),
+    ("PID", PID),
+    ("TID", TID),
+    ("MODULE", MODULE),
+    ("SEVERITY", SEVERITY),
+    ("SCOPE", SCOPE),
+    ("FUNC", FUNC),
+    ("FILE", FILE),
+    ("LINE", LINE),
+    ("LOG_COMPAT", LOGCOMPAT),
+    ("EXACT_LVL_MATCH", EXACT_LVL_MATCH),
+    ("DEDUP", DEDUP),
+    ("RAW", RAW),
+    ("SYSLOG_IDENT", SYSLOG_IDENT),
+    ("UPTIME", UPTIME),
+    ("BACKTRACE", BACKTRACE),
+    ("PREFIX_SEP", PREFIX_SEP),
+    ("INSTANCE", INSTANCE),
+    ("FILE_BASENAME", FILE_BASENAME),
+    ("FUNC_SHORT", FUNC_SHORT),
+    ("MONOTONIC_TS", MONOTONIC_TS),
+];
+
+/// Get the [`Options`] as configured by the environment
+///
+/// Starts from [`Options::default`] and applies all `HCLOG_OPT_*` environment variables. This
+/// allows to show the effective options before initializing any [`Scope`](trait@crate::Scope).
+/// If the environment is ignored (see [`ignore_env`](fn@crate::ignore_env)) the default is
+/// returned.
+///
+/// # Example
+/// ```rust
+/// use hclog::options::{Options, parse_options_from_env};
+///
+/// let effective = parse_options_from_env().unwrap();
+/// print!("current effective options: {}", effective);
+/// for (name, _) in Options::default().iter().filter(|(_, f)| !effective.has(*f)) {
+///     print!(" ({} disabled by HCLOG_OPT_{}=0)", name, name);
+/// }
+/// println!();
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if an environment variable can't be parsed.
+pub fn parse_options_from_env() -> Result<Options> {
+    let mut options = Options::default();
+    options.parse_from_env()?;
+    Ok(options)
 }
 
 /* tests below */
 #[cfg(test)]
 mod options_tests {
     use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn bitwise_operators() {
+        assert_eq!(TIMESTAMP | PID, TIMESTAMP + PID);
+        assert_eq!((TIMESTAMP + PID) & (PID + TID), PID);
+        assert_eq!((TIMESTAMP + PID) ^ (PID + TID), TIMESTAMP + TID);
+
+        let mut opts = TIMESTAMP;
+        opts |= PID + TID;
+        assert_eq!(opts, TIMESTAMP + PID + TID);
+        opts &= PID + TID + FILE;
+        assert_eq!(opts, PID + TID);
+        opts ^= TID + LINE;
+        assert_eq!(opts, PID + LINE);
+    }
+
+    #[test]
+    fn set_algebra() {
+        let a = TIMESTAMP + PID + FILE;
+        let b = PID + TID + FILE + RAW;
+        assert_eq!(a.difference(b), TIMESTAMP);
+        assert_eq!(b.difference(a), TID + RAW);
+        assert_eq!(a.symmetric_difference(b), TIMESTAMP + TID + RAW);
+        assert_eq!(a.symmetric_difference(a), NONE);
+        assert!(!a.is_disjoint(b));
+        assert!(a.difference(b).is_disjoint(b));
+        assert!(NONE.is_disjoint(NONE));
+
+        let all = FLAG_NAMES.iter().fold(NONE, |opts, (_, flag)| opts + *flag);
+        assert_eq!(NONE.complement(), all);
+        assert_eq!(all.complement(), NONE);
+        assert_eq!(a.complement(), all - a);
+        assert_eq!(a.complement().complement(), a);
+        assert!(a.complement().is_disjoint(a));
+        assert_eq!(a.complement() + a, all);
+    }
+
+    #[test]
+    fn set_predicates() {
+        let a = TIMESTAMP + PID + FILE;
+        assert!(a.contains(PID + FILE));
+        assert!(a.contains(a));
+        assert!(a.contains(NONE));
+        assert!(!a.contains(PID + TID));
+        assert!(!NONE.contains(PID));
+        for other in [PID + TID, a, TIMESTAMP, TID + RAW, NONE] {
+            assert_eq!(a.contains(other), a.has(other));
+            assert_eq!(a.intersects(other), !a.is_disjoint(other));
+        }
+        assert!(a.intersects(PID + TID));
+        assert!(!a.intersects(TID + RAW));
+        assert!(!a.intersects(NONE));
+        assert!(!NONE.intersects(NONE));
+        assert!(NONE.is_empty());
+        assert!(Options::new().is_empty());
+        assert!(a.difference(a).is_empty());
+        assert!(!a.is_empty());
+    }
+
+    #[test]
+    fn union_intersection() {
+        let a = TIMESTAMP + PID + FILE;
+        let b = PID + TID + FILE + RAW;
+        assert_eq!(a.union(b), TIMESTAMP + PID + TID + FILE + RAW);
+        assert_eq!(a.union(b), a + b);
+        assert_eq!(a.union(b), a | b);
+        assert_eq!(a.union(NONE), a);
+        assert_eq!(a.intersection(b), PID + FILE);
+        assert_eq!(a.intersection(b), a & b);
+        assert_eq!(a.intersection(NONE), NONE);
+        assert_eq!(a.intersection(b), a - a.difference(b));
+    }
+
+    #[test]
+    fn not_operator() {
+        let a = TIMESTAMP + PID + FILE;
+        assert_eq!(!a, a.complement());
+        assert_eq!(!!a, a);
+        assert_eq!(!NONE, FLAG_NAMES.iter().fold(NONE, |opts, (_, flag)| opts + *flag));
+        assert_eq!(!(!NONE), NONE);
+        // the arithmetic and the bitwise style are interchangeable
+        assert_eq!(a & !PID, a - PID);
+        assert_eq!((a | TID) & !FILE, a + TID - FILE);
+    }
+
+    #[test]
+    fn raw_bits() {
+        assert_eq!(NONE.bits(), 0);
+        assert_eq!(PID.bits(), 0x0020);
+        assert_eq!((TIMESTAMP + PID).bits(), 0x0022);
+        let all = !NONE;
+        assert_eq!(Options::from_bits_truncate(all.bits()), all);
+        assert_eq!(Options::from_bits_truncate(u32::MAX), all);
+        assert_eq!(Options::from_bits_truncate(!all.bits()), NONE);
+        assert_eq!(Options::from_bits_truncate(PID.bits() | 0x8000_0000), PID);
+        for (_, flag) in FLAG_NAMES {
+            assert_eq!(Options::from_bits_truncate(flag.bits()), flag);
+        }
+    }
 
     #[test]
     fn read_from_env() {
         std::env::set_var("FOO_BAR", "1");
-        assert!(read_var_from_env::<i32>("FOO_BAR").is_ok());
+        assert!(read_env_var::<i32>("FOO_BAR").is_ok());
         std::env::set_var("FOO_BAR", "BAZ");
-        assert!(read_var_from_env::<i32>("FOO_BAR").is_err());
+        assert!(read_env_var::<i32>("FOO_BAR").is_err());
         std::env::set_var("FOO_BAR", "1");
-        assert_eq!(read_var_from_env::<i32>("FOO_BAR").unwrap().unwrap(), 1i32);
+        assert_eq!(read_env_var::<i32>("FOO_BAR").unwrap().unwrap(), 1i32);
         std::env::set_var("FOO_BAR", "BAZ");
-        assert_eq!(read_var_from_env::<String>("FOO_BAR").unwrap().unwrap(), "BAZ".to_owned())
+        assert_eq!(read_env_var::<String>("FOO_BAR").unwrap().unwrap(), "BAZ".to_owned())
     }
 
    #[test]
@@ -356,6 +1067,25 @@ mod options_tests {
         assert!(default.has(NONE));
     }
 
+    #[test]
+    #[serial]
+    fn options_from_env() {
+        std::env::set_var("HCLOG_OPT_LINEBUFFERED", "0");
+        let opts = parse_options_from_env().unwrap();
+        assert!(!opts.has(LINEBUFFERED));
+        assert!(opts.has(TIMESTAMP));
+        assert!(Options::default().has(LINEBUFFERED));
+
+        std::env::set_var("HCLOG_OPT_UPTIME", "1");
+        assert!(parse_options_from_env().unwrap().has(UPTIME));
+        std::env::set_var("HCLOG_OPT_UPTIME", "0");
+        assert!(!parse_options_from_env().unwrap().has(UPTIME));
+
+        // other tests read the env as well
+        std::env::remove_var("HCLOG_OPT_LINEBUFFERED");
+        std::env::remove_var("HCLOG_OPT_UPTIME");
+    }
+
     #[test]
     fn from_env_new() {
         let new = Options::default();
@@ -366,6 +1096,17 @@ mod options_tests {
         assert!(new.has(DATESTAMP));
     }
 
+    #[test]
+    fn auto_for_output() {
+        assert_eq!(Options::auto(true), OPTIONS_COMPACT);
+        assert_eq!(Options::auto(false), Options::default());
+        for facade in [FacadeVariant::File("/dev/null".into(), false),
+            FacadeVariant::Syslog("user".to_owned()), FacadeVariant::None]
+        {
+            assert_eq!(Options::auto_for(&facade), Options::default());
+        }
+    }
+
     #[test]
     fn syslog_fields() {
         let mut syslog = Options::default() + SCOPE;
@@ -382,5 +1123,93 @@ mod options_tests {
         assert!(syslog.has(FILE));
         assert!(syslog.has(FUNC));
         assert!(syslog.has(LINE));
+
+        let mut uptime = OPTIONS_FOR_SYSLOG + UPTIME;
+        uptime.for_syslog();
+        assert!(uptime.has(UPTIME));
+    }
+
+    #[test]
+    fn flag_names_round_trip() {
+        assert!(NONE.named_flags().is_empty());
+        assert_eq!((SEVERITY + TIMESTAMP).named_flags(), ["TIMESTAMP", "SEVERITY"]);
+        for opts in [NONE, Options::default(), PID + DEDUP + EXACT_LVL_MATCH] {
+            assert_eq!(Options::from_flag_names(&opts.named_flags()), Ok(opts));
+            let names = opts.to_string();
+            assert_eq!(Options::from_flag_names(&names.split('+').collect::<Vec<_>>()), Ok(opts));
+        }
+        assert_eq!((FILE + LINE).to_string(), "FILE+LINE");
+        assert_eq!(format!("{:?}", SYSLOG_IDENT + UPTIME), "[SYSLOG_IDENT, UPTIME, ]");
+        assert_eq!("uptime+timestamp".parse(), Ok(TIMESTAMP + UPTIME));
+        assert_eq!(Options::from_flag_names(&["tid", "Func"]), Ok(TID + FUNC));
+        assert_eq!(Options::from_flag_names(&["TID", "FOO"]), Err(ErrorKind::ParseArg));
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        // random subsets of all flags via a fixed xorshift sequence
+        let all = FLAG_NAMES.iter().fold(NONE, |opts, (_, flag)| opts + *flag);
+        let mut x = 0x2545_f491_u32;
+        for _ in 0..1000 {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            let opts = Options(x) & all;
+            assert_eq!(opts.to_string().parse(), Ok(opts));
+            assert_eq!(opts.to_string().to_lowercase().parse(), Ok(opts));
+        }
+        assert_eq!(NONE.to_string().parse(), Ok(NONE));
+        assert_eq!(all.to_string().parse(), Ok(all));
+    }
+
+    #[test]
+    fn parse_edge_cases() {
+        let default = Options::default();
+        assert_eq!("".parse(), Ok(NONE));
+        assert_eq!("  ".parse(), Ok(NONE));
+        assert_eq!("none".parse(), Ok(NONE));
+        assert_eq!("Default".parse(), Ok(default));
+        assert_eq!("PID+PID,pid".parse(), Ok(PID));
+        assert_eq!("TIMESTAMP, MODULE + severity".parse(), Ok(TIMESTAMP + MODULE + SEVERITY));
+        assert_eq!("default-PID-TID".parse(), Ok(default - PID - TID));
+        assert_eq!("-PID-TID".parse(), Ok(default - PID - TID));
+        assert_eq!("-PID+SCOPE".parse(), Ok(default - PID + SCOPE));
+        assert_eq!("PID-PID".parse(), Ok(NONE));
+        assert_eq!("exact_lvl_match+syslog_ident".parse(), Ok(EXACT_LVL_MATCH + SYSLOG_IDENT));
+
+        for s in ["PID+FOO", "PID|TID", "+", "-", ",", "PID+", "+PID", "PID++TID", "PID,,TID",
+            "PID+-TID", "--PID"] {
+            assert_eq!(s.parse::<Options>(), Err(ErrorKind::ParseArg), "{}", s);
+        }
+    }
+
+    #[test]
+    fn iter_flags() {
+        for (pos, (_, flag)) in FLAG_NAMES.iter().enumerate() {
+            assert_eq!(flag.0, 1 << pos);
+        }
+        assert_eq!(NONE.iter().count(), 0);
+        let all = FLAG_NAMES.iter().fold(NONE, |opts, (_, flag)| opts + *flag);
+        assert!(all.iter().map(|(n, _)| n).eq(FLAG_NAMES.iter().map(|(n, _)| *n)));
+        for opts in [Options::default(), OPTIONS_FOR_SYSLOG, RAW + LINEBUFFERED] {
+            assert_eq!(opts.into_iter().fold(NONE, |acc, (_, flag)| acc + flag), opts);
+        }
+        let added = (TIMESTAMP + PID).iter()
+            .filter(|(_, f)| !(TIMESTAMP + SEVERITY).has(*f))
+            .collect::<Vec<_>>();
+        assert_eq!(added, [("PID", PID)]);
+    }
+
+    #[test]
+    fn presets() {
+        assert_eq!(Options::default(), OPTIONS_VERBOSE);
+        let mut syslog = Options::default();
+        syslog.for_syslog();
+        assert_eq!(syslog, OPTIONS_FOR_SYSLOG);
+        // MONOTONIC_TS is removed for syslog but not part of the defaults
+        assert_eq!(OPTIONS_FOR_SYSLOG + OPTIONS_FOR_SYSLOG_MASK - MONOTONIC_TS, OPTIONS_VERBOSE);
+        assert_eq!(OPTIONS_DEBUG - SCOPE, OPTIONS_VERBOSE);
+        assert_eq!(OPTIONS_COMPACT.named_flags(),
+            ["LINEBUFFERED", "TIMESTAMP", "MODULE", "SEVERITY", "LOGCOMPAT"]);
     }
 }
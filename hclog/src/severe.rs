@@ -0,0 +1,92 @@
+use crate::{Level, Result};
+use std::{
+    fmt::Arguments,
+    sync::{atomic::{AtomicU64, AtomicUsize, Ordering}, Arc, RwLock},
+};
+
+/// Callback registered via [`on_severe`](fn@crate::on_severe)
+///
+/// Called with the name of the LogKey, the [`Level`] and the formatted message.
+pub type SevereHandler = Box<dyn Fn(&str, Level, &str) + Send + Sync>;
+
+/// Token returned by [`on_severe`](fn@crate::on_severe) to remove the handler again
+///
+/// Pass it to [`remove_on_severe`](fn@crate::remove_on_severe).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SevereHandlerId(u64);
+
+type SharedHandler = Arc<dyn Fn(&str, Level, &str) + Send + Sync>;
+
+struct Entry {
+    id: SevereHandlerId,
+    threshold: Level,
+    handler: SharedHandler,
+}
+
+/*
+ * process wide list of the handlers, independent of any Scope or task scope
+ *
+ * COUNT mirrors the length of the list, so messages are passed without taking the lock as long
+ * as there is no handler.
+ */
+static HANDLERS: RwLock<Vec<Entry>> = RwLock::new(Vec::new());
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+pub (crate) fn register(threshold: Level, handler: SevereHandler) -> Result<SevereHandlerId> {
+    let id = SevereHandlerId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let mut handlers = HANDLERS.write()?;
+    handlers.push(Entry { id, threshold, handler: Arc::from(handler) });
+    COUNT.store(handlers.len(), Ordering::Release);
+    Ok(id)
+}
+pub (crate) fn remove(id: SevereHandlerId) -> Result<bool> {
+    let mut handlers = HANDLERS.write()?;
+    let before = handlers.len();
+    handlers.retain(|e| e.id != id);
+    COUNT.store(handlers.len(), Ordering::Release);
+    Ok(handlers.len() != before)
+}
+// call all handlers whose threshold is reached by `level`
+pub (crate) fn notify(module: &str, level: Level, fmt: &Arguments) {
+    if level == Level::Off || COUNT.load(Ordering::Acquire) == 0 {
+        return;
+    }
+    // the lock is released before calling, so handlers may (de)register handlers themselves
+    let matching = HANDLERS.read().unwrap_or_else(|e| e.into_inner()).iter()
+        .filter(|e| e.threshold.is_enabled(level))
+        .map(|e| Arc::clone(&e.handler))
+        .collect::<Vec<_>>();
+    if matching.is_empty() {
+        return;
+    }
+    let msg = fmt.to_string();
+    for handler in matching {
+        handler(module, level, &msg);
+    }
+}
+
+#[cfg(test)]
+mod severe_tests {
+    use super::{notify, register, remove};
+    use crate::Level;
+    use serial_test::serial;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    #[serial]
+    fn threshold_and_remove() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let record = Arc::clone(&seen);
+        let id = register(Level::Crit, Box::new(move |module, level, msg| {
+            record.lock().unwrap().push(format!("{} {} {}", module, level, msg));
+        })).unwrap();
+        for level in [Level::Off, Level::Emerg, Level::Crit, Level::Error, Level::Debug10] {
+            notify("mod", level, &format_args!("at {}", level as u8));
+        }
+        assert_eq!(remove(id), Ok(true));
+        assert_eq!(remove(id), Ok(false));
+        notify("mod", Level::Emerg, &format_args!("removed"));
+        assert_eq!(*seen.lock().unwrap(), ["mod emerg at 1", "mod crit at 3"]);
+    }
+}
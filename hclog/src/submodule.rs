@@ -1,13 +1,163 @@
 use crate::{
-    facades::{FacadeScope, FacadeVariant},
-    logmod::ScopeEnv,
+    clock,
+    facades::{FacadeScope, FacadeVariant, FlushPolicy, Newline, RingBuffer},
+    logmod::{Provenance, ScopeEnv, Source},
     level::Level,
-    message::Message,
+    message::{self, Message},
+    subscription::{Subscribers, Subscription},
     log_internal::InternalLogKeys::Internal,
     options::*,
-    Result, ContextKey, LogKey,
+    severe,
+    Result, ErrorKind, ContextKey, LogKey,
 };
-use std::fmt::{self, Debug, Display, Arguments};
+use std::{
+    fmt::{self, Debug, Display, Arguments},
+    sync::{Mutex, MutexGuard, TryLockError, atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering}},
+    time::{Duration, Instant},
+};
+
+/*
+ * names of LogKeys and Scopes are compared case-insensitive (ASCII) everywhere: on lookups by
+ * name (set_mod_level, scope/key), for `_all` and when keys are added to a Scope
+ */
+pub (crate) fn name_eq(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+/*
+ * last payload logged with the DEDUP option set
+ *
+ * The location of the first message is kept to pass it to the synthetic summary line.
+ */
+#[derive(Debug)]
+struct DedupState {
+    payload: String,
+    level: Level,
+    file: String,
+    func: Option<String>,
+    line: u32,
+    repeated: usize,
+    since: Instant,
+}
+impl DedupState {
+    fn new(payload: String, level: Level, file: &str, func: Option<&str>, line: u32) -> Self {
+        Self {
+            payload,
+            level,
+            file: file.to_owned(),
+            func: func.map(str::to_owned),
+            line,
+            repeated: 0,
+            since: clock::instant(),
+        }
+    }
+}
+
+/*
+ * do_log only gets a shared reference to the Submodule so the state needs its own lock.
+ * A cloned Submodule (e.g. for a task scope) starts with a fresh state.
+ */
+#[derive(Debug, Default)]
+struct Dedup(Mutex<Option<DedupState>>);
+impl Clone for Dedup {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/*
+ * sampling set via set_sampling
+ *
+ * Only every rate-th message at or more verbose than level is written. The counter also counts
+ * the suppressed messages to keep the ratio. Like the Dedup state a cloned Submodule starts
+ * with fresh counters.
+ */
+#[derive(Debug)]
+struct Sampling {
+    level: Level,
+    rate: u32,
+    counter: AtomicU32,
+    dropped: AtomicU64,
+}
+impl Sampling {
+    fn new(level: Level, rate: u32) -> Self {
+        Self { level, rate, counter: AtomicU32::new(0), dropped: AtomicU64::new(0) }
+    }
+    // true if the message is written
+    fn sample(&self, level: Level) -> bool {
+        if level < self.level {
+            return true;
+        }
+        let keep = self.counter.fetch_add(1, Ordering::Relaxed) % self.rate == 0;
+        if !keep {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        keep
+    }
+}
+impl Clone for Sampling {
+    fn clone(&self) -> Self {
+        Self::new(self.level, self.rate)
+    }
+}
+
+/*
+ * temporary level set via set_level_for
+ *
+ * The revert is lazy: once `until` passed the Submodule reports the previous level again and
+ * the first log or test call noticing it takes the expiry to emit a notice. The boost is
+ * dropped by the next change of the level.
+ */
+#[derive(Debug)]
+pub (crate) struct Boost {
+    until: Instant,
+    prev_level: Level,
+    prev_explicit: bool,
+    prev_source: Source,
+    reported: AtomicBool,
+}
+impl Clone for Boost {
+    fn clone(&self) -> Self {
+        Self {
+            until: self.until,
+            prev_level: self.prev_level,
+            prev_explicit: self.prev_explicit,
+            prev_source: self.prev_source,
+            reported: AtomicBool::new(self.reported.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+// the part of the configuration copied by Submodule::mirror, see hclog::alias_key
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub (crate) enum Mirror {
+    Level,
+    Facade,
+    Options,
+}
+
+// the fields of a Submodule covered by a Mirror, taken via Submodule::mirrored
+#[derive(Debug)]
+pub (crate) enum Mirrored {
+    Level { level: Level, source: Source, muted: bool, boost: Option<Boost> },
+    Facade { logdest: FacadeScope, flush_policy: FlushPolicy, source: Source },
+    Options { options: Options, source: Source },
+}
+
+// a single message passed down from do_log to the facade
+struct LogCall<'a> {
+    cratename: &'a str,
+    scope: ScopeEnv,
+    scope_ident: Option<&'a str>,
+    instance: Option<&'a str>,
+    func_max_len: Option<usize>,
+    lvl: Level,
+    file: &'a str,
+    func: Option<&'a str>,
+    line: u32,
+    // false to skip the message instead of waiting for a lock (see try_do_log)
+    blocking: bool,
+}
 
 #[derive(Debug, Clone)]
 pub (crate) struct Submodule {
@@ -16,7 +166,27 @@ pub (crate) struct Submodule {
     options: Options,
     initialized: bool,
     logsev: Level,
+    // false if logsev is only the scope default and should be inherited from the parent
+    explicit_level: bool,
+    parent: Option<ContextKey>,
+    // skipped by `_all`, see hclog::set_all_exempt
+    all_exempt: bool,
+    // nothing is logged while set, the level is kept for hclog::unmute
+    muted: bool,
+    // where level, facade and options came from, never read while logging
+    provenance: Provenance,
+    // level and facade at registration and the description, listed by export_metadata
+    init_level: Level,
+    init_facade: &'static str,
+    description: Option<&'static str>,
+    boost: Option<Boost>,
     logdest: FacadeScope,
+    newline: Newline,
+    flush_policy: FlushPolicy,
+    dedup: Dedup,
+    dedup_interval: Option<Duration>,
+    sampling: Option<Sampling>,
+    subscribers: Subscribers,
 }
 impl Display for Submodule {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -31,19 +201,55 @@ impl Default for Submodule {
             options: Options::default(),
             initialized: false,
             logsev: Level::default(),
+            explicit_level: false,
+            parent: None,
+            all_exempt: false,
+            muted: false,
+            provenance: Provenance::default(),
+            init_level: Level::default(),
+            init_facade: "none",
+            description: None,
+            boost: None,
             logdest: FacadeScope::None,
+            newline: Newline::Lf,
+            flush_policy: FlushPolicy::EveryMessage,
+            dedup: Dedup::default(),
+            dedup_interval: None,
+            sampling: None,
+            subscribers: Subscribers::default(),
         }
     }
 }
 impl Submodule {
-    pub fn new(key: impl LogKey, logsev: Level, f: &FacadeVariant, options: Options) -> Self {
+    // name is the trimmed name of the LogKey, ident the name of the scope used as the syslog
+    // ident if SYSLOG_IDENT is set
+    pub fn new(
+        key: impl LogKey, name: &str, logsev: Level, f: &FacadeVariant, options: Options,
+        ident: &str,
+    ) -> Self {
+        let logdest = FacadeScope::new(f, options.has(SYSLOG_IDENT).then_some(ident));
         let mut global = Self {
             key: key.log_key().to_owned(),
-            name: key.to_string(),
+            name: name.to_owned(),
             options,
             initialized: true,
             logsev,
-            logdest: FacadeScope::new(f),
+            explicit_level: key.init_level().is_some(),
+            parent: None,
+            all_exempt: key.init_all_exempt(),
+            muted: false,
+            provenance: Provenance::default(),
+            init_level: logsev,
+            init_facade: logdest.variant_name(),
+            description: key.description(),
+            boost: None,
+            logdest,
+            newline: Newline::Lf,
+            flush_policy: FlushPolicy::EveryMessage,
+            dedup: Dedup::default(),
+            dedup_interval: None,
+            sampling: None,
+            subscribers: Subscribers::default(),
         };
         global.set_options_internal();
         global
@@ -61,75 +267,513 @@ impl Submodule {
     pub fn name(&self) -> &str {
         &self.name
     }
+    pub fn name_matches(&self, name: &str) -> bool {
+        name_eq(&self.name, name)
+    }
     pub fn initialized(&self) -> bool {
         self.initialized
     }
+    pub fn init_level(&self) -> Level {
+        self.init_level
+    }
+    pub fn init_facade(&self) -> &'static str {
+        self.init_facade
+    }
+    pub fn description(&self) -> Option<&'static str> {
+        self.description
+    }
+    pub fn all_exempt(&self) -> bool {
+        self.all_exempt
+    }
+    pub fn set_all_exempt(&mut self, exempt: bool) -> &mut Self {
+        self.all_exempt = exempt;
+        self
+    }
+    pub fn muted(&self) -> bool {
+        self.muted
+    }
+    pub fn set_muted(&mut self, muted: bool) -> &mut Self {
+        self.muted = muted;
+        self
+    }
+    pub fn provenance(&self) -> Provenance {
+        match self.expired_boost() {
+            Some(boost) => Provenance { level: boost.prev_source, ..self.provenance },
+            None => self.provenance,
+        }
+    }
+    pub fn set_provenance(&mut self, provenance: Provenance) -> &mut Self {
+        self.provenance = provenance;
+        self
+    }
+    // the setters record Source::Runtime, override it for levels set from the commandline
+    pub fn set_level_source(&mut self, source: Source) -> &mut Self {
+        self.provenance.level = source;
+        self
+    }
     pub fn key(&self) -> ContextKey {
         self.key
     }
+    // a submodule is active if messages can reach a facade at all
+    pub fn is_active(&self, level: Level) -> bool {
+        !self.muted && level != Level::Off && self.logdest.inner().is_some()
+    }
+    pub fn options(&self) -> Options {
+        self.options
+    }
+    pub fn facade_variant_name(&self) -> &str {
+        self.logdest.variant_name()
+    }
+    pub fn logdest(&self) -> &FacadeScope {
+        &self.logdest
+    }
+    fn expired_boost(&self) -> Option<&Boost> {
+        self.boost.as_ref().filter(|b| clock::instant() >= b.until)
+    }
+    pub fn logsev(&self) -> Level {
+        match self.expired_boost() {
+            Some(boost) => boost.prev_level,
+            None => self.logsev,
+        }
+    }
     pub fn set_logsev(&mut self, logsev: Level) -> &mut Self {
         self.logsev = logsev;
+        self.explicit_level = true;
+        self.provenance.level = Source::Runtime;
+        self.boost = None;
+        self
+    }
+    // set logsev until `duration` passed, afterwards the current level applies again
+    pub fn set_logsev_for(&mut self, logsev: Level, duration: Duration) -> &mut Self {
+        // boosting twice keeps the level configured before the first boost
+        let (prev_level, prev_explicit) = match self.boost {
+            Some(ref boost) if clock::instant() < boost.until => {
+                (boost.prev_level, boost.prev_explicit)
+            }
+            _ => (self.logsev(), self.explicit_level()),
+        };
+        let prev_source = match self.boost {
+            Some(ref boost) if clock::instant() < boost.until => boost.prev_source,
+            _ => self.provenance().level,
+        };
+        self.set_logsev(logsev);
+        self.boost = clock::instant().checked_add(duration).map(|until| Boost {
+            until, prev_level, prev_explicit, prev_source, reported: AtomicBool::new(false),
+        });
+        self
+    }
+    // returns the boosted and the reverted level once after the boost expired
+    pub fn take_expired_boost(&self) -> Option<(Level, Level)> {
+        self.expired_boost()
+            .filter(|b| !b.reported.swap(true, Ordering::Relaxed))
+            .map(|b| (self.logsev, b.prev_level))
+    }
+    // apply the level configured before an expired boost for good
+    pub fn clear_expired_boost(&mut self) -> &mut Self {
+        if self.expired_boost().is_some() {
+            let Some(boost) = self.boost.take() else {
+                return self;
+            };
+            self.logsev = boost.prev_level;
+            self.explicit_level = boost.prev_explicit;
+            self.provenance.level = boost.prev_source;
+        }
+        self
+    }
+    pub fn explicit_level(&self) -> bool {
+        match self.expired_boost() {
+            Some(boost) => boost.prev_explicit,
+            None => self.explicit_level,
+        }
+    }
+    // fall back to the level of the parent (if any)
+    pub fn inherit_level(&mut self) -> &mut Self {
+        self.logsev = self.logsev();
+        self.explicit_level = false;
+        self.provenance.level = Source::Runtime;
+        self.boost = None;
+        self
+    }
+    pub fn parent(&self) -> Option<ContextKey> {
+        self.parent
+    }
+    pub fn set_parent(&mut self, parent: Option<ContextKey>) -> &mut Self {
+        self.parent = parent;
+        self
+    }
+    pub fn set_logdest(&mut self, variant: &FacadeVariant, ident: &str) -> &mut Self {
+        let logdest = FacadeScope::new(variant, self.facade_ident(ident));
+        self.share_logdest(&logdest)
+    }
+    // the ident a facade for this submodule is created with, see FacadeScope::new
+    pub fn facade_ident<'a>(&self, ident: &'a str) -> Option<&'a str> {
+        self.options.has(SYSLOG_IDENT).then_some(ident)
+    }
+    // use a facade created for another submodule, like set_logdest otherwise
+    pub fn share_logdest(&mut self, logdest: &FacadeScope) -> &mut Self {
+        self.logdest = logdest.clone();
+        self.provenance.facade = Source::Runtime;
+        self.set_flush_policy(self.flush_policy)
+    }
+    // the part of the configuration to copy to the alias targets. The level is the own one,
+    // LogScope::mirrored replaces it with the effective level.
+    pub (crate) fn mirrored(&self, what: Mirror) -> Mirrored {
+        match what {
+            Mirror::Level => Mirrored::Level {
+                level: self.logsev(),
+                source: self.provenance.level,
+                muted: self.muted,
+                boost: self.boost.clone(),
+            },
+            Mirror::Facade => Mirrored::Facade {
+                logdest: self.logdest.clone(),
+                flush_policy: self.flush_policy,
+                source: self.provenance.facade,
+            },
+            Mirror::Options => Mirrored::Options {
+                options: self.options,
+                source: self.provenance.options,
+            },
+        }
+    }
+    // copy a part of the configuration of another submodule, the facade is shared and not
+    // reopened. A mirrored level is set explicitly to not inherit it from the own parent.
+    pub (crate) fn mirror(&mut self, src: &Mirrored, normalize: impl Fn(Level) -> Level)
+        -> &mut Self
+    {
+        match src {
+            Mirrored::Level { level, source, muted, boost } => {
+                self.logsev = normalize(*level);
+                self.explicit_level = true;
+                self.muted = *muted;
+                self.provenance.level = *source;
+                self.boost = boost.as_ref().map(|boost| Boost {
+                    prev_level: normalize(boost.prev_level),
+                    prev_explicit: true,
+                    ..boost.clone()
+                });
+            }
+            Mirrored::Facade { logdest, flush_policy, source } => {
+                self.logdest = logdest.clone();
+                self.flush_policy = *flush_policy;
+                self.provenance.facade = *source;
+            }
+            Mirrored::Options { options, source } => {
+                self.options = *options;
+                self.provenance.options = *source;
+                self.set_options_internal();
+            }
+        }
+        self
+    }
+    pub fn set_newline(&mut self, newline: Newline) -> &mut Self {
+        self.newline = newline;
+        self
+    }
+    // kept in the Submodule as well to be applied to a new facade
+    pub fn set_flush_policy(&mut self, policy: FlushPolicy) -> &mut Self {
+        self.flush_policy = policy;
+        if let Some(f) = self.logdest.inner() {
+            f.set_flush_policy(policy);
+        }
         self
     }
-    pub fn set_logdest(&mut self, variant: &FacadeVariant) -> &mut Self {
-        self.logdest = FacadeScope::new(variant);
+    pub fn set_dedup_interval(&mut self, interval: Option<Duration>) -> &mut Self {
+        self.dedup_interval = interval;
+        self
+    }
+    // rate <= 1 or Level::Off disables the sampling
+    pub fn set_sampling(&mut self, level: Level, rate: u32) -> &mut Self {
+        self.sampling = (rate > 1 && level != Level::Off).then(|| Sampling::new(level, rate));
+        self
+    }
+    // number of messages suppressed by the sampling
+    pub fn sampled_out(&self) -> u64 {
+        self.sampling.as_ref().map_or(0, |s| s.dropped.load(Ordering::Relaxed))
+    }
+    pub fn ring_buffer(&self) -> Option<&RingBuffer> {
+        self.logdest.ring_buffer()
+    }
+    pub fn subscribe(&self, capacity: usize) -> Subscription {
+        self.subscribers.subscribe(capacity)
+    }
+    #[cfg(test)]
+    pub fn set_facade(&mut self, facade: FacadeScope) -> &mut Self {
+        self.logdest = facade;
+        self
+    }
+    #[cfg(feature = "test-util")]
+    pub fn inject_failures(&mut self, n: usize, kind: std::io::ErrorKind) -> &mut Self {
+        self.logdest = self.logdest.inject_failures(n, kind);
+        self
+    }
+    #[cfg(feature = "test-util")]
+    pub fn clear_failures(&mut self) -> &mut Self {
+        self.logdest = self.logdest.clear_failures();
         self
     }
     pub fn reset_options(&mut self) -> Result<&mut Self> {
         self.options.reset()?;
+        self.provenance.options = Source::Runtime;
         Ok(self.set_options_internal())
     }
     pub fn set_options(&mut self, flags: Options) -> &mut Self {
         self.options.set(flags);
+        self.provenance.options = Source::Runtime;
         self.set_options_internal()
     }
     pub fn unset_options(&mut self, flags: Options) -> &mut Self {
         self.options.unset(flags);
+        self.provenance.options = Source::Runtime;
         self.set_options_internal()
     }
     pub fn will_log(&self, logsev: Level) -> bool {
+        self.will_log_at(self.logsev(), logsev)
+    }
+    // check against a level resolved by the LogScope (inherited from a parent)
+    pub fn will_log_at(&self, level: Level, logsev: Level) -> bool {
+        if self.muted {
+            return false;
+        }
         if self.options.has(EXACT_LVL_MATCH) {
-            self.logsev == logsev
+            logsev != Level::Off && level == logsev
         } else {
-            self.logsev.is_enabled(logsev)
+            level.is_enabled(logsev)
         }
     }
+    #[allow(clippy::too_many_arguments)]
     pub fn do_log(
         &self, cratename: &str, scope: ScopeEnv, scope_ident: Option<&str>,
-        lvl: Level, file: &str, func: &str, line: u32, fmt: &Arguments,
+        instance: Option<&str>, func_max_len: Option<usize>, lvl: Level, file: &str,
+        func: Option<&str>, line: u32, fmt: &Arguments,
+    ) -> Result<()> {
+        let call = LogCall {
+            cratename, scope, scope_ident, instance, func_max_len, lvl, file, func, line,
+            blocking: true,
+        };
+        self.write(&call, fmt).map(|_| ())
+    }
+    // like do_log but false instead of waiting for a lock held by another writer
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_do_log(
+        &self, cratename: &str, scope: ScopeEnv, scope_ident: Option<&str>,
+        instance: Option<&str>, func_max_len: Option<usize>, lvl: Level, file: &str,
+        func: Option<&str>, line: u32, fmt: &Arguments,
+    ) -> Result<bool> {
+        let call = LogCall {
+            cratename, scope, scope_ident, instance, func_max_len, lvl, file, func, line,
+            blocking: false,
+        };
+        self.write(&call, fmt)
+    }
+    fn write(&self, call: &LogCall, fmt: &Arguments) -> Result<bool> {
+        let res = self.dispatch(call, fmt);
+        // also if the message is filtered by DEDUP or sampling or the write failed
+        severe::notify(&self.name, call.lvl, fmt);
+        res
+    }
+    fn dispatch(&self, call: &LogCall, fmt: &Arguments) -> Result<bool> {
+        if self.logdest.inner().is_none() && self.subscribers.is_empty() {
+            return Ok(true);
+        }
+        if self.sampling.as_ref().is_some_and(|s| !s.sample(call.lvl)) {
+            return Ok(true);
+        }
+        if !self.options.has(DEDUP) {
+            return self.emit_message(call, fmt);
+        }
+        // keep the lock while writing to not mix up the order of summary and message
+        let Some(mut last) = self.lock_dedup(call.blocking)? else {
+            return Ok(false);
+        };
+        let payload = fmt.to_string();
+        if let Some(ref mut state) = *last {
+            if state.level == call.lvl && state.payload == payload {
+                state.repeated += 1;
+                let elapsed = clock::instant().saturating_duration_since(state.since);
+                let expired = self.dedup_interval.is_some_and(|i| elapsed >= i);
+                // a summary skipped by a locked facade is emitted with the next one
+                if expired && self.emit_repeated(call, state)? {
+                    state.repeated = 0;
+                    state.since = clock::instant();
+                }
+                return Ok(true);
+            }
+            if !self.emit_repeated(call, state)? {
+                return Ok(false);
+            }
+            state.repeated = 0;
+        }
+        if !self.emit_message(call, fmt)? {
+            return Ok(false);
+        }
+        *last = Some(DedupState::new(payload, call.lvl, call.file, call.func, call.line));
+        Ok(true)
+    }
+    // emit the pending repeat counter of a DEDUP enabled Submodule (if any) and write out the
+    // messages buffered by the facade
+    pub fn flush(
+        &self, cratename: &str, scope: ScopeEnv, scope_ident: Option<&str>,
+        instance: Option<&str>, func_max_len: Option<usize>,
     ) -> Result<()> {
-        let logdest = match self.logdest.inner() {
-            None => return Ok(()),
-            Some(f) => f,
+        let call = LogCall {
+            cratename, scope, scope_ident, instance, func_max_len, lvl: Level::Off,
+            file: "", func: None, line: 0, blocking: true,
+        };
+        self.flush_pending(call).map(|_| ())
+    }
+    // like flush but false instead of waiting for a lock held by another writer
+    pub fn try_flush(
+        &self, cratename: &str, scope: ScopeEnv, scope_ident: Option<&str>,
+        instance: Option<&str>, func_max_len: Option<usize>,
+    ) -> Result<bool> {
+        let call = LogCall {
+            cratename, scope, scope_ident, instance, func_max_len, lvl: Level::Off,
+            file: "", func: None, line: 0, blocking: false,
         };
+        self.flush_pending(call)
+    }
+    fn flush_pending(&self, mut call: LogCall) -> Result<bool> {
+        let Some(mut last) = self.lock_dedup(call.blocking)? else {
+            return Ok(false);
+        };
+        if let Some(ref mut state) = *last {
+            call.lvl = state.level;
+            // keep the counter for the next try if the facade is locked
+            if !self.emit_repeated(&call, state)? {
+                return Ok(false);
+            }
+            state.repeated = 0;
+            state.since = clock::instant();
+        }
+        drop(last);
+        match self.logdest.inner() {
+            None => Ok(true),
+            Some(logdest) if call.blocking => logdest.flush().map(|()| true),
+            Some(logdest) => logdest.try_flush(),
+        }
+    }
+    // None if not blocking and the state is locked by another writer
+    fn lock_dedup(&self, blocking: bool) -> Result<Option<MutexGuard<'_, Option<DedupState>>>> {
+        if blocking {
+            return self.dedup.0.lock().map(Some).map_err(|_| ErrorKind::ContextLock);
+        }
+        match self.dedup.0.try_lock() {
+            Ok(last) => Ok(Some(last)),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Poisoned(_)) => Err(ErrorKind::ContextLock),
+        }
+    }
+    fn emit_repeated(&self, call: &LogCall, state: &DedupState) -> Result<bool> {
+        if state.repeated == 0 {
+            return Ok(true);
+        }
+        self.emit(
+            call, state.level, &state.file, state.func.as_deref(), state.line,
+            &format_args!("last message repeated {} times", state.repeated),
+        )
+    }
+    // the message of the call, the stack of the BACKTRACE option is only captured once the
+    // message isn't filtered by sampling or DEDUP
+    fn emit_message(&self, call: &LogCall, fmt: &Arguments) -> Result<bool> {
+        if !self.options.has(BACKTRACE) || !Level::Error.is_enabled(call.lvl) {
+            return self.emit(call, call.lvl, call.file, call.func, call.line, fmt);
+        }
+        let trace = message::backtrace();
+        let fmt = format_args!("{}{}", fmt, trace);
+        self.emit(call, call.lvl, call.file, call.func, call.line, &fmt)
+    }
+    // the location is passed separately as the summary of DEDUP has the one of its message
+    fn emit(
+        &self, call: &LogCall, lvl: Level, file: &str, func: Option<&str>, line: u32,
+        fmt: &Arguments,
+    ) -> Result<bool> {
         let opts = &self.options;
-        let mut msg = Message::new(opts, cratename, file, func, line, fmt);
+        let mut msg = Message::new(opts, call.cratename, file, func, line, fmt);
         msg.set_severity(&lvl);
         msg.set_modname(&self.name);
-        msg.set_scope(&scope, scope_ident);
-        logdest.log(self.logsev, msg)
+        msg.set_scope(&call.scope, call.scope_ident);
+        msg.set_instance(call.instance);
+        msg.set_func_max_len(call.func_max_len);
+        self.subscribers.publish(&msg, call.blocking);
+        match self.logdest.inner() {
+            None => Ok(true),
+            Some(logdest) => {
+                if !logdest.is_syslog() {
+                    msg.set_newline(self.newline);
+                }
+                if call.blocking {
+                    logdest.log(lvl, msg).map(|()| true)
+                } else {
+                    logdest.try_log(lvl, msg)
+                }
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod submodule_test {
     use crate::{
+        facades::{FacadeRc, FacadeScope, LogFacade},
         level::Level,
+        logmod::ScopeEnv,
+        message::Message,
         submodule::Submodule,
-        options::EXACT_LVL_MATCH,
+        options::{EXACT_LVL_MATCH, DEDUP, NONE},
+        Result,
     };
+    use std::{sync::Mutex, time::Duration};
+
+    // facade remembering every message written to it
+    #[derive(Debug, Default)]
+    struct Capture(Mutex<Vec<String>>);
+    impl LogFacade for Capture {
+        fn log(&self, _: Level, msg: Message) -> Result<()> {
+            self.0.lock().unwrap().push(msg.to_string());
+            Ok(())
+        }
+        // holding the lock of the captured lines makes the facade busy
+        fn try_log(&self, _: Level, msg: Message) -> Result<bool> {
+            match self.0.try_lock() {
+                Ok(mut lines) => lines.push(msg.to_string()),
+                Err(_) => return Ok(false),
+            }
+            Ok(true)
+        }
+    }
+
+    fn dedup_submod() -> (Submodule, FacadeRc<Capture>) {
+        let capture = FacadeRc::new(Capture::default());
+        let mut submod = Submodule::default();
+        submod.set_logsev(Level::Debug10);
+        submod.logdest = FacadeScope::Local(capture.clone());
+        submod.options = NONE;
+        submod.set_options(DEDUP);
+        (submod, capture)
+    }
+
+    fn log(submod: &Submodule, lvl: Level, msg: &str) {
+        submod.do_log("test", ScopeEnv::Global, None, None, None, lvl, file!(), None, line!(),
+            &format_args!("{}", msg)).unwrap();
+    }
+
+    fn try_log(submod: &Submodule, msg: &str) -> bool {
+        submod.try_do_log("test", ScopeEnv::Global, None, None, None, Level::Info, file!(), None,
+            line!(), &format_args!("{}", msg)).unwrap()
+    }
 
     #[test]
     fn test_will_log() {
         let mut logmod = Submodule::default();
         logmod.set_logsev(Level::Info);
-        assert_eq!(logmod.will_log(Level::Emerg), true);
-        assert_eq!(logmod.will_log(Level::Error), true);
-        assert_eq!(logmod.will_log(Level::Info), true);
-        assert_eq!(logmod.will_log(Level::Debug1), false);
+        assert!(logmod.will_log(Level::Emerg));
+        assert!(logmod.will_log(Level::Error));
+        assert!(logmod.will_log(Level::Info));
+        assert!(!logmod.will_log(Level::Debug1));
         logmod.set_logsev(Level::Off);
-        assert_eq!(logmod.will_log(Level::Alert), false);
-        assert_eq!(logmod.will_log(Level::Debug5), false);
+        assert!(!logmod.will_log(Level::Alert));
+        assert!(!logmod.will_log(Level::Debug5));
     }
 
     #[test]
@@ -139,12 +783,106 @@ mod submodule_test {
         submod.set_options(EXACT_LVL_MATCH);
 
         assert!(submod.options.has(EXACT_LVL_MATCH));
-        assert_eq!(submod.will_log(Level::Emerg), false);
-        assert_eq!(submod.will_log(Level::Error), false);
-        assert_eq!(submod.will_log(Level::Info), true);
-        assert_eq!(submod.will_log(Level::Debug1), false);
+        assert!(!submod.will_log(Level::Emerg));
+        assert!(!submod.will_log(Level::Error));
+        assert!(submod.will_log(Level::Info));
+        assert!(!submod.will_log(Level::Debug1));
         submod.unset_options(EXACT_LVL_MATCH);
-        assert_eq!(submod.will_log(Level::Crit), true);
-        assert_eq!(submod.will_log(Level::Debug1), false);
+        assert!(submod.will_log(Level::Crit));
+        assert!(!submod.will_log(Level::Debug1));
+    }
+
+    #[test]
+    fn sampling() {
+        let (mut submod, capture) = dedup_submod();
+        submod.unset_options(DEDUP);
+        submod.set_sampling(Level::Debug1, 100);
+        for i in 0..10_000 {
+            log(&submod, Level::Debug5, &i.to_string());
+        }
+        let written = capture.0.lock().unwrap().len();
+        assert!((99..=101).contains(&written), "{}", written);
+        assert_eq!(submod.sampled_out(), 10_000 - written as u64);
+
+        // less verbose levels are not sampled
+        capture.0.lock().unwrap().clear();
+        for _ in 0..100 {
+            log(&submod, Level::Error, "error");
+        }
+        assert_eq!(capture.0.lock().unwrap().len(), 100);
+
+        for (level, rate) in [(Level::Debug1, 1), (Level::Debug1, 0), (Level::Off, 100)] {
+            capture.0.lock().unwrap().clear();
+            submod.set_sampling(level, rate);
+            for _ in 0..10 {
+                log(&submod, Level::Debug5, "debug");
+            }
+            assert_eq!(capture.0.lock().unwrap().len(), 10);
+            assert_eq!(submod.sampled_out(), 0);
+        }
+    }
+
+    #[test]
+    fn dedup_identical() {
+        let (submod, capture) = dedup_submod();
+        for _ in 0..3 {
+            log(&submod, Level::Error, "connection refused");
+        }
+        log(&submod, Level::Error, "connected");
+        assert_eq!(*capture.0.lock().unwrap(), [
+            "connection refused", "last message repeated 2 times", "connected",
+        ]);
+    }
+
+    #[test]
+    fn dedup_alternating() {
+        let (submod, capture) = dedup_submod();
+        for msg in ["a", "b", "a", "b"] {
+            log(&submod, Level::Info, msg);
+        }
+        // same payload on a different level is a different message
+        log(&submod, Level::Warn, "b");
+        assert_eq!(*capture.0.lock().unwrap(), ["a", "b", "a", "b", "b"]);
+    }
+
+    #[test]
+    fn dedup_flush_and_interval() {
+        let (mut submod, capture) = dedup_submod();
+        log(&submod, Level::Info, "a");
+        log(&submod, Level::Info, "a");
+        submod.flush("test", ScopeEnv::Global, None, None, None).unwrap();
+        // nothing pending anymore
+        submod.flush("test", ScopeEnv::Global, None, None, None).unwrap();
+        log(&submod, Level::Info, "a");
+        assert_eq!(*capture.0.lock().unwrap(), ["a", "last message repeated 1 times"]);
+
+        submod.set_dedup_interval(Some(Duration::ZERO));
+        log(&submod, Level::Info, "a");
+        assert_eq!(capture.0.lock().unwrap().last().unwrap(), "last message repeated 2 times");
+        submod.unset_options(DEDUP);
+        log(&submod, Level::Info, "a");
+        assert_eq!(capture.0.lock().unwrap().last().unwrap(), "a");
+    }
+
+    #[test]
+    fn dedup_busy_facade() {
+        let (mut submod, capture) = dedup_submod();
+        assert!(try_log(&submod, "a"));
+        assert!(try_log(&submod, "a"));
+        {
+            let _busy = capture.0.lock().unwrap();
+            // the message is skipped, the summary is kept for the next try
+            assert!(!try_log(&submod, "b"));
+        }
+        assert!(try_log(&submod, "b"));
+        assert_eq!(*capture.0.lock().unwrap(), ["a", "last message repeated 1 times", "b"]);
+
+        submod.set_dedup_interval(Some(Duration::ZERO));
+        {
+            let _busy = capture.0.lock().unwrap();
+            assert!(try_log(&submod, "b"));
+        }
+        assert!(try_log(&submod, "b"));
+        assert_eq!(capture.0.lock().unwrap().last().unwrap(), "last message repeated 2 times");
     }
 }
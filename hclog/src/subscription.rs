@@ -0,0 +1,139 @@
+use crate::message::Message;
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Condvar, Mutex, MutexGuard, TryLockError, Weak,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+/*
+ * bounded buffer of rendered lines shared between a Submodule and one Subscription
+ *
+ * If the buffer is full the oldest line is dropped to never block the logging thread.
+ */
+#[derive(Debug)]
+struct Ring {
+    lines: Mutex<VecDeque<String>>,
+    ready: Condvar,
+    capacity: usize,
+}
+impl Ring {
+    fn lock(&self) -> MutexGuard<'_, VecDeque<String>> {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    fn push(&self, line: &str, blocking: bool) {
+        let Some(mut lines) = try_lock(&self.lines, blocking) else {
+            return;
+        };
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line.to_owned());
+        self.ready.notify_one();
+    }
+}
+
+// the guard of `mutex`, None instead of waiting if `blocking` is false and it's locked
+fn try_lock<T>(mutex: &Mutex<T>, blocking: bool) -> Option<MutexGuard<'_, T>> {
+    if blocking {
+        return Some(mutex.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+    match mutex.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::WouldBlock) => None,
+        Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+    }
+}
+
+/*
+ * list of all Subscriptions of a Submodule
+ *
+ * Only weak references are kept, so dropping a Subscription unsubscribes it. Dead entries are
+ * removed on the next published line. A cloned Submodule (e.g. for a task scope) shares the
+ * list with the original.
+ *
+ * `live` counts the Subscriptions not dropped yet, so messages are passed without taking the
+ * lock as long as there is none.
+ */
+#[derive(Debug, Default, Clone)]
+pub (crate) struct Subscribers {
+    list: Arc<Mutex<Vec<Weak<Ring>>>>,
+    live: Arc<AtomicUsize>,
+}
+impl Subscribers {
+    pub fn subscribe(&self, capacity: usize) -> Subscription {
+        let ring = Arc::new(Ring {
+            lines: Mutex::new(VecDeque::new()),
+            ready: Condvar::new(),
+            capacity: capacity.max(1),
+        });
+        let mut list = self.list.lock().unwrap_or_else(|e| e.into_inner());
+        list.push(Arc::downgrade(&ring));
+        self.live.fetch_add(1, Ordering::Release);
+        Subscription { ring, live: Arc::clone(&self.live) }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.live.load(Ordering::Acquire) == 0
+    }
+    /*
+     * render the message once and pass it to every live Subscription. Unless `blocking` the
+     * line is skipped instead of waiting for a lock held by another writer or a consumer.
+     */
+    pub fn publish(&self, msg: &Message, blocking: bool) {
+        if self.is_empty() {
+            return;
+        }
+        let Some(mut subs) = try_lock(&self.list, blocking) else {
+            return;
+        };
+        subs.retain(|s| s.strong_count() > 0);
+        if subs.is_empty() {
+            return;
+        }
+        let line = msg.to_string();
+        subs.iter().filter_map(Weak::upgrade).for_each(|ring| ring.push(&line, blocking));
+    }
+}
+
+/// Receiving end of a subscription to a LogKey
+///
+/// Returned by [`subscribe`](crate::subscribe). Every line logged to the LogKey is passed to
+/// the subscription in addition to the facade of the LogKey. The lines are rendered the same
+/// way as for the facade, without the trailing newline.
+///
+/// The subscription buffers up to `capacity` lines. If the consumer doesn't keep up, the oldest
+/// lines are dropped. The facade output is not affected by a lagging subscription. A line
+/// logged via [`try_log`](fn@crate::try_log) is skipped if the subscription is locked by
+/// another writer or the consumer at that time.
+///
+/// Dropping the subscription unsubscribes from the LogKey.
+#[derive(Debug)]
+pub struct Subscription {
+    ring: Arc<Ring>,
+    live: Arc<AtomicUsize>,
+}
+impl Subscription {
+    /// Take all currently buffered lines without blocking
+    ///
+    /// Lines logged while iterating are not part of the returned iterator.
+    pub fn try_iter(&self) -> impl Iterator<Item = String> {
+        std::mem::take(&mut *self.ring.lock()).into_iter()
+    }
+
+    /// Wait for the next line for at most `timeout`
+    ///
+    /// Returns `None` if no line was logged until the timeout expired.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<String> {
+        let lines = self.ring.lock();
+        let (mut lines, _) = self.ring.ready.wait_timeout_while(lines, timeout, |l| l.is_empty())
+            .unwrap_or_else(|e| e.into_inner());
+        lines.pop_front()
+    }
+}
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.live.fetch_sub(1, Ordering::Release);
+    }
+}
@@ -21,7 +21,6 @@ use std::{
     future::{Future},
     task::{Context, Poll},
     pin::Pin,
-    marker::PhantomPinned,
     error::Error,
     thread,
     fmt,
@@ -74,7 +73,18 @@ impl<T: 'static> LocalKey<T> {
             local: self,
             slot: Some(value),
             future: Some(f),
-            _pinned: PhantomPinned,
+        }
+    }
+
+    // set the value for the duration of the (synchronous) closure
+    pub fn sync_scope<F, R>(&'static self, value: T, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let mut slot = Some(value);
+        match self.scope_inner(&mut slot, f) {
+            Ok(res) => res,
+            Err(err) => err.panic(),
         }
     }
 
@@ -191,6 +201,11 @@ impl<T: 'static> fmt::Debug for LocalKey<T> {
     }
 }
 
+/*
+ * Unlike tokio the future doesn't carry a PhantomPinned. Nothing in here is self-referential:
+ * the slot is only swapped with the thread local while the future is polled or dropped and is
+ * never pinned. So the TaskLocalFuture is Unpin whenever F is Unpin.
+ */
 pin_project! {
     pub struct TaskLocalFuture<T, F>
     where
@@ -200,8 +215,6 @@ pin_project! {
         slot: Option<T>,
         #[pin]
         future: Option<F>,
-        #[pin]
-        _pinned: PhantomPinned,
     }
 
     impl<T: 'static, F> PinnedDrop for TaskLocalFuture<T, F> {
@@ -331,3 +344,38 @@ impl From<std::thread::AccessError> for TaskLocalErr {
         Self::AccessError
     }
 }
+
+#[cfg(test)]
+mod task_test {
+    use std::{
+        future::{self, Future},
+        pin::{pin, Pin},
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    crate::task_local! {
+        static VALUE: u32;
+    }
+
+    struct Noop;
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn assert_unpin<T: Unpin>(_: &T) {}
+
+    #[test]
+    fn unpin_future_polled_in_place() {
+        // the async block isn't Unpin, so this one needs to be pinned
+        let mut fut = pin!(VALUE.scope(42, async { VALUE.get() }));
+        let waker = Waker::from(Arc::new(Noop));
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(Ok(42)));
+
+        let mut fut = VALUE.scope(7, future::ready(()));
+        assert_unpin(&fut);
+        assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Ready(()));
+        assert!(VALUE.get().is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! Utilities to test the log output of an application
+//!
+//! This module is only available with the `test-util` feature enabled.
+//!
+//! The [`MockFacade`] records every message passed to it and allows to assert that specific
+//! messages were logged. It's installed like any other facade via
+//! [`set_logdest`](fn@crate::set_logdest) with the
+//! [`FacadeVariant::Mock`](enum@crate::FacadeVariant#variant.Mock) variant.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use hclog::{Level, FacadeVariant, testing::MockFacade};
+//! # use hclog_macros::HCLog;
+//! # use hclog::Scope;
+//!
+//! # #[derive(HCLog, Copy, Clone)]
+//! enum Keys { Db }
+//!
+//! # Keys::init_with_defaults("test").unwrap();
+//! # hclog::set_level(Keys::Db, Level::Info).unwrap();
+//! let mock = MockFacade::new();
+//! mock.expect_log(Level::Error, "connection refused");
+//! hclog::set_logdest(Keys::Db, FacadeVariant::Mock(mock.clone())).unwrap();
+//!
+//! hclog::lE!(Keys::Db, "connection refused by {}", "localhost");
+//! mock.verify();
+//! ```
+//!
+//! The [`MockClock`] replaces the system clock via [`set_clock`](fn@crate::set_clock) to test
+//! time based behavior, e.g. of [`set_level_for`](fn@crate::set_level_for), without sleeping.
+use crate::{facades::LogFacade, level::Level, message::Message, ErrorKind::IoError, Result};
+#[doc(inline)]
+pub use crate::clock::{Clock, MockClock, SystemClock};
+use std::{
+    fmt::{self, Write},
+    io,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+// a single call registered via MockFacade::expect_log
+#[derive(Debug)]
+struct Expectation {
+    level: Level,
+    contains: String,
+    met: bool,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    strict: bool,
+    expected: Vec<Expectation>,
+    calls: Vec<(Level, String)>,
+    unexpected: Vec<(Level, String)>,
+    failing: Option<io::ErrorKind>,
+}
+
+/// A facade recording all messages to verify them against a list of expectations
+///
+/// Every message passed to the facade is matched against the first expectation with the same
+/// [`Level`] whose text is contained in the message, ignoring the prefixes added by the
+/// [`Options`](crate::options::Options). Each expectation is met by exactly one message. A
+/// message not matching any open expectation is an unexpected call.
+///
+/// Clones of a `MockFacade` share their state, so the clone passed to
+/// [`FacadeVariant::Mock`](enum@crate::FacadeVariant#variant.Mock) can be verified via the
+/// original.
+#[derive(Clone, Debug, Default)]
+pub struct MockFacade {
+    state: Arc<Mutex<MockState>>,
+}
+impl MockFacade {
+    /// Create a new `MockFacade` which records unexpected calls
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new `MockFacade` which panics on the first unexpected call
+    pub fn new_strict() -> Self {
+        let mock = Self::default();
+        mock.state().strict = true;
+        mock
+    }
+    /// Register an expected message with the given [`Level`] containing `contains`
+    pub fn expect_log(&self, level: Level, contains: &str) -> &Self {
+        self.state().expected.push(Expectation {
+            level,
+            contains: contains.to_owned(),
+            met: false,
+        });
+        self
+    }
+    /// Get all messages passed to the facade so far
+    pub fn calls(&self) -> Vec<(Level, String)> {
+        self.state().calls.clone()
+    }
+    /// Fail every following write with `kind` until `None` is set
+    ///
+    /// Failed writes are neither recorded nor matched against the expectations.
+    pub fn set_failing(&self, kind: Option<io::ErrorKind>) -> &Self {
+        self.state().failing = kind;
+        self
+    }
+    /// Assert that all expectations were met
+    ///
+    /// # Panics
+    ///
+    /// Panics with a list of the expectations which were not met and the recorded calls.
+    pub fn verify(&self) {
+        let state = self.state();
+        let missing = state.expected.iter().filter(|e| !e.met).collect::<Vec<_>>();
+        if !missing.is_empty() {
+            let mut msg = format!("MockFacade: {} expected log call(s) not made:\n",
+                missing.len());
+            for e in missing {
+                let _ = writeln!(msg, "  - {} containing {:?}", e.level, e.contains);
+            }
+            let _ = write!(msg, "{}", Calls("recorded calls", &state.calls));
+            panic!("{}", msg);
+        }
+    }
+    /// Assert that all expectations were met and no unexpected call occurred
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`verify`](Self::verify) panics or with a list of the unexpected calls.
+    pub fn verify_no_unexpected(&self) {
+        self.verify();
+        let state = self.state();
+        if !state.unexpected.is_empty() {
+            panic!("MockFacade: {} unexpected log call(s)\n{}", state.unexpected.len(),
+                Calls("unexpected calls", &state.unexpected));
+        }
+    }
+    // a panic in a test may poison the mutex - the state is still usable for verification
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+impl LogFacade for MockFacade {
+    fn log(&self, level: Level, msg: Message) -> Result<()> {
+        let payload = msg.payload().to_owned();
+        let mut state = self.state();
+        if let Some(kind) = state.failing {
+            return Err(IoError(kind));
+        }
+        let strict = state.strict;
+        let matched = state.expected.iter_mut()
+            .find(|e| !e.met && e.level == level && payload.contains(&e.contains));
+        match matched {
+            Some(e) => e.met = true,
+            None if strict => {
+                drop(state);
+                panic!("MockFacade: unexpected log call: {} {:?}", level, payload);
+            }
+            None => state.unexpected.push((level, payload.clone())),
+        }
+        state.calls.push((level, payload));
+        Ok(())
+    }
+}
+
+// helper to list recorded calls in panic messages
+struct Calls<'a>(&'a str, &'a [(Level, String)]);
+impl fmt::Display for Calls<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.1.is_empty() {
+            return writeln!(f, "no {}", self.0);
+        }
+        writeln!(f, "{}:", self.0)?;
+        for (level, payload) in self.1 {
+            writeln!(f, "  - {} {:?}", level, payload)?;
+        }
+        Ok(())
+    }
+}
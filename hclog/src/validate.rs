@@ -0,0 +1,309 @@
+use crate::{
+    facades::FacadeScope,
+    logmod::LogScope,
+    options::*,
+    InternalLogKeys::{self, LogCompat},
+    ContextKey, Level, LogKey, Scope,
+};
+use std::{collections::{HashMap, HashSet}, fmt};
+
+/// Kind of a misconfiguration reported by [`validate_configuration`](fn@crate::validate_configuration)
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ConfigWarningKind {
+    /// The LogKey is enabled but has no facade, so nothing is ever written
+    NoFacade,
+    /// [`NANOSEC`] is set without [`TIMESTAMP`] and has no effect
+    NanosecWithoutTimestamp,
+    /// [`LINE`] is set without [`FILE`] and has no effect
+    LineWithoutFile,
+    /// [`EXACT_LVL_MATCH`] is set while the level is [`Off`](Level::Off)
+    ExactMatchOff,
+    /// The Scope is initialized without any LogKey
+    EmptyScope,
+    /// Another LogKey of the same Scope has the same name
+    ///
+    /// Functions addressing a LogKey by its name (e.g.
+    /// [`set_mod_level`](fn@crate::set_mod_level)) only reach the first one.
+    DuplicateName,
+    /// [`LOGCOMPAT`] was set on a LogKey other than
+    /// [`LogCompat`](enum@InternalLogKeys#variant.LogCompat) after its initialization
+    ///
+    /// Setting the option installs the compatibility layer but the option can't be used to mute
+    /// it on any other LogKey.
+    LogCompatOnKey,
+}
+
+/// A misconfiguration found by [`validate_configuration`](fn@crate::validate_configuration)
+///
+/// The [`Display`](fmt::Display) implementation renders a human readable message including the
+/// name of the Scope and the LogKey.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigWarning {
+    scope: String,
+    key: Option<String>,
+    kind: ConfigWarningKind,
+}
+impl ConfigWarning {
+    fn new(scope: &LogScope, key: Option<&str>, kind: ConfigWarningKind) -> Self {
+        Self { scope: scope.name().to_owned(), key: key.map(str::to_owned), kind }
+    }
+    /// The name of the Scope as passed on initialization
+    pub fn scope(&self) -> &str {
+        &self.scope
+    }
+    /// The name of the LogKey, `None` for warnings about the whole Scope
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+    /// The kind of the misconfiguration
+    pub fn kind(&self) -> ConfigWarningKind {
+        self.kind
+    }
+}
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.key {
+            Some(ref key) => write!(f, "{}/{}: ", self.scope, key)?,
+            None => write!(f, "{}: ", self.scope)?,
+        }
+        f.write_str(match self.kind {
+            ConfigWarningKind::NoFacade => "logging is enabled but there is no facade",
+            ConfigWarningKind::NanosecWithoutTimestamp => "NANOSEC has no effect without TIMESTAMP",
+            ConfigWarningKind::LineWithoutFile => "LINE has no effect without FILE",
+            ConfigWarningKind::ExactMatchOff => "EXACT_LVL_MATCH is set but the level is off",
+            ConfigWarningKind::EmptyScope => "scope has no initialized keys",
+            ConfigWarningKind::DuplicateName => "another key of the scope has the same name",
+            ConfigWarningKind::LogCompatOnKey => "LOGCOMPAT only mutes the logcompat key",
+        })
+    }
+}
+
+/// Kind of an inconsistency reported by [`verify_keys`](fn@crate::verify_keys)
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum KeyMismatchKind {
+    /// The Scope has no entry at the index of the LogKey, e.g. it was never added
+    MissingSlot,
+    /// The entry at the index of the LogKey isn't initialized
+    Uninitialized,
+    /// The entry at the index of the LogKey has a different name
+    ///
+    /// Messages of the LogKey are attributed to the LogKey the entry was initialized with,
+    /// e.g. because [`LogKey::log_key`] of a hand written implementation returns a wrong index.
+    NameDrift,
+    /// Another of the verified LogKeys returns the same index
+    IndexCollision,
+}
+
+/// An inconsistency between a LogKey and its entry in the context
+///
+/// Returned by [`verify_keys`](fn@crate::verify_keys). The [`Display`](fmt::Display)
+/// implementation renders a human readable message including the name and index of the LogKey.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyMismatch {
+    key: String,
+    index: ContextKey,
+    registered: Option<String>,
+    kind: KeyMismatchKind,
+}
+impl KeyMismatch {
+    /// The name of the verified LogKey
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+    /// The index returned by [`LogKey::log_key`]
+    pub fn index(&self) -> ContextKey {
+        self.index
+    }
+    /// The name the entry at the index was initialized with, `None` if there is none
+    pub fn registered(&self) -> Option<&str> {
+        self.registered.as_deref()
+    }
+    /// The kind of the inconsistency
+    pub fn kind(&self) -> KeyMismatchKind {
+        self.kind
+    }
+}
+impl fmt::Display for KeyMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key '{}' (index {}): ", self.key, self.index)?;
+        let registered = self.registered().unwrap_or_default();
+        match self.kind {
+            KeyMismatchKind::MissingSlot => f.write_str("no entry in the scope"),
+            KeyMismatchKind::Uninitialized => f.write_str("entry is not initialized"),
+            KeyMismatchKind::NameDrift => write!(f, "entry is named '{}'", registered),
+            KeyMismatchKind::IndexCollision => {
+                write!(f, "index is used by '{}' as well", registered)
+            }
+        }
+    }
+}
+
+// compare each LogKey with the entry at its index in the scope
+pub (crate) fn check_keys<'a, K: LogKey + 'a>(
+    lm: &LogScope, keys: impl IntoIterator<Item = &'a K>,
+) -> Vec<KeyMismatch> {
+    use KeyMismatchKind::*;
+
+    let mut seen: HashMap<ContextKey, String> = HashMap::new();
+    let mut mismatches = Vec::new();
+    for key in keys {
+        let (index, name) = (key.log_key(), key.to_string().trim().to_owned());
+        let submod = lm.get_submodule_at(index);
+        let (kind, registered) = match (seen.get(&index), submod) {
+            (Some(first), _) if *first != name => (Some(IndexCollision), Some(first.clone())),
+            (_, None) => (Some(MissingSlot), None),
+            (_, Some(s)) if !s.initialized() => (Some(Uninitialized), None),
+            (_, Some(s)) => ((s.name() != name).then_some(NameDrift), Some(s.name().to_owned())),
+        };
+        seen.entry(index).or_insert_with(|| name.clone());
+        if let Some(kind) = kind {
+            mismatches.push(KeyMismatch { key: name, index, registered, kind });
+        }
+    }
+    mismatches
+}
+
+// all warnings of a single initialized scope, ordered by the index of the LogKeys
+pub (crate) fn check_scope(lm: &LogScope) -> Vec<ConfigWarning> {
+    use ConfigWarningKind::*;
+
+    let mut warnings = Vec::new();
+    let mut names = HashSet::new();
+    let is_logcompat = |key| lm.key() == InternalLogKeys::logscope() && key == LogCompat.log_key();
+    for submod in lm.submodules().filter(|s| s.initialized()) {
+        let level = lm.effective_level(submod);
+        let opts = submod.options();
+        let kinds = [
+            (level != Level::Off && matches!(submod.logdest(), FacadeScope::None), NoFacade),
+            (opts.has(NANOSEC) && !opts.has(TIMESTAMP), NanosecWithoutTimestamp),
+            (opts.has(LINE) && !opts.has(FILE), LineWithoutFile),
+            (opts.has(EXACT_LVL_MATCH) && level == Level::Off, ExactMatchOff),
+            (!names.insert(submod.name().to_ascii_lowercase()), DuplicateName),
+            (opts.has(LOGCOMPAT) && !lm.default_options().has(LOGCOMPAT)
+                && !is_logcompat(submod.key()), LogCompatOnKey),
+        ];
+        warnings.extend(kinds.into_iter().filter(|(found, _)| *found)
+            .map(|(_, kind)| ConfigWarning::new(lm, Some(submod.name()), kind)));
+    }
+    if names.is_empty() {
+        warnings.push(ConfigWarning::new(lm, None, EmptyScope));
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod validate_tests {
+    use super::{check_keys, check_scope, ConfigWarningKind::{self, *}, KeyMismatchKind};
+    use crate::{
+        log_internal::test::TestKeys::{self, *},
+        logmod::LogScope,
+        options::*,
+        FacadeVariant, Level, LogKey, Result, Scope,
+    };
+
+    // LogKey with a free index and name to provoke duplicate names
+    #[derive(Copy, Clone)]
+    struct Named(usize, &'static str);
+    impl Scope for Named {
+        fn init<S: std::fmt::Display>(_: S, _: Level, _: FacadeVariant, _: Options)
+            -> Result<()> { Ok(()) }
+    }
+    impl LogKey for Named {
+        fn log_key(&self) -> crate::ContextKey { self.0 }
+    }
+    impl std::fmt::Display for Named {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.1)
+        }
+    }
+
+    fn scope(level: Level, facade: FacadeVariant, options: Options) -> LogScope {
+        let mut lm = LogScope::init::<TestKeys, _>("validate", level, facade, options).unwrap();
+        lm.add_submodule(LIBTESTFOO).unwrap();
+        lm
+    }
+
+    fn kinds(lm: &LogScope) -> Vec<(Option<String>, ConfigWarningKind)> {
+        check_scope(lm).into_iter().map(|w| (w.key().map(str::to_owned), w.kind())).collect()
+    }
+
+    fn foo(kind: ConfigWarningKind) -> Vec<(Option<String>, ConfigWarningKind)> {
+        vec![(Some("libtestfoo".to_owned()), kind)]
+    }
+
+    #[test]
+    fn valid() {
+        let lm = scope(Level::Info, FacadeVariant::StdOut, Options::default());
+        assert_eq!(kinds(&lm), []);
+        let lm = scope(Level::Off, FacadeVariant::None, Options::default());
+        assert_eq!(kinds(&lm), []);
+    }
+
+    #[test]
+    fn key_options() {
+        let opts = Options::default();
+        let lm = scope(Level::Info, FacadeVariant::None, opts);
+        assert_eq!(kinds(&lm), foo(NoFacade));
+        let lm = scope(Level::Info, FacadeVariant::StdOut, opts - TIMESTAMP);
+        assert_eq!(kinds(&lm), foo(NanosecWithoutTimestamp));
+        let lm = scope(Level::Info, FacadeVariant::StdOut, opts - FILE);
+        assert_eq!(kinds(&lm), foo(LineWithoutFile));
+        let lm = scope(Level::Off, FacadeVariant::StdOut, opts + EXACT_LVL_MATCH);
+        assert_eq!(kinds(&lm), foo(ExactMatchOff));
+
+        let mut lm = scope(Level::Info, FacadeVariant::StdOut, opts - LOGCOMPAT);
+        assert_eq!(kinds(&lm), []);
+        lm[LIBTESTFOO].set_options(LOGCOMPAT);
+        assert_eq!(kinds(&lm), foo(LogCompatOnKey));
+    }
+
+    #[test]
+    fn scope_layout() {
+        let lm = LogScope::init::<TestKeys, _>("validate", Level::Info, FacadeVariant::StdOut,
+            Options::default()).unwrap();
+        let warnings = check_scope(&lm);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!((warnings[0].scope(), warnings[0].key(), warnings[0].kind()),
+            ("validate", None, EmptyScope));
+        assert_eq!(warnings[0].to_string(), "validate: scope has no initialized keys");
+
+        let mut lm = scope(Level::Info, FacadeVariant::StdOut, Options::default());
+        lm.add_submodule(Named(3, "libtestfoo")).unwrap();
+        lm.add_submodule(Named(4, "other")).unwrap();
+        let warnings = check_scope(&lm);
+        assert_eq!(kinds(&lm), foo(DuplicateName));
+        assert_eq!(warnings[0].to_string(),
+            "validate/libtestfoo: another key of the scope has the same name");
+    }
+
+    #[test]
+    fn key_mismatches() {
+        let mut lm = scope(Level::Info, FacadeVariant::StdOut, Options::default());
+        lm.add_submodule(Named(2, "db")).unwrap();
+        lm.add_submodule(Named(3, "net")).unwrap();
+        assert_eq!(check_keys(&lm, &[Named(2, "db"), Named(3, "net")]), []);
+
+        // index 1 is padding, index 5 doesn't exist, net points to the index of db
+        let keys = [Named(1, "gap"), Named(5, "late"), Named(2, "net"), Named(3, "net"),
+            Named(2, "db")];
+        let found = check_keys(&lm, &keys).into_iter()
+            .map(|m| (m.key().to_owned(), m.index(), m.registered().map(str::to_owned), m.kind()))
+            .collect::<Vec<_>>();
+        let mismatch = |key: &str, index, registered: Option<&str>, kind| {
+            (key.to_owned(), index, registered.map(str::to_owned), kind)
+        };
+        assert_eq!(found, [
+            mismatch("gap", 1, None, KeyMismatchKind::Uninitialized),
+            mismatch("late", 5, None, KeyMismatchKind::MissingSlot),
+            mismatch("net", 2, Some("db"), KeyMismatchKind::NameDrift),
+            mismatch("db", 2, Some("net"), KeyMismatchKind::IndexCollision),
+        ]);
+        let messages = check_keys(&lm, &keys).iter().map(|m| m.to_string()).collect::<Vec<_>>();
+        assert_eq!(messages, [
+            "key 'gap' (index 1): entry is not initialized",
+            "key 'late' (index 5): no entry in the scope",
+            "key 'net' (index 2): entry is named 'db'",
+            "key 'db' (index 2): index is used by 'net' as well",
+        ]);
+    }
+}
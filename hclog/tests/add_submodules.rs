@@ -0,0 +1,88 @@
+/*
+ * the ways to pass LogKeys to add_submodules and init_modules
+ *
+ * Every test uses its own Scope because the tests of this binary share the global context.
+ */
+use hclog::{
+    options::Options, ContextKey, ErrorKind, FacadeVariant, IntoLogKey, Level, LogKey, Scope,
+    ScopeKey,
+};
+use hclog_macros::HCLog;
+use std::fmt;
+use strum::IntoEnumIterator;
+
+#[derive(HCLog, strum_macros::EnumIter, Copy, Clone, Debug, PartialEq)]
+#[hclog(scope = ScopeKey::Application, init_submodules = false)]
+enum Derived {
+    Alpha,
+    Beta,
+    Gamma,
+}
+
+// placeholders keep the discriminants contiguous
+#[allow(dead_code)]
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(scope = ScopeKey::Application, init_submodules = false)]
+enum Colliding {
+    #[hclog(ignore)]
+    Unused = 0,
+    #[hclog(name = "other")]
+    Beta = 1,
+    #[hclog(ignore)]
+    Skipped = 2,
+    Delta = 3,
+    Epsilon = 4,
+}
+
+// implemented by hand including IntoLogKey
+#[derive(Copy, Clone, Debug)]
+enum Manual {
+    One,
+    Two,
+}
+impl fmt::Display for Manual {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+impl Scope for Manual {
+    fn init<S: fmt::Display>(name: S, level: Level, facade: FacadeVariant, options: Options)
+        -> hclog::Result<()>
+    {
+        hclog::init_modules(name, vec![Self::One], level, facade, options)
+    }
+    fn logscope() -> ScopeKey { ScopeKey::Lib }
+}
+impl LogKey for Manual {
+    fn log_key(&self) -> ContextKey { *self as ContextKey }
+}
+impl IntoLogKey for Manual {
+    type Key = Self;
+    fn into_log_key(self) -> Self { self }
+}
+
+#[test]
+#[allow(clippy::needless_borrows_for_generic_args)] // borrowing is one of the styles
+fn calling_styles() {
+    use Derived::*;
+
+    Derived::init_with_defaults("styles").unwrap();
+    hclog::add_submodules(&[Alpha]).unwrap();
+    hclog::add_submodules(vec![Beta]).unwrap();
+    hclog::add_submodules(Derived::iter().filter(|k| *k == Gamma)).unwrap();
+    hclog::add_submodules(Derived::iter()).unwrap();
+    hclog::add_submodules(&vec![Alpha, Beta]).unwrap();
+    hclog::add_submodules_with(Derived::ALL.iter().copied(), hclog::MergePolicy::Skip).unwrap();
+    assert!(Derived::iter().all(|k| hclog::has_module(k) == Ok(true)));
+
+    // the first failure aborts, the keys before it stay
+    let keys = [Colliding::Delta, Colliding::Beta, Colliding::Epsilon];
+    assert_eq!(hclog::add_submodules(keys), Err(ErrorKind::KeyCollision));
+    assert_eq!(hclog::has_module(Colliding::Delta), Ok(true));
+    assert_eq!(hclog::has_module(Colliding::Epsilon), Ok(false));
+
+    Manual::init_with_defaults("manual").unwrap();
+    assert_eq!(hclog::has_module(Manual::Two), Ok(false));
+    hclog::add_submodules(std::iter::once(Manual::Two)).unwrap();
+    assert_eq!(hclog::has_module(Manual::Two), Ok(true));
+}
@@ -0,0 +1,88 @@
+/*
+ * configuration changes of a LogKey copied to its aliases in another Scope
+ */
+use hclog::{options::{NONE, PID, TIMESTAMP}, ErrorKind, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum Host {
+    Net,
+    Db,
+    Child,
+}
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(scope = hclog::ScopeKey::Lib, max_level = hclog::Level::Debug5)]
+enum PluginKeys {
+    PluginNet,
+    PluginDb,
+    Child,
+}
+
+#[test]
+fn mirror_and_unalias() {
+    Host::init("host", Level::Info, FacadeVariant::None, NONE).unwrap();
+    PluginKeys::init("plugin", Level::Warn, FacadeVariant::None, NONE).unwrap();
+    assert_eq!(hclog::alias_key(Host::Net, Host::Net), Err(ErrorKind::AliasCycle));
+    hclog::alias_key(Host::Net, PluginKeys::PluginNet).unwrap();
+    assert_eq!(hclog::alias_key(PluginKeys::PluginNet, Host::Net), Err(ErrorKind::AliasCycle));
+    // chains are followed, a longer cycle is refused as well
+    hclog::alias_key(PluginKeys::PluginNet, PluginKeys::PluginDb).unwrap();
+    assert_eq!(hclog::alias_key(PluginKeys::PluginDb, Host::Net), Err(ErrorKind::AliasCycle));
+
+    // nothing is copied until the source changes
+    assert_eq!(hclog::test_log(PluginKeys::PluginNet, Level::Info), Ok(false));
+    hclog::set_mod_level(["net:debug3"]).unwrap();
+    for key in [PluginKeys::PluginNet, PluginKeys::PluginDb] {
+        assert_eq!(hclog::test_log(key, Level::Debug3), Ok(true));
+        assert_eq!(hclog::test_log(key, Level::Debug4), Ok(false));
+    }
+    hclog::set_level(Host::Net, Level::Error).unwrap();
+    assert_eq!(hclog::test_log(PluginKeys::PluginNet, Level::Warn), Ok(false));
+    assert_eq!(hclog::test_log(PluginKeys::PluginDb, Level::Error), Ok(true));
+    // only the aliased key is copied
+    hclog::set_level(Host::Db, Level::Debug1).unwrap();
+    assert_eq!(hclog::test_log(PluginKeys::PluginDb, Level::Debug1), Ok(false));
+
+    hclog::set_logdest(Host::Net, FacadeVariant::RingBuffer(4)).unwrap();
+    hclog::set_module_options(Host::Net, TIMESTAMP + PID).unwrap();
+    hclog::unset_module_options(Host::Net, TIMESTAMP).unwrap();
+    let plugin_net = PluginKeys::PluginNet;
+    hclog::lE!(plugin_net, "shared");
+    let lines = hclog::drain_ring(Host::Net).unwrap();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].starts_with('[') && lines[0].ends_with("shared"), "{}", lines[0]);
+
+    // changes of the target are not copied back
+    hclog::set_level(PluginKeys::PluginNet, Level::Debug5).unwrap();
+    assert_eq!(hclog::test_log(Host::Net, Level::Debug5), Ok(false));
+
+    assert_eq!(hclog::unalias_key(Host::Net, PluginKeys::PluginNet), Ok(true));
+    assert_eq!(hclog::unalias_key(Host::Net, PluginKeys::PluginNet), Ok(false));
+    hclog::set_mod_level(["net:crit"]).unwrap();
+    assert_eq!(hclog::test_log(Host::Net, Level::Error), Ok(false));
+    assert_eq!(hclog::test_log(PluginKeys::PluginNet, Level::Debug5), Ok(true));
+    // the alias of the target still applies
+    hclog::set_level(PluginKeys::PluginNet, Level::Notice).unwrap();
+    assert_eq!(hclog::test_log(PluginKeys::PluginDb, Level::Notice), Ok(true));
+
+    // the level is normalized by the Scope of the target
+    hclog::set_level(PluginKeys::PluginNet, Level::Debug10).unwrap();
+    assert_eq!(hclog::test_log(PluginKeys::PluginNet, Level::Debug10), Ok(true));
+    assert_eq!(hclog::test_log(PluginKeys::PluginDb, Level::Debug5), Ok(true));
+    assert_eq!(hclog::test_log(PluginKeys::PluginDb, Level::Debug6), Ok(false));
+
+    // an inherited level is mirrored as explicit level, not inherited from the own parent
+    hclog::set_parent(Host::Child, Host::Net).unwrap();
+    hclog::set_parent(PluginKeys::Child, PluginKeys::PluginDb).unwrap();
+    hclog::alias_key(Host::Child, PluginKeys::Child).unwrap();
+    hclog::set_level(PluginKeys::PluginDb, Level::Error).unwrap();
+    hclog::set_mod_level(["net.*:info"]).unwrap();
+    assert_eq!(hclog::test_log(PluginKeys::Child, Level::Info), Ok(true));
+
+    // the aliases of a Scope initialized again are removed
+    hclog::reinitialize::<PluginKeys, _>("plugin", Level::Warn, FacadeVariant::None, NONE).unwrap();
+    assert_eq!(hclog::unalias_key(Host::Child, PluginKeys::Child), Ok(false));
+    hclog::set_mod_level(["net.*:debug1"]).unwrap();
+    assert_eq!(hclog::test_log(PluginKeys::Child, Level::Info), Ok(false));
+}
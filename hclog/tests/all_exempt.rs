@@ -0,0 +1,39 @@
+/*
+ * LogKeys exempt from `_all` keep their level unless they are addressed by name
+ */
+use hclog::{options::NONE, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum ExemptKeys {
+    #[hclog(pin_level)]
+    Audit,
+    Net,
+    Firehose,
+}
+use ExemptKeys::*;
+
+fn enabled(key: ExemptKeys, level: Level) -> bool {
+    hclog::test_log(key, level).unwrap()
+}
+
+#[test]
+fn skip_exempt() {
+    ExemptKeys::init("exempt", Level::Info, FacadeVariant::None, NONE).unwrap();
+    hclog::set_mod_level(["_all:off"]).unwrap();
+    assert!(enabled(Audit, Level::Info));
+    assert!(!enabled(Net, Level::Error));
+    assert!(!enabled(Firehose, Level::Error));
+
+    hclog::set_all_exempt(Audit, false).unwrap();
+    hclog::set_all_exempt(Firehose, true).unwrap();
+    hclog::set_mod_level(["_all:debug10"]).unwrap();
+    assert!(enabled(Audit, Level::Debug10));
+    assert!(enabled(Net, Level::Debug10));
+    assert!(!enabled(Firehose, Level::Error));
+
+    // by name the exemption doesn't matter
+    hclog::set_mod_level(["Firehose:warn"]).unwrap();
+    assert!(enabled(Firehose, Level::Warn));
+    assert!(!enabled(Firehose, Level::Info));
+}
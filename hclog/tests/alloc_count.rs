@@ -0,0 +1,70 @@
+/*
+ * a log call of a disabled level doesn't allocate, an enabled one only a bounded amount
+ *
+ * The global allocator of this test binary counts the allocations of the current thread, the
+ * first calls warm up the lazily created thread locals and stdio buffers.
+ */
+use hclog::{options::NONE, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCS.try_with(|c| c.set(c.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCS.try_with(|c| c.set(c.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<F: FnMut()>(mut f: F) -> usize {
+    let before = ALLOCS.with(Cell::get);
+    f();
+    ALLOCS.with(Cell::get) - before
+}
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum AllocKeys {
+    Quiet,
+    Loud,
+}
+use AllocKeys::*;
+
+// upper bound for an enabled message: the function path and the rendered message
+const ENABLED_MAX: usize = 4;
+
+#[test]
+fn allocation_count() {
+    AllocKeys::init("alloc", Level::Info, FacadeVariant::StdOut, NONE).unwrap();
+    hclog::set_level(Quiet, Level::Warn).unwrap();
+    let vec = vec![1, 2, 3];
+    hclog::lD10!(Quiet, "warm up {:?}", vec);
+    hclog::lI!(Loud, "warm up {:?}", vec);
+
+    assert_eq!(allocations(|| hclog::lD10!(Quiet, "disabled {:?}", vec)), 0);
+    assert_eq!(allocations(|| hclog::lD10!(Loud, "disabled {:?}", vec)), 0);
+    assert_eq!(allocations(|| { hclog::test_log(Quiet, Level::Info).unwrap(); }), 0);
+
+    let enabled = allocations(|| hclog::lI!(Loud, "enabled {:?}", vec));
+    assert!(enabled > 0 && enabled <= ENABLED_MAX, "{} allocations", enabled);
+    // a message without arguments is borrowed instead of formatted into a String
+    let literal = allocations(|| hclog::lI!(Loud, "enabled"));
+    assert!(literal < enabled, "{} allocations", literal);
+}
@@ -0,0 +1,75 @@
+/*
+ * task local copies of a whole scope via async_scope_for
+ */
+mod common;
+
+use hclog::{Level, Scope, ScopeKey, ErrorKind};
+use hclog_macros::HCLog;
+use common::block_on;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(scope = hclog::ScopeKey::Application, default_level = Level::Info)]
+enum TaskKeys {
+    Net,
+    Db,
+}
+use TaskKeys::*;
+
+#[test]
+fn whole_scope_is_task_local() {
+    TaskKeys::init_with_defaults("async").unwrap();
+    let task = hclog::async_scope_for("worker", ScopeKey::Application, async {
+        // both keys are part of the task local copy, so the changes don't leak
+        hclog::set_level(Net, Level::Debug3).unwrap();
+        hclog::set_level(Db, Level::Debug7).unwrap();
+        (hclog::test_log(Net, Level::Debug3), hclog::test_log(Db, Level::Debug7))
+    }).unwrap();
+    assert_eq!(block_on(task), (Ok(true), Ok(true)));
+    assert_eq!(hclog::test_log(Net, Level::Debug3), Ok(false));
+    assert_eq!(hclog::test_log(Db, Level::Debug7), Ok(false));
+    assert_eq!(hclog::test_log(Db, Level::Info), Ok(true));
+}
+
+#[test]
+fn invalid_scope() {
+    assert!(matches!(hclog::async_scope_for("w", ScopeKey::MAX, async {}),
+        Err(ErrorKind::InvalidScope)));
+    assert!(matches!(hclog::async_scope_for("w", ScopeKey::Lib, async {}),
+        Err(ErrorKind::ScopeNotInitialized)));
+}
+
+#[test]
+fn scope_ident() {
+    TaskKeys::init_with_defaults("async").unwrap();
+    assert_eq!((hclog::current_scope_ident(), hclog::in_task_scope()), (None, false));
+    let task = hclog::scope("Task1", Net, async {
+        (hclog::current_scope_ident(), hclog::in_task_scope())
+    }).unwrap();
+    assert_eq!(block_on(task), (Some("Task1".to_owned()), true));
+    let task = hclog::async_scope_for("worker", ScopeKey::Application, async {
+        hclog::current_scope_ident()
+    }).unwrap();
+    assert_eq!(block_on(task).as_deref(), Some("worker"));
+    assert_eq!((hclog::current_scope_ident(), hclog::in_task_scope()), (None, false));
+}
+
+#[test]
+fn dump_task_context() {
+    TaskKeys::init_with_defaults("async").unwrap();
+    let task = hclog::scope("Task1", Net, async {
+        hclog::set_level(Net, Level::Debug4).unwrap();
+        let mut buf = Vec::new();
+        hclog::dump_always(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }).unwrap();
+    let dump = block_on(task);
+    let (global, local) = dump.split_once("=== task-local context (ident=Task1) ===\n")
+        .expect(&dump);
+    assert!(global.contains("=== global context ===\n"), "{}", global);
+    assert!(local.contains("Debug4"), "{}", local);
+
+    let mut buf = Vec::new();
+    hclog::dump_always(&mut buf).unwrap();
+    let dump = String::from_utf8(buf).unwrap();
+    assert!(!dump.contains("==="), "{}", dump);
+}
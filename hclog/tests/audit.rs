@@ -0,0 +1,54 @@
+/*
+ * audit trail of runtime changes via the Internal key
+ *
+ * The binary contains a single test because the audit toggle and the Internal key are global.
+ * run with: cargo test -p hclog --features test-util
+ */
+use hclog::{
+    Level, FacadeVariant, Scope, InternalLogKeys::Internal, options::*, testing::MockFacade,
+};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum AuditKeys {
+    #[hclog(name = "db")]
+    Db,
+}
+use AuditKeys::*;
+
+#[test]
+fn audit_changes() {
+    AuditKeys::init_with_defaults("audit").unwrap();
+    let mock = MockFacade::new();
+    hclog::set_logdest(Internal, FacadeVariant::Mock(mock.clone())).unwrap();
+    hclog::set_level(Internal, Level::Info).unwrap();
+    hclog::set_level(Db, Level::Info).unwrap();
+    hclog::unset_module_options(Db, Options::default()).unwrap();
+    // nothing recorded while disabled
+    assert!(mock.calls().is_empty());
+
+    hclog::set_audit(true);
+    hclog::set_level(Db, Level::Debug5).unwrap();
+    hclog::set_mod_level(["db:warn"]).unwrap();
+    hclog::set_logdest(Db, FacadeVariant::StdErr).unwrap();
+    hclog::set_module_options(Db, PID).unwrap();
+    hclog::unset_module_options(Db, PID).unwrap();
+    hclog::reset_module_options(Db).unwrap();
+    // failed changes are not recorded, nothing is applied if any argument fails
+    assert!(hclog::set_mod_level(["nope:info"]).is_err());
+    assert!(hclog::set_mod_level(["db:info,nope:info"]).is_err());
+    assert_eq!(hclog::test_log(Db, Level::Info), Ok(false));
+    hclog::set_audit(false);
+    hclog::set_level(Db, Level::Error).unwrap();
+
+    let calls = mock.calls();
+    assert!(calls.iter().all(|(lvl, _)| *lvl == Level::Info));
+    assert_eq!(calls.into_iter().map(|(_, msg)| msg).collect::<Vec<_>>(), [
+        "level of 'db' changed info -> debug5".to_owned(),
+        "level of 'db' changed debug5 -> warn".to_owned(),
+        "facade of 'db' changed to StdErr".to_owned(),
+        "options of 'db' changed NONE -> PID".to_owned(),
+        "options of 'db' changed PID -> NONE".to_owned(),
+        format!("options of 'db' changed NONE -> {}", Options::default()),
+    ]);
+}
@@ -0,0 +1,38 @@
+/*
+ * default options picked for the default facade via auto_options
+ *
+ * The environment is changed, so there is a single test only.
+ */
+use hclog::{options::{Options, LOGCOMPAT, PID}, FacadeVariant, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(auto_options, default_facade = FacadeVariant::File("/dev/null".into(), false))]
+enum FileKeys {
+    Disk,
+}
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(scope = hclog::ScopeKey::Lib, auto_options, no_log_compat_inherit,
+    default_facade = FacadeVariant::StdErr)]
+enum ConsoleKeys {
+    Console,
+}
+
+#[test]
+fn defaults_and_env() {
+    assert_eq!(FileKeys::default_options(), Options::default());
+    // whether stderr is a terminal depends on how the tests are run
+    assert_eq!(ConsoleKeys::default_options(),
+        Options::auto_for(&FacadeVariant::StdErr) - LOGCOMPAT);
+
+    // the environment is applied on top
+    std::env::set_var("HCLOG_OPT_PID", "0");
+    FileKeys::init_with_defaults("auto").unwrap();
+    let mut out = Vec::new();
+    hclog::list_modules_detailed(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let expected = format!("  Disk [level=off (code)] [facade=file (code)] [opts={} (env)]",
+        Options::default() - PID);
+    assert!(out.lines().any(|l| l == expected), "{}", out);
+}
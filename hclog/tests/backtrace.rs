@@ -0,0 +1,45 @@
+/*
+ * backtraces appended to severe messages by the BACKTRACE option
+ */
+use hclog::{options::{BACKTRACE, DEDUP, NONE}, Level, FacadeVariant, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info, default_facade = FacadeVariant::None)]
+enum TraceKeys {
+    Traced,
+}
+use TraceKeys::*;
+
+#[test]
+fn severe_messages_only() {
+    TraceKeys::init("backtrace", Level::Info, FacadeVariant::None, NONE + BACKTRACE).unwrap();
+    let sub = hclog::subscribe(Traced, 8).unwrap();
+    hclog::lC!(Traced, "crit");
+    hclog::lE!(Traced, "error");
+    hclog::lW!(Traced, "warn");
+    hclog::lI!(Traced, "info");
+    let lines = sub.try_iter().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 4);
+
+    let (first, frames) = lines[0].split_once('\n').unwrap();
+    assert_eq!(first, "crit");
+    assert!(frames.lines().any(|l| l.contains("severe_messages_only")), "{}", frames);
+    assert!(lines[1].starts_with("error\n"), "{}", lines[1]);
+    assert_eq!(&lines[2..], ["warn", "info"]);
+
+    // the stack is appended after DEDUP compared the messages of different call sites
+    hclog::set_module_options(Traced, DEDUP).unwrap();
+    hclog::lE!(Traced, "repeated");
+    hclog::lE!(Traced, "repeated");
+    hclog::lW!(Traced, "next");
+    let lines = sub.try_iter().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3, "{:?}", lines);
+    assert!(lines[0].starts_with("repeated\n"), "{}", lines[0]);
+    assert!(lines[1].starts_with("last message repeated 1 times"), "{}", lines[1]);
+    hclog::unset_module_options(Traced, DEDUP).unwrap();
+
+    hclog::unset_module_options(Traced, BACKTRACE).unwrap();
+    hclog::lC!(Traced, "without");
+    assert_eq!(sub.try_iter().collect::<Vec<_>>(), ["without"]);
+}
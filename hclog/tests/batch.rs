@@ -0,0 +1,36 @@
+/*
+ * several messages logged at once via the batch! macro
+ */
+use hclog::{Level, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info, default_facade = hclog::FacadeVariant::None)]
+enum BatchKeys {
+    Dump,
+}
+use BatchKeys::*;
+
+#[test]
+fn messages_in_order() {
+    BatchKeys::init_with_defaults("batch").unwrap();
+    let sub = hclog::subscribe(Dump, 16).unwrap();
+    let mut evaluated = 0;
+    let mut count = || { evaluated += 1; evaluated };
+
+    hclog::batch!(Dump, Level::Info, {
+        "first";
+        "second {}", count();
+        "third {} {}", count(), "x";
+    });
+    let lines = sub.try_iter().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3, "{:?}", lines);
+    assert!(lines[0].ends_with(" first"), "{:?}", lines);
+    assert!(lines[1].ends_with(" second 1"), "{:?}", lines);
+    assert!(lines[2].ends_with(" third 2 x"), "{:?}", lines);
+
+    // the arguments aren't evaluated if the level is disabled
+    hclog::batch!(Dump, Level::Debug1, { "fourth {}", count() });
+    assert_eq!(sub.try_iter().count(), 0);
+    assert_eq!(count(), 3);
+}
@@ -0,0 +1,187 @@
+/*
+ * the behavior shown by the examples, asserted
+ *
+ * Each part reconstructs the configuration of one example (derive.rs, enum.rs, async.rs) but
+ * logs into FacadeVariant::RingBuffer or a subscription instead of stdout. The binary contains
+ * a single test because the parts share the Application scope and the logger of crate log.
+ */
+mod common;
+
+use hclog::{
+    options::{Options, SCOPE},
+    ContextKey, FacadeVariant, InternalLogKeys::LogCompat, Level, LogKey, Scope, ScopeKey,
+};
+use hclog_macros::HCLog;
+use std::fmt;
+use common::block_on;
+
+// examples/derive.rs
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(
+    scope = ScopeKey::Application,
+    default_level = Level::Info,
+    default_facade = FacadeVariant::RingBuffer(16),
+    with_log,
+)]
+enum DeriveKeys {
+    #[hclog(name = "foo", facade = FacadeVariant::StdOut)]
+    KeyA = 0,
+    #[hclog(name = "bar", level = Level::Warn)]
+    KeyB,
+    #[hclog(ignore, name = "ignored", facade = FacadeVariant::StdErr)]
+    KeyC = 2,
+    #[hclog(ignore, name = "KeyD")]
+    KeyD = 3,
+    #[hclog(name = "KeyE", level = Level::Warn)]
+    KeyE = 4,
+}
+
+// examples/enum.rs, placed in the Lib scope
+#[derive(Copy, Clone, Debug)]
+enum EnumKeys {
+    Foo,
+    Bar,
+    Baz,
+}
+impl fmt::Display for EnumKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+impl Scope for EnumKeys {
+    fn init<S: fmt::Display>(name: S, level: Level, facade: FacadeVariant, options: Options)
+        -> hclog::Result<()>
+    {
+        hclog::init::<Self, S>(name, level, facade, options)?;
+        hclog::add_submodules(&[Self::Foo, Self::Bar])
+    }
+    fn logscope() -> ScopeKey { ScopeKey::Lib }
+}
+impl LogKey for EnumKeys {
+    fn log_key(&self) -> ContextKey { *self as ContextKey }
+    fn init_level(&self) -> Option<Level> {
+        matches!(self, Self::Foo).then_some(Level::Info)
+    }
+    fn init_facade(&self) -> Option<FacadeVariant> {
+        matches!(self, Self::Bar).then_some(FacadeVariant::None)
+    }
+}
+
+// examples/async.rs, behind the keys of DeriveKeys
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(scope = ScopeKey::Application, merge = "append")]
+enum AsyncKeys {
+    #[hclog(name = "async")]
+    Async,
+    #[hclog(name = "global")]
+    Global,
+}
+
+fn listing() -> String {
+    let mut out = Vec::new();
+    hclog::list_modules_detailed(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn derive_example() {
+    use DeriveKeys::*;
+
+    DeriveKeys::init_with_defaults("derive").unwrap();
+    // ignored keys aren't registered until they are added
+    assert_eq!(hclog::has_module(KeyC), Ok(false));
+    assert_eq!(hclog::has_module(KeyD), Ok(false));
+    assert!(!hclog::tEM!(KeyC));
+
+    // the attributes of the variants take precedence over the defaults of the scope
+    let modules = listing();
+    assert!(modules.contains("  foo [level=info (code)] [facade=stdout (code)]"), "{}", modules);
+    assert!(modules.contains("  bar [level=warn (code)] [facade=ringbuffer (code)]"),
+        "{}", modules);
+    assert!(modules.contains("  KeyE [level=warn (code)] [facade=ringbuffer (code)]"),
+        "{}", modules);
+    assert!(!modules.contains("ignored"), "{}", modules);
+
+    hclog::lI!(KeyB, "filtered by the level of the key");
+    hclog::lW!(KeyB, "written");
+    let lines = hclog::drain_ring(KeyB).unwrap();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].ends_with("written"), "{}", lines[0]);
+
+    // the facade of an ignored key is replaced by FacadeVariant::None
+    #[allow(clippy::needless_borrows_for_generic_args)]
+    hclog::add_submodules(&[KeyC]).unwrap();
+    assert_eq!(hclog::has_module(KeyC), Ok(true));
+    assert_eq!(hclog::has_module(KeyD), Ok(false));
+    let modules = listing();
+    assert!(modules.contains("  ignored [level=info (code)] [facade=none (code)]"), "{}", modules);
+    // owned keys are accepted as well
+    hclog::add_submodules(vec![KeyD]).unwrap();
+    assert_eq!(hclog::has_module(KeyD), Ok(true));
+
+    // with_log forwards crate log with the level of the LogCompat key
+    let compat = hclog::subscribe(LogCompat, 8).unwrap();
+    log::warn!("compat warn");
+    log::trace!("compat trace");
+    hclog::set_level(LogCompat, Level::Error).unwrap();
+    log::warn!("compat muted");
+    log::error!("compat error");
+    let lines = compat.try_iter().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2, "{:?}", lines);
+    assert!(lines[0].ends_with("compat warn"), "{}", lines[0]);
+    assert!(lines[1].ends_with("compat error"), "{}", lines[1]);
+}
+
+fn enum_example() {
+    use EnumKeys::*;
+
+    EnumKeys::init("enum", Level::Debug10, FacadeVariant::RingBuffer(4), Options::default())
+        .unwrap();
+    assert_eq!(hclog::has_module(Foo), Ok(true));
+    assert_eq!(hclog::has_module(Bar), Ok(true));
+    assert_eq!(hclog::has_module(Baz), Ok(false));
+    // init_level and init_facade override the values passed to init
+    assert!(hclog::tI!(Foo) && !hclog::tD1!(Foo));
+    assert!(hclog::tD10!(Bar));
+    hclog::lI!(Foo, "hello from {}", Foo);
+    assert_eq!(hclog::drain_ring(Foo).map(|l| l.len()), Ok(1));
+    assert_eq!(hclog::drain_ring(Bar), Err(hclog::ErrorKind::NoRingBuffer));
+}
+
+fn async_example() {
+    use AsyncKeys::*;
+
+    AsyncKeys::init("async", Level::Info, FacadeVariant::RingBuffer(8), Options::default() + SCOPE)
+        .unwrap();
+    // appended keys inherit the options of the Scope initialized first
+    hclog::set_module_options(Async, SCOPE).unwrap();
+    let sub = hclog::subscribe(Async, 8).unwrap();
+    let task = hclog::scope("Task1", Async, async {
+        hclog::lI!(Async, "in the task scope");
+        // the task works on a copy of the whole Scope
+        hclog::set_level(Async, Level::Debug10).unwrap();
+        hclog::set_level(Global, Level::Debug10).unwrap();
+        hclog::lD10!(Async, "boosted");
+        (hclog::drain_ring(Async).unwrap(), hclog::tD10!(Global))
+    }).unwrap();
+    let (lines, boosted) = block_on(task);
+    assert!(boosted);
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|l| l.contains("Task1")), "{:?}", lines);
+    assert!(lines[0].ends_with("in the task scope"), "{}", lines[0]);
+
+    // subscriptions are shared with the copy, the levels aren't
+    assert_eq!(sub.try_iter().count(), 2);
+    assert!(!hclog::tD10!(Async) && !hclog::tD10!(Global));
+    assert_eq!(hclog::drain_ring(Async).map(|l| l.len()), Ok(0));
+    hclog::lI!(Async, "outside of the task scope");
+    let lines = sub.try_iter().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1);
+    assert!(!lines[0].contains("Task1"), "{}", lines[0]);
+}
+
+#[test]
+fn examples() {
+    derive_example();
+    enum_example();
+    async_example();
+}
@@ -0,0 +1,54 @@
+/*
+ * a failing secondary of WithBestEffort neither affects the primary nor the caller
+ */
+use hclog::{
+    options::NONE, testing::MockFacade, ErrorKind, FacadeVariant, InternalLogKeys, Level, Scope,
+};
+use hclog_macros::HCLog;
+use std::io;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum MirrorKeys {
+    Audit,
+}
+use MirrorKeys::*;
+
+fn failures() -> String {
+    let summary = hclog::health_summary();
+    summary.split(' ').find(|f| f.starts_with("facade_failures=")).unwrap().to_owned()
+}
+
+fn log(msg: &str) -> hclog::Result<()> {
+    hclog::log(Audit, Level::Info, file!(), None, line!(), &format_args!("{}", msg))
+}
+
+#[test]
+fn failing_secondary() {
+    let (primary, secondary) = (MockFacade::new(), MockFacade::new());
+    secondary.set_failing(Some(io::ErrorKind::ConnectionRefused));
+    let facade = FacadeVariant::WithBestEffort(Box::new(FacadeVariant::Mock(primary.clone())),
+        Box::new(FacadeVariant::Mock(secondary.clone())));
+    MirrorKeys::init("mirror", Level::Info, facade, NONE).unwrap();
+    hclog::set_logdest(InternalLogKeys::Internal, FacadeVariant::RingBuffer(8)).unwrap();
+    hclog::set_level(InternalLogKeys::Internal, Level::Warn).unwrap();
+
+    assert_eq!(log("first"), Ok(()));
+    assert_eq!(hclog::try_log(Audit, Level::Info, format_args!("second")), Ok(true));
+    assert_eq!(log("third"), Ok(()));
+    let payloads = primary.calls().into_iter().map(|(_, p)| p).collect::<Vec<_>>();
+    assert_eq!(payloads, ["first", "second", "third"]);
+    assert_eq!(failures(), "facade_failures=3");
+    assert_eq!(hclog::flush(), Ok(()));
+
+    // the first failure is reported once
+    let reports = hclog::drain_ring(InternalLogKeys::Internal).unwrap();
+    assert_eq!(reports.len(), 1, "{:?}", reports);
+    assert!(reports[0].contains("best-effort facade 'custom' failed"), "{}", reports[0]);
+
+    // the secondary mirrors again once it recovered, errors of the primary are returned
+    secondary.set_failing(None);
+    primary.set_failing(Some(io::ErrorKind::PermissionDenied));
+    assert_eq!(log("fourth"), Err(ErrorKind::IoError(io::ErrorKind::PermissionDenied)));
+    assert_eq!(secondary.calls(), [(Level::Info, "fourth".to_owned())]);
+    assert_eq!(failures(), "facade_failures=3");
+}
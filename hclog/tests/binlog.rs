@@ -0,0 +1,46 @@
+/*
+ * messages logged via the Binary facade are read back by binlog::Reader
+ */
+use hclog::{binlog::Reader, options::*, FacadeVariant, Level, Scope};
+use strum::IntoEnumIterator;
+
+mod keys {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    pub enum BinKeys {
+        #[hclog(name = "store")]
+        Store,
+        #[hclog(name = "fetch")]
+        Fetch,
+    }
+}
+
+use keys::BinKeys::{self, *};
+
+#[test]
+fn binary_facade_round_trip() {
+    let path = std::env::temp_dir().join(format!("hclog_binlog_{}.bin", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    BinKeys::init("binlog", Level::Debug10, FacadeVariant::Binary(path.clone()),
+        Options::default()).unwrap();
+
+    let levels = Level::iter().skip(1).collect::<Vec<_>>();
+    let mut expected = Vec::new();
+    for i in 0..2000 {
+        let (key, name) = if i % 3 == 0 { (Store, "store") } else { (Fetch, "fetch") };
+        let level = levels[i % levels.len()];
+        hclog::log(key, level, file!(), None, line!(), &format_args!("record {}", i)).unwrap();
+        expected.push((level, name, format!("record {}", i)));
+    }
+
+    let records = Reader::open(&path).unwrap().collect::<hclog::Result<Vec<_>>>().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(records.len(), expected.len());
+    for (r, (level, name, msg)) in records.iter().zip(&expected) {
+        assert_eq!((r.level(), r.module(), r.message()), (*level, *name, msg.as_str()));
+        assert_eq!(r.render(&(SEVERITY + MODULE)), format!("{} {} {}", level, name, msg));
+        assert_eq!(r.render(&RAW), *msg);
+    }
+    assert!(records.windows(2).all(|w| w[0].time() <= w[1].time()));
+}
@@ -0,0 +1,36 @@
+/*
+ * time based features follow the clock installed via set_clock
+ */
+use hclog::{options::*, testing::MockClock, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+use std::{sync::Arc, time::{Duration, UNIX_EPOCH}};
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info)]
+enum ClockKeys {
+    Decay,
+    Stamped,
+}
+use ClockKeys::*;
+
+#[test]
+fn mocked_time() {
+    let clock = MockClock::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+    hclog::set_clock(Arc::new(clock.clone()));
+    ClockKeys::init("clock", Level::Info, FacadeVariant::StdOut,
+        DATESTAMP + TIMESTAMP + UPTIME).unwrap();
+    hclog::reset_uptime_base();
+
+    // the boost only expires once the mocked time passed
+    hclog::set_level_for(Decay, Level::Debug10, Duration::from_secs(60)).unwrap();
+    clock.advance(Duration::from_secs(59));
+    assert!(hclog::tD10!(Decay));
+    clock.advance(Duration::from_secs(2));
+    assert!(!hclog::tD1!(Decay));
+    assert!(hclog::tI!(Decay));
+
+    let lines = hclog::subscribe(Stamped, 4).unwrap();
+    hclog::lI!(Stamped, "x");
+    assert_eq!(lines.try_iter().collect::<Vec<_>>(), ["2023-11-14 22:14:21 [+61.000000s] x"]);
+    hclog::reset_clock();
+}
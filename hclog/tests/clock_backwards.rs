@@ -0,0 +1,57 @@
+/*
+ * a clock going back neither stalls nor shortens intervals, only the timestamps follow it
+ */
+use hclog::{options::*, testing::MockClock, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+use std::{sync::Arc, time::{Duration, UNIX_EPOCH}};
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info)]
+enum BackKeys {
+    Boost,
+    Repeat,
+    Stamped,
+}
+use BackKeys::*;
+
+#[test]
+fn clock_goes_back() {
+    let clock = MockClock::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+    hclog::set_clock(Arc::new(clock.clone()));
+    BackKeys::init("back", Level::Info, FacadeVariant::RingBuffer(8), NONE).unwrap();
+    hclog::reset_uptime_base();
+
+    // the time observed before the step back counts towards the boost
+    hclog::set_level_for(Boost, Level::Debug10, Duration::from_secs(60)).unwrap();
+    clock.advance(Duration::from_secs(30));
+    assert!(hclog::tD10!(Boost));
+    clock.rewind(Duration::from_secs(3600));
+    assert!(hclog::tD10!(Boost));
+    clock.advance(Duration::from_secs(29));
+    assert!(hclog::tD10!(Boost));
+    clock.advance(Duration::from_secs(2));
+    assert!(!hclog::tD1!(Boost));
+
+    // the dedup interval isn't extended by the step back either
+    hclog::set_module_options(Repeat, DEDUP).unwrap();
+    hclog::set_dedup_interval(Repeat, Some(Duration::from_secs(10))).unwrap();
+    hclog::lI!(Repeat, "a");
+    clock.advance(Duration::from_secs(5));
+    hclog::lI!(Repeat, "a");
+    clock.rewind(Duration::from_secs(600));
+    hclog::lI!(Repeat, "a");
+    clock.advance(Duration::from_secs(5));
+    hclog::lI!(Repeat, "a");
+    assert_eq!(hclog::drain_ring(Repeat).unwrap(), ["a", "last message repeated 3 times"]);
+
+    // the timestamps show the earlier time while the elapsed time keeps growing
+    hclog::set_module_options(Stamped, DATESTAMP + TIMESTAMP + UPTIME).unwrap();
+    hclog::lI!(Stamped, "x");
+    clock.rewind(Duration::from_secs(60));
+    hclog::lI!(Stamped, "y");
+    assert_eq!(hclog::drain_ring(Stamped).unwrap(), [
+        "2023-11-14 21:04:31 [+71.000000s] x",
+        "2023-11-14 21:03:31 [+71.000000s] y",
+    ]);
+    hclog::reset_clock();
+}
@@ -0,0 +1,23 @@
+/*
+ * helpers shared by the integration tests, included with `mod common;`
+ */
+use std::{
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+};
+
+struct Noop;
+impl Wake for Noop {
+    fn wake(self: Arc<Self>) {}
+}
+
+// the futures of the tests never wait, a single poll completes them
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(Noop));
+    match pin!(future).poll(&mut Context::from_waker(&waker)) {
+        Poll::Ready(out) => out,
+        Poll::Pending => panic!("future is pending"),
+    }
+}
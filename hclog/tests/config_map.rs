@@ -0,0 +1,81 @@
+/*
+ * settings applied by apply_config_map
+ *
+ * All checks run in one test as the settings are applied to the process wide context.
+ */
+use hclog::{options::TIMESTAMP, ErrorKind, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+use std::collections::HashMap;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum ConfigKeys {
+    Db,
+    Net,
+    Ui,
+}
+use ConfigKeys::*;
+
+fn detailed() -> Vec<String> {
+    let mut out = Vec::new();
+    hclog::list_modules_detailed(&mut out).unwrap();
+    String::from_utf8(out).unwrap().lines().map(|l| l.trim().to_owned())
+        .filter(|l| !l.starts_with("hclog ")).collect()
+}
+
+#[test]
+fn apply() {
+    ConfigKeys::init("config", Level::Info, FacadeVariant::None, TIMESTAMP).unwrap();
+    let map = HashMap::from([
+        ("level._all", "warn"),
+        ("level.db", "debug1"),
+        ("level.net", "loud"),
+        ("facade.config/net", "ringbuffer:8"),
+        ("facade.ghost", "stdout"),
+        ("options.ui", "+PID-TIMESTAMP"),
+        ("options.db", "PID"),
+        ("colour.db", "red"),
+        ("bogus", "1"),
+    ].map(|(k, v)| (k.to_owned(), v.to_owned())));
+    let report = hclog::apply_config_map(&map).unwrap();
+    // _all goes first, the remaining entries are ordered by their key
+    assert_eq!(report.applied(), ["level._all", "facade.config/net", "level.db", "options.db",
+        "options.ui"]);
+    assert_eq!(report.skipped(), ["bogus", "colour.db", "facade.ghost"]);
+    assert_eq!(report.failed(), [("level.net".to_owned(), ErrorKind::UnknownLogLevel)]);
+    assert!(!report.is_complete());
+    assert_eq!(detailed(), [
+        "Db [level=debug1 (runtime)] [facade=none (code)] [opts=PID (runtime)]",
+        "Net [level=warn (runtime)] [facade=ringbuffer (runtime)] [opts=TIMESTAMP (code)]",
+        "Ui [level=warn (runtime)] [facade=none (code)] [opts=PID (runtime)]",
+    ]);
+    hclog::lW!(Net, "kept");
+    assert_eq!(hclog::drain_ring(Net).unwrap().len(), 1);
+
+    // a single key overrides _all regardless of the order of the map
+    let map = HashMap::from([
+        ("options.net".to_owned(), "-PID".to_owned()),
+        ("options._all".to_owned(), "+PID".to_owned()),
+        ("level.ui".to_owned(), "+1".to_owned()),
+        ("level._all".to_owned(), "error".to_owned()),
+    ]);
+    let report = hclog::apply_config_map(&map).unwrap();
+    assert!(report.is_complete());
+    assert_eq!(detailed(), [
+        "Db [level=error (runtime)] [facade=none (code)] [opts=PID (runtime)]",
+        "Net [level=error (runtime)] [facade=ringbuffer (runtime)] [opts=TIMESTAMP (runtime)]",
+        "Ui [level=warn (runtime)] [facade=none (code)] [opts=PID (runtime)]",
+    ]);
+
+    // a file which can't be opened fails its entry instead of panicking with the context locked
+    let missing = std::env::temp_dir().join("hclog_config_map_missing").join("app.log");
+    let map = HashMap::from([
+        ("facade.db".to_owned(), format!("file:{}", missing.display())),
+        ("level.ui".to_owned(), "info".to_owned()),
+    ]);
+    let report = hclog::apply_config_map(&map).unwrap();
+    assert_eq!(report.applied(), ["level.ui"]);
+    assert_eq!(report.failed(),
+        [("facade.db".to_owned(), ErrorKind::IoError(std::io::ErrorKind::NotFound))]);
+    hclog::lE!(Net, "still logging");
+    assert_eq!(hclog::drain_ring(Net).unwrap().len(), 1);
+}
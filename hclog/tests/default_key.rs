@@ -0,0 +1,85 @@
+/*
+ * the default log macros routed via set_default_key
+ *
+ * The binary contains a single test because the default key is process wide.
+ */
+mod common;
+
+use hclog::{options::NONE, ErrorKind, FacadeVariant, InternalLogKeys::Internal, Level, Scope};
+use hclog_macros::HCLog;
+use common::block_on;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info, default_facade = FacadeVariant::None)]
+enum DefaultKeys {
+    Misc,
+    Other,
+    #[hclog(ignore)]
+    Missing,
+}
+use DefaultKeys::*;
+
+mod net {
+    pub fn connect() {
+        hclog::dI!("connected to {}", "net");
+        hclog::dD1!("connection details");
+    }
+}
+
+mod db {
+    pub fn query() {
+        hclog::dW!("slow query");
+    }
+}
+
+#[test]
+fn routing() {
+    DefaultKeys::init("default", Level::Info, FacadeVariant::None, NONE).unwrap();
+    let [internal, misc, other] = [
+        hclog::subscribe(Internal, 8).unwrap(),
+        hclog::subscribe(Misc, 8).unwrap(),
+        hclog::subscribe(Other, 8).unwrap(),
+    ];
+    let lines = |sub: &hclog::Subscription| sub.try_iter().collect::<Vec<_>>();
+
+    // without a default key the messages go to the Internal key with the same level
+    assert_eq!(hclog::default_key(), Ok(None));
+    hclog::set_level(Internal, Level::Info).unwrap();
+    net::connect();
+    let logged = lines(&internal);
+    assert_eq!(logged.len(), 1);
+    assert!(logged[0].ends_with("connected to net"), "{}", logged[0]);
+    hclog::set_level(Internal, Level::Off).unwrap();
+
+    assert_eq!(hclog::set_default_key(Missing), Err(ErrorKind::KeyNotInitialized));
+    hclog::set_default_key(Misc).unwrap();
+    assert_eq!(hclog::default_key().unwrap().as_deref(), Some("Misc"));
+    net::connect();
+    db::query();
+    assert_eq!(lines(&misc), ["connected to net", "slow query"]);
+
+    hclog::set_default_key(Other).unwrap();
+    db::query();
+    assert!(misc.try_iter().next().is_none());
+    assert_eq!(lines(&other), ["slow query"]);
+
+    // within a task scope the task-local copy of the key is used
+    let task = hclog::scope("Task1", Other, async {
+        hclog::set_level(Other, Level::Debug1).unwrap();
+        net::connect();
+    }).unwrap();
+    block_on(task);
+    assert_eq!(lines(&other), ["connected to net", "connection details"]);
+    assert!(!hclog::tD1!(Other));
+    assert!(internal.try_iter().next().is_none());
+
+    // removing the Scope of the default key falls back to the Internal key
+    hclog::reinitialize::<DefaultKeys, _>("default", Level::Info, FacadeVariant::None, NONE)
+        .unwrap();
+    assert_eq!(hclog::default_key(), Ok(None));
+    hclog::set_level(Internal, Level::Info).unwrap();
+    hclog::dI!("after reinitialize");
+    let logged = lines(&internal);
+    assert_eq!(logged.len(), 1);
+    assert!(logged[0].ends_with("after reinitialize"), "{}", logged[0]);
+}
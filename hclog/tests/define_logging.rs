@@ -0,0 +1,32 @@
+/*
+ * LogKeys, Scope and initialization defined via define_logging!
+ */
+use hclog::{FacadeVariant, Level, LogKey, Scope, ScopeKey};
+
+hclog_macros::define_logging! {
+    name: "defined",
+    level: Level::Warn,
+    facade: FacadeVariant::RingBuffer(4),
+    keys: {
+        NET: { level: Level::Debug3 },
+        DB: {},
+        AUDIT: { name: "audit", facade: FacadeVariant::None },
+    }
+}
+
+#[test]
+fn expanded_definition() {
+    assert_eq!(HclogKeys::logscope(), ScopeKey::Application);
+    assert_eq!((NET.log_key(), DB.log_key(), AUDIT.log_key()), (0, 1, 2));
+    assert_eq!((NET.to_string(), AUDIT.to_string()), ("NET".to_owned(), "audit".to_owned()));
+
+    init_logging().unwrap();
+    assert_eq!(hclog::test_log(NET, Level::Debug3), Ok(true));
+    assert_eq!(hclog::test_log(DB, Level::Warn), Ok(true));
+    assert_eq!(hclog::test_log(DB, Level::Info), Ok(false));
+    hclog::lW!(DB, "ring");
+    assert_eq!(hclog::drain_ring(DB).unwrap().len(), 1);
+    assert!(hclog::drain_ring(AUDIT).is_err());
+    hclog::set_mod_level(["audit:info"]).unwrap();
+    assert_eq!(hclog::test_log(AUDIT, Level::Info), Ok(true));
+}
@@ -0,0 +1,50 @@
+/*
+ * the environment variables which changed the configuration on initialization
+ *
+ * The environment is changed, so there is a single test only.
+ */
+use hclog::{options::*, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum EnvKeys {
+    Lingering,
+}
+
+fn entry(var: &str, value: &str, effect: &str) -> (String, String, String) {
+    (var.to_owned(), value.to_owned(), effect.to_owned())
+}
+
+#[test]
+fn recorded_overrides() {
+    std::env::set_var("HCLOG_OPT_PID", "0");
+    std::env::set_var("HCLOG_OPT_UPTIME", "1");
+    // already set by the code, nothing changes
+    std::env::set_var("HCLOG_OPT_TID", "1");
+    std::env::set_var("HCLOG_LEVEL", "debug2");
+    std::env::set_var("HCLOG_INSTANCE_ID", "pod-1");
+    EnvKeys::init("shell", Level::Info, FacadeVariant::StdOut, PID + TID).unwrap();
+
+    let app = hclog::env_overrides().into_iter()
+        .filter(|(_, _, effect)| effect.contains("(application)")).collect::<Vec<_>>();
+    assert_eq!(app, [
+        entry("HCLOG_LEVEL", "debug2",
+            "default level of scope shell (application) is debug2 instead of info"),
+        entry("HCLOG_OPT_PID", "0", "option PID of scope shell (application) disabled"),
+        entry("HCLOG_OPT_UPTIME", "1", "option UPTIME of scope shell (application) enabled"),
+        entry("HCLOG_INSTANCE_ID", "pod-1", "instance id of scope shell (application) is pod-1"),
+    ]);
+    // the internal scope reads the same variables
+    assert!(hclog::env_overrides().iter().any(|(var, _, effect)|
+        var == "HCLOG_LEVEL" && effect.contains("(hclog) is debug2 instead of off")));
+
+    let mut out = Vec::new();
+    hclog::list_modules_detailed(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.lines().any(|l| l == "  env HCLOG_OPT_PID=0: option PID of scope shell \
+        (application) disabled"), "{}", out);
+    assert!(!out.contains("HCLOG_OPT_TID"), "{}", out);
+    let mut dump = Vec::new();
+    hclog::dump_always(&mut dump).unwrap();
+    assert!(String::from_utf8(dump).unwrap().contains("option UPTIME of scope shell"));
+}
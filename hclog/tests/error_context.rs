@@ -0,0 +1,41 @@
+/*
+ * logging errors on propagation via error_context! and warn_context!
+ */
+use hclog::{Level, Scope, ErrorKind};
+use hclog_macros::HCLog;
+use std::time::Duration;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info)]
+enum CtxKeys {
+    Ctx,
+}
+use CtxKeys::*;
+
+fn parse(s: &str) -> Result<u32, std::num::ParseIntError> {
+    let v = hclog::error_context!(Ctx, s.parse::<u32>(), "failed to parse {:?}", s)?;
+    Ok(v * 2)
+}
+
+#[test]
+fn context_logged_on_error() {
+    CtxKeys::init_with_defaults("errctx").unwrap();
+    let sub = hclog::subscribe(Ctx, 8).unwrap();
+
+    assert_eq!(parse("21"), Ok(42));
+    assert_eq!(sub.try_iter().count(), 0);
+
+    let line = line!() + 1;
+    let err = hclog::warn_context!(Ctx, Err::<(), _>(ErrorKind::ParseArg), "option {}", 1);
+    assert_eq!(err, Err(ErrorKind::ParseArg));
+    let logged = sub.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert!(logged.contains(" warn "), "{}", logged);
+    assert!(logged.contains(&format!("error_context.rs:{} ", line)), "{}", logged);
+    assert!(logged.contains("error_context::context_logged_on_error"), "{}", logged);
+    assert!(logged.ends_with("option 1: Parse argument string failed"), "{}", logged);
+
+    assert!(parse("x").is_err());
+    let logged = sub.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert!(logged.contains(" error ") && logged.contains("error_context::parse"), "{}", logged);
+    assert!(logged.ends_with("failed to parse \"x\": invalid digit found in string"), "{}", logged);
+}
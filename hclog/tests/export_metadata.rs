@@ -0,0 +1,48 @@
+/*
+ * the metadata of all LogKeys rendered for the documentation
+ */
+use hclog::{options::NONE, FacadeVariant, Level, MetadataFormat, Scope, ScopeKey};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info, default_facade = FacadeVariant::StdErr)]
+enum AppKeys {
+    #[hclog(description = "connections to the database")]
+    Storage,
+    #[hclog(name = "api", level = Level::Debug2, description = "requests | responses")]
+    Api,
+    Cache,
+}
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(scope = ScopeKey::Lib)]
+enum LibKeys {
+    #[hclog(description = "-v enables the .wire dump")]
+    Wire,
+    #[hclog(level = Level::Warn)]
+    Codec,
+}
+
+#[test]
+fn golden_markdown() {
+    AppKeys::init_with_defaults("server").unwrap();
+    LibKeys::init("netlib", Level::Notice, FacadeVariant::RingBuffer(16), NONE).unwrap();
+    // runtime changes are not part of the document
+    hclog::set_level(AppKeys::Cache, Level::Debug10).unwrap();
+    hclog::set_logdest(LibKeys::Codec, FacadeVariant::StdOut).unwrap();
+
+    let markdown = hclog::export_metadata(MetadataFormat::Markdown).unwrap();
+    assert_eq!(markdown, include_str!("golden/metadata.md"));
+    // generating it again yields the same document
+    hclog::set_level(AppKeys::Storage, Level::Debug1).unwrap();
+    assert_eq!(hclog::export_metadata(MetadataFormat::Markdown).unwrap(), markdown);
+
+    let man = hclog::export_metadata(MetadataFormat::Man).unwrap();
+    assert!(man.starts_with(".SS netlib (library)\n.TP\n.B Codec\nlevel warn, facade ringbuffer\n"),
+        "{}", man);
+    assert!(man.contains(".B Wire\nlevel notice, facade ringbuffer\n.br\n\\-v enables the"),
+        "{}", man);
+    let plain = hclog::export_metadata(MetadataFormat::PlainText).unwrap();
+    assert!(plain.contains("\nserver (application):\n  api      level=debug2 facade=stderr    \
+        requests | responses\n"), "{}", plain);
+}
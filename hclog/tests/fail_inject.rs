@@ -0,0 +1,48 @@
+/*
+ * tests for the failure injection of the `test-util` feature
+ *
+ * run with: cargo test -p hclog --features test-util
+ */
+use hclog::{Level, FacadeVariant, Scope, ErrorKind};
+use hclog_macros::HCLog;
+use std::io::ErrorKind as IoErrorKind;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info, default_facade = FacadeVariant::StdOut)]
+enum FailKeys {
+    #[hclog(name = "failfoo")]
+    FailFoo,
+    #[hclog(name = "failbar")]
+    FailBar,
+}
+use FailKeys::*;
+
+fn log_info(k: FailKeys) -> hclog::Result<()> {
+    hclog::log(k, Level::Info, file!(), Some("log_info"), line!(), &format_args!("inject test"))
+}
+
+#[test]
+fn fail_next_writes() {
+    FailKeys::init_with_defaults("failinject").unwrap();
+    hclog::fail_next_writes(FailFoo, 2, IoErrorKind::Other).unwrap();
+
+    // filtered messages don't reach the facade and don't count
+    let filtered = hclog::log(FailFoo, Level::Debug1, file!(), None, line!(), &format_args!("x"));
+    assert_eq!(filtered, Ok(()));
+    assert_eq!(log_info(FailFoo), Err(ErrorKind::IoError(IoErrorKind::Other)));
+    assert_eq!(log_info(FailBar), Ok(()));
+    assert_eq!(log_info(FailFoo), Err(ErrorKind::IoError(IoErrorKind::Other)));
+    // normal behavior resumes after n messages
+    assert_eq!(log_info(FailFoo), Ok(()));
+
+    hclog::fail_next_writes(FailBar, 10, IoErrorKind::BrokenPipe).unwrap();
+    assert_eq!(log_info(FailBar), Err(ErrorKind::IoError(IoErrorKind::BrokenPipe)));
+    // re-arming replaces the previous failure
+    hclog::fail_next_writes(FailBar, 1, IoErrorKind::WriteZero).unwrap();
+    assert_eq!(log_info(FailBar), Err(ErrorKind::IoError(IoErrorKind::WriteZero)));
+    assert_eq!(log_info(FailBar), Ok(()));
+
+    hclog::fail_next_writes(FailBar, 10, IoErrorKind::BrokenPipe).unwrap();
+    hclog::clear_failures().unwrap();
+    assert_eq!(log_info(FailBar), Ok(()));
+}
@@ -0,0 +1,117 @@
+/*
+ * buffered messages are written by the atexit handler if the process exits without flush
+ *
+ * The tests run themselves as a child process which logs to a file and exits immediately.
+ */
+use hclog::{options::NONE, FacadeVariant, FlushPolicy, Level, Scope};
+use hclog_macros::HCLog;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum ExitKeys {
+    Stuck,
+    Buffered,
+}
+use ExitKeys::Buffered;
+
+const CHILD_ENV: &str = "HCLOG_TEST_EXIT_FILE";
+
+// signals the test once it's formatted and never returns, the writer keeps its DEDUP state
+// locked
+#[cfg(not(feature = "single-thread"))]
+struct Blocker(std::sync::mpsc::Sender<()>);
+#[cfg(not(feature = "single-thread"))]
+impl std::fmt::Display for Blocker {
+    fn fmt(&self, _: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.send(()).unwrap();
+        loop {
+            thread::park();
+        }
+    }
+}
+
+fn child(path: String) -> ! {
+    ExitKeys::init("exit", Level::Info, FacadeVariant::None, NONE).unwrap();
+    hclog::set_logdest(Buffered, FacadeVariant::File(path.into(), true)).unwrap();
+    hclog::set_flush_policy(Buffered, FlushPolicy::EveryN(1000)).unwrap();
+    hclog::lI!(Buffered, "first line");
+    hclog::lI!(Buffered, "final line");
+    std::process::exit(0);
+}
+
+#[cfg(not(feature = "single-thread"))]
+fn stuck_child(path: String) -> ! {
+    use hclog::options::DEDUP;
+    use ExitKeys::Stuck;
+
+    ExitKeys::init("exit", Level::Info, FacadeVariant::None, NONE).unwrap();
+    hclog::set_logdest(Stuck, FacadeVariant::File(format!("{}.stuck", path).into(), true))
+        .unwrap();
+    hclog::set_module_options(Stuck, DEDUP).unwrap();
+    hclog::set_logdest(Buffered, FacadeVariant::File(path.into(), true)).unwrap();
+    hclog::set_flush_policy(Buffered, FlushPolicy::EveryN(1000)).unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    thread::spawn(move || hclog::lI!(Stuck, "{}", Blocker(tx)));
+    rx.recv().unwrap();
+    hclog::lI!(Buffered, "first line");
+    hclog::lI!(Buffered, "final line");
+    std::process::exit(0);
+}
+
+// run the test `name` as child process logging to the returned file
+fn run_child(name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("hclog_flush_at_exit_{}_{}.log", name, std::process::id()));
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args([name, "--exact", "--nocapture"])
+        .env(CHILD_ENV, &path)
+        .spawn()
+        .unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill().unwrap();
+            panic!("{} hangs at exit", name);
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    assert!(status.success());
+    path
+}
+
+fn assert_final_line(path: &Path) {
+    let content = std::fs::read_to_string(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    let lines = content.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2, "{}", content);
+    assert!(lines[1].ends_with("final line"), "{}", content);
+}
+
+#[test]
+fn final_line() {
+    if let Ok(path) = std::env::var(CHILD_ENV) {
+        child(path);
+    }
+    assert_final_line(&run_child("final_line"));
+}
+
+// another thread hangs while holding the lock of a submodule, the others are flushed anyway.
+// In single-thread mode every thread has its own context.
+#[test]
+#[cfg(not(feature = "single-thread"))]
+fn stuck_writer() {
+    if let Ok(path) = std::env::var(CHILD_ENV) {
+        stuck_child(path);
+    }
+    let path = run_child("stuck_writer");
+    let _ = std::fs::remove_file(format!("{}.stuck", path.display()));
+    assert_final_line(&path);
+}
@@ -0,0 +1,60 @@
+/*
+ * messages buffered by the file facade for each FlushPolicy
+ */
+use hclog::{options::NONE, FacadeVariant, FlushPolicy, Level, Scope};
+use hclog_macros::HCLog;
+use std::{path::PathBuf, time::Duration};
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum FlushKeys {
+    Every,
+    Batched,
+    Timed,
+    Eager,
+}
+use FlushKeys::*;
+
+fn to_file(key: FlushKeys, name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("hclog_flush_{}_{}_{}.log", key, name, std::process::id()));
+    hclog::set_logdest(key, FacadeVariant::File(path.clone(), true)).unwrap();
+    path
+}
+
+fn lines(path: &PathBuf) -> usize {
+    std::fs::read_to_string(path).unwrap().lines().count()
+}
+
+#[test]
+fn policies() {
+    FlushKeys::init("flush", Level::Info, FacadeVariant::None, NONE).unwrap();
+    let paths = [Every, Batched, Timed, Eager].map(|key| to_file(key, "first"));
+    hclog::set_flush_policy(Batched, FlushPolicy::EveryN(4)).unwrap();
+    hclog::set_flush_policy(Timed, FlushPolicy::Interval(Duration::from_secs(3600))).unwrap();
+    hclog::set_flush_policy(Eager, FlushPolicy::Interval(Duration::ZERO)).unwrap();
+    for i in 0..10 {
+        for key in [Every, Batched, Timed, Eager] {
+            hclog::lI!(key, "message {}", i);
+        }
+    }
+    // the batched key flushed after message 4 and 8, the timed key not at all
+    assert_eq!(paths.each_ref().map(lines), [10, 8, 0, 10]);
+
+    // the policy is kept if the facade changes, the old one wrote its buffer when dropped
+    let batched = to_file(Batched, "second");
+    assert_eq!(lines(&paths[1]), 10);
+    hclog::lI!(Batched, "buffered");
+    assert_eq!(lines(&batched), 0);
+
+    hclog::flush().unwrap();
+    assert_eq!(paths.each_ref().map(lines), [10, 10, 10, 10]);
+    assert_eq!(lines(&batched), 1);
+
+    // switching back to EveryMessage writes the buffered messages
+    hclog::lI!(Timed, "late");
+    hclog::set_flush_policy(Timed, FlushPolicy::EveryMessage).unwrap();
+    assert_eq!(lines(&paths[2]), 11);
+    for path in paths.into_iter().chain([batched]) {
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,64 @@
+/*
+ * function names written with the FUNC option from closures and async code
+ */
+mod common;
+
+use hclog::{options::FUNC, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+use common::block_on;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum FnKeys {
+    Func,
+}
+use FnKeys::*;
+
+async fn ready() {}
+
+async fn handler() {
+    hclog::lI!(Func, "before await");
+    ready().await;
+    hclog::lI!(Func, "after await");
+    let outer = || {
+        let inner = || hclog::lI!(Func, "nested closure");
+        inner();
+    };
+    outer();
+    async move { hclog::lI!(Func, "async block") }.await;
+}
+
+struct Service;
+impl Service {
+    async fn request(&self) {
+        hclog::lI!(Func, "method");
+    }
+}
+
+fn written() -> Vec<String> {
+    hclog::drain_ring(Func).unwrap()
+}
+
+#[test]
+fn logical_function() {
+    FnKeys::init("fn_name", Level::Info, FacadeVariant::RingBuffer(16), FUNC).unwrap();
+    block_on(handler());
+    assert_eq!(written(), [
+        "fn_name::handler before await",
+        "fn_name::handler after await",
+        "fn_name::handler nested closure",
+        "fn_name::handler async block",
+    ]);
+
+    block_on(Service.request());
+    block_on(async move {
+        hclog::lI!(Func, "test block");
+    });
+    let spawned = async { hclog::lI!(Func, "polled elsewhere") };
+    let poll = |f| block_on(f);
+    poll(spawned);
+    assert_eq!(written(), [
+        "fn_name::request method",
+        "fn_name::logical_function test block",
+        "fn_name::logical_function polled elsewhere",
+    ]);
+}
@@ -0,0 +1,52 @@
+/*
+ * function names shortened via FUNC_SHORT and limited via set_func_max_len
+ */
+use hclog::{options::{FUNC, FUNC_SHORT}, ErrorKind, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum FuncKeys {
+    Func,
+}
+use FuncKeys::*;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(scope = hclog::ScopeKey::Lib)]
+enum OtherKeys {
+    Other,
+}
+
+mod server {
+    pub mod conn {
+        use crate::FuncKeys::Func;
+
+        pub fn handle_connection<T>(_: T) {
+            hclog::lI!(Func, "handled");
+        }
+    }
+}
+
+#[test]
+fn func_short() {
+    FuncKeys::init("func", Level::Info, FacadeVariant::RingBuffer(8), FUNC).unwrap();
+    server::conn::handle_connection(0u8);
+    assert_eq!(hclog::drain_ring(Func).unwrap(),
+        ["func_short::server::conn::handle_connection handled"]);
+
+    hclog::set_module_options(Func, FUNC_SHORT).unwrap();
+    server::conn::handle_connection(0u8);
+    assert_eq!(hclog::drain_ring(Func).unwrap(), ["conn::handle_connection handled"]);
+
+    hclog::set_func_max_len::<FuncKeys>(12).unwrap();
+    server::conn::handle_connection(0u8);
+    assert_eq!(hclog::drain_ring(Func).unwrap(), ["conn::handl… handled"]);
+
+    // 0 removes the limit
+    hclog::unset_module_options(Func, FUNC_SHORT).unwrap();
+    hclog::set_func_max_len::<FuncKeys>(0).unwrap();
+    server::conn::handle_connection(0u8);
+    assert_eq!(hclog::drain_ring(Func).unwrap(),
+        ["func_short::server::conn::handle_connection handled"]);
+
+    assert_eq!(hclog::set_func_max_len::<OtherKeys>(12), Err(ErrorKind::ScopeNotInitialized));
+}
@@ -0,0 +1,103 @@
+/*
+ * level inheritance between parent and child LogKeys
+ *
+ * All tests share the global context, so each test uses its own set of keys.
+ */
+use hclog::{ErrorKind, Level, Scope};
+
+mod app {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(scope = hclog::ScopeKey::Application)]
+    pub enum NetKeys {
+        #[hclog(name = "net")]
+        Net,
+        #[hclog(name = "net.tls")]
+        Tls,
+        #[hclog(name = "net.http", level = hclog::Level::Warn)]
+        Http,
+        #[hclog(name = "chain_a")]
+        A,
+        #[hclog(name = "chain_b")]
+        B,
+        #[hclog(name = "chain_c")]
+        C,
+    }
+}
+
+mod lib {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(scope = hclog::ScopeKey::Lib)]
+    pub enum CycleKeys {
+        X,
+        Y,
+        Z,
+    }
+}
+
+use app::NetKeys::{self, *};
+use lib::CycleKeys::{self, *};
+
+#[test]
+fn explicit_override_beats_parent() {
+    NetKeys::init_with_defaults("hierarchy").unwrap();
+    hclog::set_parent(Tls, Net).unwrap();
+    hclog::set_parent(Http, Net).unwrap();
+
+    hclog::set_level(Net, Level::Debug3).unwrap();
+    assert_eq!(hclog::test_log(Tls, Level::Debug3), Ok(true));
+    assert_eq!(hclog::test_log(Tls, Level::Debug4), Ok(false));
+    // level given by the derive attribute is explicit
+    assert_eq!(hclog::test_log(Http, Level::Debug3), Ok(false));
+    assert_eq!(hclog::test_log(Http, Level::Warn), Ok(true));
+
+    hclog::set_level(Tls, Level::Error).unwrap();
+    assert_eq!(hclog::test_log(Tls, Level::Warn), Ok(false));
+    assert_eq!(hclog::test_log(Net, Level::Debug3), Ok(true));
+
+    // the subtree syntax drops the overrides of the descendants
+    hclog::set_mod_level(["net.*:debug5"]).unwrap();
+    assert_eq!(hclog::test_log(Net, Level::Debug5), Ok(true));
+    assert_eq!(hclog::test_log(Tls, Level::Debug5), Ok(true));
+    assert_eq!(hclog::test_log(Http, Level::Debug5), Ok(true));
+    assert_eq!(hclog::test_log(Http, Level::Debug6), Ok(false));
+    assert_eq!(hclog::set_mod_level(["nope.*:debug5"]), Err(ErrorKind::InvalidLevelArg(
+        "nope.*:debug5".to_owned(), Box::new(ErrorKind::KeyNotInitialized))));
+}
+
+#[test]
+fn multi_level_chain() {
+    NetKeys::init_with_defaults("hierarchy").unwrap();
+    hclog::set_parent(B, A).unwrap();
+    hclog::set_parent(C, B).unwrap();
+
+    hclog::set_level(A, Level::Debug7).unwrap();
+    assert_eq!(hclog::test_log(B, Level::Debug7), Ok(true));
+    assert_eq!(hclog::test_log(C, Level::Debug7), Ok(true));
+    assert_eq!(hclog::test_log(C, Level::Debug8), Ok(false));
+
+    // the closest explicit level wins
+    hclog::set_level(B, Level::Notice).unwrap();
+    assert_eq!(hclog::test_log(C, Level::Info), Ok(false));
+    assert_eq!(hclog::test_log(C, Level::Notice), Ok(true));
+    assert_eq!(hclog::test_log(A, Level::Debug7), Ok(true));
+}
+
+#[test]
+fn cycle_rejection() {
+    CycleKeys::init_with_defaults("hierarchy").unwrap();
+    assert_eq!(hclog::set_parent(X, X), Err(ErrorKind::ParentCycle));
+    hclog::set_parent(Y, X).unwrap();
+    hclog::set_parent(Z, Y).unwrap();
+    assert_eq!(hclog::set_parent(X, Z), Err(ErrorKind::ParentCycle));
+    assert_eq!(hclog::set_parent(Y, Z), Err(ErrorKind::ParentCycle));
+    // moving a node to another branch is fine
+    hclog::set_parent(Z, X).unwrap();
+
+    hclog::set_level(X, Level::Debug2).unwrap();
+    assert_eq!(hclog::test_log(Z, Level::Debug2), Ok(true));
+    assert_eq!(hclog::test_log(Y, Level::Debug2), Ok(true));
+}
@@ -0,0 +1,33 @@
+/*
+ * ignore_env keeps the configuration of the code despite lingering variables
+ */
+use hclog::{options::*, FacadeVariant, Level, Scope, Source};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum HermeticKeys {
+    Sealed,
+}
+use HermeticKeys::*;
+
+#[test]
+fn hermetic_init() {
+    std::env::set_var("HCLOG_OPT_PID", "0");
+    std::env::set_var("HCLOG_LEVEL", "debug2");
+    std::env::set_var("HCLOG_FACADE", "stderr");
+    std::env::set_var("HCLOG_INSTANCE_ID", "pod-1");
+    std::env::set_var("CLOG_DEBUG", "3");
+    hclog::ignore_env(true);
+    HermeticKeys::init("hermetic", Level::Info, FacadeVariant::RingBuffer(4),
+        PID + INSTANCE).unwrap();
+
+    assert_eq!(hclog::env_overrides(), []);
+    let provenance = hclog::provenance(Sealed).unwrap();
+    assert_eq!((provenance.level, provenance.facade, provenance.options),
+        (Source::Code, Source::Code, Source::Code));
+    assert_eq!(hclog::test_log(Sealed, Level::Debug1), Ok(false));
+    assert_eq!(parse_options_from_env(), Ok(Options::default()));
+    hclog::lI!(Sealed, "sealed");
+    let line = hclog::drain_ring(Sealed).unwrap().pop().unwrap();
+    assert!(line.starts_with('[') && !line.contains("pod-1"), "{}", line);
+}
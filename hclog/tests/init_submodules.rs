@@ -0,0 +1,48 @@
+/*
+ * init_submodules = false only creates the Scope, the LogKeys are added by the caller
+ */
+use hclog::{Level, Scope};
+
+mod lazy {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(scope = hclog::ScopeKey::Application, init_submodules = false)]
+    pub enum LazyKeys {
+        Gpu,
+        #[hclog(ignore)]
+        Unused,
+        Cpu,
+    }
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(scope = hclog::ScopeKey::Lib, init_only, merge = "skip")]
+    pub enum OnlyKeys {
+        Probe,
+    }
+}
+
+use lazy::{LazyKeys::{self, *}, OnlyKeys::{self, *}};
+
+#[test]
+fn keys_added_by_caller() {
+    LazyKeys::init_with_defaults("lazy").unwrap();
+    assert_eq!(hclog::has_module(Gpu), Ok(false));
+    assert_eq!(hclog::has_module(Cpu), Ok(false));
+
+    assert!(matches!(LazyKeys::ALL, [Gpu, Cpu]));
+    hclog::add_submodules(LazyKeys::ALL).unwrap();
+    assert_eq!(hclog::has_module(Gpu), Ok(true));
+    assert_eq!(hclog::has_module(Cpu), Ok(true));
+    assert_eq!(hclog::has_module(Unused), Ok(false));
+    hclog::set_level(Cpu, Level::Info).unwrap();
+    assert_eq!(hclog::test_log(Cpu, Level::Info), Ok(true));
+}
+
+#[test]
+fn init_only() {
+    OnlyKeys::init_with_defaults("only").unwrap();
+    assert_eq!(hclog::has_module(Probe), Ok(false));
+    hclog::add_submodules_with(OnlyKeys::ALL, hclog::MergePolicy::Skip).unwrap();
+    assert_eq!(hclog::has_module(Probe), Ok(true));
+}
@@ -0,0 +1,40 @@
+/*
+ * instance id of a scope read from HCLOG_INSTANCE_ID and set via set_instance_id
+ */
+mod common;
+
+use hclog::{options::{INSTANCE, TID}, ErrorKind, FacadeVariant, Level, Scope, ScopeKey};
+use hclog_macros::HCLog;
+use common::block_on;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum PodKeys {
+    Web,
+}
+use PodKeys::*;
+
+#[test]
+fn instance_from_env() {
+    std::env::set_var("HCLOG_INSTANCE_ID", "pod-7f9c");
+    PodKeys::init("pods", Level::Info, FacadeVariant::RingBuffer(8), INSTANCE).unwrap();
+    hclog::lI!(Web, "from env");
+    assert_eq!(hclog::drain_ring(Web).unwrap(), ["[pod-7f9c] from env"]);
+
+    for invalid in ["", " ", "a/b", "a b", "[a]"] {
+        assert_eq!(hclog::set_instance_id::<PodKeys>(invalid), Err(ErrorKind::ParseArg));
+    }
+    hclog::set_instance_id::<PodKeys>("worker-3").unwrap();
+    hclog::set_module_options(Web, TID).unwrap();
+    hclog::lI!(Web, "at runtime");
+    let tid = hclog::drain_ring(Web).unwrap().pop().unwrap();
+    let tid = tid.strip_prefix("[worker-3/").and_then(|s| s.strip_suffix("] at runtime"));
+    assert!(tid.is_some_and(|t| t.parse::<u64>().is_ok()), "{:?}", tid);
+
+    // the task local copy keeps the id
+    hclog::unset_module_options(Web, TID).unwrap();
+    let task = hclog::async_scope_for("task", ScopeKey::Application, async {
+        hclog::lI!(Web, "in task");
+    }).unwrap();
+    block_on(task);
+    assert_eq!(hclog::drain_ring(Web).unwrap(), ["[worker-3] in task"]);
+}
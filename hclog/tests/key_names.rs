@@ -0,0 +1,86 @@
+/*
+ * name based lookup of LogKeys across Scopes and the derive `prefix` attribute
+ *
+ * The binary contains a single test because all tests share the global context.
+ */
+use hclog::{ErrorKind, Level, Scope};
+
+mod app {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(scope = hclog::ScopeKey::Application)]
+    pub enum AppKeys {
+        #[hclog(name = "db")]
+        Db,
+    }
+
+    pub mod prefixed {
+        use hclog_macros::HCLog;
+
+        #[derive(HCLog, Copy, Clone, Debug)]
+        #[hclog(scope = hclog::ScopeKey::Application, prefix = "moda_", merge = "append")]
+        pub enum ModAKeys {
+            #[hclog(name = "db")]
+            Db,
+            Net,
+        }
+    }
+}
+
+mod lib {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(scope = hclog::ScopeKey::Lib)]
+    pub enum LibKeys {
+        #[hclog(name = "db")]
+        Db,
+    }
+
+    pub mod prefixed {
+        use hclog_macros::HCLog;
+
+        #[derive(HCLog, Copy, Clone, Debug)]
+        #[hclog(scope = hclog::ScopeKey::Lib, prefix = "modb_", merge = "append")]
+        pub enum ModBKeys {
+            #[hclog(name = "db")]
+            Db,
+        }
+    }
+}
+
+use app::{AppKeys, prefixed::ModAKeys};
+use lib::{LibKeys, prefixed::ModBKeys};
+
+#[test]
+fn ambiguous_and_prefixed_names() {
+    AppKeys::init_with_defaults("app").unwrap();
+    LibKeys::init_with_defaults("lib").unwrap();
+    ModAKeys::init_with_defaults("moda").unwrap();
+    ModBKeys::init_with_defaults("modb").unwrap();
+    assert_eq!(ModAKeys::Db.to_string(), "moda_db");
+    assert_eq!(ModAKeys::Net.to_string(), "moda_Net");
+
+    assert_eq!(hclog::set_mod_level(["db:debug5"]), Err(ErrorKind::InvalidLevelArg(
+        "db:debug5".to_owned(), Box::new(ErrorKind::AmbiguousKeyName("db".to_owned())))));
+    assert_eq!(hclog::set_mod_level(["moda_db:debug5,modb_db:warn"]), Ok(()));
+    assert_eq!(hclog::test_log(ModAKeys::Db, Level::Debug5), Ok(true));
+    assert_eq!(hclog::test_log(ModBKeys::Db, Level::Warn), Ok(true));
+    assert_eq!(hclog::test_log(ModBKeys::Db, Level::Notice), Ok(false));
+    // names are case insensitive
+    assert_eq!(hclog::set_mod_level(["moda_net:info"]), Ok(()));
+    assert_eq!(hclog::test_log(ModAKeys::Net, Level::Info), Ok(true));
+    assert_eq!(hclog::set_mod_level(["moda_nets:info"]), Err(ErrorKind::InvalidLevelArg(
+        "moda_nets:info".to_owned(), Box::new(ErrorKind::KeyNotInitialized))));
+
+    // qualified with the name of the scope
+    assert_eq!(hclog::set_mod_level(["app/db:debug3,lib/db:error"]), Ok(()));
+    assert_eq!(hclog::test_log(AppKeys::Db, Level::Debug3), Ok(true));
+    assert_eq!(hclog::test_log(LibKeys::Db, Level::Warn), Ok(false));
+    assert_eq!(hclog::set_mod_level(["lib/db:+2"]), Ok(()));
+    assert_eq!(hclog::test_log(LibKeys::Db, Level::Notice), Ok(true));
+    assert_eq!(hclog::set_mod_level(["app/moda_db:info"]), Ok(()));
+    assert_eq!(hclog::set_mod_level(["lib/moda_db:info"]), Err(ErrorKind::InvalidLevelArg(
+        "lib/moda_db:info".to_owned(), Box::new(ErrorKind::KeyNotInitialized))));
+}
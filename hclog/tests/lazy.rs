@@ -0,0 +1,40 @@
+/*
+ * lazily evaluated arguments of the log macros
+ */
+use hclog::{Level, Scope};
+use hclog_macros::HCLog;
+use std::cell::Cell;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info)]
+enum LazyKeys {
+    State,
+}
+use LazyKeys::*;
+
+// counts its calls, like an expensive dump of a big struct would take time
+fn counted(calls: &Cell<usize>) -> String {
+    calls.set(calls.get() + 1);
+    "dump".to_owned()
+}
+
+#[test]
+fn evaluated_only_if_logged() {
+    LazyKeys::init_with_defaults("lazy").unwrap();
+    let lines = hclog::subscribe(State, 8).unwrap();
+    let calls = Cell::new(0);
+
+    hclog::lD5!(State, "state: {}", lazy = || counted(&calls));
+    hclog::lD5!(State, "state: {}", hclog::lazy(|| counted(&calls)));
+    hclog::batch!(State, Level::Debug5, { "state: {}", hclog::lazy(|| counted(&calls)) });
+    assert_eq!(calls.get(), 0);
+    assert_eq!(lines.try_iter().count(), 0);
+
+    hclog::set_level(State, Level::Debug5).unwrap();
+    hclog::lD5!(State, "{} {:>6}", 1, lazy = || counted(&calls));
+    hclog::lD5!(State, "{n} {}", hclog::lazy(|| counted(&calls)), n = 2);
+    assert_eq!(calls.get(), 2);
+    let lines = lines.try_iter().collect::<Vec<_>>();
+    assert!(lines[0].ends_with("1   dump"), "{}", lines[0]);
+    assert!(lines[1].ends_with("2 dump"), "{}", lines[1]);
+}
@@ -0,0 +1,60 @@
+/*
+ * levels set via set_level_for revert after the given duration
+ */
+use hclog::{Level, Scope, Source, InternalLogKeys::Internal};
+use hclog_macros::HCLog;
+use std::{thread::sleep, time::Duration};
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info)]
+enum DecayKeys {
+    Boosted,
+    Cancelled,
+    Extended,
+}
+use DecayKeys::*;
+
+const SHORT: Duration = Duration::from_millis(50);
+
+#[test]
+fn revert_with_notice() {
+    DecayKeys::init_with_defaults("decay").unwrap();
+    hclog::set_level(Internal, Level::Notice).unwrap();
+    let notices = hclog::subscribe(Internal, 16).unwrap();
+
+    hclog::set_level_for(Boosted, Level::Debug10, SHORT).unwrap();
+    assert!(hclog::tD10!(Boosted));
+    sleep(SHORT * 2);
+    assert!(!hclog::tD1!(Boosted));
+    assert!(hclog::tI!(Boosted));
+    hclog::lI!(Boosted, "after the expiry");
+    assert_eq!(hclog::provenance(Boosted).unwrap().level, Source::Code);
+    assert!(!hclog::tD1!(Boosted));
+
+    let reverted = notices.try_iter().filter(|l| l.contains("'Boosted'")).collect::<Vec<_>>();
+    assert_eq!(reverted.len(), 1, "{:?}", reverted);
+    assert!(reverted[0].ends_with("level of 'Boosted' reverted debug10 -> info after expiry"),
+        "{}", reverted[0]);
+}
+
+#[test]
+fn set_level_cancels_revert() {
+    DecayKeys::init_with_defaults("decay").unwrap();
+    hclog::set_level_for(Cancelled, Level::Debug10, SHORT).unwrap();
+    hclog::set_level(Cancelled, Level::Debug3).unwrap();
+    sleep(SHORT * 2);
+    assert!(hclog::tD3!(Cancelled));
+    assert!(!hclog::tD4!(Cancelled));
+}
+
+#[test]
+fn boost_again_keeps_previous_level() {
+    DecayKeys::init_with_defaults("decay").unwrap();
+    hclog::set_level(Extended, Level::Warn).unwrap();
+    hclog::set_level_for(Extended, Level::Debug5, SHORT).unwrap();
+    hclog::set_level_for(Extended, Level::Debug10, SHORT).unwrap();
+    assert!(hclog::tD10!(Extended));
+    sleep(SHORT * 2);
+    assert!(hclog::tW!(Extended));
+    assert!(!hclog::tN!(Extended));
+}
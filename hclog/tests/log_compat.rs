@@ -0,0 +1,73 @@
+/*
+ * runtime toggling of the LOGCOMPAT option
+ *
+ * run with: cargo test -p hclog --features test-util
+ *
+ * The logger of crate log is process wide, so this binary contains a single test only.
+ */
+use hclog::{
+    Level, FacadeVariant, InternalLogKeys, Scope, options::{Options, LOGCOMPAT},
+    testing::MockFacade,
+};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum CompatKeys {
+    #[hclog(name = "compatfoo")]
+    CompatFoo,
+}
+use CompatKeys::*;
+
+#[test]
+fn toggle_log_compat() {
+    CompatKeys::init("compat", Level::Info, FacadeVariant::None, Options::new()).unwrap();
+    log::info!("before init");
+    assert_eq!(hclog::has_module(InternalLogKeys::LogCompat), Ok(false));
+
+    // other options leave the log crate alone
+    hclog::set_module_options(CompatFoo, Options::default() - LOGCOMPAT).unwrap();
+    assert_eq!(hclog::has_module(InternalLogKeys::LogCompat), Ok(false));
+
+    // the defaults contain LOGCOMPAT, the layer is initialized with the defaults of the scope
+    hclog::set_module_options(CompatFoo, Options::default()).unwrap();
+    assert_eq!(hclog::has_module(InternalLogKeys::LogCompat), Ok(true));
+    assert_eq!(hclog::test_log(InternalLogKeys::LogCompat, Level::Info), Ok(true));
+    assert_eq!(hclog::test_log(InternalLogKeys::LogCompat, Level::Debug1), Ok(false));
+
+    let mock = MockFacade::new();
+    hclog::set_logdest(InternalLogKeys::LogCompat, FacadeVariant::Mock(mock.clone())).unwrap();
+    log::info!("enabled");
+    log::debug!("filtered");
+
+    // unsetting LOGCOMPAT on other keys doesn't mute
+    hclog::unset_module_options(CompatFoo, LOGCOMPAT).unwrap();
+    log::warn!("still enabled");
+
+    hclog::unset_module_options(InternalLogKeys::LogCompat, LOGCOMPAT).unwrap();
+    log::error!("muted");
+
+    hclog::set_module_options(CompatFoo, LOGCOMPAT).unwrap();
+    log::error!("unmuted");
+
+    // only newly setting the option unmutes the layer
+    hclog::set_module_options(InternalLogKeys::LogCompat, LOGCOMPAT).unwrap();
+    hclog::unset_module_options(InternalLogKeys::LogCompat, LOGCOMPAT).unwrap();
+    hclog::set_module_options(CompatFoo, LOGCOMPAT).unwrap();
+    log::error!("still muted");
+    hclog::set_module_options(InternalLogKeys::LogCompat, LOGCOMPAT).unwrap();
+    log::warn!("unmuted again");
+
+    // muting the key suppresses the bridged messages as well
+    hclog::mute(InternalLogKeys::LogCompat).unwrap();
+    log::error!("key muted");
+    hclog::unmute(InternalLogKeys::LogCompat).unwrap();
+    log::info!("key unmuted");
+
+    assert_eq!(mock.calls(), [
+        (Level::Info, "enabled".to_owned()),
+        (Level::Warn, "still enabled".to_owned()),
+        (Level::Error, "unmuted".to_owned()),
+        (Level::Warn, "unmuted again".to_owned()),
+        (Level::Info, "key unmuted".to_owned()),
+    ]);
+}
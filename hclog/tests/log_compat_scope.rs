@@ -0,0 +1,49 @@
+/*
+ * messages of crate log within hclog::scope carry the task ident like native messages
+ *
+ * The logger of crate log is process wide, so this binary contains a single test only.
+ */
+mod common;
+
+use hclog::{
+    options::{Options, LOGCOMPAT, SCOPE}, FacadeVariant, InternalLogKeys, Level, Scope,
+};
+use hclog_macros::HCLog;
+use common::block_on;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum BridgeKeys {
+    Worker,
+}
+use BridgeKeys::*;
+
+#[test]
+fn bridged_task_ident() {
+    let path = std::env::temp_dir().join(format!("hclog_compat_scope_{}.log", std::process::id()));
+    let facade = FacadeVariant::File(path.clone(), false);
+    BridgeKeys::init("bridge", Level::Info, facade.clone(), Options::new()).unwrap();
+    hclog::set_module_options(Worker, LOGCOMPAT | SCOPE).unwrap();
+    hclog::set_logdest(InternalLogKeys::LogCompat, facade).unwrap();
+    hclog::set_module_options(InternalLogKeys::LogCompat, SCOPE).unwrap();
+
+    log::info!("outside");
+    let task = hclog::scope("Task1", Worker, async {
+        hclog::lI!(Worker, "native");
+        log::info!("bridged");
+        // changes within the task don't leak to the global LogCompat key
+        hclog::set_level(InternalLogKeys::LogCompat, Level::Off).unwrap();
+        log::info!("muted");
+    }).unwrap();
+    block_on(task);
+    log::info!("global again");
+    hclog::flush().unwrap();
+
+    let lines = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let lines = lines.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 4, "{:?}", lines);
+    assert!(lines[0].contains("global ") && lines[0].ends_with("outside"), "{}", lines[0]);
+    assert!(lines[1].contains("task[Task1] ") && lines[1].ends_with("native"), "{}", lines[1]);
+    assert!(lines[2].contains("task[Task1] ") && lines[2].ends_with("bridged"), "{}", lines[2]);
+    assert!(lines[3].contains("global ") && lines[3].ends_with("global again"), "{}", lines[3]);
+}
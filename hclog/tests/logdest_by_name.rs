@@ -0,0 +1,55 @@
+/*
+ * facades changed for all LogKeys matching a name pattern across Scopes
+ */
+use hclog::{options::NONE, ErrorKind, FacadeVariant, Level, Scope, ScopeKey, Source};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum AppKeys {
+    NoisyRx,
+    NoisyTx,
+    Quiet,
+}
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(scope = ScopeKey::Lib)]
+enum LibKeys {
+    NoisyDisk,
+    Store,
+}
+use AppKeys::NoisyRx;
+use LibKeys::NoisyDisk;
+
+#[test]
+fn patterns() {
+    AppKeys::init("app", Level::Info, FacadeVariant::None, NONE).unwrap();
+    LibKeys::init("lib", Level::Info, FacadeVariant::None, NONE).unwrap();
+
+    assert_eq!(hclog::set_logdest_by_name("noisy*", FacadeVariant::RingBuffer(8)), Ok(3));
+    // the keys share a single facade
+    hclog::lI!(NoisyRx, "rx");
+    hclog::lI!(NoisyDisk, "disk");
+    let lines = hclog::drain_ring(AppKeys::NoisyTx).unwrap();
+    assert_eq!(lines.len(), 2, "{:?}", lines);
+    assert!(lines[0].ends_with("rx") && lines[1].ends_with("disk"), "{:?}", lines);
+
+    // non-matching keys are untouched
+    for res in [hclog::drain_ring(AppKeys::Quiet), hclog::drain_ring(LibKeys::Store)] {
+        assert_eq!(res, Err(ErrorKind::NoRingBuffer));
+    }
+    assert_eq!(hclog::provenance(AppKeys::Quiet).unwrap().facade, Source::Code);
+
+    // exact names, qualified with the Scope and _all
+    assert_eq!(hclog::set_logdest_by_name("STORE", FacadeVariant::RingBuffer(8)), Ok(1));
+    assert_eq!(hclog::set_logdest_by_name("lib/noisy*", FacadeVariant::None), Ok(1));
+    assert_eq!(hclog::drain_ring(NoisyDisk), Err(ErrorKind::NoRingBuffer));
+    assert!(hclog::drain_ring(NoisyRx).is_ok());
+    // _all includes the Internal key of hclog itself
+    assert_eq!(hclog::set_logdest_by_name("_all", FacadeVariant::None), Ok(6));
+
+    // no match is an error to catch typos
+    for pattern in ["nosiy*", "quit", "app/store", "ghost/_all"] {
+        assert_eq!(hclog::set_logdest_by_name(pattern, FacadeVariant::StdOut),
+            Err(ErrorKind::KeyNotInitialized), "{}", pattern);
+    }
+}
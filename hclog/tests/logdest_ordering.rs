@@ -0,0 +1,97 @@
+/*
+ * no log call started after set_logdest returned writes to the previous facade
+ */
+// in single-thread mode every thread has its own context
+#![cfg(not(feature = "single-thread"))]
+
+use hclog::{options::{DEDUP, NONE}, testing::MockFacade, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+use std::{
+    sync::{atomic::{AtomicUsize, Ordering::SeqCst}, Arc},
+    thread,
+};
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum FlipKeys {
+    Flip,
+}
+use FlipKeys::*;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(scope = hclog::ScopeKey::Lib)]
+enum SyncKeys {
+    Rotate,
+}
+use SyncKeys::*;
+
+const LOGGERS: usize = 8;
+const MESSAGES: usize = 2000;
+
+/*
+ * flip k (starting at 1) sets sinks[k % 2]. STARTED is stored before a flip, DONE after it
+ * returned. A message logged after reading DONE = b and followed by reading STARTED = a went
+ * to a facade set by a flip within [b, a], with a == b it has to be sinks[b % 2]. The messages
+ * carry the logger, a sequence number and b, the loggers return a for each sequence number.
+ */
+#[test]
+fn facade_flips() {
+    let sinks = [MockFacade::new(), MockFacade::new()];
+    FlipKeys::init("flip", Level::Info, FacadeVariant::Mock(sinks[0].clone()), NONE).unwrap();
+    let (started, done) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let finished = Arc::new(AtomicUsize::new(0));
+
+    let loggers = (0..LOGGERS).map(|logger| {
+        let (started, done, finished) = (started.clone(), done.clone(), finished.clone());
+        thread::spawn(move || {
+            let mut after = Vec::with_capacity(MESSAGES);
+            for seq in 0..MESSAGES {
+                let before = done.load(SeqCst);
+                hclog::lI!(Flip, "{} {} {}", logger, seq, before);
+                after.push(started.load(SeqCst));
+            }
+            finished.fetch_add(1, SeqCst);
+            after
+        })
+    }).collect::<Vec<_>>();
+
+    let mut k = 0;
+    while finished.load(SeqCst) < LOGGERS {
+        k += 1;
+        started.store(k, SeqCst);
+        hclog::set_logdest(Flip, FacadeVariant::Mock(sinks[k % 2].clone())).unwrap();
+        done.store(k, SeqCst);
+        thread::yield_now();
+    }
+    let after = loggers.into_iter().map(|l| l.join().unwrap()).collect::<Vec<_>>();
+
+    let (mut total, mut checked) = (0, 0);
+    for (idx, sink) in sinks.iter().enumerate() {
+        for (_, payload) in sink.calls() {
+            let msg = payload.split(' ').map(|n| n.parse().unwrap()).collect::<Vec<usize>>();
+            let (before, after) = (msg[2], after[msg[0]][msg[1]]);
+            if before == after {
+                assert_eq!(idx, before % 2, "'{}' went to a replaced facade", payload);
+                checked += 1;
+            }
+            total += 1;
+        }
+    }
+    // every message landed in exactly one of the sinks
+    assert_eq!(total, after.iter().map(Vec::len).sum::<usize>());
+    assert!(k > 1 && checked > 0);
+}
+
+#[test]
+fn sync_flushes_previous() {
+    let (old, new) = (MockFacade::new(), MockFacade::new());
+    SyncKeys::init("sync", Level::Info, FacadeVariant::Mock(old.clone()), DEDUP).unwrap();
+    for _ in 0..3 {
+        hclog::lI!(Rotate, "same");
+    }
+    hclog::set_logdest_sync(Rotate, FacadeVariant::Mock(new.clone())).unwrap();
+    hclog::lI!(Rotate, "after");
+    let payloads = |mock: &MockFacade| mock.calls().into_iter().map(|(_, p)| p)
+        .collect::<Vec<_>>();
+    assert_eq!(payloads(&old), ["same", "last message repeated 2 times"]);
+    assert_eq!(payloads(&new), ["after"]);
+}
@@ -0,0 +1,84 @@
+/*
+ * two LogKey types sharing one Scope via #[hclog(merge = "append")]
+ *
+ * Every test uses its own Scope because the tests of this binary share the global context.
+ */
+use hclog::{Level, LogKey, Scope};
+
+macro_rules! merge_keys {
+    ($mod:ident, $scope:path) => {
+        mod $mod {
+            use hclog_macros::HCLog;
+
+            #[derive(HCLog, Copy, Clone, Debug)]
+            #[hclog(scope = $scope, merge = "append")]
+            pub enum First {
+                #[hclog(name = "main")]
+                Main,
+                #[hclog(name = "worker")]
+                Worker,
+            }
+
+            pub mod second {
+                use hclog_macros::HCLog;
+
+                #[derive(HCLog, Copy, Clone, Debug)]
+                #[hclog(scope = $scope, merge = "append")]
+                pub enum Second {
+                    #[hclog(name = "db")]
+                    Db,
+                    #[hclog(name = "net")]
+                    Net,
+                }
+            }
+        }
+    };
+}
+merge_keys!(app, hclog::ScopeKey::Application);
+merge_keys!(lib, hclog::ScopeKey::Lib);
+
+fn listed_modules() -> String {
+    let mut out = Vec::new();
+    hclog::list_modules(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn first_then_second() {
+    use app::{First::{self, *}, second::Second::{self, *}};
+
+    First::init_with_defaults("first").unwrap();
+    Second::init_with_defaults("second").unwrap();
+    assert_eq!((Main.log_key(), Worker.log_key()), (0, 1));
+    assert_eq!((Db.log_key(), Net.log_key()), (2, 3));
+
+    hclog::set_level(Worker, Level::Error).unwrap();
+    hclog::set_level(Net, Level::Debug1).unwrap();
+    assert_eq!(hclog::test_log(Worker, Level::Debug1), Ok(false));
+    assert_eq!(hclog::test_log(Net, Level::Debug1), Ok(true));
+
+    // init again keeps the offsets
+    Second::init_with_defaults("second").unwrap();
+    assert_eq!(Db.log_key(), 2);
+
+    let modules = listed_modules();
+    for name in ["main", "worker", "db", "net"] {
+        assert!(modules.contains(name), "{} missing in {}", name, modules);
+    }
+}
+
+#[test]
+fn second_then_first() {
+    use lib::{First::{self, *}, second::Second::{self, *}};
+
+    Second::init_with_defaults("second").unwrap();
+    First::init_with_defaults("first").unwrap();
+    assert_eq!((Db.log_key(), Net.log_key()), (0, 1));
+    assert_eq!((Main.log_key(), Worker.log_key()), (2, 3));
+
+    hclog::set_level(Main, Level::Debug2).unwrap();
+    hclog::set_level(Db, Level::Warn).unwrap();
+    assert_eq!(hclog::test_log(Main, Level::Debug2), Ok(true));
+    assert_eq!(hclog::test_log(Db, Level::Info), Ok(false));
+    assert_eq!(hclog::has_module(Worker), Ok(true));
+}
@@ -0,0 +1,75 @@
+/*
+ * LogKey types colliding in one Scope with the "error" and "skip" merge policies
+ *
+ * Every test uses its own Scope because the tests of this binary share the global context.
+ */
+use hclog::{ErrorKind, LogKey, Scope};
+
+mod first {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    pub enum First {
+        #[hclog(name = "main")]
+        Main,
+    }
+}
+
+mod colliding {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    pub enum Colliding {
+        #[hclog(name = "db")]
+        Db,
+    }
+}
+
+mod skipping {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(merge = "skip")]
+    pub enum Skipping {
+        #[hclog(name = "net")]
+        Net,
+    }
+}
+
+mod twice {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(scope = hclog::ScopeKey::Lib)]
+    pub enum Twice {
+        #[hclog(name = "once")]
+        Once,
+    }
+}
+
+use first::First;
+use colliding::Colliding;
+use skipping::Skipping;
+use twice::Twice;
+
+#[test]
+fn collision_error_and_skip() {
+    First::init_with_defaults("first").unwrap();
+    assert_eq!(Colliding::init_with_defaults("colliding"), Err(ErrorKind::KeyCollision));
+    // the first LogKey is kept
+    Skipping::init_with_defaults("skipping").unwrap();
+    assert_eq!(Skipping::Net.log_key(), First::Main.log_key());
+
+    let mut out = Vec::new();
+    hclog::list_modules(&mut out).unwrap();
+    let modules = String::from_utf8(out).unwrap();
+    assert!(modules.contains("main"));
+    assert!(!modules.contains("db") && !modules.contains("net"));
+}
+
+#[test]
+fn same_type_twice() {
+    Twice::init_with_defaults("twice").unwrap();
+    Twice::init_with_defaults("twice").unwrap();
+    assert_eq!(hclog::has_module(Twice::Once), Ok(true));
+}
@@ -0,0 +1,70 @@
+/*
+ * tests for the MockFacade of the `test-util` feature
+ *
+ * run with: cargo test -p hclog --features test-util
+ */
+use hclog::{Level, FacadeVariant, Scope, testing::MockFacade};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info)]
+enum MockKeys {
+    #[hclog(name = "mockfoo")]
+    Expected,
+    #[hclog(name = "mockbar")]
+    Missing,
+    #[hclog(name = "mockbaz")]
+    Strict,
+}
+use MockKeys::*;
+
+fn install(k: MockKeys, mock: &MockFacade) {
+    MockKeys::init_with_defaults("mock").unwrap();
+    hclog::set_logdest(k, FacadeVariant::Mock(mock.clone())).unwrap();
+}
+
+#[test]
+fn expectations_met() {
+    let mock = MockFacade::new();
+    mock.expect_log(Level::Error, "refused").expect_log(Level::Info, "retry 2");
+    install(Expected, &mock);
+
+    hclog::lE!(Expected, "connection refused by {}", "localhost");
+    hclog::lI!(Expected, "retry {}", 1);
+    hclog::lI!(Expected, "retry {}", 2);
+    // filtered by the level of the key
+    hclog::lD1!(Expected, "not recorded");
+
+    mock.verify();
+    assert_eq!(mock.calls(), [
+        (Level::Error, "connection refused by localhost".to_owned()),
+        (Level::Info, "retry 1".to_owned()),
+        (Level::Info, "retry 2".to_owned()),
+    ]);
+    let res = std::panic::catch_unwind(|| mock.verify_no_unexpected());
+    let msg = res.unwrap_err().downcast::<String>().unwrap();
+    assert!(msg.contains("1 unexpected log call(s)") && msg.contains("\"retry 1\""), "{}", msg);
+}
+
+#[test]
+#[should_panic(expected = "1 expected log call(s) not made:\n  - warn containing \"disk full\"")]
+fn expectation_missing() {
+    let mock = MockFacade::new();
+    mock.expect_log(Level::Warn, "disk full").expect_log(Level::Error, "disk");
+    install(Missing, &mock);
+
+    // the level has to match as well
+    hclog::lE!(Missing, "disk full");
+    mock.verify();
+}
+
+#[test]
+#[should_panic(expected = "unexpected log call: error \"surprise\"")]
+fn strict_panics_immediately() {
+    let mock = MockFacade::new_strict();
+    mock.expect_log(Level::Info, "expected");
+    install(Strict, &mock);
+
+    hclog::lI!(Strict, "expected message");
+    hclog::lE!(Strict, "surprise");
+}
@@ -0,0 +1,56 @@
+/*
+ * muting LogKeys independent of their level
+ */
+use hclog::{FacadeVariant, Level, Scope, options::NONE};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum MuteKeys {
+    Net,
+    Db,
+}
+use MuteKeys::*;
+
+fn all_levels() -> impl Iterator<Item = Level> {
+    (Level::min() as u8..=Level::max() as u8).filter_map(Level::from_u8)
+}
+
+fn detailed() -> String {
+    let mut out = Vec::new();
+    hclog::list_modules_detailed(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn mute_keeps_level() {
+    MuteKeys::init("mute", Level::Info, FacadeVariant::None, NONE).unwrap();
+    hclog::set_level(Net, Level::Debug3).unwrap();
+    assert_eq!(hclog::is_muted(Net), Ok(false));
+
+    hclog::mute(Net).unwrap();
+    assert_eq!(hclog::is_muted(Net), Ok(true));
+    assert!(all_levels().all(|l| hclog::test_log(Net, l) == Ok(false)));
+    assert_eq!(hclog::test_log(Db, Level::Info), Ok(true));
+    assert!(detailed().lines().any(|l| l.starts_with("  Net [") && l.ends_with(" [muted]")));
+    assert!(detailed().lines().any(|l| l.starts_with("  Db [") && !l.ends_with(" [muted]")));
+
+    // changing the level while muted doesn't unmute
+    hclog::set_level(Net, Level::Debug5).unwrap();
+    assert_eq!(hclog::test_log(Net, Level::Error), Ok(false));
+    hclog::unmute(Net).unwrap();
+    assert_eq!(hclog::is_muted(Net), Ok(false));
+    assert_eq!(hclog::test_log(Net, Level::Debug5), Ok(true));
+    assert_eq!(hclog::test_log(Net, Level::Debug6), Ok(false));
+    assert!(!detailed().contains("[muted]"));
+
+    // the pseudo levels of the commandline syntax
+    hclog::set_mod_level_cli(["net:mute,db:debug1"]).unwrap();
+    assert_eq!((hclog::is_muted(Net), hclog::is_muted(Db)), (Ok(true), Ok(false)));
+    assert_eq!(hclog::test_log(Db, Level::Debug1), Ok(true));
+    hclog::set_mod_level(["_all:MUTE"]).unwrap();
+    assert!(all_levels().all(|l| hclog::test_log(Db, l) == Ok(false)));
+    hclog::set_mod_level(["_all:unmute"]).unwrap();
+    assert_eq!(hclog::test_log(Net, Level::Debug5), Ok(true));
+    assert_eq!(hclog::test_log(Db, Level::Debug1), Ok(true));
+    assert_eq!(hclog::test_log(Db, Level::Debug2), Ok(false));
+}
@@ -0,0 +1,51 @@
+/*
+ * names of Scopes and LogKeys are case insensitive
+ *
+ * The binary contains a single test because all tests share the global context.
+ */
+use hclog::{ErrorKind, Level, MergePolicy, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info)]
+enum CaseKeys {
+    #[hclog(name = "NetIO")]
+    NetIo,
+    #[hclog(name = "db")]
+    Db,
+}
+
+mod colliding {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(merge = "append")]
+    pub enum Colliding {
+        #[hclog(name = "DB")]
+        Db,
+    }
+}
+use colliding::Colliding;
+
+#[test]
+#[allow(clippy::needless_borrows_for_generic_args)] // keys are passed as slice and owned
+fn mixed_case_names() {
+    CaseKeys::init_with_defaults("Cases").unwrap();
+    assert_eq!(hclog::set_mod_level(["netio:debug2,DB:Warn"]), Ok(()));
+    assert_eq!(hclog::test_log(CaseKeys::NetIo, Level::Debug2), Ok(true));
+    assert_eq!(hclog::test_log(CaseKeys::Db, Level::Warn), Ok(true));
+    assert_eq!(hclog::test_log(CaseKeys::Db, Level::Notice), Ok(false));
+    assert_eq!(hclog::set_mod_level(["cases/NETIO:error,_ALL:+1"]), Ok(()));
+    assert_eq!(hclog::test_log(CaseKeys::NetIo, Level::Warn), Ok(true));
+    assert_eq!(hclog::test_log(CaseKeys::NetIo, Level::Notice), Ok(false));
+    assert_eq!(hclog::set_mod_level(["NetIO.*:info"]), Ok(()));
+    assert_eq!(hclog::test_log(CaseKeys::NetIo, Level::Info), Ok(true));
+
+    // "DB" only differs by case from "db" of the same scope
+    assert_eq!(Colliding::init_with_defaults("cases"), Err(ErrorKind::KeyCollision));
+    assert_eq!(hclog::has_module(Colliding::Db), Ok(false));
+    assert_eq!(hclog::add_submodules_with(&[Colliding::Db], MergePolicy::Skip), Ok(()));
+    assert_eq!(hclog::has_module(Colliding::Db), Ok(false));
+    assert_eq!(hclog::add_submodules_with(vec![Colliding::Db], MergePolicy::Skip), Ok(()));
+    assert_eq!(hclog::has_module(Colliding::Db), Ok(false));
+}
@@ -0,0 +1,65 @@
+/*
+ * terminators written by the file facade for each Newline policy
+ */
+use hclog::{options::{NONE, RAW}, FacadeVariant, Level, Newline, Scope};
+use hclog_macros::HCLog;
+use std::path::PathBuf;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum NewlineKeys {
+    Lf,
+    CrLf,
+    Bare,
+}
+use NewlineKeys::*;
+
+fn to_file(key: NewlineKeys) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("hclog_newline_{}_{}.log", key, std::process::id()));
+    hclog::set_logdest(key, FacadeVariant::File(path.clone(), true)).unwrap();
+    path
+}
+
+fn written(path: PathBuf) -> String {
+    let content = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    content
+}
+
+#[test]
+fn policies() {
+    NewlineKeys::init("newline", Level::Info, FacadeVariant::None, NONE).unwrap();
+    let paths = [Lf, CrLf, Bare].map(to_file);
+    hclog::set_newline(CrLf, Newline::CrLf).unwrap();
+    hclog::set_newline(Bare, Newline::None).unwrap();
+    for key in [Lf, CrLf, Bare] {
+        hclog::lI!(key, "single");
+        hclog::lI!(key, "first\nsecond");
+        hclog::lI!(key, "windows\r\nline");
+    }
+    // the caller terminates the lines itself
+    hclog::set_module_options(Bare, RAW).unwrap();
+    hclog::lI!(Bare, "raw\r\n");
+
+    let [lf, crlf, bare] = paths.map(written);
+    assert_eq!(lf, "single\nfirst\nsecond\nwindows\r\nline\n");
+    assert_eq!(crlf, "single\r\nfirst\r\nsecond\r\nwindows\r\nline\r\n");
+    assert_eq!(bare, "singlefirst\nsecondwindows\r\nlineraw\r\n");
+}
+
+#[test]
+fn survives_facade_change() {
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(scope = hclog::ScopeKey::Lib)]
+    enum Kept {
+        Changed,
+    }
+
+    Kept::init("kept", Level::Info, FacadeVariant::StdErr, NONE).unwrap();
+    hclog::set_newline(Kept::Changed, Newline::CrLf).unwrap();
+    let path = std::env::temp_dir().join(format!("hclog_newline_kept_{}.log", std::process::id()));
+    hclog::set_logdest(Kept::Changed, FacadeVariant::File(path.clone(), true)).unwrap();
+    let key = Kept::Changed;
+    hclog::lI!(key, "a\nb");
+    assert_eq!(written(path), "a\r\nb\r\n");
+}
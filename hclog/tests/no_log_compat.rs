@@ -0,0 +1,38 @@
+/*
+ * LOGCOMPAT is removed from the default options via no_log_compat_inherit
+ */
+use hclog::{options::{Options, LOGCOMPAT}, Level, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(scope = hclog::ScopeKey::Lib, no_log_compat_inherit)]
+enum OwnLogKeys {
+    Own,
+}
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info)]
+enum InheritKeys {
+    Inherit,
+}
+
+#[test]
+fn default_options() {
+    assert_eq!(OwnLogKeys::default_options(), Options::default() - LOGCOMPAT);
+    assert_eq!(InheritKeys::default_options(), Options::default());
+}
+
+#[test]
+fn init_with_defaults_skips_log_compat() {
+    OwnLogKeys::init_with_defaults("own").unwrap();
+    InheritKeys::init_with_defaults("inherit").unwrap();
+    let mut out = Vec::new();
+    hclog::list_modules_detailed(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let opts = |key: &str| out.lines()
+        .find(|l| l.starts_with(&format!("  {} ", key)))
+        .and_then(|l| l.rsplit_once("[opts=")).map(|(_, o)| o.trim_end_matches(']').to_owned())
+        .unwrap();
+    assert!(!opts("Own").contains("LOGCOMPAT"), "{}", out);
+    assert!(opts("Inherit").contains("LOGCOMPAT"), "{}", out);
+}
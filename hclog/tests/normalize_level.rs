@@ -0,0 +1,62 @@
+/*
+ * levels assigned by name are normalized by the Scope of the key
+ */
+use hclog::{Level, Scope};
+
+mod app {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(scope = hclog::ScopeKey::Application)]
+    pub enum AppKeys {
+        Main,
+    }
+}
+
+mod lib {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(scope = hclog::ScopeKey::Lib, max_level = hclog::Level::Debug5)]
+    pub enum LibKeys {
+        Chatty,
+        Quiet,
+    }
+}
+
+use app::AppKeys::{self, *};
+use lib::LibKeys::{self, *};
+
+#[test]
+fn max_level_caps_named_levels() {
+    AppKeys::init_with_defaults("normalize").unwrap();
+    LibKeys::init_with_defaults("normalize").unwrap();
+
+    hclog::set_mod_level(["_all:debug10"]).unwrap();
+    assert_eq!(hclog::test_log(Main, Level::Debug10), Ok(true));
+    assert_eq!(hclog::test_log(Chatty, Level::Debug5), Ok(true));
+    assert_eq!(hclog::test_log(Chatty, Level::Debug6), Ok(false));
+
+    hclog::set_mod_level(["Quiet:debug9"]).unwrap();
+    assert_eq!(hclog::test_log(Quiet, Level::Debug5), Ok(true));
+    assert_eq!(hclog::test_log(Quiet, Level::Debug6), Ok(false));
+
+    // levels below the cap are kept as they are
+    hclog::set_mod_level(["Quiet:info"]).unwrap();
+    assert_eq!(hclog::test_log(Quiet, Level::Info), Ok(true));
+    assert_eq!(hclog::test_log(Quiet, Level::Debug1), Ok(false));
+
+    // a subtree is capped as well, the descendants inherit the capped level
+    hclog::set_parent(Quiet, Chatty).unwrap();
+    hclog::set_mod_level(["chatty.*:debug8"]).unwrap();
+    for key in [Chatty, Quiet] {
+        assert_eq!(hclog::test_log(key, Level::Debug5), Ok(true));
+        assert_eq!(hclog::test_log(key, Level::Debug6), Ok(false));
+    }
+    hclog::set_mod_level(["chatty.*:+3"]).unwrap();
+    assert_eq!(hclog::test_log(Quiet, Level::Debug6), Ok(false));
+
+    // an explicit set_level is not normalized
+    hclog::set_level(Chatty, Level::Debug10).unwrap();
+    assert_eq!(hclog::test_log(Chatty, Level::Debug10), Ok(true));
+}
@@ -0,0 +1,55 @@
+/*
+ * message of the panic raised by the log macros for a LogKey which can't be used
+ *
+ * The Scope of PanicKeys must never be initialized in this binary.
+ */
+use hclog::{Scope, ScopeKey};
+use hclog_macros::HCLog;
+use std::panic;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum PanicKeys {
+    Cache,
+    Db,
+}
+use PanicKeys::*;
+
+// the LogKeys are added by hand, the Display name differs from the variant
+#[derive(HCLog, Copy, Clone)]
+#[hclog(scope = ScopeKey::Lib, init_submodules = false)]
+enum LateKeys {
+    LateCache,
+    #[hclog(name = "late_db")]
+    LateDb,
+}
+use LateKeys::{LateDb, LateCache};
+
+fn payload(f: impl FnOnce() + panic::UnwindSafe) -> String {
+    let err = panic::catch_unwind(f).unwrap_err();
+    err.downcast_ref::<String>().cloned().unwrap_or_default()
+}
+
+#[test]
+fn uninitialized_scope() {
+    let msg = payload(|| hclog::lE!(Db, "lost"));
+    assert_eq!(msg, "hclog: LogKey 'Db' (index 1) belongs to a scope which is not initialized; \
+        did you forget to call PanicKeys::init(...)?");
+    let msg = payload(|| hclog::batch!(Db, hclog::Level::Info, { "first"; "second" }));
+    assert!(msg.contains("'Db'") && msg.contains("PanicKeys::init(...)"), "{}", msg);
+    let msg = payload(|| { hclog::tX!(Cache, hclog::Level::Debug1); });
+    assert!(msg.starts_with("hclog: LogKey 'Cache' (index 0) belongs to"), "{}", msg);
+}
+
+#[test]
+fn uninitialized_key() {
+    LateKeys::init_with_defaults("late").unwrap();
+    hclog::add_submodules([LateCache]).unwrap();
+    // the log macros skip keys which aren't initialized, the message of an error of the
+    // underlying call is built the same way
+    assert!(!hclog::tE!(LateDb));
+    let err = hclog::log(LateDb, hclog::Level::Error, file!(), None, line!(), &format_args!("x"))
+        .unwrap_err();
+    let msg = hclog::failure_message(LateDb, err);
+    assert_eq!(msg, "hclog: LogKey 'late_db' (index 1) is not initialized in scope 'late'; \
+        did you forget to call LateKeys::init(...) or add_submodules(&[LateKeys::LateDb])?");
+}
@@ -0,0 +1,137 @@
+/*
+ * properties of the parsers for levels, facades, options and set_mod_level arguments
+ *
+ * The same parsers are fed with arbitrary input by the targets in fuzz/, these tests check
+ * the invariants with a limited number of cases on every test run.
+ */
+use hclog::{options::{Options, NONE}, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+use proptest::prelude::*;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum PropKeys {
+    Net,
+    Db,
+    NetRx,
+}
+
+fn any_level() -> impl Strategy<Value = Level> {
+    (Level::min() as u8..=Level::max() as u8).prop_filter_map("no level", Level::from_u8)
+}
+
+// the known names with a random case, mixed with arbitrary text
+fn name_like(names: &'static [&'static str]) -> impl Strategy<Value = String> {
+    prop_oneof![
+        3 => (prop::sample::select(names), any::<u64>()).prop_map(|(name, case)| {
+            name.chars().enumerate().map(|(i, c)| match case >> (i % 64) & 1 {
+                1 => c.to_ascii_uppercase(),
+                _ => c,
+            }).collect()
+        }),
+        1 => "[ -~]{0,8}",
+    ]
+}
+
+fn facade_spec() -> impl Strategy<Value = String> {
+    let names = &["none", "stdout", "stderr", "syslog", "file", "binary", "ringbuffer",
+                  "syslog-udp", "syslog-tcp"];
+    let args = prop_oneof![
+        Just(None),
+        "[ -~]{0,12}".prop_map(Some),
+        "[0-9]{1,22}".prop_map(Some),
+        Just(Some("user".to_owned())),
+        Just(Some("127.0.0.1:514:local0".to_owned())),
+        Just(Some("[::1]:601:daemon".to_owned())),
+    ];
+    (name_like(names), args).prop_map(|(name, arg)| match arg {
+        Some(arg) => format!("{}:{}", name, arg),
+        None => name,
+    })
+}
+
+fn mod_level_arg() -> impl Strategy<Value = String> {
+    let key = name_like(&["net", "db", "netrx", "_all", "net.*", "prop/net", "ghost", ""]);
+    let level = name_like(&["info", "debug3", "err", "7", "+2", "-1", "-300", "mute", "unmute",
+                            "loud", ""]);
+    let chunk = prop_oneof![
+        4 => (key, level).prop_map(|(key, level)| format!("{}:{}", key, level)),
+        1 => "[ -~]{0,12}",
+    ];
+    prop::collection::vec(chunk, 1..5).prop_map(|chunks| chunks.join(","))
+}
+
+fn detailed() -> String {
+    let mut out = Vec::new();
+    hclog::list_modules_detailed(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn reset_levels() {
+    hclog::set_mod_level(["_all:info,_all:unmute"]).unwrap();
+}
+
+proptest! {
+    #[test]
+    fn level_round_trip(level in any_level()) {
+        prop_assert_eq!(level.to_string().parse::<Level>(), Ok(level));
+        prop_assert_eq!(level.to_string().to_uppercase().parse::<Level>(), Ok(level));
+    }
+
+    #[test]
+    fn level_parse_idempotent(s in prop_oneof![name_like(&["info", "debug", "panic", "err",
+                                                           "debug10", "3", "07"]),
+                                               "\\PC{0,12}"]) {
+        if let Ok(level) = s.parse::<Level>() {
+            prop_assert_eq!(level.to_string().parse::<Level>(), Ok(level));
+        }
+    }
+
+    #[test]
+    fn facade_parse_idempotent(s in prop_oneof![3 => facade_spec(), 1 => "\\PC{0,24}"]) {
+        if let Ok(facade) = s.parse::<FacadeVariant>() {
+            let spec = facade.spec();
+            prop_assert!(spec.is_some(), "no spec for {:?}", facade);
+            let spec = spec.unwrap();
+            let reparsed = spec.parse::<FacadeVariant>();
+            prop_assert!(reparsed.is_ok(), "'{}' of '{}' doesn't parse: {:?}", spec, s, reparsed);
+            prop_assert_eq!(reparsed.unwrap().spec(), Some(spec));
+        }
+    }
+
+    #[test]
+    fn options_round_trip(bits in any::<u32>()) {
+        let opts = Options::from_bits_truncate(bits);
+        prop_assert_eq!(opts.to_string().parse::<Options>(), Ok(opts));
+    }
+
+    #[test]
+    fn options_parse_idempotent(s in "[-+, a-zA-Z_]{0,40}") {
+        if let Ok(opts) = s.parse::<Options>() {
+            prop_assert_eq!(opts.to_string().parse::<Options>(), Ok(opts));
+        }
+    }
+}
+
+/*
+ * set_mod_level either applies all arguments or none. A successful call has the same effect
+ * as applying its arguments one by one.
+ */
+#[test]
+fn set_mod_level_all_or_nothing() {
+    PropKeys::init("prop", Level::Info, FacadeVariant::None, NONE).unwrap();
+    proptest!(|(arg in prop_oneof![3 => mod_level_arg(), 1 => "[\\x00-\\x7f]{0,40}"])| {
+        reset_levels();
+        let before = detailed();
+        match hclog::set_mod_level([&arg]) {
+            Err(_) => prop_assert_eq!(detailed(), before),
+            Ok(()) => {
+                let batch = detailed();
+                reset_levels();
+                for chunk in arg.split(',') {
+                    hclog::set_mod_level([chunk]).unwrap();
+                }
+                prop_assert_eq!(detailed(), batch);
+            }
+        }
+    });
+}
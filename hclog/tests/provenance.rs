@@ -0,0 +1,65 @@
+/*
+ * the Source of level, facade and options for every way they can be assigned
+ *
+ * The environment is changed, so there is a single test only.
+ */
+use hclog::{
+    options::{NONE, PID},
+    FacadeVariant, Level, Provenance, Scope,
+    Source::{self, *},
+};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_facade = FacadeVariant::None)]
+enum SourceKeys {
+    Env,
+    #[hclog(level = Level::Warn, facade = FacadeVariant::StdErr)]
+    Code,
+    Cli,
+    Runtime,
+    Child,
+}
+
+fn provenance(key: SourceKeys) -> Provenance {
+    hclog::provenance(key).unwrap()
+}
+
+fn sources(level: Source, facade: Source, options: Source) -> Provenance {
+    Provenance { level, facade, options }
+}
+
+#[test]
+fn assignment_paths() {
+    std::env::set_var("HCLOG_LEVEL", "debug10");
+    std::env::set_var("HCLOG_OPT_PID", "1");
+    SourceKeys::init("provenance", Level::Info, FacadeVariant::None, NONE).unwrap();
+    assert_eq!(provenance(SourceKeys::Env), sources(Env, Code, Env));
+    assert_eq!(provenance(SourceKeys::Code), sources(Code, Code, Env));
+
+    hclog::set_mod_level_cli(["cli:debug3"]).unwrap();
+    assert_eq!(provenance(SourceKeys::Cli).level, Cli);
+    hclog::set_mod_level(["cli:debug4"]).unwrap();
+    assert_eq!(provenance(SourceKeys::Cli).level, Runtime);
+    hclog::set_mod_level_cli(["_all:+0"]).unwrap();
+    assert_eq!(provenance(SourceKeys::Code).level, Cli);
+
+    hclog::set_level(SourceKeys::Runtime, Level::Error).unwrap();
+    hclog::set_logdest(SourceKeys::Runtime, FacadeVariant::StdOut).unwrap();
+    hclog::unset_module_options(SourceKeys::Runtime, PID).unwrap();
+    assert_eq!(provenance(SourceKeys::Runtime), sources(Runtime, Runtime, Runtime));
+
+    // an inheriting key reports the source of the level of its parent
+    hclog::set_parent(SourceKeys::Child, SourceKeys::Env).unwrap();
+    hclog::set_mod_level_cli(["env.*:debug2"]).unwrap();
+    assert_eq!(provenance(SourceKeys::Child).level, Cli);
+    hclog::set_level(SourceKeys::Env, Level::Debug5).unwrap();
+    assert_eq!(provenance(SourceKeys::Child).level, Runtime);
+
+    let mut out = Vec::new();
+    hclog::list_modules_detailed(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("  Child [level=debug5 (runtime)] [facade=none (code)] [opts="),
+        "{}", out);
+    assert!(out.contains("  Code [level=warn (cli)] [facade=stderr (code)]"), "{}", out);
+}
@@ -0,0 +1,77 @@
+/*
+ * nested log calls from within a Display impl
+ *
+ * The suppressed message is written to stderr, so the test runs itself in a child process
+ * to read it.
+ */
+use hclog::{Level, FacadeVariant, Scope, options::Options};
+use hclog_macros::HCLog;
+use std::{fmt, process::Command, time::{Duration, Instant}};
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum RecKeys {
+    Rec,
+}
+use RecKeys::*;
+
+// logs its own value while being formatted - endless without the depth limit
+struct SelfLogging;
+impl fmt::Display for SelfLogging {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hclog::lE!(Rec, "nested {}", SelfLogging);
+        f.write_str("self")
+    }
+}
+
+const CHILD_ENV: &str = "HCLOG_TEST_RECURSIVE_CHILD";
+
+fn child(depth: usize) {
+    RecKeys::init("recursive", Level::Info, FacadeVariant::StdOut, Options::default()).unwrap();
+    hclog::set_max_log_depth(depth);
+    hclog::lE!(Rec, "outer {}", SelfLogging);
+}
+
+fn run_child(test: &str, depth: usize) -> (String, String) {
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", test, "--nocapture", "--test-threads=1"])
+        .env(CHILD_ENV, depth.to_string())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let start = Instant::now();
+    while child.try_wait().unwrap().is_none() {
+        if start.elapsed() > Duration::from_secs(10) {
+            child.kill().unwrap();
+            panic!("recursive logging hangs");
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success(), "{:?}", out);
+    (String::from_utf8(out.stdout).unwrap(), String::from_utf8(out.stderr).unwrap())
+}
+
+#[test]
+fn display_logs_itself() {
+    if let Ok(depth) = std::env::var(CHILD_ENV) {
+        return child(depth.parse().unwrap());
+    }
+    let (stdout, stderr) = run_child("display_logs_itself", 2);
+    // the first nested message is formatted by the outer one and logged normally
+    assert_eq!(stdout.matches("nested self").count(), 1, "{}", stdout);
+    assert_eq!(stdout.matches("outer self").count(), 1, "{}", stdout);
+    assert_eq!(stderr.matches("recursive logging suppressed: Rec").count(), 1, "{}", stderr);
+    assert!(stderr.contains("recursive_log.rs:"), "{}", stderr);
+}
+
+#[test]
+fn depth_zero_redirects_all() {
+    if let Ok(depth) = std::env::var(CHILD_ENV) {
+        return child(depth.parse().unwrap());
+    }
+    let (stdout, stderr) = run_child("depth_zero_redirects_all", 0);
+    assert!(!stdout.contains("outer self"), "{}", stdout);
+    assert!(stderr.contains("recursive logging suppressed: Rec"), "{}", stderr);
+    assert!(stderr.contains("outer self"), "{}", stderr);
+}
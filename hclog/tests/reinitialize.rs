@@ -0,0 +1,77 @@
+/*
+ * reinitialize drops a Scope including all LogKeys merged into it
+ */
+use hclog::{options::Options, ErrorKind, FacadeVariant, Level, Scope};
+use std::fmt::Display;
+
+mod keys {
+    use hclog_macros::HCLog;
+
+    #[derive(HCLog, Copy, Clone, Debug)]
+    #[hclog(scope = hclog::ScopeKey::Lib, merge = "append")]
+    pub enum Plugin {
+        Main,
+    }
+
+    pub mod extra {
+        use hclog_macros::HCLog;
+
+        #[derive(HCLog, Copy, Clone, Debug)]
+        #[hclog(scope = hclog::ScopeKey::Lib, merge = "append")]
+        pub enum Extra {
+            Loader,
+        }
+    }
+}
+
+use keys::{Plugin::{self, *}, extra::Extra::{self, *}};
+
+#[derive(hclog_macros::HCLog, Copy, Clone, Debug)]
+enum App {
+    Kept,
+}
+
+// shares the Scope of App but fails after initializing it
+#[derive(Copy, Clone, Debug)]
+struct Failing;
+impl Scope for Failing {
+    fn init<S: Display>(
+        name: S, level: Level, facade: FacadeVariant, options: Options
+    ) -> hclog::Result<()> {
+        hclog::init::<Self, S>(name, level, facade, options)?;
+        Err(ErrorKind::ParseArg)
+    }
+}
+
+#[test]
+fn reinitialize_starts_over() {
+    Plugin::init("plugin", Level::Info, FacadeVariant::None, Options::default()).unwrap();
+    Extra::init_with_defaults("plugin").unwrap();
+    hclog::set_level(Main, Level::Debug3).unwrap();
+    assert_eq!(hclog::test_log(Main, Level::Debug3), Ok(true));
+    assert_eq!(hclog::has_module(Loader), Ok(true));
+
+    // init on an initialized scope keeps everything
+    Plugin::init("plugin", Level::Notice, FacadeVariant::None, Options::default()).unwrap();
+    assert_eq!(hclog::test_log(Main, Level::Debug3), Ok(true));
+
+    Plugin::reinitialize("plugin", Level::Notice, FacadeVariant::None, Options::default())
+        .unwrap();
+    assert_eq!(hclog::test_log(Main, Level::Notice), Ok(true));
+    assert_eq!(hclog::test_log(Main, Level::Info), Ok(false));
+    assert_eq!(hclog::has_module(Loader), Ok(false));
+
+    Extra::init_with_defaults("plugin").unwrap();
+    assert_eq!(hclog::has_module(Loader), Ok(true));
+    Plugin::reinitialize_with_defaults("plugin").unwrap();
+    assert_eq!(hclog::test_log(Main, Level::Emerg), Ok(false));
+    assert_eq!(hclog::has_module(Loader), Ok(false));
+}
+
+#[test]
+fn failed_init_keeps_scope() {
+    App::init_with_defaults("app").unwrap();
+    hclog::set_level(App::Kept, Level::Debug2).unwrap();
+    assert_eq!(Failing::reinitialize_with_defaults("app"), Err(ErrorKind::ParseArg));
+    assert_eq!(hclog::test_log(App::Kept, Level::Debug2), Ok(true));
+}
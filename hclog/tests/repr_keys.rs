@@ -0,0 +1,35 @@
+/*
+ * LogKeys derived on a #[repr(u8)] enum are addressed by their discriminant
+ */
+use hclog::{options::NONE, FacadeVariant, Level, LogKey, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[repr(u8)]
+enum SmallKeys {
+    Rx,
+    Tx = 1,
+    Ctl,
+}
+use SmallKeys::*;
+
+#[test]
+fn log_and_set_level() {
+    SmallKeys::init("repr", Level::Info, FacadeVariant::RingBuffer(8), NONE).unwrap();
+    assert_eq!([Rx, Tx, Ctl].map(|k| k.log_key()), [0, 1, 2]);
+    assert_eq!(hclog::submodule_count::<SmallKeys>(), Ok(3));
+
+    hclog::set_level(Tx, Level::Debug2).unwrap();
+    assert!(hclog::tD2!(Tx));
+    assert!(!hclog::tD1!(Rx) && !hclog::tD1!(Ctl));
+    hclog::lD2!(Tx, "sent");
+    hclog::lD2!(Ctl, "filtered");
+    hclog::lI!(Ctl, "control");
+    assert_eq!(hclog::drain_ring(Tx).unwrap(), ["sent"]);
+    assert_eq!(hclog::drain_ring(Ctl).unwrap(), ["control"]);
+    assert_eq!(hclog::drain_ring(Rx).unwrap(), Vec::<String>::new());
+
+    hclog::set_mod_level(["ctl:debug1"]).unwrap();
+    assert!(hclog::tD1!(Ctl));
+    assert!(!hclog::tD2!(Ctl) && !hclog::tD1!(Rx));
+}
@@ -0,0 +1,65 @@
+/*
+ * flight recorder style logging into FacadeVariant::RingBuffer
+ */
+use hclog::{ErrorKind, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Debug10)]
+enum RingKeys {
+    #[hclog(facade = FacadeVariant::RingBuffer(4))]
+    Recorder,
+    #[hclog(facade = FacadeVariant::RingBuffer(1000))]
+    Shared,
+    Plain,
+}
+use RingKeys::*;
+
+#[test]
+fn drain_and_dump() {
+    RingKeys::init_with_defaults("ring").unwrap();
+    for i in 0..6 {
+        hclog::lD5!(Recorder, "step {}", i);
+    }
+    let lines = hclog::drain_ring(Recorder).unwrap();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].ends_with("step 2"), "{}", lines[0]);
+    assert!(lines[3].ends_with("step 5"), "{}", lines[3]);
+    assert_eq!(hclog::drain_ring(Recorder).unwrap(), Vec::<String>::new());
+
+    hclog::lE!(Recorder, "failed");
+    let mut buf = Vec::new();
+    assert_eq!(hclog::dump_ring_to(Recorder, &mut buf), Ok(1));
+    let dump = String::from_utf8(buf).unwrap();
+    assert!(dump.ends_with("failed\n"), "{}", dump);
+    assert_eq!(hclog::dump_ring_to(Recorder, &mut Vec::new()), Ok(0));
+
+    assert_eq!(hclog::drain_ring(Plain), Err(ErrorKind::NoRingBuffer));
+}
+
+// in single-thread mode every thread has its own context
+#[test]
+#[cfg(not(feature = "single-thread"))]
+fn concurrent_writers() {
+    use std::{collections::HashSet, thread};
+
+    RingKeys::init_with_defaults("ring").unwrap();
+    let writers = (0..8).map(|t| thread::spawn(move || {
+        for i in 0..100 {
+            hclog::lI!(Shared, "{}-{}", t, i);
+        }
+    })).collect::<Vec<_>>();
+    writers.into_iter().for_each(|w| w.join().unwrap());
+
+    let lines = hclog::drain_ring(Shared).unwrap();
+    assert_eq!(lines.len(), 800);
+    assert_eq!(lines.iter().collect::<HashSet<_>>().len(), 800);
+    // the lines of every writer keep their order
+    for t in 0..8 {
+        let own = lines.iter().filter_map(|l| l.rsplit(' ').next()?.split_once('-'))
+            .filter(|(writer, _)| *writer == t.to_string())
+            .map(|(_, i)| i.parse::<usize>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(own, (0..100).collect::<Vec<_>>());
+    }
+}
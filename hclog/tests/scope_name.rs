@@ -0,0 +1,22 @@
+/*
+ * names of Scopes which can't be used for the output or the syslog ident
+ */
+use hclog::{options::SYSLOG_IDENT, ErrorKind, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum NameKeys {
+    Name,
+}
+use NameKeys::*;
+
+#[test]
+fn invalid_scope_names() {
+    for invalid in ["", "  ", "sys\0log", "\0"] {
+        let res = NameKeys::init(invalid, Level::Info, FacadeVariant::Syslog("user".into()),
+                                 SYSLOG_IDENT);
+        assert_eq!(res, Err(ErrorKind::ParseArg), "{:?}", invalid);
+    }
+    NameKeys::init(" names ", Level::Info, FacadeVariant::None, SYSLOG_IDENT).unwrap();
+    hclog::lI!(Name, "initialized");
+}
@@ -0,0 +1,37 @@
+/*
+ * keys verified against the context by verify_keys and the selftest of the derive macro
+ *
+ * The derive adds the test hclog_keys_consistent for CheckedKeys to this binary, the other
+ * test uses a separate Scope to not interfere with it.
+ */
+use hclog::{options::NONE, ErrorKind, FacadeVariant, KeyMismatchKind, Level, Scope, ScopeKey};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(selftest, init_only)]
+#[allow(dead_code)]
+enum CheckedKeys {
+    Disk,
+    Net,
+    #[hclog(ignore)]
+    Unused,
+}
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(scope = ScopeKey::Lib, init_only)]
+enum LibKeys {
+    Store,
+    Cache,
+}
+
+#[test]
+fn mismatches() {
+    let kinds = |keys: &[LibKeys]| hclog::verify_keys(keys)
+        .map(|found| found.iter().map(|m| (m.key().to_owned(), m.kind())).collect::<Vec<_>>());
+    assert_eq!(kinds(&[LibKeys::Store]), Err(ErrorKind::ScopeNotInitialized));
+    LibKeys::init("selftest", Level::Info, FacadeVariant::None, NONE).unwrap();
+    hclog::add_submodules([LibKeys::Store]).unwrap();
+    assert_eq!(kinds(&[LibKeys::Store]), Ok(vec![]));
+    assert_eq!(kinds(&[LibKeys::Store, LibKeys::Cache]),
+        Ok(vec![("Cache".to_owned(), KeyMismatchKind::MissingSlot)]));
+}
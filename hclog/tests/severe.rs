@@ -0,0 +1,56 @@
+/*
+ * handlers registered via on_severe
+ *
+ * The binary contains a single test because the handlers are process wide.
+ */
+use hclog::{Level, FacadeVariant, Scope};
+use hclog_macros::HCLog;
+use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info, default_facade = FacadeVariant::StdOut)]
+enum SevereKeys {
+    Db,
+    Net,
+    Audit,
+}
+use SevereKeys::*;
+
+#[test]
+fn counting_handlers() {
+    SevereKeys::init_with_defaults("severe").unwrap();
+    hclog::set_level(Net, Level::Off).unwrap();
+    let crit = Arc::new(AtomicUsize::new(0));
+    let error = Arc::new(AtomicUsize::new(0));
+    let count = |n: &Arc<AtomicUsize>| {
+        let n = Arc::clone(n);
+        Box::new(move |_: &str, _: Level, _: &str| { n.fetch_add(1, Ordering::Relaxed); })
+    };
+    let crit_id = hclog::on_severe(Level::Crit, count(&crit)).unwrap();
+    let error_id = hclog::on_severe(Level::Error, count(&error)).unwrap();
+
+    hclog::lEM!(Db, "emerg");
+    hclog::lC!(Db, "crit");
+    hclog::lE!(Db, "error");
+    hclog::lW!(Db, "warn");
+    hclog::lD1!(Db, "filtered by the level of the key");
+    // disabled keys don't report anything
+    hclog::lEM!(Net, "off");
+    assert_eq!((crit.load(Ordering::Relaxed), error.load(Ordering::Relaxed)), (2, 3));
+
+    assert_eq!(hclog::remove_on_severe(crit_id), Ok(true));
+    assert_eq!(hclog::remove_on_severe(crit_id), Ok(false));
+    hclog::lC!(Db, "crit");
+    assert_eq!((crit.load(Ordering::Relaxed), error.load(Ordering::Relaxed)), (2, 4));
+    assert_eq!(hclog::remove_on_severe(error_id), Ok(true));
+
+    // a handler logging itself doesn't deadlock
+    let lines = hclog::subscribe(Audit, 4).unwrap();
+    let id = hclog::on_severe(Level::Crit, Box::new(|module, level, msg| {
+        hclog::lN!(Audit, "{} from {}: {}", level, module, msg);
+        assert_eq!(hclog::set_level(Audit, Level::Debug1), Err(hclog::ErrorKind::ContextLock));
+    })).unwrap();
+    hclog::lA!(Db, "escalated");
+    assert_eq!(lines.try_iter().collect::<Vec<_>>().len(), 1);
+    hclog::remove_on_severe(id).unwrap();
+}
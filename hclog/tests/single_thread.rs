@@ -0,0 +1,70 @@
+/*
+ * core tests for the `single-thread` feature
+ *
+ * run with: cargo test -p hclog --features single-thread
+ *
+ * Each test runs in its own thread and therefore on its own context. There is no need to
+ * serialize them like the unit tests of the default (RwLock based) build.
+ */
+use hclog::{Level, FacadeVariant, FlushPolicy, Scope, ErrorKind};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info, default_facade = FacadeVariant::StdOut)]
+enum SingleKeys {
+    #[hclog(name = "stfoo")]
+    StFoo,
+    #[hclog(name = "stbar", level = Level::Warn)]
+    StBar,
+}
+use SingleKeys::*;
+
+#[test]
+fn init_and_log() {
+    SingleKeys::init_with_defaults("single").unwrap();
+    assert_eq!(hclog::has_module(StFoo), Ok(true));
+    assert!(hclog::tI!(StFoo));
+    assert!(!hclog::tI!(StBar));
+    hclog::lI!(StFoo, "logging in single-thread mode");
+    hclog::lW!(StBar, "logging in single-thread mode");
+}
+
+#[test]
+fn set_level_and_mod_level() {
+    SingleKeys::init_with_defaults("single").unwrap();
+    hclog::set_level(StBar, Level::Debug1).unwrap();
+    assert!(hclog::tD1!(StBar));
+    hclog::set_mod_level(["_all:error,stfoo:debug5"]).unwrap();
+    assert!(hclog::tD5!(StFoo));
+    assert!(!hclog::tW!(StBar));
+}
+
+#[test]
+fn context_is_per_thread() {
+    SingleKeys::init_with_defaults("single").unwrap();
+    let other = std::thread::spawn(|| hclog::has_module(StFoo)).join().unwrap();
+    assert_eq!(other, Err(ErrorKind::ScopeNotInitialized));
+}
+
+#[test]
+fn context_dropped_on_thread_exit() {
+    let path = std::env::temp_dir()
+        .join(format!("hclog_single_thread_exit_{}.log", std::process::id()));
+    let logged = path.clone();
+    std::thread::spawn(move || {
+        SingleKeys::init_with_defaults("single").unwrap();
+        hclog::set_logdest(StFoo, FacadeVariant::File(logged, true)).unwrap();
+        hclog::set_flush_policy(StFoo, FlushPolicy::EveryN(100)).unwrap();
+        hclog::lI!(StFoo, "buffered until the thread exits");
+    }).join().unwrap();
+    let content = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(content.ends_with("buffered until the thread exits\n"), "{}", content);
+}
+
+#[test]
+fn spawn_scoped_without_context() {
+    SingleKeys::init_with_defaults("single").unwrap();
+    let child = hclog::spawn_scoped("single-child", || hclog::has_module(StFoo)).unwrap();
+    assert_eq!(child.join().unwrap(), Err(ErrorKind::ScopeNotInitialized));
+}
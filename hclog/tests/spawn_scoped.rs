@@ -0,0 +1,55 @@
+/*
+ * threads spawned from a task scope via spawn_scoped and current_scope_snapshot
+ */
+// in single-thread mode the context can't be moved to another thread
+#![cfg(not(feature = "single-thread"))]
+
+mod common;
+
+use hclog::{options::SCOPE, FacadeVariant, Level, Scope, ScopeKey};
+use hclog_macros::HCLog;
+use common::block_on;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum SpawnKeys {
+    Io,
+}
+use SpawnKeys::*;
+
+#[test]
+fn spawn_scoped() {
+    SpawnKeys::init("spawn", Level::Info, FacadeVariant::RingBuffer(8), SCOPE).unwrap();
+
+    let task = hclog::async_scope_for("worker", ScopeKey::Application, async {
+        hclog::spawn_scoped("worker-io", || {
+            hclog::lI!(Io, "from the child");
+            (hclog::current_scope_ident(), std::thread::current().name().map(str::to_owned))
+        }).unwrap().join().unwrap()
+    }).unwrap();
+    let (ident, name) = block_on(task);
+    assert_eq!(ident.as_deref(), Some("worker"));
+    assert_eq!(name.as_deref(), Some("worker-io"));
+    assert_eq!(hclog::drain_ring(Io).unwrap(), ["task[worker] from the child"]);
+
+    // the lower level pair, the context is restored after the closure
+    let task = hclog::async_scope_for("worker", ScopeKey::Application, async {
+        hclog::current_scope_snapshot().unwrap()
+    }).unwrap();
+    let snapshot = block_on(task);
+    assert!(!hclog::in_task_scope());
+    std::thread::spawn(move || {
+        snapshot.install_for(|| hclog::lI!(Io, "installed"));
+        assert!(!hclog::in_task_scope());
+        hclog::lI!(Io, "restored");
+    }).join().unwrap();
+    assert_eq!(hclog::drain_ring(Io).unwrap(), ["task[worker] installed", "global restored"]);
+
+    // outside of a task scope it's a plain spawn
+    assert!(hclog::current_scope_snapshot().is_none());
+    let ident = hclog::spawn_scoped("plain", || {
+        hclog::lI!(Io, "plain");
+        (hclog::in_task_scope(), hclog::current_scope_ident())
+    }).unwrap().join().unwrap();
+    assert_eq!(ident, (false, None));
+    assert_eq!(hclog::drain_ring(Io).unwrap(), ["global plain"]);
+}
@@ -0,0 +1,64 @@
+/*
+ * in-process subscriptions to the lines of a LogKey
+ *
+ * run with: cargo test -p hclog --features test-util
+ */
+use hclog::{Level, FacadeVariant, Scope, testing::MockFacade};
+use hclog_macros::HCLog;
+use std::time::Duration;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info)]
+enum SubKeys {
+    Shared,
+    Lagging,
+    Dropped,
+}
+use SubKeys::*;
+
+#[test]
+fn subscribers_receive_same_lines() {
+    SubKeys::init_with_defaults("subscribe").unwrap();
+    let first = hclog::subscribe(Shared, 16).unwrap();
+    let second = hclog::subscribe(Shared, 16).unwrap();
+
+    hclog::lI!(Shared, "line {}", 1);
+    hclog::lW!(Shared, "line {}", 2);
+    // filtered by the level of the key
+    hclog::lD1!(Shared, "not received");
+
+    let lines = first.try_iter().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("line 1") && lines[1].ends_with("line 2"), "{:?}", lines);
+    assert_eq!(second.recv_timeout(Duration::from_secs(1)).as_ref(), Some(&lines[0]));
+    assert_eq!(second.try_iter().collect::<Vec<_>>(), &lines[1..]);
+    assert_eq!(first.recv_timeout(Duration::from_millis(10)), None);
+}
+
+#[test]
+fn lagging_subscriber_drops_oldest() {
+    let mock = MockFacade::new();
+    SubKeys::init_with_defaults("subscribe").unwrap();
+    hclog::set_logdest(Lagging, FacadeVariant::Mock(mock.clone())).unwrap();
+    let slow = hclog::subscribe(Lagging, 2).unwrap();
+
+    for i in 0..5 {
+        hclog::lI!(Lagging, "msg {}", i);
+    }
+    let lines = slow.try_iter().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("msg 3") && lines[1].ends_with("msg 4"), "{:?}", lines);
+    assert_eq!(mock.calls().len(), 5);
+}
+
+#[test]
+fn drop_unsubscribes() {
+    SubKeys::init_with_defaults("subscribe").unwrap();
+    hclog::set_logdest(Dropped, FacadeVariant::None).unwrap();
+    let sub = hclog::subscribe(Dropped, 4).unwrap();
+    // lines reach the subscription even without a facade
+    hclog::lI!(Dropped, "to the subscription only");
+    assert_eq!(sub.try_iter().count(), 1);
+    drop(sub);
+    hclog::lI!(Dropped, "to nobody");
+}
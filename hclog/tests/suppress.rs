@@ -0,0 +1,63 @@
+/*
+ * log-free zones via suppress_guard
+ */
+use hclog::{Level, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(default_level = Level::Info)]
+enum QuietKeys {
+    Nested,
+    Threads,
+}
+use QuietKeys::*;
+
+#[test]
+fn nested_guards() {
+    QuietKeys::init_with_defaults("suppress").unwrap();
+    let lines = hclog::subscribe(Nested, 8).unwrap();
+    assert!(!hclog::is_suppressed());
+    {
+        let _outer = hclog::suppress_guard();
+        hclog::lE!(Nested, "outer");
+        {
+            let _inner = hclog::suppress_guard();
+            hclog::lE!(Nested, "inner");
+        }
+        // still suppressed by the outer guard
+        assert!(hclog::is_suppressed());
+        assert!(!hclog::tE!(Nested));
+        assert_eq!(hclog::log(Nested, Level::Error, file!(), None, line!(),
+            &format_args!("direct")), Ok(()));
+    }
+    assert!(!hclog::is_suppressed());
+    hclog::lE!(Nested, "restored");
+    let lines = lines.try_iter().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].ends_with("restored"), "{}", lines[0]);
+}
+
+// in single-thread mode every thread has its own context
+#[test]
+#[cfg(not(feature = "single-thread"))]
+fn per_thread() {
+    use std::{sync::mpsc, thread};
+
+    QuietKeys::init_with_defaults("suppress").unwrap();
+    let lines = hclog::subscribe(Threads, 8).unwrap();
+    let (tx, rx) = mpsc::channel();
+    let guard = hclog::suppress_guard();
+    let other = thread::spawn(move || {
+        let suppressed = hclog::is_suppressed();
+        hclog::lI!(Threads, "from the other thread");
+        tx.send(suppressed).unwrap();
+    });
+    assert_eq!(rx.recv(), Ok(false));
+    other.join().unwrap();
+    hclog::lI!(Threads, "suppressed");
+    drop(guard);
+
+    let lines = lines.try_iter().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].ends_with("from the other thread"), "{}", lines[0]);
+}
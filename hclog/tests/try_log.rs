@@ -0,0 +1,79 @@
+/*
+ * try_log skips the message instead of waiting for the context locked by another thread
+ *
+ * The context is held by initializing another Scope with a name whose Display impl blocks
+ * until it's released. In single-thread mode every thread has its own context, so there is
+ * nothing to contend for.
+ */
+#![cfg(not(feature = "single-thread"))]
+use hclog::{options::NONE, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+use std::{
+    fmt,
+    sync::{atomic::{AtomicBool, Ordering}, Arc, Barrier},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(HCLog, Copy, Clone, Debug)]
+enum RtKeys {
+    Audio,
+}
+use RtKeys::*;
+
+#[derive(HCLog, Copy, Clone, Debug)]
+#[hclog(scope = hclog::ScopeKey::Lib)]
+enum LateKeys {
+    Late,
+}
+
+// blocks the first formatting until released, the context is write locked meanwhile
+struct Holder {
+    entered: Barrier,
+    release: Barrier,
+    once: AtomicBool,
+}
+impl fmt::Display for Holder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.once.swap(true, Ordering::SeqCst) {
+            self.entered.wait();
+            self.release.wait();
+        }
+        f.write_str("late")
+    }
+}
+
+#[test]
+fn contended_context() {
+    let path = std::env::temp_dir().join(format!("hclog_try_log_{}.log", std::process::id()));
+    RtKeys::init("try", Level::Info, FacadeVariant::File(path.clone(), true), NONE).unwrap();
+    assert_eq!(hclog::try_log(Audio, Level::Info, format_args!("free")), Ok(true));
+    // filtered messages are not skipped
+    assert_eq!(hclog::try_log(Audio, Level::Debug1, format_args!("filtered")), Ok(true));
+
+    let holder = Arc::new(Holder {
+        entered: Barrier::new(2),
+        release: Barrier::new(2),
+        once: AtomicBool::new(false),
+    });
+    let other = {
+        let holder = Arc::clone(&holder);
+        thread::spawn(move || {
+            hclog::init::<LateKeys, _>(&*holder, Level::Info, FacadeVariant::None, NONE).unwrap();
+        })
+    };
+    holder.entered.wait();
+    let start = Instant::now();
+    assert_eq!(hclog::try_log(Audio, Level::Info, format_args!("contended")), Ok(false));
+    hclog::lI_try!(Audio, "contended {}", "macro");
+    assert!(start.elapsed() < Duration::from_secs(1), "{:?}", start.elapsed());
+    holder.release.wait();
+    other.join().unwrap();
+    assert!(hclog::health_summary().ends_with("dropped=2"), "{}", hclog::health_summary());
+
+    hclog::lI_try!(Audio, "released");
+    hclog::flush().unwrap();
+    let lines = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(lines.lines().collect::<Vec<_>>(), ["free", "released"]);
+    std::fs::remove_file(path).unwrap();
+}
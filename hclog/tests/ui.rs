@@ -0,0 +1,54 @@
+#[test]
+fn key_scope_check() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/key_scope_pass.rs");
+    t.compile_fail("tests/ui/key_scope_foreign_key.rs");
+}
+
+#[test]
+fn derive_hygiene() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_two_in_module.rs");
+    t.pass("tests/ui/derive_user_scope_type.rs");
+    t.pass("tests/ui/derive_deny_unused_imports.rs");
+    t.pass("tests/ui/derive_absolute_paths.rs");
+}
+
+#[test]
+fn macro_hygiene() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/macros_absolute_paths.rs");
+}
+
+#[test]
+fn derive_init_submodules() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/derive_init_submodules_errors.rs");
+}
+
+#[test]
+fn derive_log_compat() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/derive_log_compat_errors.rs");
+}
+
+#[test]
+fn derive_names() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_distinct_names.rs");
+    t.compile_fail("tests/ui/derive_name_errors.rs");
+}
+
+#[test]
+fn derive_repr() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_repr.rs");
+    t.compile_fail("tests/ui/derive_repr_errors.rs");
+}
+
+#[test]
+fn define_logging() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/define_logging_pass.rs");
+    t.compile_fail("tests/ui/define_logging_errors.rs");
+}
@@ -0,0 +1,58 @@
+mod duplicate_key {
+    hclog_macros::define_logging! {
+        name: "app",
+        keys: { NET: {}, DB: {}, NET: {} },
+    }
+}
+
+mod duplicate_name {
+    hclog_macros::define_logging! {
+        name: "app",
+        keys: { NET: { name: "db" }, DB: {} },
+    }
+}
+
+mod duplicate_field {
+    hclog_macros::define_logging! {
+        name: "app",
+        level: hclog::Level::Info,
+        keys: { NET: { level: hclog::Level::Debug1, level: hclog::Level::Debug2 } },
+    }
+}
+
+mod nested_keys {
+    hclog_macros::define_logging! {
+        name: "app",
+        keys: { NET: { keys: { TCP: {} } } },
+    }
+}
+
+mod key_without_braces {
+    hclog_macros::define_logging! {
+        name: "app",
+        keys: { NET: hclog::Level::Info },
+    }
+}
+
+mod unknown_field {
+    hclog_macros::define_logging! {
+        name: "app",
+        scope: hclog::ScopeKey::Lib,
+        keys: { NET: {} },
+    }
+}
+
+mod missing_name {
+    hclog_macros::define_logging! {
+        keys: { NET: {} },
+    }
+}
+
+mod no_keys {
+    hclog_macros::define_logging! {
+        name: "app",
+        keys: {},
+    }
+}
+
+fn main() {}
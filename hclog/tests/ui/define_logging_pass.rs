@@ -0,0 +1,19 @@
+// define_logging! with a custom enum name inside a module, without any imports
+mod logging {
+    hclog_macros::define_logging! {
+        enum: AppKeys,
+        keys: {
+            Main: {},
+            Worker: { name: "worker", level: hclog::Level::Debug1 },
+        },
+        name: "app",
+    }
+}
+
+use logging::Worker;
+
+fn main() {
+    logging::init_logging().unwrap();
+    hclog::lD1!(Worker, "visible");
+    let _: logging::AppKeys = logging::Main;
+}
@@ -0,0 +1,29 @@
+// the derive only uses absolute paths, a local module or types named like the ones of hclog
+// don't change what the generated code refers to
+mod app {
+    use hclog_macros::HCLog;
+
+    #[allow(dead_code)]
+    mod hclog {}
+    #[allow(dead_code)]
+    struct Level;
+    #[allow(dead_code)]
+    struct FacadeVariant;
+
+    #[derive(HCLog, Copy, Clone)]
+    #[hclog(default_level = ::hclog::Level::Info, merge = "skip")]
+    pub enum Keys {
+        #[hclog(level = ::hclog::Level::Warn, description = "the main key")]
+        Main,
+        #[hclog(ignore, level = ::hclog::Level::Debug1, facade = ::hclog::FacadeVariant::StdOut)]
+        Ignored,
+    }
+}
+
+fn main() {
+    use app::Keys::{Main, Ignored};
+    use hclog::Scope;
+    app::Keys::init_with_defaults("app").unwrap();
+    hclog::lW!(Main, "logged");
+    assert_eq!(hclog::has_module(Ignored), Ok(false));
+}
@@ -0,0 +1,11 @@
+// the derive doesn't add imports which could be unused
+#![deny(unused_imports, unused_qualifications)]
+
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone)]
+enum Keys { Main }
+
+fn main() {
+    let _ = Keys::Main;
+}
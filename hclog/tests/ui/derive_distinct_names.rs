@@ -0,0 +1,31 @@
+use hclog_macros::HCLog;
+
+// the names only clash without the prefix or if the variant isn't added to the scope
+#[derive(HCLog, Copy, Clone)]
+#[hclog(prefix = "lib_")]
+enum LibKeys {
+    #[hclog(name = "db")]
+    Primary,
+    #[hclog(name = "db_replica")]
+    Replica,
+    #[hclog(name = "db", ignore)]
+    Legacy,
+}
+
+#[derive(HCLog, Copy, Clone)]
+enum AppKeys {
+    #[hclog(name = "db")]
+    Primary,
+    #[hclog(name = "lib_db.v2")]
+    Other,
+    // names are compared ASCII case insensitive like in hclog
+    #[hclog(name = "café")]
+    Cafe,
+    #[hclog(name = "CAFÉ")]
+    CafeUpper,
+}
+
+fn main() {
+    let _ = (LibKeys::Primary, LibKeys::Replica, LibKeys::Legacy, AppKeys::Primary, AppKeys::Other);
+    let _ = (AppKeys::Cafe, AppKeys::CafeUpper);
+}
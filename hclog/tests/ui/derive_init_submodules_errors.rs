@@ -0,0 +1,11 @@
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone)]
+#[hclog(merge = "append", init_submodules = false)]
+enum AppendKeys { Main }
+
+#[derive(HCLog, Copy, Clone)]
+#[hclog(init_only, init_submodules = true)]
+enum TwiceKeys { Main }
+
+fn main() {}
@@ -0,0 +1,11 @@
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone)]
+#[hclog(with_log, no_log_compat_inherit)]
+enum BothKeys { Main }
+
+#[derive(HCLog, Copy, Clone)]
+#[hclog(no_log_compat_inherit, no_log_compat_inherit)]
+enum TwiceKeys { Main }
+
+fn main() {}
@@ -0,0 +1,48 @@
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone)]
+enum DuplicateKeys {
+    #[hclog(name = "db")]
+    Primary,
+    #[hclog(name = "DB")]
+    Replica,
+}
+
+#[derive(HCLog, Copy, Clone)]
+enum RenamedKeys {
+    Cache,
+    #[hclog(name = "Cache")]
+    Store,
+}
+
+#[derive(HCLog, Copy, Clone)]
+enum ColonKeys {
+    #[hclog(name = "db:main")]
+    Main,
+}
+
+#[derive(HCLog, Copy, Clone)]
+enum SlashKeys {
+    #[hclog(name = "lib/db")]
+    Main,
+}
+
+#[derive(HCLog, Copy, Clone)]
+enum SpaceKeys {
+    #[hclog(name = "db main")]
+    Main,
+}
+
+#[derive(HCLog, Copy, Clone)]
+#[hclog(prefix = "lib,")]
+enum PrefixKeys {
+    Main,
+}
+
+#[derive(HCLog, Copy, Clone)]
+enum EmptyKeys {
+    #[hclog(name = "")]
+    Main,
+}
+
+fn main() {}
@@ -0,0 +1,45 @@
+use hclog::LogKey;
+use hclog_macros::HCLog;
+
+// log_key() is the discriminant for every accepted representation
+#[derive(HCLog, Copy, Clone)]
+#[repr(u8)]
+enum U8Keys { A, B = 1, C }
+
+#[derive(HCLog, Copy, Clone)]
+#[repr(u16)]
+enum U16Keys { A, B }
+
+#[derive(HCLog, Copy, Clone)]
+#[repr(u32)]
+enum U32Keys { A, B }
+
+#[derive(HCLog, Copy, Clone)]
+#[repr(usize)]
+enum UsizeKeys { A, B }
+
+#[derive(HCLog, Copy, Clone)]
+#[repr(C)]
+enum CKeys { A, B }
+
+const ONE: u8 = 1;
+
+#[derive(HCLog, Copy, Clone)]
+#[repr(u8)]
+enum ExprKeys { A = ONE - 1, B = ONE }
+
+#[derive(HCLog, Copy, Clone)]
+#[hclog(merge = "append")]
+#[repr(u32)]
+enum AppendKeys { A, B }
+
+fn main() {
+    assert_eq!([U8Keys::A, U8Keys::B, U8Keys::C].map(|k| k.log_key()), [0, 1, 2]);
+    assert_eq!([U16Keys::A, U16Keys::B].map(|k| k.log_key()), [0, 1]);
+    assert_eq!([U32Keys::A, U32Keys::B].map(|k| k.log_key()), [0, 1]);
+    assert_eq!([UsizeKeys::A, UsizeKeys::B].map(|k| k.log_key()), [0, 1]);
+    assert_eq!([CKeys::A, CKeys::B].map(|k| k.log_key()), [0, 1]);
+    assert_eq!([ExprKeys::A, ExprKeys::B].map(|k| k.log_key()), [0, 1]);
+    // no offset without init
+    assert_eq!([AppendKeys::A, AppendKeys::B].map(|k| k.log_key()), [0, 1]);
+}
@@ -0,0 +1,18 @@
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone)]
+#[repr(i32)]
+enum SignedKeys { A, B }
+
+#[derive(HCLog, Copy, Clone)]
+#[repr(u64)]
+enum WideKeys { A, B }
+
+const TWO: u8 = 2;
+
+// only literal discriminants are checked by the derive itself
+#[derive(HCLog, Copy, Clone)]
+#[repr(u8)]
+enum GapKeys { A, B = TWO }
+
+fn main() {}
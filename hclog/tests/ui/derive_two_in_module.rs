@@ -0,0 +1,17 @@
+// two derives in one module must not import the same names twice
+use hclog::Scope;
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone)]
+#[hclog(scope = hclog::ScopeKey::Application)]
+enum AppKeys { Main }
+
+#[derive(HCLog, Copy, Clone)]
+#[hclog(scope = hclog::ScopeKey::Lib, merge = "append")]
+enum LibKeys { Db }
+
+fn main() {
+    AppKeys::init_with_defaults("app").unwrap();
+    LibKeys::init_with_defaults("lib").unwrap();
+    let _ = (AppKeys::Main, LibKeys::Db);
+}
@@ -0,0 +1,17 @@
+// user defined types named like the hclog traits don't clash with the derive
+use hclog_macros::HCLog;
+
+#[allow(dead_code)]
+struct Scope;
+#[allow(dead_code)]
+struct LogKey;
+
+#[derive(HCLog, Copy, Clone)]
+enum Keys { Main }
+
+fn main() {
+    <Keys as hclog::Scope>::init_with_defaults("app").unwrap();
+    hclog::lI!(Main, "logged");
+}
+
+use Keys::Main;
@@ -0,0 +1,16 @@
+use hclog_macros::HCLog;
+
+mod db {
+    #[derive(hclog_macros::HCLog, Copy, Clone)]
+    pub enum DbKeys { Db }
+}
+
+#[derive(HCLog, Copy, Clone)]
+enum AppKeys { Net }
+
+use db::DbKeys::*;
+
+fn main() {
+    hclog::lI!(Scope = AppKeys, Db, "logged via the key of the wrong crate");
+    let _ = AppKeys::Net;
+}
@@ -0,0 +1,17 @@
+use hclog::{Level, FacadeVariant, Scope, options::Options};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone)]
+enum AppKeys { Db }
+
+use AppKeys::*;
+
+fn main() {
+    AppKeys::init("app", Level::Info, FacadeVariant::None, Options::default()).unwrap();
+    hclog::hclog!(Level::Info, Scope = AppKeys, Db, "plain {}", 1);
+    hclog::lI!(Scope = AppKeys, Db, "checked {}", 2);
+    hclog::lD5!(Scope = AppKeys, Db, "checked");
+    hclog::lI!(Db, "unchecked");
+    assert!(hclog::tI!(Scope = AppKeys, Db));
+    assert!(!hclog::tD1!(Scope = AppKeys, Db));
+}
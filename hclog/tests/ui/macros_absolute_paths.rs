@@ -0,0 +1,35 @@
+// the log macros only use absolute paths, local items named like the ones of the prelude or
+// std don't change what the expanded code refers to
+use hclog::{options::Options, FacadeVariant, Level, Scope};
+use hclog_macros::HCLog;
+
+#[derive(HCLog, Copy, Clone)]
+enum Keys {
+    Main,
+}
+
+#[allow(dead_code)]
+enum Shadow {
+    Some,
+    Ok,
+    Err,
+}
+#[allow(unused_imports)]
+use Shadow::{Err, Ok, Some};
+#[allow(dead_code)]
+mod std {}
+
+fn main() {
+    use Keys::Main;
+    Keys::init("app", Level::Info, FacadeVariant::None, Options::default()).unwrap();
+    hclog::set_default_key(Main).unwrap();
+    hclog::lI!(Main, "{}", 1);
+    hclog::lI!(Main, "lazy {}", lazy = || 2);
+    hclog::dI!("default");
+    hclog::batch!(Main, Level::Info, { "first"; "second {}", 2 });
+    hclog::lI_try!(Main, "try");
+    let ret = hclog::lI_timed!(Main, "timed", { 3 });
+    let res: ::core::result::Result<u8, &str> = ::core::result::Result::Err("failed");
+    let _ = hclog::error_context!(Main, res, "context {}", ret);
+    assert!(hclog::tI!(Main));
+}
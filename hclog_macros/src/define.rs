@@ -0,0 +1,263 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    braced, custom_keyword,
+    DeriveInput, Error, Expr, Ident, LitStr, Path, Token,
+};
+use std::collections::HashMap;
+
+use crate::{parse_derive_macro, CLOG_ATTR_IDENT};
+
+mod keywords {
+    use super::custom_keyword;
+    // private for the same reason as the keywords of the derive, see meta/mod.rs
+    custom_keyword!(name);
+    custom_keyword!(level);
+    custom_keyword!(facade);
+    custom_keyword!(keys);
+    custom_keyword!(description);
+}
+
+const DEFAULT_IDENT: &str = "HclogKeys";
+
+/**
+ * Input of define_logging!
+ *
+ * ```text
+ * [enum: Ident,] name: "app", [level: Path,] [facade: Expr,] keys: { KEY: { ... }, ... }
+ * ```
+ * The fields may be given in any order, but only once.
+ */
+pub struct Definition {
+    ident: Ident,
+    name: LitStr,
+    level: Option<Path>,
+    facade: Option<Expr>,
+    keys: Vec<KeyDefinition>,
+}
+
+/**
+ * a single LogKey: `KEY: { [name: "key",] [level: Path,] [facade: Expr,] [description: "..."] }`
+ */
+struct KeyDefinition {
+    ident: Ident,
+    name: Option<LitStr>,
+    level: Option<Path>,
+    facade: Option<Expr>,
+    description: Option<LitStr>,
+}
+
+// the second occurrence of a field is pointed at along with the first one
+fn duplicate_field(fst: Span, snd: Span, field: &str) -> Error {
+    let mut e = Error::new(snd, format!("field `{}` can't occur more than once", field));
+    e.combine(Error::new(fst, "first occurrence was here"));
+    e
+}
+
+fn set_once<T>(slot: &mut Option<(Span, T)>, span: Span, field: &str, value: T)
+    -> syn::Result<()>
+{
+    if let Some((fst, _)) = slot {
+        return Err(duplicate_field(*fst, span, field));
+    }
+    *slot = Some((span, value));
+    Ok(())
+}
+
+// fields are separated by commas, a trailing comma is allowed
+fn parse_separator(input: ParseStream) -> syn::Result<()> {
+    if !input.is_empty() {
+        input.parse::<Token![,]>()?;
+    }
+    Ok(())
+}
+
+impl Parse for Definition {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut ident = None;
+        let mut name = None;
+        let mut level = None;
+        let mut facade = None;
+        let mut keys = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(Token![enum]) {
+                let kw = input.parse::<Token![enum]>()?;
+                input.parse::<Token![:]>()?;
+                set_once(&mut ident, kw.span, "enum", input.parse::<Ident>()?)?;
+            } else if lh.peek(keywords::name) {
+                let kw = input.parse::<keywords::name>()?;
+                input.parse::<Token![:]>()?;
+                set_once(&mut name, kw.span, "name", input.parse::<LitStr>()?)?;
+            } else if lh.peek(keywords::level) {
+                let kw = input.parse::<keywords::level>()?;
+                input.parse::<Token![:]>()?;
+                set_once(&mut level, kw.span, "level", input.parse::<Path>()?)?;
+            } else if lh.peek(keywords::facade) {
+                let kw = input.parse::<keywords::facade>()?;
+                input.parse::<Token![:]>()?;
+                set_once(&mut facade, kw.span, "facade", input.parse::<Expr>()?)?;
+            } else if lh.peek(keywords::keys) {
+                let kw = input.parse::<keywords::keys>()?;
+                input.parse::<Token![:]>()?;
+                set_once(&mut keys, kw.span, "keys", parse_keys(input, kw.span)?)?;
+            } else {
+                return Err(lh.error());
+            }
+            parse_separator(input)?;
+        }
+
+        let Some((_, name)) = name else {
+            return Err(Error::new(Span::call_site(), "define_logging! requires a `name` field"));
+        };
+        if name.value().trim().is_empty() {
+            return Err(Error::new(name.span(), "Scope name must not be empty"));
+        }
+        let Some((_, keys)) = keys else {
+            return Err(Error::new(Span::call_site(), "define_logging! requires a `keys` field"));
+        };
+        Ok(Self {
+            ident: ident.map(|(_, i)| i)
+                .unwrap_or_else(|| Ident::new(DEFAULT_IDENT, Span::call_site())),
+            name,
+            level: level.map(|(_, l)| l),
+            facade: facade.map(|(_, f)| f),
+            keys,
+        })
+    }
+}
+
+fn parse_keys(input: ParseStream, kw: Span) -> syn::Result<Vec<KeyDefinition>> {
+    let content;
+    braced!(content in input);
+    let mut keys = Vec::<KeyDefinition>::new();
+    // the enum would fail on duplicate variants as well, but without pointing at the first one
+    let mut idents = HashMap::new();
+    while !content.is_empty() {
+        let key = content.parse::<KeyDefinition>()?;
+        if let Some(fst) = idents.insert(key.ident.to_string(), key.ident.span()) {
+            let mut e = Error::new(key.ident.span(),
+                format!("LogKey `{}` is defined more than once", key.ident));
+            e.combine(Error::new(fst, "first defined here"));
+            return Err(e);
+        }
+        keys.push(key);
+        parse_separator(&content)?;
+    }
+    if keys.is_empty() {
+        return Err(Error::new(kw, "`keys` must define at least one LogKey"));
+    }
+    Ok(keys)
+}
+
+impl Parse for KeyDefinition {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident = input.parse::<Ident>()?;
+        input.parse::<Token![:]>()?;
+        let content;
+        braced!(content in input);
+        let mut name = None;
+        let mut level = None;
+        let mut facade = None;
+        let mut description = None;
+
+        while !content.is_empty() {
+            let lh = content.lookahead1();
+            if lh.peek(keywords::name) {
+                let kw = content.parse::<keywords::name>()?;
+                content.parse::<Token![:]>()?;
+                set_once(&mut name, kw.span, "name", content.parse::<LitStr>()?)?;
+            } else if lh.peek(keywords::level) {
+                let kw = content.parse::<keywords::level>()?;
+                content.parse::<Token![:]>()?;
+                set_once(&mut level, kw.span, "level", content.parse::<Path>()?)?;
+            } else if lh.peek(keywords::facade) {
+                let kw = content.parse::<keywords::facade>()?;
+                content.parse::<Token![:]>()?;
+                set_once(&mut facade, kw.span, "facade", content.parse::<Expr>()?)?;
+            } else if lh.peek(keywords::description) {
+                let kw = content.parse::<keywords::description>()?;
+                content.parse::<Token![:]>()?;
+                set_once(&mut description, kw.span, "description", content.parse::<LitStr>()?)?;
+            } else if content.peek(keywords::keys) || content.peek(Token![enum]) {
+                return Err(Error::new(content.span(), "LogKeys can't be nested, `keys` and \
+                    `enum` are only allowed at the top level"));
+            } else {
+                return Err(lh.error());
+            }
+            parse_separator(&content)?;
+        }
+        Ok(Self {
+            ident,
+            name: name.map(|(_, n)| n),
+            level: level.map(|(_, l)| l),
+            facade: facade.map(|(_, f)| f),
+            description: description.map(|(_, d)| d),
+        })
+    }
+}
+
+/*
+ * the definition is turned into an enum with the attributes of the derive, which generates
+ * the trait impls. The attributes are removed from the emitted enum afterwards as there is no
+ * derive to accept them.
+ */
+pub fn expand(def: Definition) -> syn::Result<TokenStream> {
+    let Definition { ident, name, level, facade, keys } = def;
+    let mut scope_attrs = vec![quote! { scope = ::hclog::ScopeKey::Application }];
+    if let Some(level) = level {
+        scope_attrs.push(quote! { default_level = #level });
+    }
+    if let Some(facade) = facade {
+        scope_attrs.push(quote! { default_facade = #facade });
+    }
+    let variants = keys.iter().map(|key| {
+        let mut attrs = vec![];
+        if let Some(ref name) = key.name {
+            attrs.push(quote! { name = #name });
+        }
+        if let Some(ref level) = key.level {
+            attrs.push(quote! { level = #level });
+        }
+        if let Some(ref facade) = key.facade {
+            attrs.push(quote! { facade = #facade });
+        }
+        if let Some(ref description) = key.description {
+            attrs.push(quote! { description = #description });
+        }
+        let v_ident = &key.ident;
+        match attrs.is_empty() {
+            true => quote! { #v_ident },
+            false => quote! { #[hclog(#(#attrs),*)] #v_ident },
+        }
+    });
+    let mut ast = syn::parse2::<DeriveInput>(quote! {
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        #[allow(non_camel_case_types)]
+        #[hclog(#(#scope_attrs),*)]
+        pub enum #ident {
+            #(#variants),*
+        }
+    })?;
+    let impls = parse_derive_macro(&ast)?;
+
+    ast.attrs.retain(|a| !a.path().is_ident(CLOG_ATTR_IDENT));
+    if let syn::Data::Enum(ref mut data) = ast.data {
+        for variant in data.variants.iter_mut() {
+            variant.attrs.retain(|a| !a.path().is_ident(CLOG_ATTR_IDENT));
+        }
+    }
+    let item = ast.into_token_stream();
+    Ok(quote! {
+        #item
+        #impls
+        pub use self::#ident::*;
+
+        /// Initialize the logging of the application with the defaults of `define_logging!`
+        pub fn init_logging() -> ::hclog::Result<()> {
+            <#ident as ::hclog::Scope>::init_with_defaults(#name)
+        }
+    })
+}
@@ -1,20 +1,33 @@
 use quote::ToTokens;
 use proc_macro2::{TokenStream, Span};
 use syn::{
+    Attribute,
     DeriveInput,
     Error,
     Ident,
 };
 
-pub (crate) fn debug_print_generated(ast: &DeriveInput, toks: &TokenStream) {
+// print the generated code if enabled via HCLOG_MACRO_DEBUG, see format_generated
+pub (crate) fn debug_print_generated<'a, I>(ast: &DeriveInput, blocks: I)
+where
+    I: IntoIterator<Item = &'a (String, TokenStream)>,
+{
     if let Some(s) = option_env!("HCLOG_MACRO_DEBUG") {
-        if s == "1" {
-            println!("{}", toks);
-        } else if ast.ident == s {
-            println!("{}", toks);
+        if s == "1" || ast.ident == s {
+            print!("{}", format_generated(&ast.ident, blocks));
         }
     }
 }
+// every non-empty block of the generated code along with its source attribute(s)
+pub (crate) fn format_generated<'a, I>(ident: &Ident, blocks: I) -> String
+where
+    I: IntoIterator<Item = &'a (String, TokenStream)>,
+{
+    blocks.into_iter()
+        .filter(|(_, toks)| !toks.is_empty())
+        .map(|(source, toks)| format!("// {}: {}\n{}\n", ident, source, toks))
+        .collect()
+}
 
 pub (crate) fn assert_enum() -> syn::Error {
     Error::new(Span::call_site(), "This macro supports enums only")
@@ -28,6 +41,32 @@ pub (crate) fn assert_discriminant_value(i: &Ident, got: usize, expect: usize) -
         ))
 }
 
+/*
+ * log_key() casts the variant to a ContextKey (usize), refuse representations which could be
+ * negative or don't fit on every target. `C` and any non-integer hint (e.g. `align`) are fine.
+ */
+pub (crate) fn assert_repr(attrs: &[Attribute]) -> syn::Result<()> {
+    for attr in attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.input.peek(syn::token::Paren) {
+                let _args;
+                syn::parenthesized!(_args in meta.input);
+            }
+            let Some(ty) = meta.path.get_ident() else {
+                return Ok(());
+            };
+            match ty.to_string().as_str() {
+                "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u64" | "u128" => Err(
+                    Error::new(ty.span(), format!("#[repr({})] is not supported for LogKeys: \
+                        expected one of u8, u16, u32 or usize", ty))
+                ),
+                _ => Ok(()),
+            }
+        })?;
+    }
+    Ok(())
+}
+
 pub (crate) fn occurrence_error<T: ToTokens>(fst: T, snd: T, attr: &str, ty: &str) -> syn::Error {
     let mut e = Error::new_spanned(
         snd,
@@ -36,3 +75,19 @@ pub (crate) fn occurrence_error<T: ToTokens>(fst: T, snd: T, attr: &str, ty: &st
     e.combine(Error::new_spanned(fst, "first occurrence was here"));
     e
 }
+
+// the first variant using the name is pointed at as well
+pub (crate) fn duplicate_name_error(fst: Span, snd: Span, name: &str) -> syn::Error {
+    let mut e = Error::new(snd, format!("LogKey name '{}' is used by more than one variant", name));
+    e.combine(Error::new(fst, "first used here"));
+    e
+}
+// names are addressed via `scope/name:level` lists, e.g. by hclog::set_mod_level
+pub (crate) fn assert_name_chars(span: Span, what: &str, name: &str) -> syn::Result<()> {
+    match name.chars().find(|c| matches!(c, ':' | ',' | '/') || c.is_whitespace()) {
+        Some(c) => Err(Error::new(span, format!(
+            "{} '{}' contains {:?}: ':', ',', '/' and whitespace are not allowed", what, name, c
+        ))),
+        None => Ok(()),
+    }
+}
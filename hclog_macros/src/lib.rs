@@ -30,24 +30,75 @@
 //! Currently only enums with unit variants are supported. The `HCLog` derive macro will generate
 //! the necessary code to implement the `hclog` traits for the given enum.
 //!
+//! The generated code doesn't add any names to the module of the enum. To call the trait
+//! functions like `init_with_defaults` the trait has to be imported via `use hclog::Scope;`.
+//!
 //! The macro allows to define attributes on the enum and its variants to customize the behavior of
 //! the generated code. The following attributes are supported by the `#[hclog()]` attribute:
 //!
 //! * enum attributes:
-//!     * `scope`: the `Scope` the `LogKey`s belong to. It expects a value of type `ScopeKey`.
+//!     * `scope`: the `Scope` the `LogKey`s belong to. It expects a variant of `ScopeKey`.
+//!     * `default_scope`: an alias for `scope`.
+//!     * `scope_key`: the index of the `Scope` the `LogKey`s belong to. It expects a `usize`
+//!       value which is checked at compile time via `ScopeKey::from_index`. Only one of `scope`,
+//!       `default_scope` and `scope_key` can be used. `scope` and `default_scope` accept an
+//!       index as well.
 //!     * `default_level`: the default `Level` for all `LogKey`s. It expects a value of type `Level`.
+//!     * `max_level`: cap levels assigned by name (e.g. `hclog::set_mod_level` with `_all` or
+//!       `HCLOG_LEVEL`) at the given `Level`. Levels set via `hclog::set_level` are not capped.
 //!     * `default_facade`: the default `FacadeVariant` for all `LogKey`s. It expects a value of type `FacadeVariant`.
-//!     * `with_log`: initialize the `hclog` compatibility mode with crate `log`. It expects a boolean value.
+//!     * `with_log`: initialize the `hclog` compatibility mode with crate `log`. `init` always
+//!       adds the `LOGCOMPAT` option to the options passed in.
+//!     * `no_log_compat_inherit`: remove the `LOGCOMPAT` option from the default options, which
+//!       are used by `init_with_defaults`. This is the counterpart of `with_log` for `Scope`s
+//!       which handle the messages of crate `log` themselves. The options passed to `init` are
+//!       not changed. Can't be combined with `with_log`.
+//!     * `auto_options`: use `Options::auto_for` with the default facade as the default
+//!       options, which are used by `init_with_defaults`. A terminal gets short messages, any
+//!       other output all properties. Can be combined with `no_log_compat_inherit`.
+//!     * `prefix`: a string prepended to the `Display` name of every `LogKey`. This avoids name
+//!       clashes of `LogKey`s from different crates, e.g. in `hclog::set_mod_level`.
+//!     * `merge`: what happens if the `LogKey`s collide with the `LogKey`s of another type in the
+//!       same `Scope`. It expects one of the strings `"error"` (default), `"skip"` or `"append"`.
+//!       With `"append"` the `LogKey`s are placed behind the already initialized ones.
+//!     * `init_submodules`: whether `Scope::init` adds the `LogKey`s to the `Scope`. It expects
+//!       a boolean value and defaults to `true`. With `false` only the `Scope` is created and the
+//!       `LogKey`s have to be added via `hclog::add_submodules`, e.g. lazily. The derive
+//!       generates an associated `const ALL` with all `LogKey`s not marked as `ignore` for this.
+//!       Can't be combined with `merge = "append"`.
+//!     * `init_only`: a shortcut for `init_submodules = false`.
+//!     * `selftest`: generate a test `hclog_keys_consistent` which initializes the `Scope` and
+//!       checks via `hclog::verify_keys` that every `LogKey` not marked as `ignore` matches its
+//!       entry in the context. The enum has to be defined at module level.
 //!
 //! * variant attributes:
 //!     * `name`: the `Display` name of the `LogKey`. It expects a [`str`] value.
+//!
+//!       Names (and the `prefix`) must not contain `:`, `,`, `/` or whitespace as they separate
+//!       the scope, name and level of the entries of `HCLOG_LEVELS` and friends. Two `LogKey`s
+//!       not marked as `ignore` can't share a name, compared ASCII case insensitive and
+//!       including the `prefix`. An empty name is rejected.
 //!     * `level`: the `Level` of the `LogKey`. It expects a value of type `Level`.
 //!     * `facade`: the `FacadeVariant` of the `LogKey`. It expects a value of type `FacadeVariant`.
+//!     * `pin_level`: exempt the `LogKey` from level changes of all `LogKey`s, i.e. `_all` in
+//!       `hclog::set_mod_level` and `HCLOG_LEVEL`. Its level can still be changed by its name.
+//!       See `hclog::set_all_exempt`.
+//!     * `description`: a description of the `LogKey` listed by `hclog::export_metadata`. It
+//!       expects a [`str`] value.
 //!
 //! The enum attributes are used as a default for all variants if they don't define their own
 //! attributes. All attributes are optional and can be omitted if the default behavior is sufficient.
 //! If no attributes are given the defaults from the `hclog` crate are used.
 //!
+//! The index of a `LogKey` (`LogKey::log_key`) is its discriminant. Explicit discriminants
+//! must ascend from `0` without gaps. A `#[repr]` of `u8`, `u16`, `u32` or `usize` is
+//! accepted, signed and wider types are rejected.
+//!
+//! # Declarative definition
+//!
+//! Application binaries can define their LogKeys, the `Scope` and its initialization in one
+//! item with the [`define_logging!`] macro instead of deriving `HCLog` on an own enum.
+//!
 //! # Example
 //!
 //! ### Derive the `HCLog` trait
@@ -85,22 +136,57 @@
 //!     #[hclog(name = "AD", level = Level::Error, facade = FacadeVariant::File("log.txt".into(), false))]
 //!     AD,
 //! }
+//! ```
 //!
-use proc_macro;
-use proc_macro2::{
-    TokenStream,
-};
-use quote::quote;
+//! ### Select the `Scope`
+//!
+//! The `Scope` can be given as a `ScopeKey` variant or as its index. Both are checked at compile
+//! time.
+//!
+//! ```rust
+//! use hclog::Scope;
+//! use hclog_macros::HCLog;
+//!
+//! #[derive(HCLog, Copy, Clone, Debug)]
+//! #[hclog(default_scope = hclog::ScopeKey::Lib)]
+//! enum LibLog { LA }
+//!
+//! mod indexed {
+//!     # use hclog_macros::HCLog;
+//!     #[derive(HCLog, Copy, Clone, Debug)]
+//!     #[hclog(scope_key = 2usize)]
+//!     pub enum IndexedLog { IA }
+//! }
+//!
+//! assert_eq!(LibLog::logscope(), indexed::IndexedLog::logscope());
+//! ```
+//!
+//! ```rust,compile_fail
+//! use hclog::Scope;
+//! use hclog_macros::HCLog;
+//!
+//! #[derive(HCLog, Copy, Clone, Debug)]
+//! #[hclog(scope_key = 42)]
+//! enum InvalidLog { IL }
+//!
+//! assert_eq!(InvalidLog::logscope(), hclog::ScopeKey::Lib);
+//! ```
+use quote::{quote, quote_spanned, format_ident};
 use syn::{
-    DeriveInput, Data, Fields, Path,
+    DeriveInput, Data, Fields, Path, LitStr,
     parse_macro_input,
+    spanned::Spanned,
 };
 
+mod define;
 mod helper;
 mod meta;
 
-use crate::meta::DerivePropertiesExt;
-use crate::helper::{assert_variant, assert_discriminant_value};
+use crate::meta::{DerivePropertiesExt, enum_ty::{MergePolicy, ScopeAttr}};
+use crate::helper::{
+    assert_variant, assert_discriminant_value, assert_name_chars, assert_repr,
+    duplicate_name_error,
+};
 
 const CLOG_ATTR_IDENT: &str = "hclog";
 
@@ -112,7 +198,64 @@ pub fn hclog_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     parse_derive_macro(&input).unwrap_or_else(|err| err.into_compile_error()).into()
 }
 
-fn parse_derive_macro(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+/// Define the LogKeys of an application, their `Scope` and its initialization in one item
+///
+/// Expands to an enum deriving `HCLog` in the `Application` scope, a `pub use` of its variants
+/// and a `pub fn init_logging() -> hclog::Result<()>` calling `init_with_defaults`. The enum is
+/// named `HclogKeys` unless another name is given via `enum`. The `name` of the Scope and the
+/// `keys` are required, `level` and `facade` set the defaults of the Scope. Each key takes the
+/// optional fields `name`, `level`, `facade` and `description`, like the variant attributes of
+/// the derive.
+///
+/// This is meant for application binaries. Libraries should derive `HCLog` on their own enum
+/// in the `Lib` scope instead.
+///
+/// ```rust
+/// use hclog::{Level, FacadeVariant};
+///
+/// hclog_macros::define_logging! {
+///     name: "myapp",
+///     level: Level::Info,
+///     facade: FacadeVariant::StdOut,
+///     keys: {
+///         NET: { level: Level::Debug3 },
+///         DB: {},
+///         AUDIT: { name: "audit", facade: FacadeVariant::StdErr, description: "user actions" },
+///     }
+/// }
+///
+/// fn main() {
+///     init_logging().unwrap();
+///     hclog::lI!(NET, "connected");
+/// }
+/// ```
+#[proc_macro]
+pub fn define_logging(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let def = parse_macro_input!(input as define::Definition);
+    define::expand(def).unwrap_or_else(|err| err.into_compile_error()).into()
+}
+
+pub (crate) fn parse_derive_macro(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let (blocks, selftest) = generate_blocks(ast)?;
+    let selftest = ("selftest".to_owned(), selftest);
+    helper::debug_print_generated(ast, blocks.iter().chain([&selftest]));
+    /*
+     * Everything is wrapped into an anonymous const, so no name leaks into the module of the
+     * caller. All paths are absolute.
+     */
+    let blocks = blocks.iter().map(|(_, block)| block);
+    let selftest = &selftest.1;
+    Ok(quote! {
+        const _: () = {
+            #(#blocks)*
+        };
+        #selftest
+    })
+}
+
+// the generated blocks along with the attributes they stem from and the selftest
+type Blocks = Vec<(String, proc_macro2::TokenStream)>;
+fn generate_blocks(ast: &DeriveInput) -> syn::Result<(Blocks, proc_macro2::TokenStream)> {
     let ident = &ast.ident;
     let generics = &ast.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
@@ -122,6 +265,7 @@ fn parse_derive_macro(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream
         Data::Enum(v) => v,
         _ => return Err(helper::assert_enum()),
     };
+    assert_repr(&ast.attrs)?;
 
     let lmk_ident = syn::parse_str::<Path>("::hclog::ScopeKey")?;
     let lvl_ident = syn::parse_str::<Path>("::hclog::Level")?;
@@ -131,34 +275,85 @@ fn parse_derive_macro(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream
 
     let mut init_trait_fns = vec![];
 
-    if let Some(ref logmod) = attrs.scope {
-        init_trait_fns.push(quote! { fn logscope() -> #lmk_ident { #logmod } });
+    match attrs.scope {
+        Some(ScopeAttr::Variant(ref logmod)) => {
+            init_trait_fns.push(("scope", quote! { fn logscope() -> #lmk_ident { #logmod } }));
+        }
+        Some(ScopeAttr::Index(index)) => {
+            // evaluated at compile time - an unknown index fails the build
+            init_trait_fns.push(("scope_key", quote! {
+                fn logscope() -> #lmk_ident {
+                    const KEY: #lmk_ident = match #lmk_ident::from_index(#index) {
+                        Some(key) => key,
+                        None => panic!("scope_key is not a valid ScopeKey index"),
+                    };
+                    KEY
+                }
+            }));
+        }
+        None => (),
     }
     if let Some(ref level) = attrs.default_level {
-        init_trait_fns.push(quote! { fn default_level() -> #lvl_ident { #level } });
+        init_trait_fns.push(("default_level", quote! { fn default_level() -> #lvl_ident { #level } }));
+    }
+    if let Some(ref level) = attrs.max_level {
+        // levels assigned by name are capped at max_level
+        init_trait_fns.push(("max_level", quote! {
+            fn normalize_level(level: &#lvl_ident) -> #lvl_ident {
+                ::std::cmp::min(*level, #level)
+            }
+        }));
     }
     if let Some(ref facade) = attrs.default_facade {
-        init_trait_fns.push(quote! { fn default_facade() -> #fav_ident { #facade } });
+        init_trait_fns.push(("default_facade", quote! { fn default_facade() -> #fav_ident { #facade } }));
+    }
+    if attrs.auto_options || attrs.no_log_compat_inherit {
+        let (attr, options) = if attrs.auto_options {
+            ("auto_options", quote! { #opt_ident::auto_for(&<Self as ::hclog::Scope>::default_facade()) })
+        } else {
+            ("no_log_compat_inherit", quote! { <#opt_ident as ::std::default::Default>::default() })
+        };
+        let logcompat = if attrs.no_log_compat_inherit {
+            quote! { - ::hclog::options::LOGCOMPAT }
+        } else {
+            quote! {}
+        };
+        init_trait_fns.push((attr, quote! {
+            fn default_options() -> #opt_ident {
+                #options #logcompat
+            }
+        }));
     }
     if let Some(ref options) = attrs.default_options {
-        init_trait_fns.push(quote! { fn default_options() -> #opt_ident { #(#options)* } });
+        init_trait_fns.push((
+            "default_options", quote! { fn default_options() -> #opt_ident { #(#options)* } },
+        ));
     }
 
     let with_log = if attrs.logcompat {
-        quote! { options + hclog::options::LOGCOMPAT }
+        quote! { options + ::hclog::options::LOGCOMPAT }
     } else {
         quote! { options }
     };
 
+    if let Some(ref prefix) = attrs.prefix {
+        assert_name_chars(prefix.span(), "LogKey prefix", &prefix.value())?;
+    }
+    // display names of the added variants, matched ASCII case insensitive like in hclog
+    let mut names: Vec<(String, proc_macro2::Span)> = vec![];
+
     let variants = &data.variants;
     let mut v_idents = vec![];
+    let mut selftest_keys = vec![];
     let mut fmt_arms = vec![];
+    let mut variant_arms = vec![];
     let mut lvl_arms = vec![];
     let mut fav_arms = vec![];
-    let mut dsc_arms = vec![];
+    let mut pin_arms = vec![];
+    let mut desc_arms = vec![];
+    let mut dsc_checks = vec![];
 
-    let mut idx = 0usize;
-    for variant in variants {
+    for (idx, variant) in variants.iter().enumerate() {
         let v_ident = &variant.ident;
         let v_attrs = variant.parse_properties(CLOG_ATTR_IDENT)?;
         let v_discriminant = &variant.discriminant;
@@ -171,46 +366,94 @@ fn parse_derive_macro(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream
             Fields::Unit => (),
         }
 
-        if let Some((_, syn::Expr::Lit(ref d))) = v_discriminant {
-            let syn::Lit::Int(ref a) = d.lit else {
-                // should never happen since enum discriminants can't be other than int
-                return Err(syn::Error::new(v_ident.span(), "invalid discriminant"));
-            };
-            let value = a.base10_parse::<usize>()?;
-            // if discriminants are given ensure the hclog Index doesn't get out of bounds on
-            // access - otherwise the submodule index would raise a panic in hclog itself
-            if value != idx {
-                return Err(assert_discriminant_value(&v_ident, value, idx));
+        match v_discriminant {
+            Some((_, syn::Expr::Lit(ref d))) => {
+                let syn::Lit::Int(ref a) = d.lit else {
+                    // should never happen since enum discriminants can't be other than int
+                    return Err(syn::Error::new(v_ident.span(), "invalid discriminant"));
+                };
+                let value = a.base10_parse::<usize>()?;
+                // if discriminants are given ensure the hclog Index doesn't get out of bounds on
+                // access - otherwise the submodule index would raise a panic in hclog itself
+                if value != idx {
+                    return Err(assert_discriminant_value(v_ident, value, idx));
+                }
             }
-            dsc_arms.push(quote! { (&#ident::#v_ident,) => #value, });
-        } else {
-            dsc_arms.push(quote! { (&#ident::#v_ident,) => #idx, });
+            // can't be evaluated here, checked when the generated code is compiled
+            Some((_, ref expr)) => dsc_checks.push(quote_spanned! {expr.span()=>
+                const _: () = assert!(
+                    #ident::#v_ident as usize == #idx,
+                    "LogKey discriminants must be ascending from 0 without gaps",
+                );
+            }),
+            None => (),
         }
-        idx+=1;
 
-        let v_display_name = match v_attrs.name {
-            Some(n) => quote! { #n },
-            None => quote! { stringify!(#v_ident) },
+        let (name, name_span) = match v_attrs.name {
+            Some(ref n) => (n.value(), n.span()),
+            None => (v_ident.to_string(), v_ident.span()),
+        };
+        if name.is_empty() {
+            return Err(syn::Error::new(name_span, "LogKey name must not be empty"));
+        }
+        assert_name_chars(name_span, "LogKey name", &name)?;
+        if !v_attrs.ignore {
+            let full = format!("{}{}", attrs.prefix.as_ref().map(LitStr::value).unwrap_or_default(),
+                name);
+            if let Some((_, first)) = names.iter().find(|(n, _)| n.eq_ignore_ascii_case(&full)) {
+                return Err(duplicate_name_error(*first, name_span, &name));
+            }
+            names.push((full, name_span));
+        }
+        let v_display_name = match (&attrs.prefix, v_attrs.name) {
+            (None, Some(n)) => quote! { #n },
+            (None, None) => quote! { stringify!(#v_ident) },
+            (Some(p), n) => {
+                let name = n.map(|n| n.value()).unwrap_or_else(|| v_ident.to_string());
+                let name = LitStr::new(&format!("{}{}", p.value(), name), v_ident.span());
+                quote! { #name }
+            }
         };
         if let Some(level) = v_attrs.level {
             if v_attrs.ignore {
-                lvl_arms.push(quote! {(&#ident::#v_ident,) => Some(Level::Off), });
+                lvl_arms.push(quote! {
+                    (&#ident::#v_ident,) => ::core::option::Option::Some(#lvl_ident::Off),
+                });
             } else {
-                lvl_arms.push(quote! {(&#ident::#v_ident,) => Some(#level), });
+                lvl_arms.push(quote! {
+                    (&#ident::#v_ident,) => ::core::option::Option::Some(#level),
+                });
             }
         }
         if let Some(facade) = v_attrs.facade {
             if v_attrs.ignore {
-                fav_arms.push(quote! {(&#ident::#v_ident,) => Some(FacadeVariant::None), });
+                fav_arms.push(quote! {
+                    (&#ident::#v_ident,) => ::core::option::Option::Some(#fav_ident::None),
+                });
             } else {
-                fav_arms.push(quote! {(&#ident::#v_ident,) => Some(#facade), });
+                fav_arms.push(quote! {
+                    (&#ident::#v_ident,) => ::core::option::Option::Some(#facade),
+                });
             }
         }
 
+        if v_attrs.pin_level && !v_attrs.ignore {
+            pin_arms.push(quote! {(&#ident::#v_ident,) => true, });
+        }
+        if let Some(description) = v_attrs.description {
+            desc_arms.push(quote! {
+                (&#ident::#v_ident,) => ::core::option::Option::Some(#description),
+            });
+        }
+
         if !v_attrs.ignore {
             v_idents.push(quote! { #ident::#v_ident, });
+            selftest_keys.push(quote! { super::#ident::#v_ident, });
         }
         fmt_arms.push(quote! {(&#ident::#v_ident,) => f.write_str(#v_display_name), });
+        variant_arms.push(quote! {
+            (&#ident::#v_ident,) => ::core::option::Option::Some(stringify!(#v_ident)),
+        });
     }
 
     let init_fav_fn = if !fav_arms.is_empty() {
@@ -218,7 +461,7 @@ fn parse_derive_macro(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream
             fn init_facade(&self) -> ::core::option::Option<#fav_ident> {
                 match (&*self,) {
                     #(#fav_arms)*
-                    _ => None,
+                    _ => ::core::option::Option::None,
                 }
             }
         }
@@ -231,7 +474,7 @@ fn parse_derive_macro(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream
             fn init_level(&self) -> ::core::option::Option<#lvl_ident> {
                 match (&*self,) {
                     #(#lvl_arms)*
-                    _ => None,
+                    _ => ::core::option::Option::None,
                 }
             }
         }
@@ -239,43 +482,239 @@ fn parse_derive_macro(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream
         quote! {}
     };
 
-    // generate the output and all necessary impls
-    let output = TokenStream::from(quote! {
-        // bring traits into scope
-        use hclog::{Scope, LogKey};
+    let init_pin_fn = if !pin_arms.is_empty() {
+        quote! {
+            fn init_all_exempt(&self) -> bool {
+                match (&*self,) {
+                    #(#pin_arms)*
+                    _ => false,
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let description_fn = if !desc_arms.is_empty() {
+        quote! {
+            fn description(&self) -> ::core::option::Option<&'static str> {
+                match (&*self,) {
+                    #(#desc_arms)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
 
+    /*
+     * with merge = "append" the keys are placed behind the keys already initialized in the
+     * scope. The offset is taken on the first init and added to every log_key().
+     */
+    let offset_ident = format_ident!("__HCLOG_{}_KEY_OFFSET", ident.to_string().to_uppercase());
+    // the discriminants are dense and ascending, so the cast is the index
+    let log_key = quote! { *self as ::hclog::ContextKey };
+    let (offset_static, add_submodules, log_key_body) = match attrs.merge {
+        MergePolicy::Error => (
+            quote! {},
+            quote! { ::hclog::add_submodules(&[#(#v_idents)*])?; },
+            log_key,
+        ),
+        MergePolicy::Skip => (
+            quote! {},
+            quote! {
+                ::hclog::add_submodules_with(&[#(#v_idents)*], ::hclog::MergePolicy::Skip)?;
+            },
+            log_key,
+        ),
+        MergePolicy::Append => (
+            quote! {
+                static #offset_ident: ::std::sync::OnceLock<::hclog::ContextKey> =
+                    ::std::sync::OnceLock::new();
+            },
+            quote! {
+                ::hclog::append_submodules(&[#(#v_idents)*], &#offset_ident)?;
+            },
+            quote! {
+                let key = #log_key;
+                key + #offset_ident.get().copied().unwrap_or(0)
+            },
+        ),
+    };
+
+    /*
+     * with init_submodules = false the keys are added by the user - provide the list of keys
+     * init would have added instead
+     */
+    let vis = &ast.vis;
+    let (add_submodules, all_keys_impl) = match attrs.init_only {
+        None => (add_submodules, quote! {}),
+        Some(_) => (quote! {}, quote! {
+            #[automatically_derived]
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// All LogKeys not marked as `ignore`, to be passed to `hclog::add_submodules`
+                #vis const ALL: &'static [Self] = &[#(#v_idents)*];
+            }
+        }),
+    };
+
+    /*
+     * selftest: a test verifying that every LogKey matches its entry in the context. It's
+     * placed in a module next to the enum as a test inside the anonymous const can't be run.
+     */
+    let selftest = if attrs.selftest {
+        let mod_ident = format_ident!("__hclog_selftest_{}", ident);
+        let add_all = match attrs.init_only {
+            Some(_) => quote! {
+                ::hclog::add_submodules_with(super::#ident::ALL, ::hclog::MergePolicy::Skip)
+                    .unwrap();
+            },
+            None => quote! {},
+        };
+        quote! {
+            #[cfg(test)]
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            mod #mod_ident {
+                #[test]
+                fn hclog_keys_consistent() {
+                    <super::#ident as ::hclog::Scope>::init_with_defaults(stringify!(#ident))
+                        .unwrap();
+                    #add_all
+                    let mismatches = ::hclog::verify_keys(&[#(#selftest_keys)*]).unwrap();
+                    assert!(mismatches.is_empty(), "inconsistent LogKeys of {}: {}",
+                        stringify!(#ident),
+                        mismatches.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(", "));
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let variant_count = variants.len();
+    let merge_attr = match attrs.merge {
+        MergePolicy::Error => "merge = \"error\"",
+        MergePolicy::Skip => "merge = \"skip\"",
+        MergePolicy::Append => "merge = \"append\"",
+    };
+    let mut scope_attrs = init_trait_fns.iter().map(|(attr, _)| *attr).collect::<Vec<_>>();
+    if attrs.auto_options && attrs.no_log_compat_inherit {
+        scope_attrs.push("no_log_compat_inherit");
+    }
+    if attrs.logcompat {
+        scope_attrs.push("logcompat");
+    }
+    scope_attrs.push(merge_attr);
+    if attrs.init_only.is_some() {
+        scope_attrs.push("init_submodules = false");
+    }
+    let init_trait_fns = init_trait_fns.iter().map(|(_, f)| f);
+
+    /*
+     * generate all necessary impls, every trait impl is marked as #[automatically_derived]
+     */
+    let prelude = quote! {
+        // the variant index is used as index into a Vec in hclog
+        const _: () = assert!(
+            #variant_count <= ::hclog::MAX_SUBMODULES,
+            "too many LogKey variants: hclog supports up to MAX_SUBMODULES LogKeys per Scope",
+        );
+        #(#dsc_checks)*
+    };
+    let display_impl = quote! {
         #[automatically_derived]
-        impl #impl_generics std::fmt::Display for #ident #ty_generics #where_clause {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        impl #impl_generics ::std::fmt::Display for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
                 match (&*self,) {
                     #(#fmt_arms)*
                 }
             }
         }
-
+    };
+    let scope_impl = quote! {
         #[automatically_derived]
-        impl #impl_generics hclog::Scope for #ident #ty_generics #where_clause {
+        impl #impl_generics ::hclog::Scope for #ident #ty_generics #where_clause {
             #(#init_trait_fns)*
             /* the init call(s) itself */
-            fn init<S: std::fmt::Display>(name: S, level: #lvl_ident, facade: #fav_ident,
+            fn init<S: ::std::fmt::Display>(name: S, level: #lvl_ident, facade: #fav_ident,
                          options: #opt_ident) -> #res_ident<()> {
-                hclog::init::<Self, S>(name, level, facade, #with_log)?;
-                hclog::add_submodules(&[#(#v_idents)*])?;
-                Ok(())
+                ::hclog::init::<Self, S>(name, level, facade, #with_log)?;
+                #add_submodules
+                ::core::result::Result::Ok(())
             }
         }
-
+    };
+    let log_key_impl = quote! {
         #[automatically_derived]
-        impl #impl_generics hclog::LogKey for #ident #ty_generics #where_clause {
-            fn log_key(&self) -> hclog::ContextKey {
-                match (&*self,) {
-                    #(#dsc_arms)*
-                }
+        impl #impl_generics ::hclog::LogKey for #ident #ty_generics #where_clause {
+            fn log_key(&self) -> ::hclog::ContextKey {
+                #log_key_body
             }
             #init_lvl_fn
             #init_fav_fn
+            #init_pin_fn
+            #description_fn
+            fn variant_name(&self) -> ::core::option::Option<&'static str> {
+                match (&*self,) {
+                    #(#variant_arms)*
+                }
+            }
         }
-    });
-    helper::debug_print_generated(ast, &output);
-    Ok(output)
+        // owned keys can be passed to hclog::add_submodules, references are covered by hclog
+        #[automatically_derived]
+        impl #impl_generics ::hclog::IntoLogKey for #ident #ty_generics #where_clause {
+            type Key = Self;
+            fn into_log_key(self) -> Self {
+                self
+            }
+        }
+    };
+    Ok((vec![
+        ("variant count check".to_owned(), prelude),
+        (merge_attr.to_owned(), offset_static),
+        ("Display: variant name, prefix".to_owned(), display_impl),
+        (format!("Scope: {}", scope_attrs.join(", ")), scope_impl),
+        ("LogKey, IntoLogKey: variant level, facade, ignore, pin_level, description, \
+            discriminant".to_owned(), log_key_impl),
+        ("init_submodules = false".to_owned(), all_keys_impl),
+    ], selftest))
+}
+
+#[cfg(test)]
+mod derive_tests {
+    use syn::DeriveInput;
+
+    #[test]
+    fn debug_output() {
+        let ast = syn::parse_str::<DeriveInput>(r#"
+            #[hclog(default_level = Level::Info, merge = "append", selftest)]
+            enum Keys {
+                #[hclog(description = "first")]
+                A,
+                B,
+            }
+        "#).unwrap();
+        let (blocks, selftest) = super::generate_blocks(&ast).unwrap();
+        let code = blocks.iter().map(|(_, toks)| toks.to_string()).collect::<String>();
+        assert!(!code.contains("doc (hidden)") && !code.contains("allow"), "{}", code);
+
+        // the empty block of init_submodules = false is left out
+        let selftest = ("selftest".to_owned(), selftest);
+        let out = super::helper::format_generated(&ast.ident, blocks.iter().chain([&selftest]));
+        let headers = out.lines().filter(|l| l.starts_with("// ")).collect::<Vec<_>>();
+        assert_eq!(headers, [
+            "// Keys: variant count check",
+            "// Keys: merge = \"append\"",
+            "// Keys: Display: variant name, prefix",
+            "// Keys: Scope: default_level, merge = \"append\"",
+            "// Keys: LogKey, IntoLogKey: variant level, facade, ignore, pin_level, description, \
+                discriminant",
+            "// Keys: selftest",
+        ]);
+        assert!(out.contains("static __HCLOG_KEYS_KEY_OFFSET"), "{}", out);
+        assert!(out.contains("Some (\"first\")"), "{}", out);
+        assert!(out.contains("fn hclog_keys_consistent"), "{}", out);
+    }
 }
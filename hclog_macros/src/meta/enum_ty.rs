@@ -1,35 +1,96 @@
+use proc_macro2::TokenStream;
+use quote::ToTokens;
 use syn::{
     parse::{
         Parse, ParseStream,
     },
     DeriveInput,
-    Ident, Path, Expr,
+    Ident, Path, Expr, LitBool, LitStr, LitInt,
 };
 use super::*;
 use crate::{
     helper::occurrence_error,
 };
 
+// valid variants of hclog::ScopeKey usable in the scope attribute
+const SCOPE_KEY_VARIANTS: &[&str] = &["Application", "CLog", "Lib"];
+
+/**
+ * Value of the scope attributes: either a ScopeKey variant or a numeric index
+ */
+#[derive(Debug, Clone)]
+pub enum ScopeAttr {
+    Variant(Path),
+    Index(usize),
+}
+impl Parse for ScopeAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitInt) {
+            let lit = input.parse::<LitInt>()?;
+            if !matches!(lit.suffix(), "" | "usize") {
+                return Err(syn::Error::new(lit.span(), "scope index must be of type usize"));
+            }
+            return Ok(Self::Index(lit.base10_parse::<usize>()?));
+        }
+        let path = input.parse::<Path>()?;
+        let variant = path.segments.last().map(|s| s.ident.to_string()).unwrap_or_default();
+        if !SCOPE_KEY_VARIANTS.contains(&variant.as_str()) {
+            return Err(syn::Error::new_spanned(path, format!(
+                "invalid ScopeKey variant '{}': expected one of {}",
+                variant, SCOPE_KEY_VARIANTS.join(", "),
+            )));
+        }
+        Ok(Self::Variant(path))
+    }
+}
+
 /**
  * Enum (type level) metadata as decoded from the DeriveInput
  */
 #[derive(Debug)]
 enum EnumAttrs {
+    // scope, default_scope and scope_key are aliases - keep the keyword for error reporting
     Scope {
-        kw: keywords::scope,
-        attr: Path,
+        kw: TokenStream,
+        attr: ScopeAttr,
     },
     WithLog {
         kw: keywords::with_log,
     },
+    NoLogCompatInherit {
+        kw: keywords::no_log_compat_inherit,
+    },
+    AutoOptions {
+        kw: keywords::auto_options,
+    },
+    Selftest {
+        kw: keywords::selftest,
+    },
     DefaultLevel {
         kw: keywords::default_level,
         attr: Path,
     },
+    MaxLevel {
+        kw: keywords::max_level,
+        attr: Path,
+    },
     DefaultFacade {
         kw: keywords::default_facade,
         attr: Expr,
     },
+    Merge {
+        kw: keywords::merge,
+        attr: LitStr,
+    },
+    Prefix {
+        kw: keywords::prefix,
+        attr: LitStr,
+    },
+    // init_only is a shortcut for init_submodules = false
+    InitSubmodules {
+        kw: TokenStream,
+        attr: bool,
+    },
     /*
     DefaultOptions {
         kw: keywords::default_options,
@@ -41,16 +102,43 @@ impl Parse for EnumAttrs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let lh = input.lookahead1();
         if lh.peek(keywords::scope) {
-            let (kw, attr) = input.parse_keyword::<keywords::scope, Path>()?;
-            Ok(Self::Scope { kw, attr })
+            let (kw, attr) = input.parse_keyword::<keywords::scope, ScopeAttr>()?;
+            Ok(Self::Scope { kw: kw.into_token_stream(), attr })
+        } else if lh.peek(keywords::default_scope) {
+            let (kw, attr) = input.parse_keyword::<keywords::default_scope, ScopeAttr>()?;
+            Ok(Self::Scope { kw: kw.into_token_stream(), attr })
+        } else if lh.peek(keywords::scope_key) {
+            let (kw, attr) = input.parse_keyword::<keywords::scope_key, ScopeAttr>()?;
+            Ok(Self::Scope { kw: kw.into_token_stream(), attr })
         } else if lh.peek(keywords::with_log) {
             Ok(Self::WithLog { kw: input.parse::<keywords::with_log>()? })
+        } else if lh.peek(keywords::no_log_compat_inherit) {
+            Ok(Self::NoLogCompatInherit { kw: input.parse::<keywords::no_log_compat_inherit>()? })
+        } else if lh.peek(keywords::auto_options) {
+            Ok(Self::AutoOptions { kw: input.parse::<keywords::auto_options>()? })
+        } else if lh.peek(keywords::selftest) {
+            Ok(Self::Selftest { kw: input.parse::<keywords::selftest>()? })
         } else if lh.peek(keywords::default_level) {
             let (kw, attr) = input.parse_keyword::<keywords::default_level, Path>()?;
             Ok(Self::DefaultLevel { kw, attr })
+        } else if lh.peek(keywords::max_level) {
+            let (kw, attr) = input.parse_keyword::<keywords::max_level, Path>()?;
+            Ok(Self::MaxLevel { kw, attr })
         } else if lh.peek(keywords::default_facade) {
             let (kw, attr) = input.parse_keyword::<keywords::default_facade, Expr>()?;
             Ok(Self::DefaultFacade { kw, attr })
+        } else if lh.peek(keywords::merge) {
+            let (kw, attr) = input.parse_keyword::<keywords::merge, LitStr>()?;
+            Ok(Self::Merge { kw, attr })
+        } else if lh.peek(keywords::prefix) {
+            let (kw, attr) = input.parse_keyword::<keywords::prefix, LitStr>()?;
+            Ok(Self::Prefix { kw, attr })
+        } else if lh.peek(keywords::init_submodules) {
+            let (kw, attr) = input.parse_keyword::<keywords::init_submodules, LitBool>()?;
+            Ok(Self::InitSubmodules { kw: kw.into_token_stream(), attr: attr.value })
+        } else if lh.peek(keywords::init_only) {
+            let kw = input.parse::<keywords::init_only>()?;
+            Ok(Self::InitSubmodules { kw: kw.into_token_stream(), attr: false })
         } else {
             Err(lh.error())
         }
@@ -59,23 +147,61 @@ impl Parse for EnumAttrs {
 
 
 /**
- *
+ * Policy if the LogKeys collide with the LogKeys of another type in the same Scope
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    #[default]
+    Error,
+    Skip,
+    Append,
+}
+impl MergePolicy {
+    fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "error" => Ok(Self::Error),
+            "skip" => Ok(Self::Skip),
+            "append" => Ok(Self::Append),
+            v => Err(syn::Error::new(lit.span(), format!(
+                "invalid merge policy '{}': expected one of \"error\", \"skip\", \"append\"", v
+            ))),
+        }
+    }
+}
+
+/**
+ * Collected enum properties used to generate the trait impls
  */
 #[derive(Debug, Clone, Default)]
 pub struct EnumProperties {
-    pub scope: Option<Path>,
+    pub scope: Option<ScopeAttr>,
     pub logcompat: bool,
+    pub no_log_compat_inherit: bool,
+    pub auto_options: bool,
+    pub selftest: bool,
     pub default_level: Option<Path>,
+    pub max_level: Option<Path>,
     pub default_facade: Option<Expr>,
     pub default_options: Option<Vec<Ident>>,
+    pub merge: MergePolicy,
+    pub prefix: Option<LitStr>,
+    // keyword of init_submodules = false / init_only for error reporting
+    pub init_only: Option<TokenStream>,
 }
 impl DerivePropertiesExt<EnumProperties> for DeriveInput {
     fn parse_properties(&self, ident: &str) -> syn::Result<EnumProperties> {
         let mut out = EnumProperties::default();
         let mut scope_kw = None;
         let mut default_level_kw = None;
+        let mut max_level_kw = None;
         let mut default_facade_kw = None;
         let mut with_log_kw = None;
+        let mut no_log_compat_kw = None;
+        let mut auto_options_kw = None;
+        let mut selftest_kw = None;
+        let mut merge_kw = None;
+        let mut prefix_kw = None;
+        let mut init_submodules_kw = None;
         for meta in self.decode_meta::<EnumAttrs>(ident)? {
             match meta {
                 EnumAttrs::Scope { kw, attr } => {
@@ -92,6 +218,27 @@ impl DerivePropertiesExt<EnumProperties> for DeriveInput {
                     with_log_kw = Some(kw);
                     out.logcompat = true;
                 }
+                EnumAttrs::NoLogCompatInherit { kw } => {
+                    if let Some(prev_kw) = no_log_compat_kw {
+                        return Err(occurrence_error(prev_kw, kw, "no_log_compat_inherit", "enum"));
+                    }
+                    no_log_compat_kw = Some(kw);
+                    out.no_log_compat_inherit = true;
+                }
+                EnumAttrs::Selftest { kw } => {
+                    if let Some(prev_kw) = selftest_kw {
+                        return Err(occurrence_error(prev_kw, kw, "selftest", "enum"));
+                    }
+                    selftest_kw = Some(kw);
+                    out.selftest = true;
+                }
+                EnumAttrs::AutoOptions { kw } => {
+                    if let Some(prev_kw) = auto_options_kw {
+                        return Err(occurrence_error(prev_kw, kw, "auto_options", "enum"));
+                    }
+                    auto_options_kw = Some(kw);
+                    out.auto_options = true;
+                }
                 EnumAttrs::DefaultLevel { kw, attr } => {
                     if let Some(dl_kw) = default_level_kw {
                         return Err(occurrence_error(dl_kw, kw, "default_level", "enum"));
@@ -99,6 +246,13 @@ impl DerivePropertiesExt<EnumProperties> for DeriveInput {
                     default_level_kw = Some(kw);
                     out.default_level = Some(attr);
                 }
+                EnumAttrs::MaxLevel { kw, attr } => {
+                    if let Some(prev_kw) = max_level_kw {
+                        return Err(occurrence_error(prev_kw, kw, "max_level", "enum"));
+                    }
+                    max_level_kw = Some(kw);
+                    out.max_level = Some(attr);
+                }
                 EnumAttrs::DefaultFacade { kw, attr } => {
                     if let Some(prev_kw) = default_facade_kw {
                         return Err(occurrence_error(prev_kw, kw, "default_facade", "enum"));
@@ -106,8 +260,39 @@ impl DerivePropertiesExt<EnumProperties> for DeriveInput {
                     default_facade_kw = Some(kw);
                     out.default_facade = Some(attr);
                 }
+                EnumAttrs::Merge { kw, attr } => {
+                    if let Some(prev_kw) = merge_kw {
+                        return Err(occurrence_error(prev_kw, kw, "merge", "enum"));
+                    }
+                    merge_kw = Some(kw);
+                    out.merge = MergePolicy::from_lit(&attr)?;
+                }
+                EnumAttrs::Prefix { kw, attr } => {
+                    if let Some(prev_kw) = prefix_kw {
+                        return Err(occurrence_error(prev_kw, kw, "prefix", "enum"));
+                    }
+                    prefix_kw = Some(kw);
+                    out.prefix = Some(attr);
+                }
+                EnumAttrs::InitSubmodules { kw, attr } => {
+                    if let Some(prev_kw) = init_submodules_kw {
+                        return Err(occurrence_error(prev_kw, kw, "init_submodules", "enum"));
+                    }
+                    init_submodules_kw = Some(kw.clone());
+                    out.init_only = (!attr).then_some(kw);
+                }
             }
         }
+        // with_log always adds LOGCOMPAT on init, removing it from the defaults has no effect
+        if let (Some(_), Some(kw)) = (&with_log_kw, &no_log_compat_kw) {
+            return Err(syn::Error::new_spanned(kw,
+                "no_log_compat_inherit can't be combined with with_log"));
+        }
+        // the offset of appended keys is taken when the keys are added on init
+        if let (Some(kw), MergePolicy::Append) = (&out.init_only, out.merge) {
+            return Err(syn::Error::new_spanned(kw,
+                "init_submodules = false can't be combined with merge = \"append\""));
+        }
         Ok(out)
     }
 }
@@ -25,16 +25,28 @@ mod keywords {
      */
     // enum metadata
     custom_keyword!(scope);
+    custom_keyword!(default_scope);
+    custom_keyword!(scope_key);
     custom_keyword!(with_log);
+    custom_keyword!(no_log_compat_inherit);
+    custom_keyword!(auto_options);
+    custom_keyword!(selftest);
     custom_keyword!(default_level);
+    custom_keyword!(max_level);
     custom_keyword!(default_facade);
     custom_keyword!(default_options);
+    custom_keyword!(merge);
+    custom_keyword!(prefix);
+    custom_keyword!(init_submodules);
+    custom_keyword!(init_only);
 
     // variant metadata
     custom_keyword!(ignore);
+    custom_keyword!(pin_level);
     custom_keyword!(level);
     custom_keyword!(facade);
     custom_keyword!(name);
+    custom_keyword!(description);
 }
 
 use std::fmt::Debug;
@@ -24,9 +24,16 @@ pub enum VariantMeta {
         kw: keywords::name,
         attr: LitStr,
     },
+    Description {
+        kw: keywords::description,
+        attr: LitStr,
+    },
     Ignore {
         kw: keywords::ignore,
-    }
+    },
+    PinLevel {
+        kw: keywords::pin_level,
+    },
 }
 impl Parse for VariantMeta {
     fn parse(input: ParseStream) -> syn::Result<Self> {
@@ -40,8 +47,13 @@ impl Parse for VariantMeta {
         } else if lh.peek(keywords::name) {
             let (kw, attr) = input.parse_keyword::<keywords::name, LitStr>()?;
             Ok(Self::Name { kw, attr })
+        } else if lh.peek(keywords::description) {
+            let (kw, attr) = input.parse_keyword::<keywords::description, LitStr>()?;
+            Ok(Self::Description { kw, attr })
         } else if lh.peek(keywords::ignore) {
             Ok(Self::Ignore { kw: input.parse::<keywords::ignore>()? })
+        } else if lh.peek(keywords::pin_level) {
+            Ok(Self::PinLevel { kw: input.parse::<keywords::pin_level>()? })
         } else {
             Err(lh.error())
         }
@@ -53,7 +65,9 @@ pub struct VariantProperties {
     pub level: Option<Path>,
     pub facade: Option<Expr>,
     pub name: Option<LitStr>,
+    pub description: Option<LitStr>,
     pub ignore: bool,
+    pub pin_level: bool,
 }
 impl DerivePropertiesExt<VariantProperties> for Variant {
     fn parse_properties(&self, ident: &str) -> syn::Result<VariantProperties> {
@@ -61,7 +75,9 @@ impl DerivePropertiesExt<VariantProperties> for Variant {
         let mut level_kw = None;
         let mut facade_kw = None;
         let mut name_kw = None;
+        let mut description_kw = None;
         let mut ignore_kw = None;
+        let mut pin_kw = None;
 
         for meta in self.decode_meta::<VariantMeta>(ident)? {
             match meta {
@@ -72,6 +88,13 @@ impl DerivePropertiesExt<VariantProperties> for Variant {
                     ignore_kw = Some(kw);
                     out.ignore = true;
                 }
+                VariantMeta::PinLevel { kw } => {
+                    if let Some(p_kw) = pin_kw {
+                        return Err(occurrence_error(p_kw, kw, "pin_level", "variant"));
+                    }
+                    pin_kw = Some(kw);
+                    out.pin_level = true;
+                }
                 VariantMeta::Level { kw, attr } => {
                     if let Some(lvl_kw) = level_kw {
                         return Err(occurrence_error(lvl_kw, kw, "level", "variant"));
@@ -93,6 +116,13 @@ impl DerivePropertiesExt<VariantProperties> for Variant {
                     name_kw = Some(kw);
                     out.name = Some(attr);
                 }
+                VariantMeta::Description { kw, attr } => {
+                    if let Some(d_kw) = description_kw {
+                        return Err(occurrence_error(d_kw, kw, "description", "variant"));
+                    }
+                    description_kw = Some(kw);
+                    out.description = Some(attr);
+                }
             }
         }
 